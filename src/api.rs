@@ -1,3 +1,13 @@
+pub mod auth;
+
+use std::fs;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::atomic;
+use crate::collection;
+use crate::error::{Error, Result};
 use crate::theme::{ConfigResponse, GhosttyConfig};
 
 const BASE_URL: &str = "https://ghostty-style.vercel.app/api/configs";
@@ -9,9 +19,12 @@ pub struct FetchParams {
     pub sort: SortOrder,
     pub page: i32,
     pub dark: Option<bool>,
+    /// Only the logged-in user's own themes.
+    pub mine: bool,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, clap::ValueEnum, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum SortOrder {
     Popular,
     Newest,
@@ -52,12 +65,108 @@ impl Default for FetchParams {
             sort: SortOrder::Popular,
             page: 1,
             dark: None,
+            mine: false,
         }
     }
 }
 
-pub fn fetch_configs(params: &FetchParams) -> Result<ConfigResponse, String> {
-    let client = reqwest::blocking::Client::new();
+/// One client for the whole process, so connections are reused.
+fn client() -> &'static reqwest::Client {
+    static CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+    CLIENT.get_or_init(reqwest::Client::new)
+}
+
+/// GET a public endpoint, failing on a non-success status. Never signed,
+/// so an expired or revoked login can't get in the way of browsing.
+async fn get(url: &str) -> Result<reqwest::Response> {
+    send_with_token(client().get(url), url, None).await
+}
+
+/// GET an endpoint that needs the login, such as the `mine` listing.
+async fn get_signed(url: &str) -> Result<reqwest::Response> {
+    send(client().get(url), url).await
+}
+
+/// GET a file from anywhere, e.g. a shared collection. Unlike `get` it's
+/// never signed, so the login token only goes to the site.
+pub async fn download(url: &str) -> Result<String> {
+    let resp = client()
+        .get(url)
+        .header("User-Agent", "ghostty-styles-tui/0.1")
+        .send()
+        .await
+        .map_err(|source| Error::Network {
+            url: url.to_string(),
+            source,
+        })?;
+    if !resp.status().is_success() {
+        return Err(Error::Api {
+            url: url.to_string(),
+            status: resp.status(),
+        });
+    }
+    resp.text().await.map_err(|source| Error::Network {
+        url: url.to_string(),
+        source,
+    })
+}
+
+/// Send a request, signed with the login token, failing on a non-success
+/// status. Logged with its timing. For calls that need the login, after
+/// `auth::require`.
+async fn send(req: reqwest::RequestBuilder, url: &str) -> Result<reqwest::Response> {
+    send_with_token(req, url, auth::token().as_deref()).await
+}
+
+/// `send`, signed with `token` instead of the stored one.
+async fn send_with_token(
+    req: reqwest::RequestBuilder,
+    url: &str,
+    token: Option<&str>,
+) -> Result<reqwest::Response> {
+    let req = sign(req, token);
+    let started = Instant::now();
+    let result = req.send().await;
+    let elapsed = started.elapsed().as_millis();
+    let resp = result.map_err(|source| {
+        log::warn!("{} failed after {}ms: {}", url, elapsed, source);
+        Error::Network {
+            url: url.to_string(),
+            source,
+        }
+    })?;
+    log::debug!("{} -> {} in {}ms", url, resp.status(), elapsed);
+
+    // Only a signed request can blame the login; public GETs go unsigned
+    if token.is_some()
+        && matches!(
+            resp.status(),
+            reqwest::StatusCode::UNAUTHORIZED | reqwest::StatusCode::FORBIDDEN
+        )
+    {
+        return Err(Error::Auth(
+            "The site rejected your login token. Run `ghostty-styles login` again.".to_string(),
+        ));
+    }
+    if !resp.status().is_success() {
+        return Err(Error::Api {
+            url: url.to_string(),
+            status: resp.status(),
+        });
+    }
+    Ok(resp)
+}
+
+/// Add the user agent and, with a token, its one `Authorization` header.
+fn sign(req: reqwest::RequestBuilder, token: Option<&str>) -> reqwest::RequestBuilder {
+    let req = req.header("User-Agent", "ghostty-styles-tui/0.1");
+    match token {
+        Some(token) => req.bearer_auth(token),
+        None => req,
+    }
+}
+
+pub async fn fetch_configs(params: &FetchParams) -> Result<ConfigResponse> {
     let mut url = format!(
         "{}?sort={}&page={}",
         BASE_URL,
@@ -76,38 +185,243 @@ pub fn fetch_configs(params: &FetchParams) -> Result<ConfigResponse, String> {
     if let Some(dark) = params.dark {
         url.push_str(&format!("&dark={}", dark));
     }
+    if params.mine {
+        auth::require()?;
+        url.push_str("&mine=true");
+    }
 
-    let resp = client
-        .get(&url)
-        .header("User-Agent", "ghostty-styles-tui/0.1")
-        .send()
-        .map_err(|e| format!("Network error: {}", e))?;
+    let key = listing_key(&url);
+    let response = if params.mine {
+        get_signed(&url).await
+    } else {
+        get(&url).await
+    };
+    let fetched = match response {
+        Ok(resp) => resp.text().await.map_err(|source| Error::Network {
+            url: url.clone(),
+            source,
+        }),
+        Err(e) => Err(e),
+    };
+    let body = match fetched {
+        Ok(body) => body,
+        // Offline: an earlier copy of the same listing beats an error. Not
+        // for a refused login, or for your own themes, which belong to
+        // whoever was logged in and aren't cached.
+        Err(e @ Error::Network { .. }) if !params.mine => {
+            let Some((body, age)) = read_cached_listing(key) else {
+                return Err(e);
+            };
+            log::info!("Listing {} failed, using cache: {}", url, e);
+            let mut resp: ConfigResponse = serde_json::from_str(&body).map_err(|_| e)?;
+            resp.cached = Some(age);
+            return Ok(resp);
+        }
+        Err(e) => return Err(e),
+    };
+    let resp = serde_json::from_str(&body)
+        .map_err(|e| Error::parse(format!("response from {}", url), e))?;
+    if !params.mine {
+        write_cached_listing(key, &body);
+    }
+    Ok(resp)
+}
 
-    if !resp.status().is_success() {
-        return Err(format!("API error: {}", resp.status()));
+/// The first `limit` themes for `params`, starting at its page and
+/// fetching following pages as needed.
+pub async fn fetch_top(params: &FetchParams, limit: usize) -> Result<Vec<GhosttyConfig>> {
+    let mut params = params.clone();
+    let mut themes = Vec::new();
+    while themes.len() < limit {
+        let resp = fetch_configs(&params).await?;
+        let last_page = resp.configs.is_empty() || resp.page >= resp.total_pages;
+        themes.extend(resp.configs);
+        if last_page {
+            break;
+        }
+        params.page = resp.page + 1;
     }
+    themes.truncate(limit);
+    Ok(themes)
+}
+
+/// A random theme from all the pages `params` would list.
+pub async fn fetch_random(params: &FetchParams) -> Result<GhosttyConfig> {
+    use rand::seq::SliceRandom;
+    use rand::Rng;
 
-    resp.json::<ConfigResponse>()
-        .map_err(|e| format!("Parse error: {}", e))
+    let mut params = params.clone();
+    params.page = 1;
+    let mut resp = fetch_configs(&params).await?;
+    if resp.total_pages > 1 {
+        params.page = rand::thread_rng().gen_range(1..=resp.total_pages);
+        if params.page > 1 {
+            resp = fetch_configs(&params).await?;
+        }
+    }
+    resp.configs
+        .choose(&mut rand::thread_rng())
+        .cloned()
+        .ok_or_else(|| Error::Other("No themes match those filters".to_string()))
 }
 
-#[allow(dead_code)]
-pub fn fetch_config_by_id(id: &str) -> Result<GhosttyConfig, String> {
-    let client = reqwest::blocking::Client::new();
-    let url = format!("{}/{}", BASE_URL, id);
+/// A new theme for `upload`.
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NewTheme {
+    pub title: String,
+    pub description: Option<String>,
+    pub tags: Vec<String>,
+    pub raw_config: String,
+    pub author_name: Option<String>,
+}
 
-    let resp = client
-        .get(&url)
-        .header("User-Agent", "ghostty-styles-tui/0.1")
-        .send()
-        .map_err(|e| format!("Network error: {}", e))?;
+/// Publish a theme under the logged-in account, returning it as the site
+/// stored it.
+pub async fn upload(theme: &NewTheme) -> Result<GhosttyConfig> {
+    auth::require()?;
+    let resp = send(client().post(BASE_URL).json(theme), BASE_URL).await?;
+    resp.json()
+        .await
+        .map_err(|e| Error::parse(format!("response from {}", BASE_URL), e))
+}
 
-    if !resp.status().is_success() {
-        return Err(format!("API error: {}", resp.status()));
+/// Upvote a theme as the logged-in user.
+pub async fn vote(id: &str) -> Result<()> {
+    auth::require()?;
+    let url = format!("{}/{}/vote", BASE_URL, urlencoding(id));
+    send(client().post(&url), &url).await?;
+    Ok(())
+}
+
+/// How long a cached theme is used before it's fetched again.
+const CACHE_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Fetch one theme, from the on-disk cache while it's fresh. If the network
+/// is down, a stale cached copy is better than nothing.
+pub async fn fetch_config_by_id(id: &str) -> Result<GhosttyConfig> {
+    let cached = read_cached(id);
+    if let Some((body, age)) = &cached {
+        if *age < CACHE_TTL {
+            if let Ok(theme) = serde_json::from_str(body) {
+                log::debug!("Using cached '{}'", id);
+                return Ok(theme);
+            }
+        }
     }
 
-    resp.json::<GhosttyConfig>()
-        .map_err(|e| format!("Parse error: {}", e))
+    refresh_config(id).await.or_else(|e| {
+        let stale = cached.and_then(|(body, _)| serde_json::from_str(&body).ok());
+        if stale.is_some() {
+            log::info!("Fetching '{}' failed, using stale cache: {}", id, e);
+        }
+        stale.ok_or(e)
+    })
+}
+
+/// Fetch one theme from the site even if it's cached, updating the cache.
+pub async fn refresh_config(id: &str) -> Result<GhosttyConfig> {
+    let body = fetch_config_body(id).await?;
+    let theme =
+        serde_json::from_str(&body).map_err(|e| Error::parse(format!("theme '{}'", id), e))?;
+    // Best effort: a cache write failure shouldn't fail the fetch
+    write_cached(id, &body);
+    Ok(theme)
+}
+
+async fn fetch_config_body(id: &str) -> Result<String> {
+    let url = format!("{}/{}", BASE_URL, urlencoding(id));
+
+    let resp = match get(&url).await {
+        Err(Error::Api { status, .. }) if status == reqwest::StatusCode::NOT_FOUND => {
+            return Err(Error::not_found("Theme", id));
+        }
+        result => result?,
+    };
+    resp.text()
+        .await
+        .map_err(|source| Error::Network { url, source })
+}
+
+/// Whether a theme id can be used as a file name as-is.
+pub(crate) fn is_file_safe(id: &str) -> bool {
+    !id.is_empty()
+        && !id.starts_with('.')
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+}
+
+/// `<cache dir>/configs/<id>.json`, or None if the id can't be a file name.
+fn cache_path(id: &str) -> Option<PathBuf> {
+    is_file_safe(id).then(|| {
+        collection::cache_dir()
+            .join("configs")
+            .join(format!("{}.json", id))
+    })
+}
+
+/// The cached response body for `id` and how old it is.
+fn read_cached(id: &str) -> Option<(String, Duration)> {
+    let path = cache_path(id)?;
+    let age = file_age(&path).unwrap_or(Duration::MAX);
+    let body = fs::read_to_string(&path).ok()?;
+    Some((body, age))
+}
+
+fn write_cached(id: &str, body: &str) {
+    let Some(path) = cache_path(id) else {
+        return;
+    };
+    if let Some(parent) = path.parent() {
+        let _ = fs::create_dir_all(parent);
+    }
+    let _ = atomic::write(&path, body);
+}
+
+/// How long a cached listing can stand in for the site when it's down.
+const LISTING_CACHE_TTL: Duration = Duration::from_secs(3 * 24 * 60 * 60);
+
+/// A stable file key for a listing URL (FNV-1a), which carries the query,
+/// tag, sort, page, and dark filter.
+fn listing_key(url: &str) -> u64 {
+    url.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, b| {
+        (hash ^ u64::from(b)).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+fn listings_dir() -> PathBuf {
+    collection::cache_dir().join("listings")
+}
+
+fn read_cached_listing(key: u64) -> Option<(String, Duration)> {
+    let path = listings_dir().join(format!("{:016x}.json", key));
+    let age = file_age(&path)?;
+    if age >= LISTING_CACHE_TTL {
+        return None;
+    }
+    Some((fs::read_to_string(&path).ok()?, age))
+}
+
+/// Save a listing, clearing out expired ones so searches don't pile up.
+fn write_cached_listing(key: u64, body: &str) {
+    let dir = listings_dir();
+    if fs::create_dir_all(&dir).is_err() {
+        return;
+    }
+    if let Ok(entries) = fs::read_dir(&dir) {
+        for entry in entries.flatten() {
+            if file_age(&entry.path()).is_some_and(|age| age >= LISTING_CACHE_TTL) {
+                let _ = fs::remove_file(entry.path());
+            }
+        }
+    }
+    let _ = atomic::write(&dir.join(format!("{:016x}.json", key)), body);
+}
+
+fn file_age(path: &std::path::Path) -> Option<Duration> {
+    let modified = fs::metadata(path).and_then(|m| m.modified()).ok()?;
+    SystemTime::now().duration_since(modified).ok()
 }
 
 fn urlencoding(s: &str) -> String {
@@ -129,6 +443,39 @@ fn urlencoding(s: &str) -> String {
 mod tests {
     use super::*;
 
+    #[test]
+    fn signed_requests_carry_one_authorization_header() {
+        let req = sign(client().get(BASE_URL), Some("new-token"))
+            .build()
+            .unwrap();
+        let auth: Vec<_> = req
+            .headers()
+            .get_all(reqwest::header::AUTHORIZATION)
+            .iter()
+            .collect();
+        assert_eq!(auth, ["Bearer new-token"]);
+
+        let req = sign(client().get(BASE_URL), None).build().unwrap();
+        assert!(req.headers().get(reqwest::header::AUTHORIZATION).is_none());
+    }
+
+    #[test]
+    fn cache_path_only_for_plain_ids() {
+        let path = cache_path("tokyo-night_2.1").unwrap();
+        assert!(path.ends_with("configs/tokyo-night_2.1.json"));
+        assert!(cache_path("../config").is_none());
+        assert!(cache_path("a/b").is_none());
+        assert!(cache_path("").is_none());
+    }
+
+    #[test]
+    fn listing_key_differs_by_url() {
+        let popular = format!("{}?sort=popular&page=1", BASE_URL);
+        let page_two = format!("{}?sort=popular&page=2", BASE_URL);
+        assert_eq!(listing_key(&popular), listing_key(&popular.clone()));
+        assert_ne!(listing_key(&popular), listing_key(&page_two));
+    }
+
     #[test]
     fn sort_order_as_str() {
         assert_eq!(SortOrder::Popular.as_str(), "popular");
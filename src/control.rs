@@ -0,0 +1,190 @@
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+use crate::collection;
+use crate::error::{Error, Result};
+
+/// How long a command waits for the daemon to answer.
+const REPLY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A message for the running daemon, sent over `daemon.sock` as one line.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Control {
+    /// Stop switching themes on its own until resumed.
+    Pause,
+    Resume,
+    /// Switch to the next theme now and restart the interval.
+    Skip,
+    /// Ask whether it's paused.
+    Status,
+    /// Shut down, as SIGTERM does on Unix. How `cycle stop` reaches the
+    /// daemon on Windows.
+    Shutdown,
+}
+
+impl Control {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Control::Pause => "pause",
+            Control::Resume => "resume",
+            Control::Skip => "skip",
+            Control::Status => "status",
+            Control::Shutdown => "shutdown",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim() {
+            "pause" => Some(Control::Pause),
+            "resume" => Some(Control::Resume),
+            "skip" => Some(Control::Skip),
+            "status" => Some(Control::Status),
+            "shutdown" => Some(Control::Shutdown),
+            _ => None,
+        }
+    }
+}
+
+/// A control message and where to send the daemon's answer.
+pub type Request = (Control, mpsc::Sender<String>);
+
+/// Listen on `daemon.sock`, passing each message to `handle` and writing
+/// the answer back.
+pub fn listen(handle: impl Fn(Request) -> bool + Send + 'static) -> Result<()> {
+    listen_at(&collection::socket_path(), handle)
+}
+
+/// Send `control` to the running daemon and return its answer.
+pub fn send(control: Control) -> Result<String> {
+    send_to(&collection::socket_path(), control)
+}
+
+/// `handle` returns `false` once the daemon is gone, which stops the
+/// listener.
+fn listen_at(path: &Path, handle: impl Fn(Request) -> bool + Send + 'static) -> Result<()> {
+    let listener = transport::bind(path).map_err(|e| Error::io("listen on", path, e))?;
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let Ok(mut stream) = stream else { continue };
+            let _ = stream.set_read_timeout(Some(REPLY_TIMEOUT));
+            let mut line = String::new();
+            if BufReader::new(&stream).read_line(&mut line).is_err() {
+                continue;
+            }
+            let reply = match Control::parse(&line) {
+                Some(control) => {
+                    let (tx, rx) = mpsc::channel();
+                    if !handle((control, tx)) {
+                        return;
+                    }
+                    rx.recv_timeout(REPLY_TIMEOUT)
+                        .unwrap_or_else(|_| "The daemon didn't answer".to_string())
+                }
+                None => format!("Unknown command '{}'", line.trim()),
+            };
+            let _ = writeln!(stream, "{}", reply);
+        }
+    });
+    Ok(())
+}
+
+fn send_to(path: &Path, control: Control) -> Result<String> {
+    let mut stream = transport::connect(path).map_err(|_| {
+        Error::Invalid(
+            "No daemon is running. Start one with: ghostty-styles cycle start".to_string(),
+        )
+    })?;
+    stream
+        .set_read_timeout(Some(REPLY_TIMEOUT))
+        .map_err(|e| Error::io("talk to the daemon at", path, e))?;
+    writeln!(stream, "{}", control.as_str())
+        .map_err(|e| Error::io("talk to the daemon at", path, e))?;
+    let mut reply = String::new();
+    BufReader::new(&stream)
+        .read_line(&mut reply)
+        .map_err(|e| Error::io("talk to the daemon at", path, e))?;
+    Ok(reply.trim().to_string())
+}
+
+#[cfg(unix)]
+mod transport {
+    use std::fs;
+    use std::io;
+    use std::os::unix::net::{UnixListener, UnixStream};
+    use std::path::Path;
+
+    pub fn bind(path: &Path) -> io::Result<UnixListener> {
+        // Left behind by a daemon that was killed
+        let _ = fs::remove_file(path);
+        UnixListener::bind(path)
+    }
+
+    pub fn connect(path: &Path) -> io::Result<UnixStream> {
+        UnixStream::connect(path)
+    }
+}
+
+/// Windows has no socket files to lean on, so the daemon listens on a
+/// loopback port and writes its number to `daemon.sock` instead.
+#[cfg(windows)]
+mod transport {
+    use std::fs;
+    use std::io;
+    use std::net::{Ipv4Addr, TcpListener, TcpStream};
+    use std::path::Path;
+
+    pub fn bind(path: &Path) -> io::Result<TcpListener> {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))?;
+        fs::write(path, listener.local_addr()?.port().to_string())?;
+        Ok(listener)
+    }
+
+    pub fn connect(path: &Path) -> io::Result<TcpStream> {
+        let port: u16 = fs::read_to_string(path)?
+            .trim()
+            .parse()
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "not a port number"))?;
+        TcpStream::connect((Ipv4Addr::LOCALHOST, port))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn controls_round_trip_through_their_names() {
+        for control in [
+            Control::Pause,
+            Control::Resume,
+            Control::Skip,
+            Control::Status,
+            Control::Shutdown,
+        ] {
+            assert_eq!(Control::parse(control.as_str()), Some(control));
+        }
+        assert_eq!(Control::parse("stop"), None);
+    }
+
+    #[test]
+    fn messages_reach_the_handler_and_answers_come_back() {
+        let dir =
+            std::env::temp_dir().join(format!("ghostty-styles-control-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("daemon.sock");
+
+        listen_at(&path, |(control, reply)| {
+            reply.send(format!("got {}", control.as_str())).is_ok()
+        })
+        .unwrap();
+        assert_eq!(send_to(&path, Control::Pause).unwrap(), "got pause");
+        assert_eq!(send_to(&path, Control::Skip).unwrap(), "got skip");
+        fs::remove_dir_all(&dir).unwrap();
+        assert!(send_to(&path, Control::Status).is_err());
+    }
+}
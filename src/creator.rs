@@ -282,6 +282,8 @@ pub struct CreatorState {
     pub forked_from: Option<String>,
     /// Scroll offset for the field list (for when list exceeds visible area).
     pub field_scroll: usize,
+    /// Theme pack that variants of this theme are saved into.
+    pub pack_name: String,
 }
 
 impl CreatorState {
@@ -333,6 +335,7 @@ impl CreatorState {
             unsaved: false,
             forked_from: None,
             field_scroll: 0,
+            pack_name: String::new(),
         };
 
         state.generate_palette();
@@ -392,6 +395,7 @@ impl CreatorState {
             unsaved: false,
             forked_from: Some(config.slug.clone()),
             field_scroll: 0,
+            pack_name: String::new(),
         };
 
         state.sync_hex_from_color();
@@ -574,6 +578,18 @@ impl CreatorState {
     // Queries
     // -----------------------------------------------------------------------
 
+    /// The pack variant label for this theme: `high-contrast` when tagged
+    /// so, otherwise `dark` or `light`.
+    pub fn pack_variant(&self, tags: &[String]) -> &'static str {
+        if tags.iter().any(|t| t == "high-contrast") {
+            "high-contrast"
+        } else if self.is_dark() {
+            "dark"
+        } else {
+            "light"
+        }
+    }
+
     /// Returns `true` if the theme background is dark (lightness < 50).
     pub fn is_dark(&self) -> bool {
         self.colors[0].l < 50.0
@@ -616,6 +632,7 @@ impl CreatorState {
             vote_count: 0,
             view_count: 0,
             download_count: 0,
+            parsed: Default::default(),
         }
     }
 
@@ -644,6 +661,16 @@ impl CreatorState {
     }
 
     /// Derive a URL-friendly slug from the title.
+    /// The slug the theme is saved under in a collection, marked so it's
+    /// never looked up on the site.
+    pub fn local_slug(&self) -> String {
+        format!(
+            "{}{}",
+            crate::collection::LOCAL_SLUG_PREFIX,
+            self.slug_from_title()
+        )
+    }
+
     pub fn slug_from_title(&self) -> String {
         self.title
             .to_lowercase()
@@ -752,12 +779,33 @@ mod tests {
         assert!(state.is_dark());
     }
 
+    #[test]
+    fn pack_variant_prefers_high_contrast_tag() {
+        let state = CreatorState::new("Dark Theme");
+        assert_eq!(state.pack_variant(&[]), "dark");
+        assert_eq!(
+            state.pack_variant(&["high-contrast".to_string()]),
+            "high-contrast"
+        );
+    }
+
     #[test]
     fn slug_from_title() {
         let state = CreatorState::new("My Cool Theme!");
         assert_eq!(state.slug_from_title(), "my-cool-theme");
     }
 
+    #[test]
+    fn local_slug_is_marked_local() {
+        let state = CreatorState::new("My Cool Theme!");
+        let theme = crate::collection::CollectionTheme::from(&GhosttyConfig {
+            slug: state.local_slug(),
+            ..state.build_preview_config()
+        });
+        assert_eq!(theme.slug, "local-my-cool-theme");
+        assert!(theme.is_local());
+    }
+
     #[test]
     fn build_raw_config_format() {
         let state = CreatorState::new("Test");
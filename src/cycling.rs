@@ -1,22 +1,295 @@
-use rand::Rng;
+use std::collections::HashMap;
 
-use crate::collection::{self, CycleOrder};
-use crate::config;
+use rand::seq::SliceRandom;
+
+use crate::atomic;
+use crate::collection::{
+    self, AppConfig, Collection, CollectionTheme, CycleOrder, Rotation, RotationMode,
+};
+use crate::config::{self, ApplyScope};
 use crate::darkmode;
+use crate::error::{Error, Result};
+use crate::ghostty;
+use crate::history::{self, Trigger};
+use crate::lock::{self, Resource};
+use crate::preview;
+use crate::schedule;
 use crate::theme::GhosttyConfig;
 
+/// A movement of the active collection's pointer.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Step {
+    /// Advance according to the collection's order (sequential or shuffle).
+    Next,
+    /// Step back one theme in collection order.
+    Prev,
+    /// Jump to a specific entry, ignoring the mode filter.
+    Goto(Target),
+    /// The mode flipped: move to the current theme's twin if it has one in
+    /// the wanted mode, otherwise advance like `Next`.
+    Flip,
+}
+
+/// An entry in a collection, addressed by 1-based position or slug.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Target {
+    Index(usize),
+    Slug(String),
+}
+
+impl Target {
+    /// Parse a user-supplied target: a number is a 1-based index, anything
+    /// else is treated as a slug.
+    pub fn parse(s: &str) -> Self {
+        match s.trim().parse::<usize>() {
+            Ok(n) => Target::Index(n),
+            Err(_) => Target::Slug(s.trim().to_string()),
+        }
+    }
+}
+
+/// What the engine would do for a step, without having applied anything.
+pub struct Plan {
+    pub collection: Collection,
+    pub index: usize,
+    pub want_dark: Option<bool>,
+    /// New rotation `(position, played)` to persist, when a rotation moved.
+    pub rotation: Option<(usize, usize)>,
+    /// Something the caller should pass on, e.g. that the mode filter
+    /// matched nothing and was ignored.
+    pub warning: Option<String>,
+}
+
+impl Plan {
+    pub fn theme(&self) -> &CollectionTheme {
+        &self.collection.themes[self.index]
+    }
+}
+
+/// Resolve the dark/light filter implied by the mode preference: the
+/// global one, or the cycling collection's own.
+pub fn resolve_want_dark(app_config: &AppConfig) -> Option<bool> {
+    global_want_dark(&schedule::mode_config(app_config))
+}
+
+/// `resolve_want_dark` from the global mode settings alone.
+pub fn global_want_dark(app_config: &AppConfig) -> Option<bool> {
+    app_config
+        .effective_mode()
+        .and_then(|pref| darkmode::resolve_mode(&pref, app_config))
+}
+
 /// Advance to the next theme in the active collection and apply it.
-/// Respects the global mode preference to filter themes.
-pub fn apply_next() -> Result<String, String> {
+/// Respects the mode preference to filter themes.
+/// Non-empty `tags` stand in for the collection's `tag_filter`.
+pub fn apply_next(tags: &[String]) -> Result<String> {
     let app_config = collection::load_config();
-    let coll_name = app_config
-        .active_collection
-        .ok_or("No active collection. Run: ghostty-styles collection use <name>")?;
+    let want_dark = resolve_want_dark(&app_config);
+    apply_tagged(&app_config, &Step::Next, want_dark, Trigger::Manual, tags)
+}
+
+/// A switch the daemon requested but left for the shell hook to apply.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Pending {
+    /// A regular cycle; the mode is resolved when it's applied.
+    Next,
+    /// The appearance changed; switch to a dark theme.
+    Dark,
+    /// The appearance changed; switch to a light theme.
+    Light,
+}
+
+impl Pending {
+    /// The pending switch for a mode change (`None` means any theme).
+    pub fn for_mode(want_dark: Option<bool>) -> Self {
+        match want_dark {
+            Some(true) => Pending::Dark,
+            Some(false) => Pending::Light,
+            None => Pending::Next,
+        }
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            Pending::Next => "next",
+            Pending::Dark => "dark",
+            Pending::Light => "light",
+        }
+    }
+
+    fn parse(s: &str) -> Option<Self> {
+        match s.trim() {
+            "next" => Some(Pending::Next),
+            "dark" => Some(Pending::Dark),
+            "light" => Some(Pending::Light),
+            _ => None,
+        }
+    }
+
+    /// Combine with a switch that's already waiting. A mode change is kept
+    /// over a later regular cycle so the appearance still flips.
+    fn merge(self, existing: Option<Pending>) -> Self {
+        match (self, existing) {
+            (Pending::Next, Some(mode)) => mode,
+            _ => self,
+        }
+    }
+}
+
+/// Apply `pending` now, or leave it for the shell hook when
+/// `defer_to_prompt` is set.
+pub fn switch(app_config: &AppConfig, pending: Pending) -> Result<String> {
+    if !app_config.defer_to_prompt {
+        let trigger = match pending {
+            Pending::Next => Trigger::Interval,
+            Pending::Dark | Pending::Light => Trigger::Appearance,
+        };
+        return apply_pending_switch(app_config, pending, trigger);
+    }
+    let _lock = lock::acquire(Resource::State)?;
+    let path = collection::pending_path();
+    let existing = std::fs::read_to_string(&path)
+        .ok()
+        .and_then(|s| Pending::parse(&s));
+    let pending = pending.merge(existing);
+    collection::ensure_dirs()?;
+    atomic::write(&path, pending.as_str()).map_err(|e| Error::io("write", &path, e))?;
+    Ok(format!(
+        "Switch ({}) pending until the next prompt",
+        pending.as_str()
+    ))
+}
+
+/// Apply and clear the switch left by the daemon, if there is one.
+pub fn take_pending() -> Result<Option<String>> {
+    let path = collection::pending_path();
+    let pending = match std::fs::read_to_string(&path) {
+        Ok(s) => Pending::parse(&s).unwrap_or(Pending::Next),
+        Err(_) => return Ok(None),
+    };
+    // Clear first so a failing switch isn't retried at every prompt. If it's
+    // already gone, another prompt got to it first.
+    match std::fs::remove_file(&path) {
+        Ok(()) => {}
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(Error::io("delete", &path, e)),
+    }
+    apply_pending_switch(&collection::load_config(), pending, Trigger::Prompt).map(Some)
+}
+
+fn apply_pending_switch(
+    app_config: &AppConfig,
+    pending: Pending,
+    trigger: Trigger,
+) -> Result<String> {
+    let want_dark = match pending {
+        Pending::Next => resolve_want_dark(app_config),
+        Pending::Dark => Some(true),
+        Pending::Light => Some(false),
+    };
+    let step = match pending {
+        Pending::Next => Step::Next,
+        Pending::Dark | Pending::Light => Step::Flip,
+    };
+    apply(app_config, &step, want_dark, trigger)
+}
+
+/// Work out which theme `step` lands on in the active collection.
+pub fn plan(app_config: &AppConfig, step: &Step, want_dark: Option<bool>) -> Result<Plan> {
+    plan_tagged(app_config, step, want_dark, &[])
+}
 
-    let mut coll = collection::load_collection(&coll_name)?;
+/// `plan`, with non-empty `tags` in place of the collection's `tag_filter`.
+fn plan_tagged(
+    app_config: &AppConfig,
+    step: &Step,
+    want_dark: Option<bool>,
+    tags: &[String],
+) -> Result<Plan> {
+    plan_with(
+        app_config,
+        step,
+        want_dark,
+        tags,
+        &mut crate::smart::load_collection,
+    )
+}
+
+/// The themes the next `count` forward steps would apply, as
+/// `(collection, theme)` pairs, without changing anything on disk.
+/// Shuffle picks are random, so they show one possible order.
+pub fn preview(
+    app_config: &AppConfig,
+    want_dark: Option<bool>,
+    tags: &[String],
+    count: usize,
+) -> Result<Vec<(String, CollectionTheme)>> {
+    let mut config = app_config.clone();
+    let mut simulated: HashMap<String, Collection> = HashMap::new();
+    let mut upcoming = Vec::with_capacity(count);
+
+    for _ in 0..count {
+        let mut load = |name: &str| match simulated.get(name) {
+            Some(coll) => Ok(coll.clone()),
+            None => crate::smart::load_collection(name),
+        };
+        let mut plan = plan_with(&config, &Step::Next, want_dark, tags, &mut load)?;
+        upcoming.push((plan.collection.name.clone(), plan.theme().clone()));
+
+        plan.collection.current_index = plan.index;
+        if let (Some((position, played)), Some(rot)) = (plan.rotation, config.rotation.as_mut()) {
+            rot.position = position;
+            rot.played = played;
+            // The rotation names collections as configured, which may
+            // differ from the name stored inside the file
+            let key = rot.collections[position].clone();
+            simulated.insert(key, plan.collection);
+        } else if let Some(name) = schedule::collection_for_mode(&config, want_dark) {
+            simulated.insert(name, plan.collection);
+        }
+    }
+
+    Ok(upcoming)
+}
+
+/// `plan_tagged`, loading collections through `load` so callers can
+/// substitute in-memory state.
+fn plan_with(
+    app_config: &AppConfig,
+    step: &Step,
+    want_dark: Option<bool>,
+    tags: &[String],
+    load: &mut dyn FnMut(&str) -> Result<Collection>,
+) -> Result<Plan> {
+    let coll_name = schedule::collection_for_mode(app_config, want_dark).ok_or_else(|| {
+        Error::Invalid(
+            "No active collection. Run: ghostty-styles collection use <name>".to_string(),
+        )
+    })?;
+
+    let mut coll = load(&coll_name)?;
+
+    // Only forward steps move a rotation on to another collection
+    let mut rotation = None;
+    if let Some(rot) =
+        schedule::rotation_for_mode(app_config, want_dark).filter(|_| *step == Step::Next)
+    {
+        let played_len = eligible_indices(&coll.themes, want_dark)
+            .map_or(coll.themes.len(), |indices| indices.len());
+        let (position, played) = advance_rotation(rot, played_len);
+        if position != rot.position % rot.collections.len() {
+            coll = load(&rot.collections[position])?;
+        }
+        rotation = Some((position, played));
+    }
+    let coll_name = coll.name.clone();
+    let coll_name = coll_name.as_str();
 
     if coll.themes.is_empty() {
-        return Err(format!("Collection '{}' is empty", coll_name));
+        return Err(Error::Invalid(format!(
+            "Collection '{}' is empty",
+            coll_name
+        )));
     }
 
     // Clamp current_index in case the collection was modified externally
@@ -24,62 +297,242 @@ pub fn apply_next() -> Result<String, String> {
         coll.current_index = 0;
     }
 
-    // Resolve mode filter
-    let want_dark: Option<bool> = app_config.mode_preference.as_ref().and_then(|pref| {
-        darkmode::resolve_mode(pref, &app_config.dark_after, &app_config.light_after)
-    });
-
-    // Build list of eligible indices
-    let eligible: Vec<usize> = if let Some(dark) = want_dark {
-        let filtered: Vec<usize> = coll
-            .themes
-            .iter()
-            .enumerate()
-            .filter(|(_, t)| t.is_dark == dark)
-            .map(|(i, _)| i)
-            .collect();
-        if filtered.is_empty() {
-            eprintln!(
-                "[warning] No {} themes in '{}', ignoring mode filter",
-                if dark { "dark" } else { "light" },
-                coll_name
-            );
-            (0..coll.themes.len()).collect()
-        } else {
-            filtered
-        }
-    } else {
-        (0..coll.themes.len()).collect()
+    let mut warning = None;
+    let twin = match step {
+        Step::Flip => twin_index(&coll.themes, coll.current_index, want_dark),
+        _ => None,
     };
 
-    // Find current position within eligible list
-    let current_eligible_pos = eligible
-        .iter()
-        .position(|&i| i == coll.current_index)
-        .unwrap_or(0);
-
-    let next_eligible_pos = match coll.order {
-        CycleOrder::Sequential => (current_eligible_pos + 1) % eligible.len(),
-        CycleOrder::Shuffle => {
-            let mut rng = rand::thread_rng();
-            if eligible.len() == 1 {
-                0
+    let index = match (step, twin) {
+        (Step::Goto(target), _) => find_target(&coll.themes, target).ok_or_else(|| {
+            Error::Invalid(match target {
+                Target::Index(n) => format!(
+                    "No theme #{} in '{}' (it has {})",
+                    n,
+                    coll_name,
+                    coll.themes.len()
+                ),
+                Target::Slug(slug) => format!("No theme '{}' in '{}'", slug, coll_name),
+            })
+        })?,
+        (Step::Flip, Some(twin)) => twin,
+        (Step::Next | Step::Prev | Step::Flip, _) => {
+            // Build list of eligible indices
+            let mut eligible = match eligible_indices(&coll.themes, want_dark) {
+                Some(indices) => indices,
+                None => {
+                    warning = Some(format!(
+                        "no {} themes in '{}', ignoring the mode filter",
+                        if want_dark == Some(true) {
+                            "dark"
+                        } else {
+                            "light"
+                        },
+                        coll_name
+                    ));
+                    eligible_indices(&coll.themes, None).unwrap_or_default()
+                }
+            };
+            if eligible.is_empty() {
+                return Err(Error::Invalid(format!(
+                    "Every theme in '{}' is disabled",
+                    coll_name
+                )));
+            }
+            let tags = if tags.is_empty() {
+                &coll.tag_filter
             } else {
-                let mut next = current_eligible_pos;
-                while next == current_eligible_pos {
-                    next = rng.gen_range(0..eligible.len());
+                tags
+            };
+            if !tags.is_empty() {
+                eligible = filter_by_tags(&coll.themes, eligible, tags);
+                if eligible.is_empty() {
+                    return Err(Error::Invalid(format!(
+                        "No themes in '{}' are tagged {}",
+                        coll_name,
+                        tags.join(" or ")
+                    )));
                 }
-                next
+            }
+            let backward = *step == Step::Prev;
+            let current = pair_position(&coll.themes, coll.current_index, &eligible);
+            if !backward && matches!(coll.order, CycleOrder::Bag) {
+                draw_from_bag(&mut coll.shuffle_bag, &coll.themes, &eligible, current)
+            } else {
+                let weights: Vec<u32> = coll.themes.iter().map(|t| t.weight).collect();
+                step_index(&eligible, &weights, current, &coll.order, backward)
             }
         }
     };
 
-    let next_index = eligible[next_eligible_pos];
-    let theme_entry = &coll.themes[next_index];
+    Ok(Plan {
+        collection: coll,
+        index,
+        want_dark,
+        rotation,
+        warning,
+    })
+}
+
+/// Carry out `step`: write the chosen theme to the Ghostty config,
+/// persist the new pointer, and record the switch in the history log.
+pub fn apply(
+    app_config: &AppConfig,
+    step: &Step,
+    want_dark: Option<bool>,
+    trigger: Trigger,
+) -> Result<String> {
+    apply_tagged(app_config, step, want_dark, trigger, &[])
+}
+
+/// `apply`, with non-empty `tags` in place of the collection's
+/// `tag_filter`.
+fn apply_tagged(
+    app_config: &AppConfig,
+    step: &Step,
+    want_dark: Option<bool>,
+    trigger: Trigger,
+    tags: &[String],
+) -> Result<String> {
+    // Hold the lock from reading the pointer until it's saved
+    let _lock = lock::acquire(Resource::State)?;
+    let mut plan = plan_tagged(app_config, step, want_dark, tags)?;
+    let theme_entry = plan.theme().clone();
+
+    let mode = plan
+        .want_dark
+        .filter(|_| !matches!(step, Step::Goto(_)))
+        .map(|d| if d { "dark" } else { "light" });
+    let origin = history::Origin {
+        collection: Some(plan.collection.name.clone()),
+        trigger,
+        mode: mode.map(str::to_string),
+    };
+    write_entry(app_config, &theme_entry, origin)?;
+
+    plan.collection.current_index = plan.index;
+    collection::save_collection(&plan.collection)?;
+
+    if let Some((position, played)) = plan.rotation {
+        // Reload so settings changed while we were planning aren't lost
+        let mut config = collection::load_config();
+        if let Some(rot) = config.rotation.as_mut() {
+            rot.position = position;
+            rot.played = played;
+            collection::save_config(&config)?;
+        }
+    }
 
-    // Build a minimal GhosttyConfig to use with apply_theme.
-    // Only raw_config and title are used by apply_theme.
-    let ghost_config = GhosttyConfig {
+    let mode_label = mode.map(|m| format!(" [{}]", m)).unwrap_or_default();
+    if let Some(warning) = &plan.warning {
+        log::warn!("{}", warning);
+    }
+    let warning = plan
+        .warning
+        .map(|w| format!(" ({})", w))
+        .unwrap_or_default();
+    Ok(format!(
+        "Applied '{}' from '{}'{}{}",
+        theme_entry.title, plan.collection.name, mode_label, warning
+    ))
+}
+
+/// Apply the theme at `index` in the named collection and point the
+/// collection at it, whether or not it's the one cycling.
+pub fn apply_at(app_config: &AppConfig, coll_name: &str, index: usize) -> Result<String> {
+    let _lock = lock::acquire(Resource::State)?;
+    let mut coll = collection::load_collection(coll_name)?;
+    let theme_entry = coll.themes.get(index).cloned().ok_or_else(|| {
+        Error::Invalid(format!(
+            "No theme #{} in '{}' (it has {})",
+            index + 1,
+            coll.name,
+            coll.themes.len()
+        ))
+    })?;
+    let origin = history::Origin {
+        collection: Some(coll.name.clone()),
+        ..Default::default()
+    };
+    write_entry(app_config, &theme_entry, origin)?;
+    coll.current_index = index;
+    collection::save_collection(&coll)?;
+    Ok(format!(
+        "Applied '{}' from '{}'",
+        theme_entry.title, coll.name
+    ))
+}
+
+/// Write a collection entry to the Ghostty config, and to open terminals
+/// when live update is on.
+fn write_entry(
+    app_config: &AppConfig,
+    theme_entry: &CollectionTheme,
+    origin: history::Origin,
+) -> Result<()> {
+    config::apply_theme_from(&entry_config(theme_entry), ApplyScope::Colors, origin)?;
+    if app_config.live_update {
+        ghostty::broadcast_osc(&preview::osc_from_raw_config(&theme_entry.raw_config));
+    }
+    Ok(())
+}
+
+/// Apply a random theme from the named collection, leaving its pointer
+/// alone.
+pub fn apply_random(
+    app_config: &AppConfig,
+    coll_name: &str,
+    want_dark: Option<bool>,
+) -> Result<String> {
+    let coll = crate::smart::load_collection(coll_name)?;
+    let index = eligible_indices(&coll.themes, want_dark)
+        .and_then(|eligible| {
+            eligible
+                .choose_weighted(&mut rand::thread_rng(), |&i| coll.themes[i].weight.max(1))
+                .ok()
+                .copied()
+        })
+        .ok_or_else(|| {
+            Error::Invalid(match want_dark {
+                Some(dark) => format!(
+                    "No {} themes in '{}'",
+                    if dark { "dark" } else { "light" },
+                    coll.name
+                ),
+                None => format!("Collection '{}' is empty", coll.name),
+            })
+        })?;
+    let theme_entry = &coll.themes[index];
+
+    let origin = history::Origin {
+        collection: Some(coll.name.clone()),
+        ..Default::default()
+    };
+    write_entry(app_config, theme_entry, origin)?;
+
+    Ok(format!(
+        "Applied '{}' from '{}' (random)",
+        theme_entry.title, coll.name
+    ))
+}
+
+/// Where a forward step leaves the rotation, as `(position, played)`.
+/// Interleave moves to the next collection every time; sequence stays put
+/// until `current_len` themes of the current collection have played.
+fn advance_rotation(rotation: &Rotation, current_len: usize) -> (usize, usize) {
+    let len = rotation.collections.len();
+    let position = rotation.position % len;
+    match rotation.mode {
+        RotationMode::Interleave => ((position + 1) % len, 0),
+        RotationMode::Sequence if rotation.played >= current_len => ((position + 1) % len, 1),
+        RotationMode::Sequence => (position, rotation.played + 1),
+    }
+}
+
+/// Build a minimal GhosttyConfig to use with apply_theme.
+/// Only raw_config and title are used by apply_theme.
+pub(crate) fn entry_config(theme_entry: &CollectionTheme) -> GhosttyConfig {
+    GhosttyConfig {
         id: String::new(),
         slug: theme_entry.slug.clone(),
         title: theme_entry.title.clone(),
@@ -105,18 +558,575 @@ pub fn apply_next() -> Result<String, String> {
         vote_count: 0,
         view_count: 0,
         download_count: 0,
+        parsed: Default::default(),
+    }
+}
+
+/// Pick the index after (or before) `current` among `eligible`.
+/// Shuffle only applies going forward, favoring themes by their `weights`
+/// (indexed like the collection); stepping back is always sequential.
+fn step_index(
+    eligible: &[usize],
+    weights: &[u32],
+    current: usize,
+    order: &CycleOrder,
+    backward: bool,
+) -> usize {
+    let len = eligible.len();
+
+    // Find current position within eligible list
+    let current_pos = eligible.iter().position(|&i| i == current);
+
+    let next_pos = if backward {
+        match current_pos {
+            Some(pos) => (pos + len - 1) % len,
+            // Current theme isn't eligible: go to the nearest one before it
+            None => eligible
+                .iter()
+                .rposition(|&i| i < current)
+                .unwrap_or(len - 1),
+        }
+    } else {
+        let current_pos = current_pos.unwrap_or(0);
+        match order {
+            CycleOrder::Sequential => (current_pos + 1) % len,
+            // Bag draws go through draw_from_bag; without a bag it's a plain shuffle
+            CycleOrder::Shuffle | CycleOrder::Bag => {
+                let others: Vec<usize> = (0..len).filter(|&pos| pos != current_pos).collect();
+                others
+                    .choose_weighted(&mut rand::thread_rng(), |&pos| {
+                        weights.get(eligible[pos]).copied().unwrap_or(1).max(1)
+                    })
+                    .copied()
+                    .unwrap_or(0)
+            }
+        }
     };
 
-    config::apply_theme(&ghost_config)?;
+    eligible[next_pos]
+}
 
-    coll.current_index = next_index;
-    collection::save_collection(&coll)?;
+/// Take the next eligible slug from the persisted bag, refilling it with a
+/// fresh permutation of `eligible` once every eligible theme has played.
+/// A theme with weight n is dealt n times a round.
+/// Ineligible entries (e.g. light themes in dark mode) keep their place.
+fn draw_from_bag(
+    bag: &mut Vec<String>,
+    themes: &[CollectionTheme],
+    eligible: &[usize],
+    current: usize,
+) -> usize {
+    // Forget themes that have since been removed from the collection
+    bag.retain(|slug| themes.iter().any(|t| t.slug == *slug));
 
-    let mode_label = want_dark
-        .map(|d| if d { " [dark]" } else { " [light]" })
-        .unwrap_or("");
-    Ok(format!(
-        "Applied '{}' from '{}'{}",
-        theme_entry.title, coll_name, mode_label
-    ))
+    // A twin pair shares one place in the bag, under either half's slug
+    let is_half =
+        |i: usize, slug: &String| themes[i].slug == *slug || themes[i].twin.as_ref() == Some(slug);
+    let is_eligible = |slug: &String| eligible.iter().any(|&i| is_half(i, slug));
+
+    if !bag.iter().any(is_eligible) {
+        let mut round: Vec<String> = eligible
+            .iter()
+            .flat_map(|&i| {
+                std::iter::repeat_n(themes[i].slug.clone(), themes[i].weight.max(1) as usize)
+            })
+            .collect();
+        round.shuffle(&mut rand::thread_rng());
+        // Don't open the new round with the theme that closed the last one
+        if round.len() > 1 && themes.get(current).is_some_and(|t| t.slug == round[0]) {
+            let last = round.len() - 1;
+            round.swap(0, last);
+        }
+        bag.extend(round);
+    }
+
+    let pos = bag.iter().position(is_eligible).unwrap_or(0);
+    let slug = bag.remove(pos);
+    eligible
+        .iter()
+        .copied()
+        .find(|&i| is_half(i, &slug))
+        .unwrap_or(eligible[0])
+}
+
+/// The index of the twin of `current` when it matches `want_dark`.
+fn twin_index(
+    themes: &[CollectionTheme],
+    current: usize,
+    want_dark: Option<bool>,
+) -> Option<usize> {
+    let slug = themes.get(current)?.twin.as_ref()?;
+    themes
+        .iter()
+        .position(|t| t.slug == *slug && t.enabled && Some(t.is_dark) == want_dark)
+}
+
+/// Locate a goto target. Slugs match exactly first, then by title
+/// ignoring case.
+pub fn find_target(themes: &[CollectionTheme], target: &Target) -> Option<usize> {
+    match target {
+        Target::Index(n) => (*n >= 1 && *n <= themes.len()).then(|| n - 1),
+        Target::Slug(slug) => themes.iter().position(|t| t.slug == *slug).or_else(|| {
+            themes
+                .iter()
+                .position(|t| t.title.eq_ignore_ascii_case(slug))
+        }),
+    }
+}
+
+/// Where `current` sits among `eligible`: itself, or its twin when the half
+/// that was applied isn't eligible (say, after the mode flipped), so a pair
+/// moves on as one entry.
+fn pair_position(themes: &[CollectionTheme], current: usize, eligible: &[usize]) -> usize {
+    if eligible.contains(&current) {
+        return current;
+    }
+    themes
+        .get(current)
+        .and_then(|t| t.twin.as_ref())
+        .and_then(|twin| eligible.iter().copied().find(|&i| themes[i].slug == *twin))
+        .unwrap_or(current)
+}
+
+/// Whether the theme at `i` is the later-listed half of a twin pair.
+fn is_second_half(themes: &[CollectionTheme], i: usize) -> bool {
+    themes[i]
+        .twin
+        .as_ref()
+        .is_some_and(|twin| themes[..i].iter().any(|t| t.slug == *twin && t.enabled))
+}
+
+/// Locate a theme loosely: as `find_target` does, or else the one theme
+/// whose slug or title contains `target`, ignoring case.
+pub fn find_fuzzy(themes: &[CollectionTheme], target: &str) -> Result<usize> {
+    if let Some(index) = find_target(themes, &Target::parse(target)) {
+        return Ok(index);
+    }
+    let needle = target.trim().to_lowercase();
+    let matches: Vec<usize> = themes
+        .iter()
+        .enumerate()
+        .filter(|(_, t)| {
+            t.slug.to_lowercase().contains(&needle) || t.title.to_lowercase().contains(&needle)
+        })
+        .map(|(i, _)| i)
+        .collect();
+    match matches.as_slice() {
+        [index] => Ok(*index),
+        [] => Err(Error::not_found("Theme", target)),
+        several => Err(Error::Invalid(format!(
+            "'{}' matches several themes: {}",
+            target,
+            several
+                .iter()
+                .map(|&i| themes[i].slug.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        ))),
+    }
+}
+
+/// Indices of enabled themes matching `want_dark`, or of every enabled
+/// theme when no mode is requested. A twin pair counts once: with a mode
+/// its matching half is eligible, without one the half listed first.
+/// Returns `None` when a mode is requested but nothing matches.
+fn eligible_indices(themes: &[CollectionTheme], want_dark: Option<bool>) -> Option<Vec<usize>> {
+    match want_dark {
+        Some(dark) => {
+            let filtered: Vec<usize> = themes
+                .iter()
+                .enumerate()
+                .filter(|(_, t)| t.enabled && t.is_dark == dark)
+                .map(|(i, _)| i)
+                .collect();
+            if filtered.is_empty() {
+                None
+            } else {
+                Some(filtered)
+            }
+        }
+        None => Some(
+            (0..themes.len())
+                .filter(|&i| themes[i].enabled && !is_second_half(themes, i))
+                .collect(),
+        ),
+    }
+}
+
+/// The `eligible` themes carrying one of `tags`. A twin pair counts as
+/// tagged when either half is.
+fn filter_by_tags(themes: &[CollectionTheme], eligible: Vec<usize>, tags: &[String]) -> Vec<usize> {
+    eligible
+        .into_iter()
+        .filter(|&i| {
+            themes[i].has_any_tag(tags)
+                || themes[i].twin.as_ref().is_some_and(|twin| {
+                    themes
+                        .iter()
+                        .any(|t| t.slug == *twin && t.has_any_tag(tags))
+                })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(slug: &str, is_dark: bool) -> CollectionTheme {
+        CollectionTheme {
+            slug: slug.to_string(),
+            title: slug.to_string(),
+            is_dark,
+            raw_config: String::new(),
+            twin: None,
+            weight: 1,
+            enabled: true,
+            tags: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn plan_reports_an_ignored_mode_filter() {
+        let config = AppConfig {
+            active_collection: Some("light-only".to_string()),
+            ..Default::default()
+        };
+        let mut coll: Collection = serde_json::from_value(serde_json::json!({
+            "name": "light-only",
+            "themes": [],
+            "current_index": 0,
+            "order": "sequential",
+            "interval": null,
+        }))
+        .unwrap();
+        coll.themes = vec![entry("latte", false), entry("dawn", false)];
+        let mut load = |_: &str| Ok(coll.clone());
+
+        let plan = plan_with(&config, &Step::Next, Some(true), &[], &mut load).unwrap();
+        assert_eq!(
+            plan.warning.as_deref(),
+            Some("no dark themes in 'light-only', ignoring the mode filter")
+        );
+        let plan = plan_with(&config, &Step::Next, Some(false), &[], &mut load).unwrap();
+        assert_eq!(plan.warning, None);
+    }
+
+    #[test]
+    fn eligible_indices_no_mode_returns_all() {
+        let themes = vec![entry("a", true), entry("b", false)];
+        assert_eq!(eligible_indices(&themes, None), Some(vec![0, 1]));
+    }
+
+    #[test]
+    fn eligible_indices_filters_by_mode() {
+        let themes = vec![entry("a", true), entry("b", false), entry("c", true)];
+        assert_eq!(eligible_indices(&themes, Some(true)), Some(vec![0, 2]));
+        assert_eq!(eligible_indices(&themes, Some(false)), Some(vec![1]));
+    }
+
+    #[test]
+    fn eligible_indices_no_match_returns_none() {
+        let themes = vec![entry("a", true)];
+        assert_eq!(eligible_indices(&themes, Some(false)), None);
+    }
+
+    #[test]
+    fn twin_pairs_cycle_as_one_entry() {
+        let mut themes = vec![
+            entry("mocha", true),
+            entry("nord", true),
+            entry("latte", false),
+        ];
+        themes[0].twin = Some("latte".to_string());
+        themes[2].twin = Some("mocha".to_string());
+        assert_eq!(eligible_indices(&themes, None), Some(vec![0, 1]));
+        assert_eq!(eligible_indices(&themes, Some(false)), Some(vec![2]));
+
+        // Latte was applied in light mode; in dark mode the pair moves on
+        let eligible = eligible_indices(&themes, Some(true)).unwrap();
+        let current = pair_position(&themes, 2, &eligible);
+        assert_eq!(current, 0);
+        assert_eq!(
+            step_index(&eligible, &[], current, &CycleOrder::Sequential, false),
+            1
+        );
+
+        // A bag filled in light mode still counts the pair as played
+        let mut bag = vec!["latte".to_string()];
+        assert_eq!(draw_from_bag(&mut bag, &themes, &eligible, 1), 0);
+        assert!(bag.is_empty());
+    }
+
+    #[test]
+    fn find_fuzzy_needs_a_unique_match() {
+        let themes = vec![
+            entry("catppuccin-mocha", true),
+            entry("catppuccin-latte", false),
+            entry("nord", true),
+        ];
+        assert_eq!(find_fuzzy(&themes, "2").unwrap(), 1);
+        assert_eq!(find_fuzzy(&themes, "MOCHA").unwrap(), 0);
+        assert!(matches!(
+            find_fuzzy(&themes, "catppuccin"),
+            Err(Error::Invalid(_))
+        ));
+        assert!(matches!(
+            find_fuzzy(&themes, "gruvbox"),
+            Err(Error::NotFound { .. })
+        ));
+    }
+
+    #[test]
+    fn tag_filter_keeps_tagged_themes_and_their_twins() {
+        let mut themes = vec![
+            entry("a", true),
+            entry("b", true),
+            entry("c", false),
+            entry("d", true),
+        ];
+        themes[0].tags = vec!["Retro".to_string()];
+        themes[2].tags = vec!["retro".to_string()];
+        themes[3].twin = Some("c".to_string());
+        themes[2].twin = Some("d".to_string());
+        let tags = vec!["retro".to_string()];
+        assert_eq!(filter_by_tags(&themes, vec![0, 1, 3], &tags), vec![0, 3]);
+        assert!(filter_by_tags(&themes, vec![1], &tags).is_empty());
+    }
+
+    #[test]
+    fn disabled_themes_are_skipped() {
+        let mut themes = vec![entry("a", true), entry("b", true), entry("c", false)];
+        themes[1].enabled = false;
+        assert_eq!(eligible_indices(&themes, None), Some(vec![0, 2]));
+        assert_eq!(eligible_indices(&themes, Some(true)), Some(vec![0]));
+        themes[0].enabled = false;
+        assert_eq!(eligible_indices(&themes, Some(true)), None);
+    }
+
+    #[test]
+    fn weights_bias_shuffle_and_bag() {
+        let mut themes = vec![entry("a", true), entry("b", true), entry("c", true)];
+        themes[2].weight = 5;
+        let eligible = [0, 1, 2];
+        let weights: Vec<u32> = themes.iter().map(|t| t.weight).collect();
+        let heavy = (0..600)
+            .filter(|_| step_index(&eligible, &weights, 0, &CycleOrder::Shuffle, false) == 2)
+            .count();
+        // 5 in 6 when stepping from a; far from the unweighted half
+        assert!(heavy > 420, "picked the heavy theme {} of 600 times", heavy);
+
+        let mut bag = Vec::new();
+        draw_from_bag(&mut bag, &themes, &eligible, 0);
+        assert_eq!(bag.len(), 6);
+    }
+
+    #[test]
+    fn step_index_sequential_wraps() {
+        let eligible = [0, 1, 2];
+        assert_eq!(
+            step_index(&eligible, &[], 1, &CycleOrder::Sequential, false),
+            2
+        );
+        assert_eq!(
+            step_index(&eligible, &[], 2, &CycleOrder::Sequential, false),
+            0
+        );
+    }
+
+    #[test]
+    fn step_index_backward_wraps() {
+        let eligible = [0, 1, 2];
+        assert_eq!(
+            step_index(&eligible, &[], 1, &CycleOrder::Sequential, true),
+            0
+        );
+        assert_eq!(step_index(&eligible, &[], 0, &CycleOrder::Shuffle, true), 2);
+    }
+
+    #[test]
+    fn step_index_backward_from_ineligible_current() {
+        // Current theme 3 was filtered out; step back to the closest earlier one
+        let eligible = [0, 2, 5];
+        assert_eq!(
+            step_index(&eligible, &[], 3, &CycleOrder::Sequential, true),
+            2
+        );
+        assert_eq!(
+            step_index(&eligible, &[], 0, &CycleOrder::Sequential, true),
+            5
+        );
+    }
+
+    #[test]
+    fn step_index_shuffle_avoids_current() {
+        let eligible = [0, 1];
+        for _ in 0..20 {
+            assert_eq!(
+                step_index(&eligible, &[], 0, &CycleOrder::Shuffle, false),
+                1
+            );
+        }
+    }
+
+    #[test]
+    fn draw_from_bag_plays_each_theme_once_per_round() {
+        let themes = vec![entry("a", true), entry("b", true), entry("c", true)];
+        let eligible = [0, 1, 2];
+        let mut bag = Vec::new();
+        let mut current = 0;
+        let mut seen = Vec::new();
+        for _ in 0..3 {
+            current = draw_from_bag(&mut bag, &themes, &eligible, current);
+            seen.push(current);
+        }
+        seen.sort();
+        assert_eq!(seen, vec![0, 1, 2]);
+        assert!(bag.is_empty());
+
+        // Next draw starts a new round without repeating the last theme
+        let last = current;
+        let next = draw_from_bag(&mut bag, &themes, &eligible, current);
+        assert_ne!(next, last);
+        assert_eq!(bag.len(), 2);
+    }
+
+    #[test]
+    fn draw_from_bag_keeps_ineligible_entries() {
+        let themes = vec![entry("a", true), entry("b", false), entry("c", true)];
+        let mut bag = vec!["b".to_string(), "c".to_string()];
+        assert_eq!(draw_from_bag(&mut bag, &themes, &[0, 2], 0), 2);
+        assert_eq!(bag, vec!["b".to_string()]);
+    }
+
+    #[test]
+    fn draw_from_bag_drops_removed_themes() {
+        let themes = vec![entry("a", true), entry("b", true)];
+        let mut bag = vec!["gone".to_string(), "b".to_string()];
+        assert_eq!(draw_from_bag(&mut bag, &themes, &[0, 1], 0), 1);
+        assert!(bag.is_empty());
+    }
+
+    fn rotation(mode: RotationMode, position: usize, played: usize) -> Rotation {
+        Rotation {
+            collections: vec!["a".to_string(), "b".to_string(), "c".to_string()],
+            mode,
+            position,
+            played,
+        }
+    }
+
+    #[test]
+    fn advance_rotation_interleave_moves_every_step() {
+        assert_eq!(
+            advance_rotation(&rotation(RotationMode::Interleave, 0, 0), 5),
+            (1, 0)
+        );
+        assert_eq!(
+            advance_rotation(&rotation(RotationMode::Interleave, 2, 0), 5),
+            (0, 0)
+        );
+    }
+
+    #[test]
+    fn advance_rotation_sequence_finishes_collection_first() {
+        assert_eq!(
+            advance_rotation(&rotation(RotationMode::Sequence, 0, 0), 3),
+            (0, 1)
+        );
+        assert_eq!(
+            advance_rotation(&rotation(RotationMode::Sequence, 0, 2), 3),
+            (0, 3)
+        );
+        assert_eq!(
+            advance_rotation(&rotation(RotationMode::Sequence, 0, 3), 3),
+            (1, 1)
+        );
+        assert_eq!(
+            advance_rotation(&rotation(RotationMode::Sequence, 2, 3), 3),
+            (0, 1)
+        );
+    }
+
+    #[test]
+    fn advance_rotation_clamps_stale_position() {
+        assert_eq!(
+            advance_rotation(&rotation(RotationMode::Interleave, 7, 0), 1),
+            (2, 0)
+        );
+    }
+
+    #[test]
+    fn pending_round_trips() {
+        for pending in [Pending::Next, Pending::Dark, Pending::Light] {
+            assert_eq!(Pending::parse(pending.as_str()), Some(pending));
+        }
+        assert_eq!(Pending::parse("dark\n"), Some(Pending::Dark));
+        assert_eq!(Pending::parse("bogus"), None);
+    }
+
+    #[test]
+    fn pending_for_mode() {
+        assert_eq!(Pending::for_mode(Some(true)), Pending::Dark);
+        assert_eq!(Pending::for_mode(Some(false)), Pending::Light);
+        assert_eq!(Pending::for_mode(None), Pending::Next);
+    }
+
+    #[test]
+    fn pending_merge_keeps_mode_change() {
+        assert_eq!(Pending::Next.merge(Some(Pending::Dark)), Pending::Dark);
+        assert_eq!(Pending::Light.merge(Some(Pending::Dark)), Pending::Light);
+        assert_eq!(Pending::Next.merge(None), Pending::Next);
+    }
+
+    #[test]
+    fn twin_index_follows_link_in_wanted_mode() {
+        let mut themes = vec![
+            entry("night", true),
+            entry("day", false),
+            entry("noon", false),
+        ];
+        themes[0].twin = Some("day".to_string());
+        themes[1].twin = Some("night".to_string());
+        assert_eq!(twin_index(&themes, 0, Some(false)), Some(1));
+        assert_eq!(twin_index(&themes, 1, Some(true)), Some(0));
+        // Twin exists but is the wrong mode, or no mode was asked for
+        assert_eq!(twin_index(&themes, 0, Some(true)), None);
+        assert_eq!(twin_index(&themes, 0, None), None);
+        // No twin at all
+        assert_eq!(twin_index(&themes, 2, Some(true)), None);
+    }
+
+    #[test]
+    fn target_parse_index_and_slug() {
+        assert_eq!(Target::parse("3"), Target::Index(3));
+        assert_eq!(Target::parse("nord"), Target::Slug("nord".to_string()));
+    }
+
+    #[test]
+    fn find_target_by_index_is_one_based() {
+        let themes = vec![entry("a", true), entry("b", false)];
+        assert_eq!(find_target(&themes, &Target::Index(1)), Some(0));
+        assert_eq!(find_target(&themes, &Target::Index(2)), Some(1));
+        assert_eq!(find_target(&themes, &Target::Index(0)), None);
+        assert_eq!(find_target(&themes, &Target::Index(3)), None);
+    }
+
+    #[test]
+    fn find_target_by_slug_or_title() {
+        let mut themes = vec![entry("nord", true), entry("paper", false)];
+        themes[1].title = "Paper Light".to_string();
+        assert_eq!(
+            find_target(&themes, &Target::Slug("paper".to_string())),
+            Some(1)
+        );
+        assert_eq!(
+            find_target(&themes, &Target::Slug("paper light".to_string())),
+            Some(1)
+        );
+        assert_eq!(
+            find_target(&themes, &Target::Slug("nope".to_string())),
+            None
+        );
+    }
 }
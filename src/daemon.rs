@@ -1,137 +1,177 @@
 use std::fs;
-use std::sync::mpsc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
 use std::thread;
 use std::time::{Duration, Instant};
 
-use nix::sys::signal::{self, Signal};
-use nix::unistd::Pid;
+use log::Level;
+use rand::Rng;
+use serde::Serialize;
 
+use crate::atomic;
 use crate::collection;
+use crate::config::{self, ApplyScope};
+use crate::control::{self, Control};
 use crate::cycling;
 use crate::darkmode;
+use crate::error::{Error, Result};
+use crate::history;
+use crate::logging;
+use crate::notification;
+use crate::process;
+use crate::schedule;
+use crate::sync;
 
-/// Parse an interval string like "30m", "1h", "90s" into a `Duration`.
-fn parse_interval(s: &str) -> Result<Duration, String> {
-    let s = s.trim();
-    if s.is_empty() {
-        return Err("Interval string is empty".to_string());
-    }
-
-    let (num_str, suffix) = if let Some(stripped) = s.strip_suffix('h') {
-        (stripped, "h")
-    } else if let Some(stripped) = s.strip_suffix('m') {
-        (stripped, "m")
-    } else if let Some(stripped) = s.strip_suffix('s') {
-        (stripped, "s")
-    } else {
-        return Err(format!(
-            "Invalid interval '{}': must end with 's', 'm', or 'h'",
-            s
-        ));
-    };
-
-    let value: u64 = num_str
-        .parse()
-        .map_err(|_| format!("Invalid interval '{}': could not parse number", s))?;
-
-    if value == 0 {
-        return Err("Interval must be greater than zero".to_string());
-    }
+/// Report on the daemon's stderr and in the log file. Detached, stderr is
+/// `daemon.log`, so lines carry a timestamp and level for `cycle logs`.
+macro_rules! report {
+    ($level:expr, $($arg:tt)+) => {{
+        let msg = format!($($arg)+);
+        if DETACHED.load(Ordering::Relaxed) {
+            eprintln!("{}", logging::daemon_line($level, &msg));
+        } else {
+            eprintln!("[daemon] {}", msg);
+        }
+        log::log!($level, "{}", msg);
+    }};
+}
 
-    let secs = match suffix {
-        "s" => value,
-        "m" => value * 60,
-        "h" => value * 3600,
-        _ => unreachable!(),
-    };
+/// Set in the background process of `cycle start --detach`.
+static DETACHED: AtomicBool = AtomicBool::new(false);
 
-    Ok(Duration::from_secs(secs))
-}
+/// Set by `cycle start --notify`, on top of `notify_on_switch`.
+static NOTIFY: AtomicBool = AtomicBool::new(false);
 
-/// Check whether a process with the given PID is alive.
-fn is_process_alive(pid: i32) -> bool {
-    signal::kill(Pid::from_raw(pid), None).is_ok()
-}
+/// How long `cycle stop` waits for the daemon to exit.
+const STOP_TIMEOUT: Duration = Duration::from_secs(3);
 
-/// Start the cycling daemon as a foreground process.
-pub fn start() -> Result<(), String> {
+/// Start the cycling daemon. It runs in the foreground unless `detach`
+/// is set, in which case it moves to the background and this returns.
+pub fn start(detach: bool, notify: bool, restore: bool) -> Result<()> {
+    NOTIFY.store(notify, Ordering::Relaxed);
     let pid_file = collection::pid_path();
 
     // Check for existing daemon
     if pid_file.exists() {
-        let contents =
-            fs::read_to_string(&pid_file).map_err(|e| format!("Failed to read PID file: {}", e))?;
-        let existing_pid: i32 = contents
-            .trim()
-            .parse()
-            .map_err(|_| "Corrupt PID file".to_string())?;
-
-        if is_process_alive(existing_pid) {
-            return Err(format!(
+        let existing_pid = read_pid(&pid_file)?;
+
+        // On Windows the detached copy finds the PID its parent wrote for it
+        if existing_pid != std::process::id() as i32 && process::is_alive(existing_pid) {
+            return Err(Error::Invalid(format!(
                 "Daemon is already running (PID {}). Stop it first with: ghostty-styles cycle stop",
                 existing_pid
-            ));
+            )));
         }
 
         // Stale PID file, remove it
         let _ = fs::remove_file(&pid_file);
     }
 
-    // Load active collection and verify interval
-    let app_config = collection::load_config();
-    let coll_name = app_config
-        .active_collection
-        .ok_or("No active collection. Run: ghostty-styles collection use <name>")?;
+    // Load today's collection and verify interval
+    let mut app_config = collection::load_config();
+    let coll_name = schedule::collection_for_today(&app_config).ok_or_else(|| {
+        Error::Invalid(
+            "No active collection. Run: ghostty-styles collection use <name>".to_string(),
+        )
+    })?;
 
+    // As it is on disk: refreshing a smart collection starts the network
+    // runtime, whose threads a fork wouldn't carry over to the daemon
     let coll = collection::load_collection(&coll_name)?;
 
-    let interval_str = coll.interval.as_deref().ok_or(format!(
-        "Collection '{}' has no interval set. Set one before starting the daemon.",
-        coll_name
-    ))?;
+    let (mut timing, timing_label) = resolve_timing(&app_config)?.ok_or_else(|| {
+        Error::Invalid(format!(
+            "Collection '{}' has no interval or schedule set. Set one before starting the daemon.",
+            coll_name
+        ))
+    })?;
 
-    let interval = parse_interval(interval_str)?;
+    let no_themes = || Error::Invalid(format!("Collection '{}' has no themes", coll_name));
+    // A smart collection may only have themes once its query runs
+    if coll.themes.is_empty() && coll.smart.is_none() {
+        return Err(no_themes());
+    }
+    if let Some(jitter) = &app_config.jitter {
+        parse_jitter(jitter)?;
+    }
 
-    if coll.themes.is_empty() {
-        return Err(format!("Collection '{}' has no themes", coll_name));
+    collection::ensure_dirs()?;
+    if detach {
+        if let Some(child) = detach_to_log()? {
+            atomic::write(&pid_file, child.to_string())
+                .map_err(|e| Error::io("write", &pid_file, e))?;
+            println!(
+                "Daemon started in the background (PID {}), logging to {}",
+                child,
+                collection::daemon_log_path().display()
+            );
+            return Ok(());
+        }
+    }
+    if crate::smart::load_collection(&coll_name)?.themes.is_empty() {
+        return Err(no_themes());
     }
 
     // Write PID file
-    collection::ensure_dirs()?;
     let my_pid = std::process::id();
-    fs::write(&pid_file, my_pid.to_string())
-        .map_err(|e| format!("Failed to write PID file: {}", e))?;
+    atomic::write(&pid_file, my_pid.to_string()).map_err(|e| Error::io("write", &pid_file, e))?;
+
+    // Everything that can wake the daemon early arrives on one channel
+    let (wake_tx, wake_rx) = mpsc::channel();
+    // Shutdown waits for the loop, so it never cuts a write short. Watched
+    // before any other thread starts, so every thread leaves signals to it.
+    let shutdown_tx = wake_tx.clone();
+    if let Err(e) = process::watch_shutdown(move |name| {
+        let _ = shutdown_tx.send(Wake::Shutdown(name));
+    }) {
+        report!(Level::Warn, "{}", e);
+    }
+    let restore_to = (restore || app_config.restore_on_exit)
+        .then(theme_before_start)
+        .flatten();
 
-    let mode_label = app_config
-        .mode_preference
-        .as_ref()
+    let mode_label = schedule::mode_config(&app_config)
+        .effective_mode()
         .map(|p| format!(", mode: {}", p.label()))
         .unwrap_or_default();
-    println!(
-        "Daemon started (PID {}) — collection '{}', interval {}{}",
-        my_pid, coll_name, interval_str, mode_label
+    report!(
+        Level::Info,
+        "Daemon started (PID {}) — collection '{}', {}{}",
+        my_pid,
+        coll_name,
+        timing_label,
+        mode_label
     );
 
-    // Spawn OS mode watcher if auto-os
-    let watcher_rx: Option<mpsc::Receiver<bool>> =
-        if app_config.mode_preference == Some(collection::ModePreference::AutoOs) {
-            Some(darkmode::spawn_watcher())
-        } else {
-            None
-        };
+    let mut os_watcher_running = false;
+    if follows_os(&app_config) {
+        // Started even under an override, so it's ready when the override ends
+        spawn_os_watcher(wake_tx.clone());
+        os_watcher_running = true;
+    }
+    let _file_watcher = watch_settings(wake_tx.clone());
+    let control_tx = wake_tx.clone();
+    if let Err(e) = control::listen(move |request| control_tx.send(Wake::Control(request)).is_ok())
+    {
+        report!(Level::Warn, "cycle pause/resume/skip won't work: {}", e);
+    }
+    // Set by `cycle pause`: only switches asked for by hand happen
+    let mut paused = false;
+    let mut last_os_dark = darkmode::detect_current();
+    let mut settings = Settings::current();
 
-    let mut next_cycle = Instant::now() + interval;
+    let mut next_cycle = timing.next_from(Instant::now(), &app_config);
 
     // For auto-time, calculate next boundary
-    let mut next_boundary: Option<Instant> =
-        if app_config.mode_preference == Some(collection::ModePreference::AutoTime) {
-            darkmode::seconds_until_boundary(&app_config.dark_after, &app_config.light_after)
-                .map(|s| Instant::now() + Duration::from_secs(s))
-        } else {
-            None
-        };
+    let mut next_boundary = auto_time_boundary(&app_config);
+    let mut boundary_mode = next_boundary_mode(&app_config);
+
+    let mut override_end = override_expiry(&app_config);
+
+    let mut written_next: Option<u64> = None;
 
     loop {
+        rotate_log();
         let now = Instant::now();
         let mut sleep_dur = next_cycle.saturating_duration_since(now);
 
@@ -139,134 +179,1056 @@ pub fn start() -> Result<(), String> {
             let boundary_dur = boundary.saturating_duration_since(now);
             sleep_dur = sleep_dur.min(boundary_dur);
         }
+        if let Some(end) = override_end {
+            sleep_dur = sleep_dur.min(end.saturating_duration_since(now));
+        }
 
-        // Sleep, but wake up for watcher events
-        let triggered_by_watcher = if let Some(ref rx) = watcher_rx {
-            rx.recv_timeout(sleep_dur).is_ok()
-        } else {
-            thread::sleep(sleep_dur);
-            false
+        // Let `cycle status` and the TUI show a countdown
+        let next_at = darkmode::unix_now() + sleep_dur.as_secs();
+        if written_next != Some(next_at) {
+            let _ = atomic::write(&collection::next_switch_path(), next_at.to_string());
+            written_next = Some(next_at);
+        }
+
+        // Sleep, but wake up for watcher events. We hold a sender ourselves,
+        // so the channel never disconnects.
+        let wake = match wake_rx.recv_timeout(sleep_dur) {
+            Ok(wake) => Some(wake),
+            Err(RecvTimeoutError::Timeout) | Err(RecvTimeoutError::Disconnected) => None,
         };
 
         let now = Instant::now();
 
-        if triggered_by_watcher {
-            eprintln!("[daemon] OS dark mode changed, switching theme");
-            match cycling::apply_next() {
-                Ok(msg) => eprintln!("[daemon] {}", msg),
-                Err(e) => eprintln!("[daemon] Error: {}", e),
+        match wake {
+            Some(Wake::OsMode(is_dark)) if last_os_dark != Some(is_dark) => {
+                last_os_dark = Some(is_dark);
+                let config = collection::load_config();
+                if schedule::mode_config(&config).effective_mode()
+                    != Some(collection::ModePreference::AutoOs)
+                {
+                    // A temporary override is holding the mode
+                    continue;
+                }
+                report!(
+                    Level::Info,
+                    "OS switched to {} mode, switching theme",
+                    if is_dark { "dark" } else { "light" }
+                );
+                switch_unless_paused(paused, &config, cycling::Pending::for_mode(Some(is_dark)));
+                timing = current_timing(timing);
+                next_cycle = timing.next_from(now, &collection::load_config());
+                continue;
+            }
+            Some(Wake::Shutdown(name)) => {
+                report!(Level::Info, "Got {}, shutting down", name);
+                if let Some(theme) = &restore_to {
+                    let origin = history::Origin {
+                        trigger: history::Trigger::Rollback,
+                        ..Default::default()
+                    };
+                    match config::apply_theme_from(theme, ApplyScope::Colors, origin) {
+                        Ok(_) => report!(Level::Info, "Restored '{}'", theme.title),
+                        Err(e) => report!(Level::Warn, "Couldn't restore '{}': {}", theme.title, e),
+                    }
+                }
+                clean_up(my_pid);
+                return Ok(());
+            }
+            Some(Wake::Control((control, reply))) => {
+                let answer = match control {
+                    Control::Pause if paused => "Already paused".to_string(),
+                    Control::Pause => {
+                        paused = true;
+                        report!(Level::Info, "Paused by `cycle pause`");
+                        "Paused automatic switching".to_string()
+                    }
+                    Control::Resume if !paused => "Not paused".to_string(),
+                    Control::Resume => {
+                        paused = false;
+                        report!(Level::Info, "Resumed by `cycle resume`");
+                        "Resumed automatic switching".to_string()
+                    }
+                    Control::Skip => {
+                        let config = collection::load_config();
+                        let result = cycling::switch(&config, cycling::Pending::Next);
+                        timing = current_timing(timing);
+                        next_cycle = timing.next_from(now, &config);
+                        match result {
+                            Ok(msg) => {
+                                report!(Level::Info, "Skipped ahead: {}", msg);
+                                announce(&msg);
+                                msg
+                            }
+                            Err(e) => {
+                                report!(Level::Warn, "Error: {}", e);
+                                format!("Error: {}", e)
+                            }
+                        }
+                    }
+                    Control::Status if paused => "paused".to_string(),
+                    Control::Status => "running".to_string(),
+                    Control::Shutdown => {
+                        let _ = wake_tx.send(Wake::Shutdown("`cycle stop`"));
+                        "Stopping".to_string()
+                    }
+                };
+                let _ = reply.send(answer);
+                continue;
+            }
+            Some(Wake::SettingsChanged) => {
+                let latest = Settings::current();
+                let changes = settings.changes_to(&latest);
+                settings = latest;
+                // Our own writes (pointer moves, rotation) change nothing here
+                if changes.is_empty() {
+                    continue;
+                }
+                report!(Level::Info, "Settings changed: {}", changes.join(", "));
+                app_config = collection::load_config();
+                timing = current_timing(timing);
+                next_cycle = timing.next_from(now, &app_config);
+                next_boundary = auto_time_boundary(&app_config);
+                boundary_mode = next_boundary_mode(&app_config);
+                override_end = override_expiry(&app_config);
+                if !os_watcher_running && follows_os(&app_config) {
+                    spawn_os_watcher(wake_tx.clone());
+                    os_watcher_running = true;
+                }
+                continue;
+            }
+            _ => {}
+        }
+
+        if let Some(boundary) = next_boundary {
+            if now >= boundary {
+                // Decide the mode from the boundary we were scheduled for,
+                // so waking a moment early or late can't pick the old one.
+                let is_dark = boundary_mode;
+                report!(
+                    Level::Info,
+                    "Time boundary crossed, switching to {} themes",
+                    if is_dark == Some(true) {
+                        "dark"
+                    } else {
+                        "light"
+                    }
+                );
+                switch_unless_paused(
+                    paused,
+                    &collection::load_config(),
+                    cycling::Pending::for_mode(is_dark),
+                );
+                timing = current_timing(timing);
+                next_cycle = timing.next_from(now, &collection::load_config());
+                boundary_mode = next_boundary_mode(&app_config);
+                next_boundary = auto_time_boundary(&app_config);
+                continue;
             }
         }
 
+        if override_end.is_some_and(|end| now >= end) {
+            override_end = None;
+            app_config = collection::load_config();
+            let back_to = schedule::mode_config(&app_config).effective_mode();
+            report!(
+                Level::Info,
+                "Mode override ended, back to {}",
+                back_to.as_ref().map_or("off", |p| p.label())
+            );
+            let want_dark = cycling::resolve_want_dark(&app_config);
+            if want_dark.is_some() {
+                switch_unless_paused(paused, &app_config, cycling::Pending::for_mode(want_dark));
+            }
+            next_boundary = auto_time_boundary(&app_config);
+            boundary_mode = next_boundary_mode(&app_config);
+            continue;
+        }
+
         if now >= next_cycle {
-            match cycling::apply_next() {
-                Ok(msg) => eprintln!("[daemon] {}", msg),
-                Err(e) => eprintln!("[daemon] Error: {}", e),
+            log::debug!("Cycle tick");
+            let config = collection::load_config();
+            match pause_reason(&config) {
+                Some(reason) => report!(Level::Info, "Paused ({}), skipping this cycle", reason),
+                None => switch_unless_paused(paused, &config, cycling::Pending::Next),
             }
-            next_cycle = now + interval;
+            timing = current_timing(timing);
+            next_cycle = timing.next_from(now, &collection::load_config());
+            // A rotation may have moved on to a collection with its own mode
+            let latest = collection::load_config();
+            next_boundary = auto_time_boundary(&latest);
+            boundary_mode = next_boundary_mode(&latest);
         }
+    }
+}
 
-        if let Some(boundary) = next_boundary {
-            if now >= boundary {
-                eprintln!("[daemon] Time boundary crossed, switching theme");
-                match cycling::apply_next() {
-                    Ok(msg) => eprintln!("[daemon] {}", msg),
-                    Err(e) => eprintln!("[daemon] Error: {}", e),
-                }
-                next_boundary = darkmode::seconds_until_boundary(
-                    &app_config.dark_after,
-                    &app_config.light_after,
+/// Move to the background with stdout and stderr appended to the daemon
+/// log. Returns the background process's PID here and `None` in that
+/// process, which carries on as the daemon.
+fn detach_to_log() -> Result<Option<i32>> {
+    let log_path = collection::daemon_log_path();
+    logging::rotate(&log_path);
+    let child = process::detach(open_log(&log_path)?)?;
+    if child.is_none() {
+        DETACHED.store(true, Ordering::Relaxed);
+    }
+    Ok(child)
+}
+
+fn open_log(path: &std::path::Path) -> Result<fs::File> {
+    fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| Error::io("open", path, e))
+}
+
+/// Start `daemon.log` over once it's past the size limit, pointing stdout
+/// and stderr at the new file. Checked each time the loop comes round, so
+/// a daemon left running for weeks doesn't grow it without end.
+fn rotate_log() {
+    if !DETACHED.load(Ordering::Relaxed) {
+        return;
+    }
+    let path = collection::daemon_log_path();
+    if !logging::rotate(&path) {
+        return;
+    }
+    match open_log(&path).and_then(process::redirect_output) {
+        Ok(()) => report!(Level::Info, "Started a new log"),
+        Err(e) => log::warn!("Couldn't reopen the daemon log: {}", e),
+    }
+}
+
+/// Why the daemon woke before its next planned switch.
+enum Wake {
+    /// The OS appearance watcher reported dark (`true`) or light.
+    OsMode(bool),
+    /// `config.json` or a collection file was written.
+    SettingsChanged,
+    /// A message from `cycle pause`, `cycle resume`, or `cycle skip`.
+    Control(control::Request),
+    /// `cycle stop`, Ctrl+C, or the like, named for the log.
+    Shutdown(&'static str),
+}
+
+/// The theme in the Ghostty config as the daemon starts, to put back when
+/// it stops.
+fn theme_before_start() -> Option<crate::theme::GhosttyConfig> {
+    let mut theme = sync::current_theme().ok()?;
+    if theme.raw_config.is_empty() {
+        return None;
+    }
+    if let Some(applied) = collection::load_applied() {
+        theme.slug = applied.slug;
+        theme.title = applied.title;
+    }
+    Some(theme)
+}
+
+/// Remove the files that mark a running daemon. The PID file only goes if
+/// it's still ours.
+fn clean_up(pid: u32) {
+    let pid_file = collection::pid_path();
+    if read_pid(&pid_file).is_ok_and(|p| p == pid as i32) {
+        let _ = fs::remove_file(&pid_file);
+    }
+    let _ = fs::remove_file(collection::next_switch_path());
+    let _ = fs::remove_file(collection::socket_path());
+}
+
+/// Forward OS appearance changes onto the daemon's wake channel.
+fn spawn_os_watcher(tx: mpsc::Sender<Wake>) {
+    let rx = darkmode::spawn_watcher();
+    thread::spawn(move || {
+        for is_dark in rx {
+            if tx.send(Wake::OsMode(is_dark)).is_err() {
+                return;
+            }
+        }
+        report!(
+            Level::Warn,
+            "OS dark mode watcher exited, falling back to interval only"
+        );
+    });
+}
+
+/// Watch `config.json` and the collections directory. The returned
+/// watcher must be kept alive for events to keep arriving.
+fn watch_settings(tx: mpsc::Sender<Wake>) -> Option<notify::RecommendedWatcher> {
+    use notify::{EventKind, RecursiveMode, Watcher};
+
+    let collections_dir = collection::collections_dir();
+    let handler = move |res: notify::Result<notify::Event>| {
+        let Ok(event) = res else { return };
+        if !matches!(
+            event.kind,
+            EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)
+        ) {
+            return;
+        }
+        let relevant = event.paths.iter().any(|p| {
+            p.file_name().is_some_and(|n| n == "config.json")
+                || (p.parent() == Some(collections_dir.as_path())
+                    && p.extension().is_some_and(|e| e == "json"))
+        });
+        if relevant {
+            let _ = tx.send(Wake::SettingsChanged);
+        }
+    };
+
+    let mut watcher = match notify::recommended_watcher(handler) {
+        Ok(w) => w,
+        Err(e) => {
+            report!(Level::Warn, "Can't watch settings for changes: {}", e);
+            return None;
+        }
+    };
+    for dir in [collection::base_dir(), collection::collections_dir()] {
+        if let Err(e) = watcher.watch(&dir, RecursiveMode::NonRecursive) {
+            report!(Level::Warn, "Can't watch {}: {}", dir.display(), e);
+        }
+    }
+    Some(watcher)
+}
+
+/// The settings the daemon acts on, compared across file changes so it
+/// can log what was edited.
+#[derive(Debug, Clone, PartialEq)]
+struct Settings {
+    collection: Option<String>,
+    themes: Option<usize>,
+    timing: Option<String>,
+    mode: Option<String>,
+    override_until: Option<u64>,
+    dark_after: String,
+    light_after: String,
+}
+
+impl Settings {
+    fn current() -> Self {
+        let config = collection::load_config();
+        let collection = schedule::collection_for_today(&config);
+        let themes = collection
+            .as_deref()
+            .and_then(|name| collection::load_collection(name).ok())
+            .map(|c| c.themes.len());
+        let timing = match resolve_timing(&config) {
+            Ok(Some((_, label))) => Some(label),
+            Ok(None) => None,
+            Err(e) => Some(format!("invalid ({})", e)),
+        };
+        let mode_config = schedule::mode_config(&config);
+        let (dark_after, light_after) = mode_config
+            .effective_mode()
+            .and_then(|pref| darkmode::switch_times(&pref, &mode_config))
+            .unwrap_or_else(|| {
+                (
+                    mode_config.dark_after.clone(),
+                    mode_config.light_after.clone(),
                 )
-                .map(|s| Instant::now() + Duration::from_secs(s));
+            });
+        Self {
+            collection,
+            themes,
+            timing,
+            mode: mode_config.effective_mode().map(|m| m.label().to_string()),
+            override_until: config.mode_override.as_ref().map(|o| o.until),
+            dark_after,
+            light_after,
+        }
+    }
+
+    /// Human-readable differences from `self` to `new`.
+    fn changes_to(&self, new: &Settings) -> Vec<String> {
+        fn show<T: ToString>(v: &Option<T>) -> String {
+            v.as_ref()
+                .map(|v| v.to_string())
+                .unwrap_or_else(|| "none".to_string())
+        }
+        let mut changes = Vec::new();
+        if self.collection != new.collection {
+            changes.push(format!(
+                "collection {} → {}",
+                show(&self.collection),
+                show(&new.collection)
+            ));
+        } else if self.themes != new.themes {
+            changes.push(format!(
+                "themes {} → {}",
+                show(&self.themes),
+                show(&new.themes)
+            ));
+        }
+        if self.timing != new.timing {
+            changes.push(format!("{} → {}", show(&self.timing), show(&new.timing)));
+        }
+        if self.mode != new.mode {
+            changes.push(format!("mode {} → {}", show(&self.mode), show(&new.mode)));
+        } else if self.override_until != new.override_until {
+            changes.push("mode override".to_string());
+        }
+        if (&self.dark_after, &self.light_after) != (&new.dark_after, &new.light_after) {
+            changes.push(format!(
+                "dark after {} / light after {}",
+                new.dark_after, new.light_after
+            ));
+        }
+        changes
+    }
+}
+
+/// When a temporary mode override runs out.
+fn override_expiry(config: &collection::AppConfig) -> Option<Instant> {
+    let now = darkmode::unix_now();
+    let end = config.active_override(now)?.until;
+    Some(Instant::now() + Duration::from_secs(end - now))
+}
+
+/// Whether the mode in force, global or the cycling collection's, follows
+/// the OS appearance.
+fn follows_os(config: &collection::AppConfig) -> bool {
+    schedule::mode_config(config).mode_preference == Some(collection::ModePreference::AutoOs)
+}
+
+/// When a time-based mode is on, the instant of the next dark/light boundary.
+fn auto_time_boundary(config: &collection::AppConfig) -> Option<Instant> {
+    let config = &schedule::mode_config(config);
+    let (dark_after, light_after) = darkmode::switch_times(&config.effective_mode()?, config)?;
+    darkmode::seconds_until_boundary(&dark_after, &light_after)
+        .map(|s| Instant::now() + Duration::from_secs(s))
+}
+
+/// The mode the next time-based boundary switches to.
+fn next_boundary_mode(config: &collection::AppConfig) -> Option<bool> {
+    let config = &schedule::mode_config(config);
+    let (dark_after, light_after) = darkmode::switch_times(&config.effective_mode()?, config)?;
+    darkmode::mode_after_next_boundary(&dark_after, &light_after)
+}
+
+/// When the daemon's regular (non mode-driven) switches happen.
+#[derive(Clone)]
+enum Timing {
+    Every(Duration),
+    Cron(schedule::CronSchedule),
+}
+
+impl Timing {
+    /// The next switch after `now`, moved by a random amount up to the
+    /// configured jitter either way.
+    fn next_from(&self, now: Instant, config: &collection::AppConfig) -> Instant {
+        now + self.wait(self.jitter(config), darkmode::unix_now())
+    }
+
+    /// How long from `unix_now` until the next switch. A jittered cron
+    /// switch never fires before `unix_now`: slots closer than `jitter` are
+    /// skipped, so one fired early isn't fired again on time.
+    fn wait(&self, jitter: Duration, unix_now: u64) -> Duration {
+        match self {
+            Timing::Every(interval) => apply_jitter(*interval, jitter),
+            Timing::Cron(cron) => {
+                // A valid expression fires within a year; retry daily if not.
+                let secs = cron
+                    .seconds_until_next(unix_now + jitter.as_secs())
+                    .unwrap_or(86_400);
+                apply_jitter(Duration::from_secs(secs) + jitter, jitter)
+            }
+        }
+    }
+
+    /// `jitter` from the config, or the deprecated `interval_jitter`
+    /// percentage of an interval.
+    fn jitter(&self, config: &collection::AppConfig) -> Duration {
+        match (&config.jitter, self) {
+            (Some(jitter), _) => parse_jitter(jitter).unwrap_or_else(|e| {
+                report!(Level::Warn, "{}, switching without jitter", e);
+                Duration::ZERO
+            }),
+            (None, Timing::Every(interval)) => {
+                interval.mul_f64(f64::from(config.interval_jitter.min(100)) / 100.0)
             }
+            (None, Timing::Cron(_)) => Duration::ZERO,
         }
     }
 }
 
-/// Stop a running daemon by sending SIGTERM.
-pub fn stop() -> Result<(), String> {
-    let pid_file = collection::pid_path();
+fn parse_jitter(jitter: &str) -> Result<Duration> {
+    schedule::parse_interval(jitter)
+        .map_err(|e| Error::Invalid(format!("Invalid jitter '{}': {}", jitter, e)))
+}
 
-    if !pid_file.exists() {
-        return Err("No daemon is running (PID file not found)".to_string());
+/// Move `wait` by a random amount up to `jitter` either way, never going
+/// below one second.
+fn apply_jitter(wait: Duration, jitter: Duration) -> Duration {
+    if jitter.is_zero() {
+        return wait;
+    }
+    let spread = jitter.as_secs_f64();
+    let offset = rand::thread_rng().gen_range(-spread..=spread);
+    Duration::from_secs_f64((wait.as_secs_f64() + offset).max(1.0))
+}
+
+/// Today's timing with a label for messages. A collection's cron schedule
+/// takes precedence over its interval. `Ok(None)` means neither is set.
+fn resolve_timing(config: &collection::AppConfig) -> Result<Option<(Timing, String)>> {
+    if let Some(expr) = schedule::schedule_for_today(config) {
+        let cron = schedule::CronSchedule::parse(&expr).map_err(Error::Invalid)?;
+        return Ok(Some((Timing::Cron(cron), format!("schedule '{}'", expr))));
+    }
+    match schedule::interval_for_today(config) {
+        Some(interval) => Ok(Some((
+            Timing::Every(schedule::parse_interval(&interval).map_err(Error::Invalid)?),
+            format!("interval {}", interval),
+        ))),
+        None => Ok(None),
+    }
+}
+
+/// Today's timing, re-resolved each tick so weekday rules and edits to
+/// the collection take effect. Falls back to `fallback` if it's unusable.
+fn current_timing(fallback: Timing) -> Timing {
+    match resolve_timing(&collection::load_config()) {
+        Ok(Some((timing, _))) => timing,
+        Ok(None) => fallback,
+        Err(e) => {
+            report!(Level::Warn, "{}, keeping previous timing", e);
+            fallback
+        }
+    }
+}
+
+/// Why interval cycling is currently paused, if it is.
+/// Mode-driven switches (OS flips, time boundaries) are not paused.
+pub fn pause_reason(config: &collection::AppConfig) -> Option<String> {
+    let now = darkmode::local_seconds_now() / 60;
+    for window in &config.pause_windows {
+        let start = darkmode::parse_hhmm(&window.start);
+        let end = darkmode::parse_hhmm(&window.end);
+        if let (Some(start), Some(end)) = (start, end) {
+            if in_window(now, start, end) {
+                return Some(format!("quiet window {}-{}", window.start, window.end));
+            }
+        }
+    }
+    if config.pause_on_battery && on_battery() {
+        return Some("on battery".to_string());
+    }
+    None
+}
+
+/// Whether `now` falls in the `start..end` window (minutes since midnight),
+/// wrapping past midnight when `end` is before `start`.
+fn in_window(now: u32, start: u32, end: u32) -> bool {
+    if start <= end {
+        now >= start && now < end
+    } else {
+        now >= start || now < end
+    }
+}
+
+/// Whether the machine is running on battery power. Unknown counts as not.
+#[cfg(target_os = "linux")]
+fn on_battery() -> bool {
+    let Ok(entries) = fs::read_dir("/sys/class/power_supply") else {
+        return false;
+    };
+    let mut saw_mains = false;
+    let mut discharging = false;
+    for entry in entries.filter_map(|e| e.ok()) {
+        let path = entry.path();
+        let kind = fs::read_to_string(path.join("type")).unwrap_or_default();
+        match kind.trim() {
+            "Mains" => {
+                saw_mains = true;
+                let online = fs::read_to_string(path.join("online")).unwrap_or_default();
+                if online.trim() == "1" {
+                    return false;
+                }
+            }
+            "Battery" => {
+                let status = fs::read_to_string(path.join("status")).unwrap_or_default();
+                discharging |= status.trim() == "Discharging";
+            }
+            _ => {}
+        }
+    }
+    saw_mains || discharging
+}
+
+/// Whether the machine is running on battery power. Unknown counts as not.
+#[cfg(target_os = "macos")]
+fn on_battery() -> bool {
+    std::process::Command::new("pmset")
+        .args(["-g", "batt"])
+        .output()
+        .map(|o| String::from_utf8_lossy(&o.stdout).contains("'Battery Power'"))
+        .unwrap_or(false)
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "linux")))]
+fn on_battery() -> bool {
+    false
+}
+
+/// Make an automatic switch, or note that `cycle pause` is holding it.
+fn switch_unless_paused(paused: bool, config: &collection::AppConfig, pending: cycling::Pending) {
+    if paused {
+        report!(Level::Info, "Paused by `cycle pause`, not switching");
+    } else {
+        log_apply(cycling::switch(config, pending));
+    }
+}
+
+/// Report the outcome of a theme switch.
+fn log_apply(result: Result<String>) {
+    match result {
+        Ok(msg) => {
+            report!(Level::Info, "{}", msg);
+            announce(&msg);
+        }
+        Err(e) => report!(Level::Warn, "Error: {}", e),
     }
+}
 
-    let contents =
-        fs::read_to_string(&pid_file).map_err(|e| format!("Failed to read PID file: {}", e))?;
-    let pid: i32 = contents
+/// Show a switch as a desktop notification when they're turned on. A
+/// switch left for the next prompt isn't announced until it happens.
+fn announce(msg: &str) {
+    let config = collection::load_config();
+    if !(NOTIFY.load(Ordering::Relaxed) || config.notify_on_switch) || config.defer_to_prompt {
+        return;
+    }
+    if let Err(e) = notification::send("Ghostty theme changed", msg) {
+        report!(Level::Warn, "Notification failed: {}", e);
+    }
+}
+
+fn read_pid(path: &std::path::Path) -> Result<i32> {
+    let contents = fs::read_to_string(path).map_err(|e| Error::io("read", path, e))?;
+    contents
         .trim()
         .parse()
-        .map_err(|_| "Corrupt PID file".to_string())?;
+        .map_err(|e| Error::parse(format!("PID file {}", path.display()), e))
+}
 
-    if !is_process_alive(pid) {
+/// Stop a running daemon: SIGTERM on Unix, a shutdown request over the
+/// control channel on Windows (killing it if that can't be sent).
+pub fn stop() -> Result<()> {
+    let pid_file = collection::pid_path();
+
+    if !pid_file.exists() {
+        return Err(Error::Invalid(
+            "No daemon is running (PID file not found)".to_string(),
+        ));
+    }
+
+    let pid = read_pid(&pid_file)?;
+
+    if !process::is_alive(pid) {
         let _ = fs::remove_file(&pid_file);
-        return Err(format!(
+        return Err(Error::Invalid(format!(
             "Daemon (PID {}) is not running. Removed stale PID file.",
             pid
-        ));
+        )));
     }
 
-    signal::kill(Pid::from_raw(pid), Signal::SIGTERM)
-        .map_err(|e| format!("Failed to send SIGTERM to PID {}: {}", pid, e))?;
+    process::terminate(pid)?;
+
+    // Give it a moment to restore the theme and clean up after itself
+    let deadline = Instant::now() + STOP_TIMEOUT;
+    while process::is_alive(pid) && Instant::now() < deadline {
+        thread::sleep(Duration::from_millis(50));
+    }
 
     let _ = fs::remove_file(&pid_file);
+    let _ = fs::remove_file(collection::next_switch_path());
+    let _ = fs::remove_file(collection::socket_path());
     println!("Stopped daemon (PID {})", pid);
 
     Ok(())
 }
 
-/// Print the current status of the daemon and active collection.
-pub fn status() -> Result<(), String> {
-    let pid_file = collection::pid_path();
-
-    if pid_file.exists() {
-        let contents =
-            fs::read_to_string(&pid_file).map_err(|e| format!("Failed to read PID file: {}", e))?;
-        let pid: i32 = contents
-            .trim()
-            .parse()
-            .map_err(|_| "Corrupt PID file".to_string())?;
-
-        if is_process_alive(pid) {
-            println!("Daemon: running (PID {})", pid);
-        } else {
-            println!("Daemon: not running (stale PID file for {})", pid);
+/// Send `cycle pause`, `resume`, or `skip` to the running daemon and print
+/// its answer.
+pub fn control(control: Control) -> Result<()> {
+    let answer = control::send(control)?;
+    match answer.strip_prefix("Error: ") {
+        Some(e) => Err(Error::Other(e.to_string())),
+        None => {
+            println!("{}", answer);
+            Ok(())
         }
+    }
+}
+
+/// PID of the running daemon, if there is one.
+pub fn running_pid() -> Option<i32> {
+    let pid = fs::read_to_string(collection::pid_path())
+        .ok()?
+        .trim()
+        .parse()
+        .ok()?;
+    process::is_alive(pid).then_some(pid)
+}
+
+/// Seconds until the running daemon's next planned switch.
+pub fn next_switch_in() -> Option<u64> {
+    running_pid()?;
+    let at: u64 = fs::read_to_string(collection::next_switch_path())
+        .ok()?
+        .trim()
+        .parse()
+        .ok()?;
+    Some(at.saturating_sub(darkmode::unix_now()))
+}
+
+/// Short countdown like "45s", "12m", or "1h 5m".
+pub fn format_countdown(secs: u64) -> String {
+    if secs < 60 {
+        format!("{}s", secs)
+    } else if secs < 3600 {
+        format!("{}m", secs.div_ceil(60))
     } else {
-        println!("Daemon: not running");
+        let mins = secs.div_ceil(60);
+        match mins % 60 {
+            0 => format!("{}h", mins / 60),
+            m => format!("{}h {}m", mins / 60, m),
+        }
+    }
+}
+
+/// What the next regular switch will pick, e.g. "'Nord' from 'pastel'"
+/// or "shuffle pick from 'pastel'".
+fn describe_next(app_config: &collection::AppConfig) -> Option<String> {
+    let want_dark = cycling::resolve_want_dark(app_config);
+    let plan = cycling::plan(app_config, &cycling::Step::Next, want_dark).ok()?;
+    Some(match plan.collection.order {
+        collection::CycleOrder::Sequential => {
+            format!("'{}' from '{}'", plan.theme().title, plan.collection.name)
+        }
+        ref order => format!("{} pick from '{}'", order.label(), plan.collection.name),
+    })
+}
+
+/// Everything `cycle status` reports, for printing as text or JSON.
+#[derive(Debug, Default, Serialize)]
+struct StatusReport {
+    running: bool,
+    /// From the PID file, even when that process is gone.
+    pid: Option<i32>,
+    /// Paused by `cycle pause`.
+    paused: bool,
+    /// Why automatic switches are held back (quiet hours, battery).
+    pause_reason: Option<String>,
+    deferred: bool,
+    switch_pending: bool,
+    /// The weekday rule in effect today.
+    weekday_rule: Option<String>,
+    rotation: Option<RotationReport>,
+    collection: Option<String>,
+    /// Why the collection couldn't be loaded.
+    collection_error: Option<String>,
+    themes: Option<usize>,
+    order: Option<String>,
+    interval: Option<String>,
+    schedule: Option<String>,
+    current: Option<String>,
+    last_applied: Option<collection::AppliedTheme>,
+    /// Unix seconds of the next timed switch.
+    next_switch_at: Option<u64>,
+    next_switch_in: Option<u64>,
+    /// The theme the next switch is expected to apply.
+    next: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct RotationReport {
+    collections: Vec<String>,
+    mode: String,
+}
+
+fn status_report() -> Result<StatusReport> {
+    let mut report = StatusReport::default();
+    let pid_file = collection::pid_path();
+    if pid_file.exists() {
+        let pid = read_pid(&pid_file)?;
+        report.pid = Some(pid);
+        report.running = process::is_alive(pid);
+        report.paused =
+            report.running && control::send(Control::Status).is_ok_and(|state| state == "paused");
     }
 
-    // Print active collection info
     let app_config = collection::load_config();
-    match app_config.active_collection {
-        Some(name) => match collection::load_collection(&name) {
+    report.pause_reason = pause_reason(&app_config);
+    report.deferred = app_config.defer_to_prompt;
+    report.switch_pending = app_config.defer_to_prompt && collection::pending_path().exists();
+    report.weekday_rule = schedule::rule_for_today(&app_config).map(|rule| rule.days.clone());
+    report.rotation = schedule::rotation_for_today(&app_config).map(|rotation| RotationReport {
+        collections: rotation.collections.clone(),
+        mode: rotation.mode.label().to_string(),
+    });
+    report.collection = schedule::collection_for_today(&app_config);
+    if let Some(name) = &report.collection {
+        match collection::load_collection(name) {
             Ok(coll) => {
-                let order_str = match coll.order {
-                    collection::CycleOrder::Sequential => "sequential",
-                    collection::CycleOrder::Shuffle => "shuffle",
-                };
-                let interval_str = coll.interval.as_deref().unwrap_or("not set");
-                let current_theme = if coll.themes.is_empty() {
-                    "(none)".to_string()
-                } else {
-                    let idx = coll.current_index.min(coll.themes.len() - 1);
-                    coll.themes[idx].title.clone()
-                };
-
-                println!("Collection: {}", name);
-                println!("Themes:     {}", coll.themes.len());
-                println!("Order:      {}", order_str);
-                println!("Interval:   {}", interval_str);
-                println!("Current:    {}", current_theme);
+                report.themes = Some(coll.themes.len());
+                report.order = Some(coll.order.label().to_string());
+                report.interval = schedule::interval_for_today(&app_config);
+                report.schedule = schedule::schedule_for_today(&app_config);
+                report.current = coll
+                    .themes
+                    .get(coll.current_index.min(coll.themes.len().saturating_sub(1)))
+                    .map(|theme| theme.title.clone());
+                report.last_applied = collection::load_applied();
+                if let Some(secs) = next_switch_in() {
+                    report.next_switch_at = Some(darkmode::unix_now() + secs);
+                    report.next_switch_in = Some(secs);
+                    report.next = describe_next(&app_config);
+                }
             }
-            Err(e) => {
-                println!("Collection: {} (error: {})", name, e);
+            Err(e) => report.collection_error = Some(e.to_string()),
+        }
+    }
+    Ok(report)
+}
+
+/// Print the current status of the daemon and active collection.
+pub fn status(json: bool) -> Result<()> {
+    let report = status_report()?;
+    if json {
+        let out =
+            serde_json::to_string_pretty(&report).map_err(|e| Error::parse("daemon status", e))?;
+        println!("{}", out);
+        return Ok(());
+    }
+
+    match report.pid {
+        Some(pid) if report.running => {
+            println!("Daemon: running (PID {})", pid);
+            if report.paused {
+                println!("Paused:     by `cycle pause` (resume with `cycle resume`)");
             }
-        },
-        None => {
-            println!("Collection: (none active)");
         }
+        Some(pid) => println!("Daemon: not running (stale PID file for {})", pid),
+        None => println!("Daemon: not running"),
+    }
+
+    if let Some(reason) = &report.pause_reason {
+        println!("Paused:     {}", reason);
+    }
+    if report.deferred {
+        println!(
+            "Deferred:   until next prompt{}",
+            if report.switch_pending {
+                " (switch pending)"
+            } else {
+                ""
+            }
+        );
+    }
+    if let Some(days) = &report.weekday_rule {
+        println!("Schedule:   weekday rule '{}'", days);
+    }
+    if let Some(rotation) = &report.rotation {
+        println!(
+            "Rotation:   {} ({})",
+            rotation.collections.join(" → "),
+            rotation.mode
+        );
+    }
+    let Some(name) = &report.collection else {
+        println!("Collection: (none active)");
+        return Ok(());
+    };
+    if let Some(e) = &report.collection_error {
+        println!("Collection: {} (error: {})", name, e);
+        return Ok(());
+    }
+
+    println!("Collection: {}", name);
+    println!("Themes:     {}", report.themes.unwrap_or(0));
+    println!(
+        "Order:      {}",
+        report.order.as_deref().unwrap_or_default()
+    );
+    match &report.schedule {
+        Some(expr) => println!("Schedule:   {}", expr),
+        None => println!(
+            "Interval:   {}",
+            report.interval.as_deref().unwrap_or("not set")
+        ),
+    }
+    println!(
+        "Current:    {}",
+        report.current.as_deref().unwrap_or("(none)")
+    );
+    if let Some(applied) = &report.last_applied {
+        let ago = darkmode::unix_now().saturating_sub(applied.applied_at);
+        println!(
+            "Last:       {} ({} ago)",
+            darkmode::format_clock(applied.applied_at),
+            format_countdown(ago)
+        );
+    }
+    if let (Some(at), Some(secs)) = (report.next_switch_at, report.next_switch_in) {
+        let target = report
+            .next
+            .as_ref()
+            .map(|d| format!(" (to {})", d))
+            .unwrap_or_default();
+        println!(
+            "Next:       {} (in {}){}",
+            darkmode::format_clock(at),
+            format_countdown(secs),
+            target
+        );
     }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings() -> Settings {
+        Settings {
+            collection: Some("pastel".to_string()),
+            themes: Some(4),
+            timing: Some("interval 30m".to_string()),
+            mode: None,
+            override_until: None,
+            dark_after: "19:00".to_string(),
+            light_after: "07:00".to_string(),
+        }
+    }
+
+    #[test]
+    fn settings_changes_none_when_equal() {
+        assert!(settings().changes_to(&settings()).is_empty());
+    }
+
+    #[test]
+    fn settings_changes_describe_edits() {
+        let mut new = settings();
+        new.themes = Some(5);
+        new.timing = Some("interval 15m".to_string());
+        new.mode = Some("dark".to_string());
+        assert_eq!(
+            settings().changes_to(&new),
+            vec![
+                "themes 4 → 5".to_string(),
+                "interval 30m → interval 15m".to_string(),
+                "mode none → dark".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn settings_changes_notice_override_extension() {
+        let mut old = settings();
+        old.mode = Some("dark".to_string());
+        old.override_until = Some(100);
+        let mut new = old.clone();
+        new.override_until = Some(200);
+        assert_eq!(old.changes_to(&new), vec!["mode override".to_string()]);
+    }
+
+    #[test]
+    fn settings_changes_collection_hides_theme_count() {
+        let mut new = settings();
+        new.collection = Some("neon".to_string());
+        new.themes = Some(9);
+        assert_eq!(
+            settings().changes_to(&new),
+            vec!["collection pastel → neon".to_string()]
+        );
+    }
+
+    #[test]
+    fn format_countdown_units() {
+        assert_eq!(format_countdown(0), "0s");
+        assert_eq!(format_countdown(45), "45s");
+        assert_eq!(format_countdown(60), "1m");
+        assert_eq!(format_countdown(691), "12m");
+        assert_eq!(format_countdown(3600), "1h");
+        assert_eq!(format_countdown(3900), "1h 5m");
+    }
+
+    #[test]
+    fn apply_jitter_zero_is_exact() {
+        let interval = Duration::from_secs(1800);
+        assert_eq!(apply_jitter(interval, Duration::ZERO), interval);
+    }
+
+    #[test]
+    fn apply_jitter_stays_within_spread() {
+        let interval = Duration::from_secs(1800);
+        for _ in 0..100 {
+            let secs = apply_jitter(interval, Duration::from_secs(300)).as_secs_f64();
+            assert!((1500.0..=2100.0).contains(&secs), "{} out of range", secs);
+        }
+    }
+
+    #[test]
+    fn apply_jitter_never_below_one_second() {
+        for _ in 0..100 {
+            let wait = apply_jitter(Duration::from_secs(1), Duration::from_secs(60));
+            assert!(wait >= Duration::from_secs(1));
+        }
+    }
+
+    #[test]
+    fn jitter_setting_wins_over_the_percentage() {
+        let every = Timing::Every(Duration::from_secs(1800));
+        let mut config = collection::AppConfig {
+            interval_jitter: 10,
+            ..Default::default()
+        };
+        assert_eq!(every.jitter(&config), Duration::from_secs(180));
+        config.jitter = Some("10m".to_string());
+        assert_eq!(every.jitter(&config), Duration::from_secs(600));
+
+        let cron = Timing::Cron(schedule::CronSchedule::parse("0 * * * *").unwrap());
+        config.jitter = None;
+        assert_eq!(cron.jitter(&config), Duration::ZERO);
+        config.jitter = Some("5m".to_string());
+        assert_eq!(cron.jitter(&config), Duration::from_secs(300));
+    }
+
+    #[test]
+    fn jittered_cron_switches_never_fire_early_twice() {
+        let cron = Timing::Cron(schedule::CronSchedule::parse("* * * * *").unwrap());
+        let jitter = Duration::from_secs(600);
+        // 30s past a minute: slots in the next ten minutes are skipped, and the
+        // one after is moved at most ten minutes either way
+        let now = 1_700_000_010;
+        for _ in 0..100 {
+            let wait = cron.wait(jitter, now).as_secs();
+            assert!((30..=1230).contains(&wait), "{} out of range", wait);
+        }
+        assert_eq!(cron.wait(Duration::ZERO, now).as_secs(), 30);
+    }
+
+    #[test]
+    fn in_window_same_day() {
+        // 09:00-11:00
+        assert!(!in_window(539, 540, 660));
+        assert!(in_window(540, 540, 660));
+        assert!(in_window(659, 540, 660));
+        assert!(!in_window(660, 540, 660));
+    }
+
+    #[test]
+    fn in_window_wraps_midnight() {
+        // 22:00-08:00
+        assert!(in_window(1320, 1320, 480));
+        assert!(in_window(0, 1320, 480));
+        assert!(in_window(479, 1320, 480));
+        assert!(!in_window(480, 1320, 480));
+        assert!(!in_window(720, 1320, 480));
+    }
+
+    #[test]
+    fn pause_reason_none_by_default() {
+        assert_eq!(pause_reason(&collection::AppConfig::default()), None);
+    }
+}
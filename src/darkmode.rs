@@ -6,6 +6,36 @@ use std::time::Duration;
 /// Detect the current OS dark mode setting.
 /// Returns Some(true) if dark, Some(false) if light, None if undetectable.
 pub fn detect_current() -> Option<bool> {
+    if let Some(cmd) = crate::collection::load_config().darkmode_command {
+        if let Some(is_dark) = detect_with_command(&cmd) {
+            return Some(is_dark);
+        }
+    }
+    detect_builtin()
+}
+
+/// Run the user's `darkmode_command` through the shell and read its answer.
+fn detect_with_command(cmd: &str) -> Option<bool> {
+    #[cfg(windows)]
+    let output = Command::new("cmd").args(["/C", cmd]).output().ok()?;
+    #[cfg(not(windows))]
+    let output = Command::new("sh").args(["-c", cmd]).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    parse_command_output(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Interpret a detection command's output: `dark` or `light`, any case.
+fn parse_command_output(stdout: &str) -> Option<bool> {
+    match stdout.trim().to_ascii_lowercase().as_str() {
+        "dark" => Some(true),
+        "light" => Some(false),
+        _ => None,
+    }
+}
+
+fn detect_builtin() -> Option<bool> {
     #[cfg(target_os = "macos")]
     {
         detect_macos()
@@ -14,7 +44,11 @@ pub fn detect_current() -> Option<bool> {
     {
         detect_linux()
     }
-    #[cfg(not(any(target_os = "macos", target_os = "linux")))]
+    #[cfg(target_os = "windows")]
+    {
+        detect_windows()
+    }
+    #[cfg(not(any(target_os = "macos", target_os = "linux", target_os = "windows")))]
     {
         None
     }
@@ -37,6 +71,11 @@ fn detect_macos() -> Option<bool> {
 
 #[cfg(target_os = "linux")]
 fn detect_linux() -> Option<bool> {
+    // The desktop portal works across GNOME, KDE, and wlroots desktops
+    if let Some(is_dark) = detect_portal() {
+        return Some(is_dark);
+    }
+
     // Try GTK_THEME env var
     if let Ok(theme) = std::env::var("GTK_THEME") {
         if theme.to_lowercase().contains("dark") {
@@ -69,22 +108,153 @@ fn detect_linux() -> Option<bool> {
     None
 }
 
+#[cfg(target_os = "windows")]
+fn detect_windows() -> Option<bool> {
+    let output = Command::new("reg")
+        .args([
+            "query",
+            r"HKCU\Software\Microsoft\Windows\CurrentVersion\Themes\Personalize",
+            "/v",
+            "AppsUseLightTheme",
+        ])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    parse_apps_use_light_theme(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Parse `reg query ... /v AppsUseLightTheme` output. The value is a
+/// DWORD: 0x0 means dark, 0x1 means light.
+#[cfg(any(target_os = "windows", test))]
+fn parse_apps_use_light_theme(output: &str) -> Option<bool> {
+    let line = output
+        .lines()
+        .find(|l| l.trim_start().starts_with("AppsUseLightTheme"))?;
+    let value = line.split_whitespace().last()?;
+    let light = u32::from_str_radix(value.trim_start_matches("0x"), 16).ok()?;
+    Some(light == 0)
+}
+
+#[cfg(target_os = "linux")]
+const PORTAL_NAMESPACE: &str = "org.freedesktop.appearance";
+
+#[cfg(target_os = "linux")]
+fn portal_settings(conn: &zbus::blocking::Connection) -> zbus::Result<zbus::blocking::Proxy<'_>> {
+    zbus::blocking::Proxy::new(
+        conn,
+        "org.freedesktop.portal.Desktop",
+        "/org/freedesktop/portal/desktop",
+        "org.freedesktop.portal.Settings",
+    )
+}
+
+/// Linux: read `color-scheme` from the XDG desktop portal over D-Bus.
+/// Returns None when there's no portal or no preference is set.
+#[cfg(target_os = "linux")]
+fn detect_portal() -> Option<bool> {
+    let conn = zbus::blocking::Connection::session().ok()?;
+    let proxy = portal_settings(&conn).ok()?;
+    portal_scheme_is_dark(read_portal_scheme(&proxy)?)
+}
+
+/// The raw color-scheme value, or None if the portal doesn't answer.
+#[cfg(target_os = "linux")]
+fn read_portal_scheme(proxy: &zbus::blocking::Proxy) -> Option<u32> {
+    use zbus::zvariant::OwnedValue;
+
+    // ReadOne is the current API; older portals only have the deprecated Read
+    let value: OwnedValue = proxy
+        .call("ReadOne", &(PORTAL_NAMESPACE, "color-scheme"))
+        .or_else(|_| proxy.call("Read", &(PORTAL_NAMESPACE, "color-scheme")))
+        .ok()?;
+    variant_u32(&value)
+}
+
+/// Unwrap a portal value to its u32, looking through nested variants.
+#[cfg(target_os = "linux")]
+fn variant_u32(value: &zbus::zvariant::Value) -> Option<u32> {
+    match value {
+        zbus::zvariant::Value::U32(n) => Some(*n),
+        zbus::zvariant::Value::Value(inner) => variant_u32(inner),
+        _ => None,
+    }
+}
+
+/// Map the portal's color-scheme value: 1 prefers dark, 2 prefers light,
+/// 0 means no preference.
+#[cfg(any(target_os = "linux", test))]
+fn portal_scheme_is_dark(scheme: u32) -> Option<bool> {
+    match scheme {
+        1 => Some(true),
+        2 => Some(false),
+        _ => None,
+    }
+}
+
+/// Linux: listen for the portal's `SettingChanged` signal. Returns true if
+/// the watcher was set up (blocks until the bus goes away).
+#[cfg(target_os = "linux")]
+fn watch_portal(tx: &mpsc::Sender<bool>) -> bool {
+    use zbus::zvariant::OwnedValue;
+
+    let Ok(conn) = zbus::blocking::Connection::session() else {
+        return false;
+    };
+    let Ok(proxy) = portal_settings(&conn) else {
+        return false;
+    };
+    // Without a portal service the signal would simply never arrive
+    if read_portal_scheme(&proxy).is_none() {
+        return false;
+    }
+    let Ok(signals) = proxy.receive_signal("SettingChanged") else {
+        return false;
+    };
+
+    for msg in signals {
+        let Ok((namespace, key, value)) = msg.body().deserialize::<(String, String, OwnedValue)>()
+        else {
+            continue;
+        };
+        if namespace != PORTAL_NAMESPACE || key != "color-scheme" {
+            continue;
+        }
+        // "No preference" reads as light, as desktops render it that way
+        let is_dark = variant_u32(&value)
+            .and_then(portal_scheme_is_dark)
+            .unwrap_or(false);
+        if tx.send(is_dark).is_err() {
+            break;
+        }
+    }
+
+    true
+}
+
 /// Spawn a background thread that watches for OS dark mode changes.
 /// Returns a Receiver that emits `true` for dark, `false` for light.
-/// Falls back to polling every 30 seconds if event listening is unavailable.
+/// Falls back to polling every 30 seconds if event listening is unavailable
+/// (always the case on Windows, which has no CLI change notification, and
+/// with a custom `darkmode_command`).
 pub fn spawn_watcher() -> mpsc::Receiver<bool> {
     let (tx, rx) = mpsc::channel();
 
+    // A custom command has no change notification, so it is always polled
+    #[cfg(any(target_os = "macos", target_os = "linux"))]
+    let use_events = crate::collection::load_config().darkmode_command.is_none();
+
     thread::spawn(move || {
         #[cfg(target_os = "macos")]
         {
-            if watch_macos(&tx) {
+            if use_events && watch_macos(&tx) {
                 return;
             }
         }
         #[cfg(target_os = "linux")]
         {
-            if watch_linux(&tx) {
+            if use_events && (watch_portal(&tx) || watch_linux(&tx)) {
                 return;
             }
         }
@@ -150,7 +320,8 @@ RunLoop.current.run()
     true
 }
 
-/// Linux: watch via `gsettings monitor`. Returns true if watcher set up (blocks forever).
+/// Linux fallback when there's no portal: watch via `gsettings monitor`.
+/// Returns true if watcher set up (blocks forever).
 #[cfg(target_os = "linux")]
 fn watch_linux(tx: &mpsc::Sender<bool>) -> bool {
     use std::io::BufRead;
@@ -171,62 +342,254 @@ fn watch_linux(tx: &mpsc::Sender<bool>) -> bool {
     };
 
     let reader = std::io::BufReader::new(stdout);
-    for line in reader.lines() {
-        if let Ok(line) = line {
-            let is_dark = line.contains("prefer-dark");
-            let _ = tx.send(is_dark);
-        }
+    for line in reader.lines().map_while(Result::ok) {
+        let is_dark = line.contains("prefer-dark");
+        let _ = tx.send(is_dark);
     }
 
     true
 }
 
-/// Resolve the desired mode from the given preference.
+/// Resolve the desired mode from the given preference. Time-based
+/// preferences take their switch times from `config`.
 pub fn resolve_mode(
     pref: &crate::collection::ModePreference,
-    dark_after: &str,
-    light_after: &str,
+    config: &crate::collection::AppConfig,
 ) -> Option<bool> {
     use crate::collection::ModePreference;
     match pref {
         ModePreference::Dark => Some(true),
         ModePreference::Light => Some(false),
         ModePreference::AutoOs => detect_current(),
-        ModePreference::AutoTime => resolve_time(dark_after, light_after),
+        ModePreference::AutoTime | ModePreference::AutoSun => {
+            let (dark_after, light_after) = switch_times(pref, config)?;
+            resolve_time(&dark_after, &light_after)
+        }
     }
 }
 
+/// Today's "HH:MM" (dark_after, light_after) for a time-based preference.
+/// Auto-sun uses local sunset and sunrise, falling back to the fixed
+/// times when no location is set or the sun doesn't rise or set today.
+pub fn switch_times(
+    pref: &crate::collection::ModePreference,
+    config: &crate::collection::AppConfig,
+) -> Option<(String, String)> {
+    use crate::collection::ModePreference;
+    let fixed = (config.dark_after.clone(), config.light_after.clone());
+    match pref {
+        ModePreference::AutoTime => Some(fixed),
+        ModePreference::AutoSun => Some(
+            config
+                .latitude
+                .zip(config.longitude)
+                .and_then(|(lat, lon)| {
+                    let now = unix_now();
+                    let tm = local_tm_at(now);
+                    let offset_mins = (utc_offset_at(now) / 60) as i32;
+                    sun_times(tm.tm_yday as u32 + 1, lat, lon, offset_mins)
+                })
+                .map(|(sunrise, sunset)| (format_hhmm(sunset), format_hhmm(sunrise)))
+                .unwrap_or(fixed),
+        ),
+        _ => None,
+    }
+}
+
+/// Why `pref` currently resolves the way it does, e.g. "OS reports dark"
+/// or "dark from 19:00 until 07:00".
+pub fn mode_reason(
+    pref: &crate::collection::ModePreference,
+    config: &crate::collection::AppConfig,
+) -> String {
+    use crate::collection::ModePreference;
+    match pref {
+        ModePreference::Dark | ModePreference::Light => "set explicitly".to_string(),
+        ModePreference::AutoOs => match detect_current() {
+            Some(true) => "OS reports dark".to_string(),
+            Some(false) => "OS reports light".to_string(),
+            None => "OS appearance can't be detected".to_string(),
+        },
+        ModePreference::AutoTime | ModePreference::AutoSun => {
+            let Some((dark_after, light_after)) = switch_times(pref, config) else {
+                return "no switch times".to_string();
+            };
+            let (Some(dark_mins), Some(light_mins)) =
+                (parse_hhmm(&dark_after), parse_hhmm(&light_after))
+            else {
+                return format!("invalid switch times {} / {}", dark_after, light_after);
+            };
+            let is_dark = is_dark_time(local_seconds_now() / 60, dark_mins, light_mins);
+            let mut reason = time_window_reason(is_dark, &dark_after, &light_after);
+            if *pref == ModePreference::AutoSun {
+                reason.push_str(if config.latitude.is_some() && config.longitude.is_some() {
+                    ", following sunset and sunrise"
+                } else {
+                    ", fixed times as no location is set"
+                });
+            }
+            reason
+        }
+    }
+}
+
+/// "dark from 19:00 until 07:00" or "light from 07:00 until 19:00".
+fn time_window_reason(is_dark: bool, dark_after: &str, light_after: &str) -> String {
+    if is_dark {
+        format!("dark from {} until {}", dark_after, light_after)
+    } else {
+        format!("light from {} until {}", light_after, dark_after)
+    }
+}
+
+/// The next automatic flip for a time-based preference: the mode it
+/// switches to, the local "HH:MM" it happens at, and seconds until then.
+pub fn next_flip(
+    pref: &crate::collection::ModePreference,
+    config: &crate::collection::AppConfig,
+) -> Option<(bool, String, u64)> {
+    let (dark_after, light_after) = switch_times(pref, config)?;
+    let to_dark = mode_after_next_boundary(&dark_after, &light_after)?;
+    let secs = seconds_until_boundary(&dark_after, &light_after)?;
+    let at = if to_dark { dark_after } else { light_after };
+    Some((to_dark, at, secs))
+}
+
+/// Local sunrise and sunset as minutes since midnight, using the NOAA
+/// solar position approximation. None during polar day or night.
+fn sun_times(day_of_year: u32, lat: f64, lon: f64, utc_offset_mins: i32) -> Option<(u32, u32)> {
+    use std::f64::consts::PI;
+
+    let gamma = 2.0 * PI / 365.0 * (day_of_year as f64 - 1.0);
+    let eqtime = 229.18
+        * (0.000075 + 0.001868 * gamma.cos()
+            - 0.032077 * gamma.sin()
+            - 0.014615 * (2.0 * gamma).cos()
+            - 0.040849 * (2.0 * gamma).sin());
+    let decl = 0.006918 - 0.399912 * gamma.cos() + 0.070257 * gamma.sin()
+        - 0.006758 * (2.0 * gamma).cos()
+        + 0.000907 * (2.0 * gamma).sin()
+        - 0.002697 * (3.0 * gamma).cos()
+        + 0.00148 * (3.0 * gamma).sin();
+
+    let lat = lat.to_radians();
+    // 90.833° accounts for refraction and the size of the sun's disc
+    let cos_ha = 90.833_f64.to_radians().cos() / (lat.cos() * decl.cos()) - lat.tan() * decl.tan();
+    if !(-1.0..=1.0).contains(&cos_ha) {
+        return None;
+    }
+    let ha = cos_ha.acos().to_degrees();
+
+    let to_local =
+        |utc_mins: f64| (utc_mins.round() as i64 + utc_offset_mins as i64).rem_euclid(1440) as u32;
+    let sunrise = to_local(720.0 - 4.0 * (lon + ha) - eqtime);
+    let sunset = to_local(720.0 - 4.0 * (lon - ha) - eqtime);
+    Some((sunrise, sunset))
+}
+
+/// Format minutes since midnight as "HH:MM".
+pub fn format_hhmm(mins: u32) -> String {
+    format!("{:02}:{:02}", mins / 60, mins % 60)
+}
+
 /// Determine whether it's "dark time" based on current local time.
 fn resolve_time(dark_after: &str, light_after: &str) -> Option<bool> {
-    let now = local_minutes_now();
+    let now = local_seconds_now() / 60;
     let dark_mins = parse_hhmm(dark_after)?;
     let light_mins = parse_hhmm(light_after)?;
+    Some(is_dark_time(now, dark_mins, light_mins))
+}
 
+/// Whether `now` (minutes since midnight) falls in the dark period.
+fn is_dark_time(now: u32, dark_mins: u32, light_mins: u32) -> bool {
     if light_mins < dark_mins {
         // Normal: light=07:00, dark=19:00
         // Light period: light_after..dark_after
-        Some(now < light_mins || now >= dark_mins)
+        now < light_mins || now >= dark_mins
     } else {
         // Inverted: dark=01:00, light=09:00
-        Some(now >= dark_mins && now < light_mins)
+        now >= dark_mins && now < light_mins
     }
 }
 
-/// Get current local time as minutes since midnight.
-fn local_minutes_now() -> u32 {
+/// Get current local time as seconds since midnight.
+pub fn local_seconds_now() -> u32 {
+    let tm = local_tm_now();
+    (tm.tm_hour as u32) * 3600 + (tm.tm_min as u32) * 60 + (tm.tm_sec as u32)
+}
+
+/// Get the current local weekday, 0 = Sunday through 6 = Saturday.
+pub fn local_weekday_now() -> u32 {
+    local_tm_now().tm_wday as u32
+}
+
+fn local_tm_now() -> libc::tm {
+    local_tm_at(unix_now())
+}
+
+/// Current time as seconds since the Unix epoch.
+pub fn unix_now() -> u64 {
     use std::time::{SystemTime, UNIX_EPOCH};
-    let secs = SystemTime::now()
+    SystemTime::now()
         .duration_since(UNIX_EPOCH)
         .unwrap_or_default()
-        .as_secs();
+        .as_secs()
+}
+
+/// Break a Unix timestamp down into local calendar time.
+pub fn local_tm_at(secs: u64) -> libc::tm {
     unsafe {
         let t = secs as libc::time_t;
         let mut tm: libc::tm = std::mem::zeroed();
+        #[cfg(unix)]
         libc::localtime_r(&t, &mut tm);
-        (tm.tm_hour as u32) * 60 + (tm.tm_min as u32)
+        #[cfg(windows)]
+        libc::localtime_s(&mut tm, &t);
+        tm
     }
 }
 
+/// Local time's offset from UTC at `secs`, in seconds east. Worked out
+/// from the calendar fields, since only Unix has `tm_gmtoff`.
+fn utc_offset_at(secs: u64) -> i64 {
+    let tm = local_tm_at(secs);
+    let local = days_from_civil(
+        i64::from(tm.tm_year) + 1900,
+        i64::from(tm.tm_mon) + 1,
+        i64::from(tm.tm_mday),
+    ) * 86_400
+        + i64::from(tm.tm_hour) * 3600
+        + i64::from(tm.tm_min) * 60
+        + i64::from(tm.tm_sec);
+    local - secs as i64
+}
+
+/// Days from 1970-01-01 to a proleptic Gregorian date.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// Local "HH:MM" for a Unix timestamp.
+pub fn format_clock(secs: u64) -> String {
+    let tm = local_tm_at(secs);
+    format!("{:02}:{:02}", tm.tm_hour, tm.tm_min)
+}
+
+/// Parse a "HH:MM-HH:MM" (dark after, light after) pair, as typed into the
+/// Collections screen. A space works as the separator too.
+pub fn parse_switch_times(s: &str) -> Option<(String, String)> {
+    let (dark, light) = s.trim().split_once(['-', ' '])?;
+    let (dark, light) = (dark.trim(), light.trim());
+    parse_hhmm(dark)?;
+    parse_hhmm(light)?;
+    Some((dark.to_string(), light.to_string()))
+}
+
 /// Parse "HH:MM" into minutes since midnight.
 pub fn parse_hhmm(s: &str) -> Option<u32> {
     let parts: Vec<&str> = s.split(':').collect();
@@ -243,23 +606,51 @@ pub fn parse_hhmm(s: &str) -> Option<u32> {
 
 /// Calculate seconds until the next dark/light time boundary.
 pub fn seconds_until_boundary(dark_after: &str, light_after: &str) -> Option<u64> {
-    let now = local_minutes_now();
     let dark_mins = parse_hhmm(dark_after)?;
     let light_mins = parse_hhmm(light_after)?;
+    seconds_until_next(local_seconds_now(), &[dark_mins, light_mins])
+}
 
-    let boundaries = [dark_mins, light_mins];
-    let min_future = boundaries
+/// Seconds from `now_secs` (since midnight) until the soonest boundary,
+/// given as minutes since midnight. A boundary equal to now counts as
+/// tomorrow's.
+fn seconds_until_next(now_secs: u32, boundaries: &[u32]) -> Option<u64> {
+    boundaries
         .iter()
-        .map(|&b| if b > now { b - now } else { b + 1440 - now })
-        .min()?;
+        .map(|&b| {
+            let b_secs = b * 60;
+            if b_secs > now_secs {
+                b_secs - now_secs
+            } else {
+                b_secs + 86_400 - now_secs
+            }
+        })
+        .min()
+        .map(u64::from)
+}
 
-    Some((min_future as u64) * 60)
+/// The mode that takes effect at the next auto-time boundary.
+pub fn mode_after_next_boundary(dark_after: &str, light_after: &str) -> Option<bool> {
+    let dark_mins = parse_hhmm(dark_after)?;
+    let light_mins = parse_hhmm(light_after)?;
+    let now = local_seconds_now();
+    let to_dark = seconds_until_next(now, &[dark_mins])?;
+    let to_light = seconds_until_next(now, &[light_mins])?;
+    Some(to_dark <= to_light)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn parse_command_output_accepts_dark_and_light() {
+        assert_eq!(parse_command_output("dark\n"), Some(true));
+        assert_eq!(parse_command_output("  Light "), Some(false));
+        assert_eq!(parse_command_output("prefer-dark"), None);
+        assert_eq!(parse_command_output(""), None);
+    }
+
     #[test]
     fn parse_hhmm_valid() {
         assert_eq!(parse_hhmm("07:00"), Some(420));
@@ -276,15 +667,135 @@ mod tests {
         assert_eq!(parse_hhmm(""), None);
     }
 
+    #[test]
+    fn parse_switch_times_pairs() {
+        let want = Some(("20:00".to_string(), "06:30".to_string()));
+        assert_eq!(parse_switch_times("20:00-06:30"), want);
+        assert_eq!(parse_switch_times(" 20:00 06:30 "), want);
+        assert_eq!(parse_switch_times("20:00"), None);
+        assert_eq!(parse_switch_times("25:00-06:30"), None);
+    }
+
     #[test]
     fn detect_current_returns_option() {
         let _ = detect_current();
     }
 
+    fn assert_near(actual: u32, expected: u32) {
+        assert!(
+            actual.abs_diff(expected) <= 5,
+            "{} is not within 5 minutes of {}",
+            format_hhmm(actual),
+            format_hhmm(expected)
+        );
+    }
+
+    #[test]
+    fn sun_times_berlin_midsummer() {
+        // 21 June, UTC+2: sunrise ~04:43, sunset ~21:33
+        let (sunrise, sunset) = sun_times(172, 52.52, 13.405, 120).unwrap();
+        assert_near(sunrise, 4 * 60 + 43);
+        assert_near(sunset, 21 * 60 + 33);
+    }
+
+    #[test]
+    fn sun_times_equator_equinox() {
+        let (sunrise, sunset) = sun_times(79, 0.0, 0.0, 0).unwrap();
+        assert_near(sunrise, 6 * 60 + 4);
+        assert_near(sunset, 18 * 60 + 11);
+    }
+
+    #[test]
+    fn sun_times_polar_day_is_none() {
+        assert_eq!(sun_times(172, 80.0, 15.0, 120), None);
+    }
+
+    #[test]
+    fn time_window_reason_names_current_window() {
+        assert_eq!(
+            time_window_reason(true, "19:00", "07:00"),
+            "dark from 19:00 until 07:00"
+        );
+        assert_eq!(
+            time_window_reason(false, "19:00", "07:00"),
+            "light from 07:00 until 19:00"
+        );
+    }
+
+    #[test]
+    fn format_hhmm_pads() {
+        assert_eq!(format_hhmm(0), "00:00");
+        assert_eq!(format_hhmm(4 * 60 + 3), "04:03");
+    }
+
+    #[test]
+    fn portal_scheme_values() {
+        assert_eq!(portal_scheme_is_dark(1), Some(true));
+        assert_eq!(portal_scheme_is_dark(2), Some(false));
+        assert_eq!(portal_scheme_is_dark(0), None);
+    }
+
+    #[test]
+    fn parse_apps_use_light_theme_values() {
+        let dark = "\r\nHKEY_CURRENT_USER\\Software\\Microsoft\\Windows\\CurrentVersion\\Themes\\Personalize\r\n    AppsUseLightTheme    REG_DWORD    0x0\r\n\r\n";
+        assert_eq!(parse_apps_use_light_theme(dark), Some(true));
+        let light = "    AppsUseLightTheme    REG_DWORD    0x1\r\n";
+        assert_eq!(parse_apps_use_light_theme(light), Some(false));
+        assert_eq!(parse_apps_use_light_theme("ERROR: not found"), None);
+    }
+
+    #[test]
+    fn is_dark_time_normal_schedule() {
+        // light 07:00, dark 19:00
+        assert!(is_dark_time(0, 1140, 420));
+        assert!(!is_dark_time(420, 1140, 420));
+        assert!(!is_dark_time(1139, 1140, 420));
+        assert!(is_dark_time(1140, 1140, 420));
+    }
+
+    #[test]
+    fn is_dark_time_inverted_schedule() {
+        // dark 01:00, light 09:00
+        assert!(!is_dark_time(0, 60, 540));
+        assert!(is_dark_time(60, 60, 540));
+        assert!(!is_dark_time(540, 60, 540));
+    }
+
+    #[test]
+    fn seconds_until_next_same_day() {
+        // 18:59:30 -> 19:00 is 30 seconds away
+        assert_eq!(seconds_until_next(68_370, &[1140, 420]), Some(30));
+    }
+
+    #[test]
+    fn seconds_until_next_wraps_past_midnight() {
+        // 23:00 -> 07:00 next day
+        assert_eq!(seconds_until_next(82_800, &[1140, 420]), Some(8 * 3600));
+    }
+
+    #[test]
+    fn seconds_until_next_boundary_now_is_tomorrow() {
+        assert_eq!(seconds_until_next(68_400, &[1140]), Some(86_400));
+    }
+
     #[test]
     fn seconds_until_boundary_returns_some() {
         let result = seconds_until_boundary("19:00", "07:00");
         assert!(result.is_some());
         assert!(result.unwrap() > 0);
     }
+
+    #[test]
+    fn days_from_civil_counts_from_the_epoch() {
+        assert_eq!(days_from_civil(1970, 1, 1), 0);
+        assert_eq!(days_from_civil(2000, 3, 1), 11_017);
+        assert_eq!(days_from_civil(1969, 12, 31), -1);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn utc_offset_matches_tm_gmtoff() {
+        let now = unix_now();
+        assert_eq!(utc_offset_at(now), local_tm_at(now).tm_gmtoff);
+    }
 }
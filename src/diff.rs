@@ -0,0 +1,224 @@
+use std::io::IsTerminal;
+
+/// Unchanged lines shown around each change in a unified diff.
+const CONTEXT: usize = 3;
+
+/// Past this many line pairs the diff gives up on matching lines and shows
+/// everything as replaced, rather than building a huge table.
+const MAX_CELLS: usize = 4_000_000;
+
+/// One line of a diff.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change {
+    Same(String),
+    Removed(String),
+    Added(String),
+}
+
+/// A run of changes with the unchanged lines around them. Starts are
+/// 1-based line numbers.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hunk {
+    pub old_start: usize,
+    pub old_len: usize,
+    pub new_start: usize,
+    pub new_len: usize,
+    pub lines: Vec<Change>,
+}
+
+/// Line-by-line changes from `old` to `new`, keeping the longest run of
+/// common lines.
+pub fn changes(old: &str, new: &str) -> Vec<Change> {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+    if old.len().saturating_mul(new.len()) > MAX_CELLS {
+        return old
+            .iter()
+            .map(|l| Change::Removed(l.to_string()))
+            .chain(new.iter().map(|l| Change::Added(l.to_string())))
+            .collect();
+    }
+
+    // common[i][j]: longest common subsequence of old[i..] and new[j..]
+    let mut common = vec![vec![0u32; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            common[i][j] = if old[i] == new[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+
+    let mut out = Vec::with_capacity(old.len().max(new.len()));
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() && j < new.len() {
+        if old[i] == new[j] {
+            out.push(Change::Same(old[i].to_string()));
+            i += 1;
+            j += 1;
+        } else if common[i + 1][j] >= common[i][j + 1] {
+            out.push(Change::Removed(old[i].to_string()));
+            i += 1;
+        } else {
+            out.push(Change::Added(new[j].to_string()));
+            j += 1;
+        }
+    }
+    out.extend(old[i..].iter().map(|l| Change::Removed(l.to_string())));
+    out.extend(new[j..].iter().map(|l| Change::Added(l.to_string())));
+    out
+}
+
+/// Group `changes` into hunks with `context` unchanged lines on each side,
+/// merging hunks whose context would overlap.
+pub fn hunks(changes: &[Change], context: usize) -> Vec<Hunk> {
+    // The 0-based old and new line each change sits at
+    let mut positions = Vec::with_capacity(changes.len());
+    let (mut old_line, mut new_line) = (0, 0);
+    for change in changes {
+        positions.push((old_line, new_line));
+        match change {
+            Change::Same(_) => {
+                old_line += 1;
+                new_line += 1;
+            }
+            Change::Removed(_) => old_line += 1,
+            Change::Added(_) => new_line += 1,
+        }
+    }
+
+    let mut hunks = Vec::new();
+    let mut i = 0;
+    while i < changes.len() {
+        if matches!(changes[i], Change::Same(_)) {
+            i += 1;
+            continue;
+        }
+        let start = i.saturating_sub(context);
+        let mut last = i;
+        for (j, change) in changes.iter().enumerate().skip(i + 1) {
+            if !matches!(change, Change::Same(_)) {
+                last = j;
+            } else if j - last > 2 * context {
+                break;
+            }
+        }
+        let stop = (last + context + 1).min(changes.len());
+        let lines = changes[start..stop].to_vec();
+        let (old_start, new_start) = positions[start];
+        hunks.push(Hunk {
+            old_start: old_start + 1,
+            old_len: lines
+                .iter()
+                .filter(|c| !matches!(c, Change::Added(_)))
+                .count(),
+            new_start: new_start + 1,
+            new_len: lines
+                .iter()
+                .filter(|c| !matches!(c, Change::Removed(_)))
+                .count(),
+            lines,
+        });
+        i = stop;
+    }
+    hunks
+}
+
+/// A unified diff from `old` to `new`, or an empty string when they match.
+pub fn unified(old: &str, new: &str, old_label: &str, new_label: &str) -> String {
+    let hunks = hunks(&changes(old, new), CONTEXT);
+    if hunks.is_empty() {
+        return String::new();
+    }
+    let mut out = format!("--- {}\n+++ {}\n", old_label, new_label);
+    for hunk in &hunks {
+        // An empty side is numbered from the line before it, like diff(1)
+        let old_start = hunk.old_start - usize::from(hunk.old_len == 0);
+        let new_start = hunk.new_start - usize::from(hunk.new_len == 0);
+        out.push_str(&format!(
+            "@@ -{},{} +{},{} @@\n",
+            old_start, hunk.old_len, new_start, hunk.new_len
+        ));
+        for line in &hunk.lines {
+            let (sign, text) = match line {
+                Change::Same(l) => (' ', l),
+                Change::Removed(l) => ('-', l),
+                Change::Added(l) => ('+', l),
+            };
+            out.push(sign);
+            out.push_str(text);
+            out.push('\n');
+        }
+    }
+    out
+}
+
+/// Print a unified diff, colored when stdout is a terminal.
+pub fn print(diff: &str) {
+    if !std::io::stdout().is_terminal() {
+        print!("{}", diff);
+        return;
+    }
+    for line in diff.lines() {
+        let color = if line.starts_with("+++") || line.starts_with("---") {
+            "1"
+        } else if line.starts_with("@@") {
+            "36"
+        } else if line.starts_with('+') {
+            "32"
+        } else if line.starts_with('-') {
+            "31"
+        } else {
+            ""
+        };
+        if color.is_empty() {
+            println!("{}", line);
+        } else {
+            println!("\x1b[{}m{}\x1b[0m", color, line);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn changes_keep_common_lines() {
+        let diff = changes("a\nb\nc", "a\nx\nc\nd");
+        assert_eq!(
+            diff,
+            [
+                Change::Same("a".into()),
+                Change::Removed("b".into()),
+                Change::Added("x".into()),
+                Change::Same("c".into()),
+                Change::Added("d".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn unified_splits_distant_changes_into_hunks() {
+        let old: String = (1..=20).map(|n| format!("line {}\n", n)).collect();
+        let new = old
+            .replace("line 2\n", "line two\n")
+            .replace("line 18\n", "");
+        let diff = unified(&old, &new, "a", "b");
+        assert!(diff.starts_with("--- a\n+++ b\n@@ -1,5 +1,5 @@\n line 1\n-line 2\n+line two\n"));
+        assert!(diff.contains("@@ -15,6 +15,5 @@\n"));
+        assert_eq!(diff.matches("@@ -").count(), 2);
+    }
+
+    #[test]
+    fn unified_is_empty_without_changes() {
+        assert_eq!(unified("a\nb\n", "a\nb\n", "a", "b"), "");
+    }
+
+    #[test]
+    fn unified_numbers_an_empty_old_file_from_zero() {
+        assert!(unified("", "new\n", "a", "b").contains("@@ -0,0 +1,1 @@\n+new\n"));
+    }
+}
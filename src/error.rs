@@ -0,0 +1,187 @@
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use serde_json::json;
+
+/// Errors from the API, collections, config, cycling, daemon, and export
+/// modules. Each carries the URL, path, or name it's about, so the CLI can
+/// pick an exit code and print it as JSON, and the TUI can show it as-is.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("Network error: {source}")]
+    Network {
+        url: String,
+        #[source]
+        source: reqwest::Error,
+    },
+    #[error("API error: {status} for {url}")]
+    Api {
+        url: String,
+        status: reqwest::StatusCode,
+    },
+    #[error("Failed to parse {what}: {message}")]
+    Parse { what: String, message: String },
+    #[error("Failed to {action} {}: {source}", path.display())]
+    Io {
+        action: &'static str,
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("{what} '{name}' not found")]
+    NotFound { what: &'static str, name: String },
+    /// Not logged in, or the site rejected the token.
+    #[error("{0}")]
+    Auth(String),
+    /// Another ghostty-styles process kept a file locked too long.
+    #[error("{0}")]
+    Locked(String),
+    /// Bad input or a state the command can't work with.
+    #[error("{0}")]
+    Invalid(String),
+    /// From modules that still report errors as plain strings.
+    #[error("{0}")]
+    Other(String),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl Error {
+    pub fn io(action: &'static str, path: &Path, source: std::io::Error) -> Self {
+        Error::Io {
+            action,
+            path: path.to_path_buf(),
+            source,
+        }
+    }
+
+    pub fn parse(what: impl Into<String>, message: impl ToString) -> Self {
+        Error::Parse {
+            what: what.into(),
+            message: message.to_string(),
+        }
+    }
+
+    pub fn not_found(what: &'static str, name: &str) -> Self {
+        Error::NotFound {
+            what,
+            name: name.to_string(),
+        }
+    }
+
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Error::Network { .. } => "network",
+            Error::Api { .. } => "api",
+            Error::Parse { .. } => "parse",
+            Error::Io { .. } => "io",
+            Error::NotFound { .. } => "not_found",
+            Error::Auth(_) => "auth",
+            Error::Locked(_) => "locked",
+            Error::Invalid(_) => "invalid",
+            Error::Other(_) => "other",
+        }
+    }
+
+    /// The process exit code for this error; documented in the README.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Error::Other(_) => 1,
+            Error::Invalid(_) => 2,
+            Error::Network { .. } | Error::Api { .. } => 3,
+            Error::NotFound { .. } => 4,
+            Error::Parse { .. } => 5,
+            Error::Io { .. } => 6,
+            Error::Auth(_) => 7,
+            Error::Locked(_) => 8,
+        }
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        let mut value = json!({
+            "kind": self.kind(),
+            "message": self.to_string(),
+        });
+        match self {
+            Error::Network { url, .. } => value["url"] = json!(url),
+            Error::Api { url, status } => {
+                value["url"] = json!(url);
+                value["status"] = json!(status.as_u16());
+            }
+            Error::Io { path, .. } => value["path"] = json!(path.display().to_string()),
+            Error::NotFound { name, .. } => value["name"] = json!(name),
+            Error::Parse { what, .. } => value["what"] = json!(what),
+            Error::Auth(_) | Error::Locked(_) | Error::Invalid(_) | Error::Other(_) => {}
+        }
+        json!({ "error": value })
+    }
+}
+
+impl From<String> for Error {
+    fn from(message: String) -> Self {
+        Error::Other(message)
+    }
+}
+
+impl From<&str> for Error {
+    fn from(message: &str) -> Self {
+        Error::Other(message.to_string())
+    }
+}
+
+impl From<Error> for String {
+    fn from(e: Error) -> Self {
+        e.to_string()
+    }
+}
+
+static JSON_ERRORS: AtomicBool = AtomicBool::new(false);
+
+/// Print CLI errors as JSON from now on (`--json-errors`).
+pub fn set_json(on: bool) {
+    JSON_ERRORS.store(on, Ordering::Relaxed);
+}
+
+/// Print the error on stderr and exit with its code.
+pub fn exit(e: impl Into<Error>) -> ! {
+    let e = e.into();
+    if JSON_ERRORS.load(Ordering::Relaxed) {
+        eprintln!("{}", e.to_json());
+    } else {
+        eprintln!("Error: {}", e);
+    }
+    std::process::exit(e.exit_code());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn not_found_names_the_thing() {
+        let e = Error::not_found("Collection", "work");
+        assert_eq!(e.to_string(), "Collection 'work' not found");
+        assert_eq!(e.exit_code(), 4);
+        assert_eq!(e.to_json()["error"]["name"], "work");
+        assert_eq!(e.to_json()["error"]["kind"], "not_found");
+    }
+
+    #[test]
+    fn io_error_includes_path() {
+        let e = Error::io(
+            "read",
+            Path::new("/tmp/x.json"),
+            std::io::Error::new(std::io::ErrorKind::NotFound, "gone"),
+        );
+        assert_eq!(e.to_string(), "Failed to read /tmp/x.json: gone");
+        assert_eq!(e.to_json()["error"]["path"], "/tmp/x.json");
+    }
+
+    #[test]
+    fn strings_convert_both_ways() {
+        let e: Error = "plain".into();
+        assert_eq!(e.exit_code(), 1);
+        let s: String = Error::Invalid("bad".to_string()).into();
+        assert_eq!(s, "bad");
+    }
+}
@@ -1,9 +1,94 @@
 use std::fs;
+use std::io::Write;
 use std::process::Command;
 
+use crate::atomic;
 use crate::collection;
 use crate::config;
 use crate::creator::CreatorState;
+use crate::error::{Error, Result};
+use crate::theme::GhosttyConfig;
+
+/// Color formats for other terminals, written alongside the Ghostty `.conf`.
+#[derive(Debug, Clone, Copy, PartialEq, clap::ValueEnum)]
+pub enum Format {
+    /// kitty.conf color settings
+    Kitty,
+    /// A Windows Terminal `schemes` entry
+    WindowsTerminal,
+    /// VS Code `workbench.colorCustomizations` for the integrated terminal
+    Vscode,
+    /// tmux status line, pane border, and message colors
+    Tmux,
+    /// `*.colorN` lines for xterm, urxvt, and other Xresources readers
+    Xresources,
+    /// A dircolors database, for `eval "$(dircolors -b FILE)"`
+    Dircolors,
+    /// `export LS_COLORS=...`, for shells without dircolors
+    LsColors,
+    /// A base16 scheme YAML, for base16 builders and templates
+    Base16,
+}
+
+impl Format {
+    pub const ALL: [Format; 8] = [
+        Format::Kitty,
+        Format::WindowsTerminal,
+        Format::Vscode,
+        Format::Tmux,
+        Format::Xresources,
+        Format::Dircolors,
+        Format::LsColors,
+        Format::Base16,
+    ];
+
+    /// File name suffix, after the theme slug.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Format::Kitty => "kitty.conf",
+            Format::WindowsTerminal => "windows-terminal.json",
+            Format::Vscode => "vscode.json",
+            Format::Tmux => "tmux.conf",
+            Format::Xresources => "Xresources",
+            Format::Dircolors => "dircolors",
+            Format::LsColors => "ls_colors.sh",
+            Format::Base16 => "base16.yaml",
+        }
+    }
+
+    pub fn render(&self, theme: &GhosttyConfig) -> String {
+        match self {
+            Format::Kitty => to_kitty(theme),
+            Format::WindowsTerminal => to_windows_terminal(theme),
+            Format::Vscode => to_vscode(theme),
+            Format::Tmux => to_tmux(theme),
+            Format::Xresources => to_xresources(theme),
+            Format::Dircolors => to_dircolors(&theme.title, &theme.palette),
+            Format::LsColors => ls_colors_script(&theme.title, &theme.palette),
+            Format::Base16 => to_base16(theme),
+        }
+    }
+}
+
+/// A kitty.conf snippet with the theme's colors.
+pub fn to_kitty(theme: &GhosttyConfig) -> String {
+    let mut out = format!("# {}\n", theme.title);
+    let mut line = |key: &str, value: Option<&String>| {
+        if let Some(value) = value {
+            out.push_str(&format!("{} {}\n", key, value));
+        }
+    };
+    line("background", Some(&theme.background));
+    line("foreground", Some(&theme.foreground));
+    line("cursor", theme.cursor_color.as_ref());
+    line("cursor_text_color", theme.cursor_text.as_ref());
+    line("selection_background", theme.selection_bg.as_ref());
+    line("selection_foreground", theme.selection_fg.as_ref());
+    for (i, color) in theme.palette.iter().take(16).enumerate() {
+        line(&format!("color{}", i), Some(color));
+    }
+    out
+}
 
 /// Derive a URL-friendly slug from a title string.
 ///
@@ -21,33 +106,355 @@ pub fn slug_from_title(title: &str) -> String {
         .join("-")
 }
 
+/// Windows Terminal's names for the 16 ANSI colors, in palette order.
+pub const WT_COLOR_NAMES: [&str; 16] = [
+    "black",
+    "red",
+    "green",
+    "yellow",
+    "blue",
+    "purple",
+    "cyan",
+    "white",
+    "brightBlack",
+    "brightRed",
+    "brightGreen",
+    "brightYellow",
+    "brightBlue",
+    "brightPurple",
+    "brightCyan",
+    "brightWhite",
+];
+
+/// A color scheme object to paste into the `schemes` list of Windows
+/// Terminal's settings.json. Keys keep Windows Terminal's usual order.
+pub fn to_windows_terminal(theme: &GhosttyConfig) -> String {
+    let mut entries: Vec<(&str, &String)> = vec![
+        ("name", &theme.title),
+        ("background", &theme.background),
+        ("foreground", &theme.foreground),
+    ];
+    if let Some(cursor) = &theme.cursor_color {
+        entries.push(("cursorColor", cursor));
+    }
+    if let Some(selection) = &theme.selection_bg {
+        entries.push(("selectionBackground", selection));
+    }
+    entries.extend(WT_COLOR_NAMES.iter().copied().zip(theme.palette.iter()));
+    format!("{{\n{}\n}}\n", json_fields(&entries, "  "))
+}
+
+/// VS Code's suffixes for the 16 ANSI colors, in palette order.
+const VSCODE_COLOR_NAMES: [&str; 16] = [
+    "Black",
+    "Red",
+    "Green",
+    "Yellow",
+    "Blue",
+    "Magenta",
+    "Cyan",
+    "White",
+    "BrightBlack",
+    "BrightRed",
+    "BrightGreen",
+    "BrightYellow",
+    "BrightBlue",
+    "BrightMagenta",
+    "BrightCyan",
+    "BrightWhite",
+];
+
+/// A settings.json fragment that colors VS Code's integrated terminal.
+pub fn to_vscode(theme: &GhosttyConfig) -> String {
+    let ansi_keys: Vec<String> = VSCODE_COLOR_NAMES
+        .iter()
+        .map(|name| format!("terminal.ansi{}", name))
+        .collect();
+    let mut entries: Vec<(&str, &String)> = vec![
+        ("terminal.background", &theme.background),
+        ("terminal.foreground", &theme.foreground),
+    ];
+    if let Some(cursor) = &theme.cursor_color {
+        entries.push(("terminalCursor.foreground", cursor));
+    }
+    if let Some(cursor_text) = &theme.cursor_text {
+        entries.push(("terminalCursor.background", cursor_text));
+    }
+    if let Some(selection) = &theme.selection_bg {
+        entries.push(("terminal.selectionBackground", selection));
+    }
+    if let Some(selection) = &theme.selection_fg {
+        entries.push(("terminal.selectionForeground", selection));
+    }
+    entries.extend(
+        ansi_keys
+            .iter()
+            .map(String::as_str)
+            .zip(theme.palette.iter()),
+    );
+    format!(
+        "{{\n  \"workbench.colorCustomizations\": {{\n{}\n  }}\n}}\n",
+        json_fields(&entries, "    ")
+    )
+}
+
+/// tmux settings that color the status line, pane borders, and messages
+/// from the theme, for `source-file` in tmux.conf.
+pub fn to_tmux(theme: &GhosttyConfig) -> String {
+    let ansi = |i: usize| theme.palette.get(i).unwrap_or(&theme.foreground);
+    let (bg, fg) = (&theme.background, &theme.foreground);
+    let accent = ansi(4);
+    let muted = ansi(8);
+    let selection = theme.selection_bg.as_ref().unwrap_or(muted);
+    let lines = [
+        format!("# {}", theme.title),
+        format!("set -g status-style \"bg={},fg={}\"", bg, fg),
+        format!(
+            "set -g window-status-current-style \"bg={},fg={},bold\"",
+            accent, bg
+        ),
+        format!("set -g pane-border-style \"fg={}\"", muted),
+        format!("set -g pane-active-border-style \"fg={}\"", accent),
+        format!("set -g message-style \"bg={},fg={}\"", selection, fg),
+        format!("set -g mode-style \"bg={},fg={}\"", selection, fg),
+    ];
+    lines.join("\n") + "\n"
+}
+
+/// Xresources color lines, for `xrdb -merge`.
+pub fn to_xresources(theme: &GhosttyConfig) -> String {
+    let mut out = format!("! {}\n", theme.title);
+    out.push_str(&format!("*.background: {}\n", theme.background));
+    out.push_str(&format!("*.foreground: {}\n", theme.foreground));
+    if let Some(cursor) = &theme.cursor_color {
+        out.push_str(&format!("*.cursorColor: {}\n", cursor));
+    }
+    for (i, color) in theme.palette.iter().take(16).enumerate() {
+        out.push_str(&format!("*.color{}: {}\n", i, color));
+    }
+    out
+}
+
+/// Palette index for each of base08..base0F. Base16 has no real ANSI
+/// equivalent for orange (base09) or brown (base0F), so the bright red and
+/// bright magenta stand in.
+const BASE16_ACCENTS: [usize; 8] = [1, 9, 3, 2, 6, 4, 5, 13];
+
+/// A base16 scheme: background and grays from the background, palette
+/// grays, and foreground; accents from the ANSI colors.
+pub fn to_base16(theme: &GhosttyConfig) -> String {
+    let color = |i: usize| theme.palette.get(i).cloned().unwrap_or_default();
+    let selection = theme.selection_bg.clone().unwrap_or_else(|| color(8));
+    let mut bases = vec![
+        theme.background.clone(),
+        color(0),
+        selection,
+        color(8),
+        color(7),
+        theme.foreground.clone(),
+        color(15),
+        color(15),
+    ];
+    bases.extend(BASE16_ACCENTS.iter().map(|&i| color(i)));
+
+    let mut out = format!(
+        "scheme: \"{}\"\nauthor: \"{}\"\n",
+        yaml_escape(&theme.title),
+        yaml_escape(theme.author_name.as_deref().unwrap_or("ghostty-styles"))
+    );
+    for (i, hex) in bases.iter().enumerate() {
+        out.push_str(&format!(
+            "base0{:X}: \"{}\"\n",
+            i,
+            hex.trim_start_matches('#').to_lowercase()
+        ));
+    }
+    out
+}
+
+fn yaml_escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// File kinds colored by `ls`: LS_COLORS key, dircolors keyword, palette
+/// index, and whether it's bold.
+const LS_KINDS: [(&str, &str, usize, bool); 9] = [
+    ("di", "DIR", 4, true),
+    ("ln", "LINK", 6, true),
+    ("so", "SOCK", 5, true),
+    ("pi", "FIFO", 3, false),
+    ("bd", "BLK", 3, true),
+    ("cd", "CHR", 3, true),
+    ("or", "ORPHAN", 1, true),
+    ("ex", "EXEC", 2, true),
+    ("su", "SETUID", 1, false),
+];
+
+/// Extensions colored by `ls`, with their palette index.
+const LS_EXTENSIONS: [(&str, usize); 12] = [
+    ("tar", 1),
+    ("gz", 1),
+    ("zip", 1),
+    ("xz", 1),
+    ("zst", 1),
+    ("7z", 1),
+    ("png", 5),
+    ("jpg", 5),
+    ("jpeg", 5),
+    ("gif", 5),
+    ("svg", 5),
+    ("mp4", 5),
+];
+
+/// The SGR attribute for palette color `index` as 24-bit color, so `ls`
+/// matches the theme even where the terminal palette differs.
+fn ls_sgr(palette: &[String], index: usize, bold: bool) -> Option<String> {
+    let (r, g, b) = GhosttyConfig::parse_hex(palette.get(index)?)?;
+    let weight = if bold { "01;" } else { "" };
+    Some(format!("{}38;2;{};{};{}", weight, r, g, b))
+}
+
+/// An LS_COLORS value from the theme's palette. Kinds whose palette color
+/// is missing are left to `ls`'s defaults.
+pub fn ls_colors(palette: &[String]) -> String {
+    let kinds = LS_KINDS.iter().filter_map(|(key, _, index, bold)| {
+        Some(format!("{}={}", key, ls_sgr(palette, *index, *bold)?))
+    });
+    let extensions = LS_EXTENSIONS
+        .iter()
+        .filter_map(|(ext, index)| Some(format!("*.{}={}", ext, ls_sgr(palette, *index, false)?)));
+    kinds.chain(extensions).collect::<Vec<_>>().join(":")
+}
+
+/// A dircolors database with the same colors as `ls_colors`.
+pub fn to_dircolors(title: &str, palette: &[String]) -> String {
+    let mut out = format!("# {}\n", title);
+    for (_, keyword, index, bold) in LS_KINDS {
+        if let Some(sgr) = ls_sgr(palette, index, bold) {
+            out.push_str(&format!("{} {}\n", keyword, sgr));
+        }
+    }
+    for (ext, index) in LS_EXTENSIONS {
+        if let Some(sgr) = ls_sgr(palette, index, false) {
+            out.push_str(&format!(".{} {}\n", ext, sgr));
+        }
+    }
+    out
+}
+
+/// A shell snippet that exports `ls_colors`.
+pub fn ls_colors_script(title: &str, palette: &[String]) -> String {
+    format!("# {}\nexport LS_COLORS='{}'\n", title, ls_colors(palette))
+}
+
+/// `"key": "value"` lines for a JSON object body, in the given order.
+fn json_fields(entries: &[(&str, &String)], indent: &str) -> String {
+    entries
+        .iter()
+        .map(|(key, value)| {
+            format!(
+                "{}{}: {}",
+                indent,
+                serde_json::Value::String(key.to_string()),
+                serde_json::Value::String(value.to_string())
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(",\n")
+}
+
 /// Export a theme as a `.conf` file to `~/.config/ghostty-styles/themes/<slug>.conf`.
 ///
-/// Creates the themes directory if it does not exist. Returns the absolute path
-/// to the written file on success.
-pub fn export_theme(state: &CreatorState) -> Result<String, String> {
-    let themes_dir = collection::base_dir().join("themes");
-    fs::create_dir_all(&themes_dir)
-        .map_err(|e| format!("Failed to create themes directory: {}", e))?;
-
+/// Creates the themes directory if it does not exist, and writes the other
+/// terminal formats next to it. Returns the absolute path to the `.conf`
+/// file on success.
+pub fn export_theme(state: &CreatorState) -> Result<String> {
     let slug = slug_from_title(&state.title);
     if slug.is_empty() {
-        return Err("Theme title is empty — cannot generate file name".to_string());
+        return Err(Error::Invalid(
+            "Theme title is empty — cannot generate file name".to_string(),
+        ));
     }
 
+    let themes_dir = themes_dir()?;
     let file_path = themes_dir.join(format!("{}.conf", slug));
     let raw_config = state.build_raw_config();
 
-    fs::write(&file_path, &raw_config).map_err(|e| format!("Failed to write theme file: {}", e))?;
+    atomic::write(&file_path, &raw_config).map_err(|e| Error::io("write", &file_path, e))?;
+
+    let theme = state.build_preview_config();
+    for format in Format::ALL {
+        write_format(&themes_dir, &slug, &theme, format)?;
+    }
 
     Ok(file_path.display().to_string())
 }
 
+/// Export any theme in another terminal's format to
+/// `~/.config/ghostty-styles/themes/<slug>.<ext>`. Returns the written path.
+pub fn export_as(theme: &GhosttyConfig, format: Format) -> Result<String> {
+    let slug = slug_from_title(&theme.slug);
+    if slug.is_empty() {
+        return Err(Error::Invalid(
+            "Theme has no slug — cannot generate file name".to_string(),
+        ));
+    }
+    write_format(&themes_dir()?, &slug, theme, format)
+}
+
+/// Write one rendering of a theme to `out`, or to stdout when `out` is `-`.
+/// Without a format that's the theme's Ghostty config, titled so `apply -`
+/// and `create --import -` keep the name.
+pub fn export_to(
+    theme: &GhosttyConfig,
+    format: Option<Format>,
+    out: &std::path::Path,
+) -> Result<()> {
+    let content = match format {
+        Some(format) => format.render(theme),
+        None => ghostty_config(theme),
+    };
+    if out == std::path::Path::new("-") {
+        let mut stdout = std::io::stdout();
+        return stdout
+            .write_all(content.as_bytes())
+            .and_then(|()| stdout.flush())
+            .map_err(|e| Error::io("write", out, e));
+    }
+    fs::write(out, content).map_err(|e| Error::io("write", out, e))
+}
+
+/// The theme's Ghostty config with a `# Theme:` title line.
+pub fn ghostty_config(theme: &GhosttyConfig) -> String {
+    let mut out = format!("# Theme: {}\n{}", theme.title, theme.raw_config);
+    if !out.ends_with('\n') {
+        out.push('\n');
+    }
+    out
+}
+
+fn themes_dir() -> Result<std::path::PathBuf> {
+    let dir = collection::base_dir().join("themes");
+    fs::create_dir_all(&dir).map_err(|e| Error::io("create", &dir, e))?;
+    Ok(dir)
+}
+
+fn write_format(
+    dir: &std::path::Path,
+    slug: &str,
+    theme: &GhosttyConfig,
+    format: Format,
+) -> Result<String> {
+    let path = dir.join(format!("{}.{}", slug, format.extension()));
+    atomic::write(&path, format.render(theme)).map_err(|e| Error::io("write", &path, e))?;
+    Ok(path.display().to_string())
+}
+
 /// Apply the creator's current theme to the Ghostty config file.
 ///
 /// Builds a `GhosttyConfig` from the `CreatorState` and delegates to
 /// `config::apply_theme`. Returns the config file path on success.
-pub fn apply_created_theme(state: &CreatorState) -> Result<String, String> {
+pub fn apply_created_theme(state: &CreatorState) -> Result<String> {
     let ghostty_config = state.build_preview_config();
     config::apply_theme(&ghostty_config)
 }
@@ -55,7 +462,7 @@ pub fn apply_created_theme(state: &CreatorState) -> Result<String, String> {
 /// Open a URL in the user's default browser.
 ///
 /// Uses `open` on macOS and `xdg-open` on Linux.
-pub fn open_url(url: &str) -> Result<(), String> {
+pub fn open_url(url: &str) -> Result<()> {
     let program = if cfg!(target_os = "macos") {
         "open"
     } else {
@@ -65,7 +472,7 @@ pub fn open_url(url: &str) -> Result<(), String> {
     Command::new(program)
         .arg(url)
         .spawn()
-        .map_err(|e| format!("Failed to open URL with {}: {}", program, e))?;
+        .map_err(|e| Error::Other(format!("Failed to open URL with {}: {}", program, e)))?;
 
     Ok(())
 }
@@ -74,7 +481,7 @@ pub fn open_url(url: &str) -> Result<(), String> {
 ///
 /// Returns a user-facing message indicating the saved path and that the upload
 /// page has been opened.
-pub fn upload_theme(state: &CreatorState) -> Result<String, String> {
+pub fn upload_theme(state: &CreatorState) -> Result<String> {
     let path = export_theme(state)?;
 
     open_url("https://ghostty-style.vercel.app/upload")?;
@@ -130,12 +537,143 @@ mod tests {
         assert_eq!(slug_from_title("Theme 42"), "theme-42");
     }
 
+    #[test]
+    fn ghostty_config_round_trips_through_import() {
+        let theme = CreatorState::new("Piped").build_preview_config();
+        let conf = ghostty_config(&theme);
+        assert!(conf.starts_with("# Theme: Piped\n"));
+        assert!(conf.ends_with('\n'));
+        let read = config::theme_from_config(&conf, "stdin");
+        assert_eq!(read.title, "Piped");
+        assert_eq!(read.raw_config.trim(), theme.raw_config.trim());
+    }
+
+    #[test]
+    fn base16_maps_palette() {
+        let mut theme = CreatorState::new("Say \"Hi\"").build_preview_config();
+        theme.selection_bg = None;
+        let yaml = to_base16(&theme);
+        assert!(yaml.starts_with("scheme: \"Say \\\"Hi\\\"\"\nauthor: \"ghostty-styles\"\n"));
+        let hex = |c: &str| c.trim_start_matches('#').to_lowercase();
+        assert!(yaml.contains(&format!("base00: \"{}\"\n", hex(&theme.background))));
+        assert!(yaml.contains(&format!("base02: \"{}\"\n", hex(&theme.palette[8]))));
+        assert!(yaml.contains(&format!("base05: \"{}\"\n", hex(&theme.foreground))));
+        assert!(yaml.contains(&format!("base08: \"{}\"\n", hex(&theme.palette[1]))));
+        assert!(yaml.contains(&format!("base0F: \"{}\"\n", hex(&theme.palette[13]))));
+        assert_eq!(yaml.lines().count(), 18);
+    }
+
+    #[test]
+    fn kitty_has_colors_and_palette() {
+        let theme = CreatorState::new("Kitty Test").build_preview_config();
+        let kitty = to_kitty(&theme);
+        assert!(kitty.starts_with("# Kitty Test\n"));
+        assert!(kitty.contains(&format!("background {}\n", theme.background)));
+        assert!(kitty.contains("cursor_text_color #"));
+        assert!(kitty.contains("selection_foreground #"));
+        assert!(kitty.contains(&format!("color15 {}\n", theme.palette[15])));
+        assert!(!kitty.contains("color16"));
+    }
+
+    #[test]
+    fn kitty_skips_missing_optional_colors() {
+        let mut theme = CreatorState::new("Bare").build_preview_config();
+        theme.cursor_color = None;
+        theme.selection_bg = None;
+        let kitty = to_kitty(&theme);
+        assert!(!kitty.contains("cursor #"));
+        assert!(!kitty.contains("selection_background"));
+    }
+
+    #[test]
+    fn windows_terminal_is_valid_scheme_json() {
+        let mut theme = CreatorState::new("Say \"Hi\"").build_preview_config();
+        theme.cursor_color = None;
+        let json = to_windows_terminal(&theme);
+        let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed["name"], "Say \"Hi\"");
+        assert_eq!(parsed["background"], theme.background.as_str());
+        assert_eq!(parsed["brightWhite"], theme.palette[15].as_str());
+        assert!(parsed.get("cursorColor").is_none());
+        assert!(json.find("\"name\"").unwrap() < json.find("\"black\"").unwrap());
+    }
+
+    #[test]
+    fn vscode_nests_terminal_colors() {
+        let theme = CreatorState::new("Code").build_preview_config();
+        let json = to_vscode(&theme);
+        let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+        let colors = &parsed["workbench.colorCustomizations"];
+        assert_eq!(colors["terminal.background"], theme.background.as_str());
+        assert_eq!(colors["terminal.ansiBlack"], theme.palette[0].as_str());
+        assert_eq!(
+            colors["terminal.ansiBrightWhite"],
+            theme.palette[15].as_str()
+        );
+        assert!(colors["terminalCursor.foreground"].is_string());
+    }
+
+    #[test]
+    fn tmux_uses_theme_colors() {
+        let theme = CreatorState::new("Tmux").build_preview_config();
+        let tmux = to_tmux(&theme);
+        assert!(tmux.contains(&format!(
+            "status-style \"bg={},fg={}\"",
+            theme.background, theme.foreground
+        )));
+        assert!(tmux.contains(&format!(
+            "pane-active-border-style \"fg={}\"",
+            theme.palette[4]
+        )));
+    }
+
+    #[test]
+    fn tmux_tolerates_short_palette() {
+        let mut theme = CreatorState::new("Short").build_preview_config();
+        theme.palette.clear();
+        theme.selection_bg = None;
+        let tmux = to_tmux(&theme);
+        assert!(tmux.contains(&format!("pane-border-style \"fg={}\"", theme.foreground)));
+    }
+
+    #[test]
+    fn xresources_lines() {
+        let theme = CreatorState::new("Xres").build_preview_config();
+        let xres = to_xresources(&theme);
+        assert!(xres.starts_with("! Xres\n"));
+        assert!(xres.contains(&format!("*.background: {}\n", theme.background)));
+        assert!(xres.contains(&format!("*.color0: {}\n", theme.palette[0])));
+        assert!(xres.contains(&format!("*.color15: {}\n", theme.palette[15])));
+    }
+
+    #[test]
+    fn ls_colors_uses_palette_as_truecolor() {
+        let mut palette = vec![String::new(); 16];
+        palette[4] = "#7aa2f7".to_string();
+        palette[1] = "#f7768e".to_string();
+        let ls = ls_colors(&palette);
+        assert!(ls.starts_with("di=01;38;2;122;162;247:"));
+        assert!(ls.contains(":*.tar=38;2;247;118;142"));
+        // No palette color, no entry
+        assert!(!ls.contains("ex="));
+    }
+
+    #[test]
+    fn dircolors_matches_ls_colors() {
+        let theme = CreatorState::new("Dir").build_preview_config();
+        let db = to_dircolors(&theme.title, &theme.palette);
+        let ls = ls_colors(&theme.palette);
+        let dir = ls.split(':').next().unwrap().trim_start_matches("di=");
+        assert!(db.contains(&format!("DIR {}\n", dir)));
+        assert!(db.contains(".png 38;2;"));
+    }
+
     #[test]
     fn export_theme_empty_title_fails() {
         let mut state = CreatorState::new("test");
         state.title = String::new();
         let result = export_theme(&state);
         assert!(result.is_err());
-        assert!(result.unwrap_err().contains("empty"));
+        assert!(result.unwrap_err().to_string().contains("empty"));
     }
 }
@@ -0,0 +1,102 @@
+use std::fs;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::atomic;
+use crate::collection;
+use crate::error::{Error, Result};
+use crate::library;
+use crate::theme::GhosttyConfig;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Favorite {
+    pub slug: String,
+    pub title: String,
+}
+
+/// `~/.config/ghostty-styles/favorites.json`, in the order themes were starred.
+pub fn favorites_path() -> PathBuf {
+    collection::base_dir().join("favorites.json")
+}
+
+/// The starred themes; none when the file is missing or unreadable.
+pub fn load() -> Vec<Favorite> {
+    fs::read_to_string(favorites_path())
+        .ok()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+pub fn save(favorites: &[Favorite]) -> Result<()> {
+    let path = favorites_path();
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| Error::io("create", parent, e))?;
+    }
+    let json = serde_json::to_string_pretty(favorites).map_err(|e| Error::parse("favorites", e))?;
+    atomic::write(&path, json).map_err(|e| Error::io("write", &path, e))
+}
+
+/// Star or unstar a theme. Starred themes are also kept in the library, so
+/// they can be browsed and applied offline. Returns whether it's now starred.
+pub fn toggle(theme: &GhosttyConfig) -> Result<bool> {
+    let mut favorites = load();
+    let starred = toggle_in(&mut favorites, &theme.slug, &theme.title);
+    if starred {
+        library::remember(theme);
+    }
+    save(&favorites)?;
+    Ok(starred)
+}
+
+fn toggle_in(favorites: &mut Vec<Favorite>, slug: &str, title: &str) -> bool {
+    if let Some(pos) = favorites.iter().position(|f| f.slug == slug) {
+        favorites.remove(pos);
+        return false;
+    }
+    favorites.push(Favorite {
+        slug: slug.to_string(),
+        title: title.to_string(),
+    });
+    true
+}
+
+/// The favorite named by slug or 1-based position.
+pub fn find<'a>(favorites: &'a [Favorite], target: &str) -> Option<&'a Favorite> {
+    match target.parse::<usize>() {
+        Ok(n) if n >= 1 => favorites.get(n - 1),
+        _ => favorites.iter().find(|f| f.slug == target),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn toggle_stars_then_unstars() {
+        let mut favorites = Vec::new();
+        assert!(toggle_in(&mut favorites, "nord", "Nord"));
+        assert!(toggle_in(&mut favorites, "dracula", "Dracula"));
+        assert!(!toggle_in(&mut favorites, "nord", "Nord"));
+        assert_eq!(
+            favorites,
+            vec![Favorite {
+                slug: "dracula".to_string(),
+                title: "Dracula".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn find_by_slug_or_position() {
+        let mut favorites = Vec::new();
+        toggle_in(&mut favorites, "nord", "Nord");
+        toggle_in(&mut favorites, "dracula", "Dracula");
+        assert_eq!(find(&favorites, "2").unwrap().slug, "dracula");
+        assert_eq!(find(&favorites, "nord").unwrap().title, "Nord");
+        assert!(find(&favorites, "0").is_none());
+        assert!(find(&favorites, "3").is_none());
+        assert!(find(&favorites, "gruvbox").is_none());
+    }
+}
@@ -1,3 +1,7 @@
+use std::collections::HashSet;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
 pub fn reload_shortcut_label() -> &'static str {
@@ -40,3 +44,122 @@ pub fn try_reload_config() -> Result<(), String> {
         Err("automatic reload is not supported on this platform".to_string())
     }
 }
+
+/// Terminal devices of the shells running inside Ghostty, found by walking
+/// the process tree down from every `ghostty` process.
+pub fn session_ttys() -> Vec<PathBuf> {
+    let output = match Command::new("ps")
+        .args(["-A", "-o", "pid=,ppid=,tty=,comm="])
+        .output()
+    {
+        Ok(o) if o.status.success() => o,
+        _ => return Vec::new(),
+    };
+    ttys_under_ghostty(&String::from_utf8_lossy(&output.stdout))
+        .into_iter()
+        .map(|tty| PathBuf::from("/dev").join(tty))
+        .collect()
+}
+
+/// Write `sequence` to every Ghostty session's tty so open windows pick up
+/// new colors immediately. Returns how many sessions were updated.
+pub fn broadcast_osc(sequence: &str) -> usize {
+    if sequence.is_empty() {
+        return 0;
+    }
+    session_ttys()
+        .iter()
+        .filter(|path| {
+            open_tty(path)
+                .and_then(|mut tty| tty.write_all(sequence.as_bytes()))
+                .is_ok()
+        })
+        .count()
+}
+
+/// O_NOCTTY so the daemon never adopts a session as its terminal.
+#[cfg(unix)]
+fn open_tty(path: &Path) -> std::io::Result<File> {
+    use std::os::unix::fs::OpenOptionsExt;
+
+    OpenOptions::new()
+        .write(true)
+        .custom_flags(libc::O_NOCTTY | libc::O_NONBLOCK)
+        .open(path)
+}
+
+/// Windows has no ttys to find, so this is never reached in practice.
+#[cfg(windows)]
+fn open_tty(path: &Path) -> std::io::Result<File> {
+    OpenOptions::new().write(true).open(path)
+}
+
+/// Parse `ps -o pid=,ppid=,tty=,comm=` output and return the ttys (as
+/// names like `pts/3` or `ttys003`) of all descendants of Ghostty.
+fn ttys_under_ghostty(ps_output: &str) -> Vec<String> {
+    let procs: Vec<(u32, u32, &str, &str)> = ps_output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let pid = fields.next()?.parse().ok()?;
+            let ppid = fields.next()?.parse().ok()?;
+            let tty = fields.next()?;
+            let comm = fields.next()?;
+            Some((pid, ppid, tty, comm))
+        })
+        .collect();
+
+    let mut tree: HashSet<u32> = procs
+        .iter()
+        .filter(|(_, _, _, comm)| {
+            comm.rsplit('/')
+                .next()
+                .is_some_and(|name| name.eq_ignore_ascii_case("ghostty"))
+        })
+        .map(|(pid, ..)| *pid)
+        .collect();
+    loop {
+        let before = tree.len();
+        for (pid, ppid, ..) in &procs {
+            if tree.contains(ppid) {
+                tree.insert(*pid);
+            }
+        }
+        if tree.len() == before {
+            break;
+        }
+    }
+
+    let mut ttys: Vec<String> = procs
+        .iter()
+        .filter(|(pid, _, tty, _)| tree.contains(pid) && !tty.starts_with('?'))
+        .map(|(_, _, tty, _)| tty.to_string())
+        .collect();
+    ttys.sort();
+    ttys.dedup();
+    ttys
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ttys_under_ghostty_walks_descendants() {
+        let ps = "\
+    1     0 ?        init
+  100     1 ??       /Applications/Ghostty.app/Contents/MacOS/ghostty
+  101   100 ttys001  login
+  102   101 ttys001  -zsh
+  103   100 ttys002  login
+  200     1 pts/5    bash
+";
+        assert_eq!(ttys_under_ghostty(ps), vec!["ttys001", "ttys002"]);
+    }
+
+    #[test]
+    fn ttys_under_ghostty_without_ghostty_is_empty() {
+        let ps = "    1     0 ?        init\n  200     1 pts/5    bash\n";
+        assert!(ttys_under_ghostty(ps).is_empty());
+    }
+}
@@ -0,0 +1,459 @@
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Output, Stdio};
+
+use crate::atomic;
+use crate::collection::{self, AppConfig, Collection};
+use crate::error::{Error, Result};
+use crate::lock::{self, Resource};
+
+/// What the sync repo tracks: collections, app config, favorites, and
+/// templates. Everything else under the config dir stays on this machine,
+/// including the login token, caches, and lock files. Where this machine
+/// is in its cycle stays out too, though it lives in shared files; see
+/// `without_runtime`.
+const GITIGNORE: &str = "\
+# Written by `ghostty-styles sync init`; only these are shared
+/*
+!/.gitignore
+!/collections/
+!/config.json
+!/favorites.json
+!/templates/
+";
+
+const REMOTE: &str = "origin";
+
+const CONFIG_FILE: &str = "config.json";
+
+/// Tracked files as they were before a checkout or merge, by path relative
+/// to the repo, so this machine's runtime state can be put back.
+type Snapshot = Vec<(String, String)>;
+
+/// `git init` the config dir, optionally pointing it at `remote`. When the
+/// remote already has history (another machine pushed first), its files are
+/// checked out over ours, keeping local collections it doesn't have.
+pub fn init(remote: Option<&str>) -> Result<String> {
+    let _state = lock::acquire(Resource::State)?;
+    collection::ensure_dirs()?;
+    let dir = collection::base_dir();
+    let fresh = !dir.join(".git").exists();
+    if fresh {
+        git(&dir, &["init", "--quiet"])?;
+    }
+    let ignore = dir.join(".gitignore");
+    if !ignore.exists() {
+        atomic::write(&ignore, GITIGNORE).map_err(|e| Error::io("write", &ignore, e))?;
+    }
+
+    let mut notes = Vec::new();
+    let local = snapshot(&dir);
+    if let Some(url) = remote {
+        if has_remote(&dir) {
+            git(&dir, &["remote", "set-url", REMOTE, url])?;
+        } else {
+            git(&dir, &["remote", "add", REMOTE, url])?;
+        }
+        let branch = remote_branch(&dir)?;
+        if let Some(branch) = branch.filter(|_| !has_commits(&dir)) {
+            git(&dir, &["fetch", "--quiet", REMOTE, &branch])?;
+            let tracking = format!("{}/{}", REMOTE, branch);
+            git(&dir, &["checkout", "--quiet", "-B", &branch, &tracking])
+                .or_else(|_| adopt_remote(&dir, &branch, &tracking))?;
+            restore_runtime(&dir, &local)?;
+            notes.push(format!("Pulled {} from {}", branch, url));
+        }
+    }
+    if commit(&dir, "Start syncing ghostty-styles")? {
+        notes.push("Committed the current collections and config".to_string());
+    }
+    let verb = if fresh {
+        "Initialized"
+    } else {
+        "Already syncing"
+    };
+    notes.insert(0, format!("{} {}", verb, dir.display()));
+    Ok(notes.join("\n"))
+}
+
+/// Commit local changes and push the branch. Returns a summary line.
+pub fn push() -> Result<String> {
+    let dir = repo()?;
+    let committed = {
+        let _state = lock::acquire(Resource::State)?;
+        commit(&dir, &sync_message())?
+    };
+    require_remote(&dir)?;
+    let branch = current_branch(&dir)?;
+    git(&dir, &["push", "--quiet", "-u", REMOTE, &branch]).map_err(|e| match e {
+        Error::Other(msg) if msg.contains("rejected") || msg.contains("fetch first") => {
+            Error::Other(
+                "The remote has changes this machine doesn't; run `ghostty-styles sync pull` first"
+                    .to_string(),
+            )
+        }
+        e => e,
+    })?;
+    Ok(if committed {
+        format!(
+            "Committed local changes and pushed {} to {}",
+            branch, REMOTE
+        )
+    } else {
+        format!("Pushed {} to {}", branch, REMOTE)
+    })
+}
+
+/// Commit local changes, then merge the remote's. Where both machines
+/// edited the same lines the remote wins; the local edit stays in history.
+/// This machine keeps its own place in the cycle either way.
+pub fn pull() -> Result<String> {
+    let dir = repo()?;
+    require_remote(&dir)?;
+    let _state = lock::acquire(Resource::State)?;
+    commit(&dir, &sync_message())?;
+    // After the commit only runtime state differs from HEAD; check out
+    // HEAD's copy so the merge can update those files
+    let local = snapshot(&dir);
+    if !local.is_empty() {
+        let mut args = vec!["checkout", "--quiet", "--"];
+        args.extend(local.iter().map(|(rel, _)| rel.as_str()));
+        git(&dir, &args)?;
+    }
+    let branch = current_branch(&dir)?;
+    let before = head(&dir);
+    let merged = git(
+        &dir,
+        &[
+            "pull",
+            "--quiet",
+            "--no-rebase",
+            "--no-edit",
+            "-X",
+            "theirs",
+            REMOTE,
+            &branch,
+        ],
+    );
+    if merged.is_err() {
+        let _ = git(&dir, &["merge", "--abort"]);
+    }
+    restore_runtime(&dir, &local)?;
+    merged?;
+    Ok(if head(&dir) == before {
+        "Already up to date".to_string()
+    } else {
+        format!("Pulled changes from {}", REMOTE)
+    })
+}
+
+/// The config dir, if `sync init` has been run.
+fn repo() -> Result<std::path::PathBuf> {
+    let dir = collection::base_dir();
+    if !dir.join(".git").exists() {
+        return Err(Error::Invalid(
+            "Sync isn't set up; run `ghostty-styles sync init <remote>` first".to_string(),
+        ));
+    }
+    Ok(dir)
+}
+
+fn require_remote(dir: &Path) -> Result<()> {
+    if has_remote(dir) {
+        return Ok(());
+    }
+    Err(Error::Invalid(
+        "No remote to sync with; run `ghostty-styles sync init <remote>`".to_string(),
+    ))
+}
+
+/// Take the remote's history when local files would be overwritten by a
+/// plain checkout: point at it, then check its files out over ours.
+fn adopt_remote(dir: &Path, branch: &str, tracking: &str) -> Result<Output> {
+    git(
+        dir,
+        &["symbolic-ref", "HEAD", &format!("refs/heads/{}", branch)],
+    )?;
+    git(dir, &["reset", "--quiet", tracking])?;
+    git(dir, &["checkout", tracking, "--", "."])
+}
+
+/// Stage everything the `.gitignore` lets through, minus runtime state,
+/// and commit it. Returns whether there was anything to commit.
+fn commit(dir: &Path, message: &str) -> Result<bool> {
+    git(dir, &["add", "-A"])?;
+    for (rel, data) in snapshot(dir) {
+        let Some(shared) = without_runtime(&rel, &data).filter(|s| *s != data) else {
+            continue;
+        };
+        let blob = hash_blob(dir, &shared)?;
+        let entry = format!("100644,{},{}", blob, rel);
+        git(dir, &["update-index", "--cacheinfo", &entry])?;
+    }
+    let staged = git(dir, &["diff", "--cached", "--name-only"])?;
+    if staged.stdout.is_empty() {
+        return Ok(false);
+    }
+    let mut args = Vec::new();
+    // A machine with no git identity can still sync
+    if git(dir, &["config", "user.email"]).is_err() {
+        args.extend([
+            "-c",
+            "user.name=ghostty-styles",
+            "-c",
+            "user.email=ghostty-styles@localhost",
+        ]);
+    }
+    args.extend(["commit", "--quiet", "-m", message]);
+    git(dir, &args)?;
+    Ok(true)
+}
+
+/// The config and collection files, which hold runtime state as well as
+/// settings.
+fn snapshot(dir: &Path) -> Snapshot {
+    let mut files = vec![CONFIG_FILE.to_string()];
+    if let Ok(entries) = fs::read_dir(dir.join("collections")) {
+        let mut names: Vec<String> = entries
+            .flatten()
+            .filter_map(|e| e.file_name().into_string().ok())
+            .filter(|name| name.ends_with(".json"))
+            .collect();
+        names.sort();
+        files.extend(
+            names
+                .into_iter()
+                .map(|name| format!("collections/{}", name)),
+        );
+    }
+    files
+        .into_iter()
+        .filter_map(|rel| {
+            let data = fs::read_to_string(dir.join(&rel)).ok()?;
+            Some((rel, data))
+        })
+        .collect()
+}
+
+/// `data` without this machine's place in the cycle: the applied theme
+/// older versions kept in the config, the rotation's pointer, and each
+/// collection's current theme and shuffle bag. Committing those would make
+/// a commit per switch, and merging them would move other machines' cycles.
+/// `None` when the file doesn't parse, so it's committed as it is.
+fn without_runtime(rel: &str, data: &str) -> Option<String> {
+    if rel == CONFIG_FILE {
+        let mut config: AppConfig = serde_json::from_str(data).ok()?;
+        config.applied = None;
+        if let Some(rot) = config.rotation.as_mut() {
+            rot.position = 0;
+            rot.played = 0;
+        }
+        serde_json::to_string_pretty(&config).ok()
+    } else {
+        let mut coll: Collection = serde_json::from_str(data).ok()?;
+        coll.current_index = 0;
+        coll.shuffle_bag.clear();
+        serde_json::to_string_pretty(&coll).ok()
+    }
+}
+
+/// `merged` with the runtime state `without_runtime` leaves out taken from
+/// `local`, this machine's copy from before the merge.
+fn with_runtime(rel: &str, merged: &str, local: &str) -> Option<String> {
+    if rel == CONFIG_FILE {
+        let mut config: AppConfig = serde_json::from_str(merged).ok()?;
+        let local: AppConfig = serde_json::from_str(local).ok()?;
+        config.applied = local.applied;
+        if let (Some(rot), Some(old)) = (config.rotation.as_mut(), local.rotation) {
+            rot.position = old.position;
+            rot.played = old.played;
+        }
+        serde_json::to_string_pretty(&config).ok()
+    } else {
+        let mut coll: Collection = serde_json::from_str(merged).ok()?;
+        let local: Collection = serde_json::from_str(local).ok()?;
+        coll.current_index = local.current_index;
+        coll.shuffle_bag = local.shuffle_bag;
+        serde_json::to_string_pretty(&coll).ok()
+    }
+}
+
+/// Put this machine's runtime state from `local` back into the files a
+/// checkout or merge left behind. Files the merge removed stay removed.
+fn restore_runtime(dir: &Path, local: &Snapshot) -> Result<()> {
+    for (rel, old) in local {
+        let path = dir.join(rel);
+        let Ok(merged) = fs::read_to_string(&path) else {
+            continue;
+        };
+        if let Some(data) = with_runtime(rel, &merged, old).filter(|d| *d != merged) {
+            atomic::write(&path, &data).map_err(|e| Error::io("write", &path, e))?;
+        }
+    }
+    Ok(())
+}
+
+/// Store `data` as a blob in the repo and return its hash.
+fn hash_blob(dir: &Path, data: &str) -> Result<String> {
+    let failed = |e: std::io::Error| Error::Other(format!("Couldn't run git: {}", e));
+    let mut child = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(["hash-object", "-w", "--stdin"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(failed)?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(data.as_bytes()).map_err(failed)?;
+    }
+    let out = child.wait_with_output().map_err(failed)?;
+    if !out.status.success() {
+        return Err(Error::Other(format!(
+            "git hash-object: {}",
+            String::from_utf8_lossy(&out.stderr).trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&out.stdout).trim().to_string())
+}
+
+fn sync_message() -> String {
+    let host = collection::hostname().unwrap_or_else(|| "another machine".to_string());
+    format!("Sync from {}", host)
+}
+
+fn has_remote(dir: &Path) -> bool {
+    git(dir, &["remote", "get-url", REMOTE]).is_ok()
+}
+
+fn has_commits(dir: &Path) -> bool {
+    head(dir).is_some()
+}
+
+fn head(dir: &Path) -> Option<String> {
+    git(dir, &["rev-parse", "--verify", "--quiet", "HEAD"])
+        .ok()
+        .map(|out| String::from_utf8_lossy(&out.stdout).trim().to_string())
+}
+
+fn current_branch(dir: &Path) -> Result<String> {
+    let out = git(dir, &["symbolic-ref", "--short", "HEAD"])?;
+    Ok(String::from_utf8_lossy(&out.stdout).trim().to_string())
+}
+
+/// The remote's default branch, or `None` when it has no commits yet.
+fn remote_branch(dir: &Path) -> Result<Option<String>> {
+    let out = git(dir, &["ls-remote", "--symref", REMOTE, "HEAD"])?;
+    Ok(parse_symref(&String::from_utf8_lossy(&out.stdout)))
+}
+
+/// The branch in `ls-remote --symref` output's `ref: refs/heads/<b>\tHEAD`.
+fn parse_symref(output: &str) -> Option<String> {
+    output.lines().find_map(|line| {
+        let target = line.strip_prefix("ref: ")?.split('\t').next()?;
+        target.strip_prefix("refs/heads/").map(str::to_string)
+    })
+}
+
+/// Run `git` in `dir`, turning a non-zero exit into an error with its stderr.
+fn git(dir: &Path, args: &[&str]) -> Result<Output> {
+    let out = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => {
+                Error::Invalid("Syncing needs git, which isn't installed".to_string())
+            }
+            _ => Error::Other(format!("Couldn't run git: {}", e)),
+        })?;
+    if out.status.success() {
+        return Ok(out);
+    }
+    let stderr = String::from_utf8_lossy(&out.stderr).trim().to_string();
+    Err(Error::Other(if stderr.is_empty() {
+        format!("git {} failed", args.join(" "))
+    } else {
+        format!("git {}: {}", args.join(" "), stderr)
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_symref_reads_the_default_branch() {
+        let out = "ref: refs/heads/main\tHEAD\n3f2a9c1d\tHEAD\n";
+        assert_eq!(parse_symref(out).as_deref(), Some("main"));
+        assert_eq!(parse_symref(""), None);
+    }
+
+    #[test]
+    fn gitignore_keeps_secrets_out() {
+        let shared: Vec<&str> = GITIGNORE
+            .lines()
+            .filter_map(|l| l.strip_prefix("!/"))
+            .collect();
+        assert!(GITIGNORE.lines().any(|l| l == "/*"));
+        assert!(shared.contains(&"collections/"));
+        assert!(!shared.iter().any(|s| s.starts_with("auth")));
+    }
+
+    fn collection_json(current_index: usize, bag: &[&str], themes: &[&str]) -> String {
+        let themes: Vec<_> = themes
+            .iter()
+            .map(|slug| {
+                serde_json::json!({
+                    "slug": slug, "title": slug, "is_dark": true, "raw_config": ""
+                })
+            })
+            .collect();
+        serde_json::json!({
+            "name": "Evening",
+            "themes": themes,
+            "current_index": current_index,
+            "order": "sequential",
+            "interval": "30m",
+            "shuffle_bag": bag,
+        })
+        .to_string()
+    }
+
+    #[test]
+    fn commits_leave_out_where_the_cycle_is() {
+        let local = collection_json(2, &["a"], &["a", "b", "c"]);
+        let shared = without_runtime("collections/evening.json", &local).unwrap();
+        let coll: Collection = serde_json::from_str(&shared).unwrap();
+        assert_eq!(coll.current_index, 0);
+        assert!(coll.shuffle_bag.is_empty());
+        assert_eq!(coll.themes.len(), 3);
+        assert_eq!(coll.interval.as_deref(), Some("30m"));
+
+        let config = r#"{"active_collection":null,
+            "rotation":{"collections":["a","b"],"position":1,"played":4},
+            "applied":{"slug":"x","title":"X","applied_at":1}}"#;
+        let shared = without_runtime(CONFIG_FILE, config).unwrap();
+        let config: AppConfig = serde_json::from_str(&shared).unwrap();
+        assert!(config.applied.is_none());
+        let rot = config.rotation.unwrap();
+        assert_eq!((rot.position, rot.played), (0, 0));
+        assert_eq!(rot.collections, ["a", "b"]);
+
+        assert_eq!(without_runtime(CONFIG_FILE, "not json"), None);
+    }
+
+    #[test]
+    fn merges_keep_this_machines_place() {
+        let local = collection_json(2, &["c"], &["a", "b", "c"]);
+        let merged = collection_json(0, &[], &["a", "b", "c", "d"]);
+        let restored = with_runtime("collections/evening.json", &merged, &local).unwrap();
+        let coll: Collection = serde_json::from_str(&restored).unwrap();
+        assert_eq!(coll.current_index, 2);
+        assert_eq!(coll.shuffle_bag, ["c"]);
+        assert_eq!(coll.themes.len(), 4);
+    }
+}
@@ -0,0 +1,242 @@
+use std::fs;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::atomic;
+use crate::collection;
+use crate::darkmode;
+
+/// Oldest events are dropped once the log grows past this many.
+const MAX_EVENTS: usize = 1000;
+
+/// What caused a theme switch.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Trigger {
+    /// A command or the browser, e.g. `apply`, `next`, or `a`.
+    #[default]
+    Manual,
+    /// The daemon's interval or schedule.
+    Interval,
+    /// An OS appearance change or auto-time boundary.
+    Appearance,
+    /// A deferred switch applied by the shell prompt hook.
+    Prompt,
+    /// `rollback` to an earlier theme.
+    Rollback,
+}
+
+impl Trigger {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Trigger::Manual => "manual",
+            Trigger::Interval => "interval",
+            Trigger::Appearance => "appearance",
+            Trigger::Prompt => "prompt",
+            Trigger::Rollback => "rollback",
+        }
+    }
+}
+
+/// One applied theme, as recorded in the history log.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Event {
+    /// Seconds since the Unix epoch.
+    pub timestamp: u64,
+    pub slug: String,
+    pub title: String,
+    /// The collection the theme came from, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub collection: Option<String>,
+    pub trigger: Trigger,
+    /// "dark" or "light" when the switch was filtered by mode.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mode: Option<String>,
+    /// The theme's color lines, so `rollback` can restore it exactly.
+    /// Missing from events recorded by older versions.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub raw_config: String,
+}
+
+/// How an apply came about, for its history event.
+#[derive(Debug, Clone, Default)]
+pub struct Origin {
+    pub collection: Option<String>,
+    pub trigger: Trigger,
+    pub mode: Option<String>,
+}
+
+pub fn history_path() -> PathBuf {
+    collection::base_dir().join("history.jsonl")
+}
+
+/// Append an event to the log, keeping at most `MAX_EVENTS`.
+pub fn record(event: &Event) -> Result<(), String> {
+    collection::ensure_dirs()?;
+    let mut events = load();
+    events.push(event.clone());
+    let skip = events.len().saturating_sub(MAX_EVENTS);
+    let lines: Vec<String> = events[skip..]
+        .iter()
+        .filter_map(|e| serde_json::to_string(e).ok())
+        .collect();
+    atomic::write(&history_path(), lines.join("\n") + "\n")
+        .map_err(|e| format!("Failed to write history: {}", e))
+}
+
+/// All recorded events, oldest first. Unreadable lines are skipped.
+pub fn load() -> Vec<Event> {
+    fs::read_to_string(history_path())
+        .map(|data| parse_events(&data))
+        .unwrap_or_default()
+}
+
+fn parse_events(data: &str) -> Vec<Event> {
+    data.lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect()
+}
+
+/// The event `steps` applies before the latest one: 0 is the theme
+/// applied last, 1 the one before it.
+pub fn nth_back(events: &[Event], steps: usize) -> Option<&Event> {
+    events.iter().rev().nth(steps)
+}
+
+/// Print the `limit` most recent events, newest first, numbered the way
+/// `rollback` counts them. With `json`, a JSON array of them without their
+/// raw configs.
+pub fn print(limit: usize, json: bool) -> Result<(), String> {
+    let events = load();
+    if json {
+        let recent: Vec<Event> = events
+            .into_iter()
+            .rev()
+            .take(limit)
+            .map(|event| Event {
+                raw_config: String::new(),
+                ..event
+            })
+            .collect();
+        let out = serde_json::to_string_pretty(&recent).map_err(|e| e.to_string())?;
+        println!("{}", out);
+        return Ok(());
+    }
+    if events.is_empty() {
+        println!("No themes applied yet.");
+        return Ok(());
+    }
+    for (steps, event) in events.iter().rev().take(limit).enumerate() {
+        println!("{:>3}  {}", steps, format_event(event));
+    }
+    Ok(())
+}
+
+fn format_event(event: &Event) -> String {
+    let from = event
+        .collection
+        .as_deref()
+        .map(|c| format!(" from '{}'", c))
+        .unwrap_or_default();
+    let mode = event
+        .mode
+        .as_deref()
+        .map(|m| format!(", {}", m))
+        .unwrap_or_default();
+    format!(
+        "{}  {} ({}){}  [{}{}]",
+        format_timestamp(event.timestamp),
+        event.title,
+        event.slug,
+        from,
+        event.trigger.label(),
+        mode
+    )
+}
+
+/// Local "YYYY-MM-DD HH:MM" for a Unix timestamp.
+fn format_timestamp(secs: u64) -> String {
+    let tm = darkmode::local_tm_at(secs);
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}",
+        tm.tm_year + 1900,
+        tm.tm_mon + 1,
+        tm.tm_mday,
+        tm.tm_hour,
+        tm.tm_min
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(slug: &str, trigger: Trigger, mode: Option<&str>) -> Event {
+        Event {
+            timestamp: 1_700_000_000,
+            slug: slug.to_string(),
+            title: slug.to_uppercase(),
+            collection: Some("favorites".to_string()),
+            trigger,
+            mode: mode.map(str::to_string),
+            raw_config: String::new(),
+        }
+    }
+
+    #[test]
+    fn parse_events_skips_bad_lines() {
+        let good = serde_json::to_string(&event("nord", Trigger::Interval, None)).unwrap();
+        let data = format!("{}\nnot json\n\n{}\n", good, good);
+        assert_eq!(parse_events(&data).len(), 2);
+    }
+
+    #[test]
+    fn event_round_trips() {
+        let e = event("nord", Trigger::Appearance, Some("dark"));
+        let json = serde_json::to_string(&e).unwrap();
+        assert!(json.contains(r#""trigger":"appearance""#));
+        assert_eq!(serde_json::from_str::<Event>(&json).unwrap(), e);
+    }
+
+    #[test]
+    fn format_event_shows_trigger_and_mode() {
+        let line = format_event(&event("nord", Trigger::Manual, Some("light")));
+        assert!(line.contains("NORD (nord) from 'favorites'"));
+        assert!(line.ends_with("[manual, light]"));
+
+        let line = format_event(&event("nord", Trigger::Prompt, None));
+        assert!(line.ends_with("[prompt]"));
+
+        let mut e = event("nord", Trigger::Rollback, None);
+        e.collection = None;
+        assert!(format_event(&e).ends_with("NORD (nord)  [rollback]"));
+    }
+
+    #[test]
+    fn old_events_still_parse() {
+        let old =
+            r#"{"timestamp":1,"slug":"nord","title":"Nord","collection":"c1","trigger":"manual"}"#;
+        let events = parse_events(old);
+        assert_eq!(events[0].collection.as_deref(), Some("c1"));
+        assert!(events[0].raw_config.is_empty());
+    }
+
+    #[test]
+    fn nth_back_counts_from_the_latest() {
+        let events = vec![
+            event("a", Trigger::Manual, None),
+            event("b", Trigger::Manual, None),
+        ];
+        assert_eq!(nth_back(&events, 0).unwrap().slug, "b");
+        assert_eq!(nth_back(&events, 1).unwrap().slug, "a");
+        assert!(nth_back(&events, 2).is_none());
+    }
+
+    #[test]
+    fn format_timestamp_shape() {
+        let s = format_timestamp(1_700_000_000);
+        assert_eq!(s.len(), 16);
+        assert!(s.starts_with("2023-11-1"));
+    }
+}
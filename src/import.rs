@@ -0,0 +1,569 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::creator::HslColor;
+use crate::export;
+use crate::theme::GhosttyConfig;
+
+/// Color scheme formats that can be imported: other terminals' schemes,
+/// plus Ghostty's own config for piped input.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Source {
+    Ghostty,
+    Alacritty,
+    Kitty,
+    Iterm2,
+    WindowsTerminal,
+}
+
+impl Source {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Source::Ghostty => "Ghostty",
+            Source::Alacritty => "Alacritty",
+            Source::Kitty => "kitty",
+            Source::Iterm2 => "iTerm2",
+            Source::WindowsTerminal => "Windows Terminal",
+        }
+    }
+
+    /// Pick the format from the file extension, falling back to the content.
+    pub fn detect(path: &Path, content: &str) -> Self {
+        let ext = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or("")
+            .to_lowercase();
+        match ext.as_str() {
+            "toml" => Source::Alacritty,
+            "itermcolors" | "plist" => Source::Iterm2,
+            "json" => Source::WindowsTerminal,
+            // Both kitty and Ghostty theme files end in .conf
+            "conf" if is_ghostty(content) => Source::Ghostty,
+            "conf" => Source::Kitty,
+            _ => {
+                let trimmed = content.trim_start();
+                if is_ghostty(content) {
+                    Source::Ghostty
+                } else if trimmed.starts_with("<?xml") || trimmed.starts_with("<plist") {
+                    Source::Iterm2
+                } else if trimmed.starts_with('{') {
+                    Source::WindowsTerminal
+                } else if content.contains("[colors") {
+                    Source::Alacritty
+                } else {
+                    Source::Kitty
+                }
+            }
+        }
+    }
+}
+
+/// Ghostty sets colors with `key = value`; kitty has no `=`.
+fn is_ghostty(content: &str) -> bool {
+    content.lines().any(|line| {
+        line.split_once('=')
+            .is_some_and(|(key, _)| matches!(key.trim(), "background" | "foreground" | "palette"))
+    })
+}
+
+/// Colors read from another terminal's scheme, as `#rrggbb` strings.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Colors {
+    pub name: Option<String>,
+    pub background: Option<String>,
+    pub foreground: Option<String>,
+    pub cursor: Option<String>,
+    pub cursor_text: Option<String>,
+    pub selection_bg: Option<String>,
+    pub selection_fg: Option<String>,
+    pub palette: [Option<String>; 16],
+}
+
+impl Colors {
+    /// Build a theme the creator can open. Palette slots the scheme left
+    /// out are black, as in `CreatorState::from_theme`.
+    pub fn into_theme(self, fallback_title: &str) -> Result<GhosttyConfig, String> {
+        let background = self.background.ok_or("Scheme has no background color")?;
+        let foreground = self.foreground.ok_or("Scheme has no foreground color")?;
+        let title = self
+            .name
+            .filter(|n| !n.trim().is_empty())
+            .unwrap_or_else(|| fallback_title.to_string());
+        let is_dark = HslColor::from_hex(&background).is_none_or(|c| c.l < 50.0);
+        Ok(GhosttyConfig {
+            id: String::new(),
+            slug: export::slug_from_title(&title),
+            title,
+            description: None,
+            raw_config: String::new(),
+            background,
+            foreground,
+            cursor_color: self.cursor,
+            cursor_text: self.cursor_text,
+            selection_bg: self.selection_bg,
+            selection_fg: self.selection_fg,
+            palette: self
+                .palette
+                .into_iter()
+                .map(|c| c.unwrap_or_else(|| "#000000".to_string()))
+                .collect(),
+            font_family: None,
+            font_size: None,
+            cursor_style: None,
+            bg_opacity: None,
+            is_dark,
+            tags: Vec::new(),
+            source_url: None,
+            author_name: None,
+            author_url: None,
+            is_featured: false,
+            vote_count: 0,
+            view_count: 0,
+            download_count: 0,
+            parsed: Default::default(),
+        })
+    }
+}
+
+/// Read a scheme file from another terminal into a theme.
+pub fn import_file(path: &Path) -> Result<GhosttyConfig, String> {
+    let content = if path == Path::new("-") {
+        io::read_to_string(io::stdin()).map_err(|e| format!("Failed to read stdin: {}", e))?
+    } else {
+        fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?
+    };
+    let source = Source::detect(path, &content);
+    let colors = parse(source, &content)
+        .map_err(|e| format!("Not a valid {} scheme: {}", source.label(), e))?;
+    let stem = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .filter(|s| *s != "-")
+        .unwrap_or("Imported");
+    colors.into_theme(stem)
+}
+
+pub fn parse(source: Source, content: &str) -> Result<Colors, String> {
+    match source {
+        Source::Ghostty => parse_ghostty(content),
+        Source::Alacritty => parse_alacritty(content),
+        Source::Kitty => parse_kitty(content),
+        Source::Iterm2 => parse_iterm2(content),
+        Source::WindowsTerminal => parse_windows_terminal(content),
+    }
+}
+
+/// `#rrggbb` from `#RRGGBB`, `0xRRGGBB`, or bare hex.
+fn normalize_hex(value: &str) -> Option<String> {
+    let value = value.trim();
+    let hex = value
+        .strip_prefix("0x")
+        .or_else(|| value.strip_prefix("0X"))
+        .unwrap_or(value);
+    let (r, g, b) = GhosttyConfig::parse_hex(hex)?;
+    Some(format!("#{:02x}{:02x}{:02x}", r, g, b))
+}
+
+/// Alacritty's ANSI color names, in palette order within `normal`/`bright`.
+const ANSI_NAMES: [&str; 8] = [
+    "black", "red", "green", "yellow", "blue", "magenta", "cyan", "white",
+];
+
+/// Alacritty TOML: `[colors.primary]`, `[colors.normal]`, and friends.
+fn parse_alacritty(content: &str) -> Result<Colors, String> {
+    let mut colors = Colors::default();
+    let mut section = String::new();
+    for line in content.lines() {
+        let line = line.trim();
+        if line.starts_with('#') {
+            continue;
+        }
+        if line.starts_with('[') {
+            section = line
+                .split(']')
+                .next()
+                .unwrap_or("")
+                .trim_start_matches('[')
+                .trim()
+                .to_string();
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let key = key.trim();
+        let Some(hex) = toml_string(value).and_then(normalize_hex) else {
+            continue;
+        };
+        let slot = match (section.as_str(), key) {
+            ("colors.primary", "background") => &mut colors.background,
+            ("colors.primary", "foreground") => &mut colors.foreground,
+            ("colors.cursor", "cursor") => &mut colors.cursor,
+            ("colors.cursor", "text") => &mut colors.cursor_text,
+            ("colors.selection", "background") => &mut colors.selection_bg,
+            ("colors.selection", "text") => &mut colors.selection_fg,
+            ("colors.normal", name) | ("colors.bright", name) => {
+                let Some(i) = ANSI_NAMES.iter().position(|n| *n == name) else {
+                    continue;
+                };
+                let offset = if section == "colors.bright" { 8 } else { 0 };
+                &mut colors.palette[i + offset]
+            }
+            _ => continue,
+        };
+        *slot = Some(hex);
+    }
+    if colors.background.is_none() {
+        return Err("no [colors.primary] background".to_string());
+    }
+    Ok(colors)
+}
+
+/// The contents of a quoted TOML string value, ignoring anything after it.
+fn toml_string(value: &str) -> Option<&str> {
+    let value = value.trim();
+    let quote = value.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+    let rest = &value[1..];
+    Some(&rest[..rest.find(quote)?])
+}
+
+/// kitty.conf: `background #1a1b26`, `color0 #15161e`, ...
+fn parse_kitty(content: &str) -> Result<Colors, String> {
+    let mut colors = Colors::default();
+    for line in content.lines() {
+        let line = line.trim();
+        if line.starts_with('#') {
+            continue;
+        }
+        let mut parts = line.split_whitespace();
+        let (Some(key), Some(value)) = (parts.next(), parts.next()) else {
+            continue;
+        };
+        let Some(hex) = normalize_hex(value) else {
+            continue;
+        };
+        let slot = match key {
+            "background" => &mut colors.background,
+            "foreground" => &mut colors.foreground,
+            "cursor" => &mut colors.cursor,
+            "cursor_text_color" => &mut colors.cursor_text,
+            "selection_background" => &mut colors.selection_bg,
+            "selection_foreground" => &mut colors.selection_fg,
+            _ => match key
+                .strip_prefix("color")
+                .and_then(|n| n.parse::<usize>().ok())
+            {
+                Some(i) if i < 16 => &mut colors.palette[i],
+                _ => continue,
+            },
+        };
+        *slot = Some(hex);
+    }
+    if colors.background.is_none() && colors.palette.iter().all(Option::is_none) {
+        return Err("no color settings found".to_string());
+    }
+    Ok(colors)
+}
+
+/// A Ghostty config or theme file, named by a `# Theme:` comment.
+fn parse_ghostty(content: &str) -> Result<Colors, String> {
+    let mut colors = Colors::default();
+    for line in content.lines() {
+        let line = line.trim();
+        if let Some(name) = line.strip_prefix("# Theme:") {
+            colors.name = Some(name.trim().to_string());
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let (slot, value) = match key.trim() {
+            "background" => (&mut colors.background, value),
+            "foreground" => (&mut colors.foreground, value),
+            "cursor-color" => (&mut colors.cursor, value),
+            "cursor-text" => (&mut colors.cursor_text, value),
+            "selection-background" => (&mut colors.selection_bg, value),
+            "selection-foreground" => (&mut colors.selection_fg, value),
+            "palette" => match value
+                .split_once('=')
+                .and_then(|(i, v)| Some((i.trim().parse::<usize>().ok()?, v)))
+            {
+                Some((i, v)) if i < 16 => (&mut colors.palette[i], v),
+                _ => continue,
+            },
+            _ => continue,
+        };
+        if let Some(hex) = normalize_hex(value) {
+            *slot = Some(hex);
+        }
+    }
+    if colors.background.is_none() && colors.palette.iter().all(Option::is_none) {
+        return Err("no color settings found".to_string());
+    }
+    Ok(colors)
+}
+
+/// iTerm2 `.itermcolors` plist: each `<key>... Color</key>` is followed by
+/// a dict of `Red/Green/Blue Component` reals from 0 to 1.
+fn parse_iterm2(content: &str) -> Result<Colors, String> {
+    let mut colors = Colors::default();
+    let mut rest = content;
+    while let Some(start) = rest.find("<key>") {
+        rest = &rest[start + 5..];
+        let Some(end) = rest.find("</key>") else {
+            break;
+        };
+        let name = rest[..end].trim().to_string();
+        rest = &rest[end + 6..];
+        if !name.ends_with("Color") {
+            continue;
+        }
+        let (Some(open), Some(close)) = (rest.find("<dict>"), rest.find("</dict>")) else {
+            break;
+        };
+        if open > close {
+            continue;
+        }
+        let hex = iterm_dict_hex(&rest[open + 6..close]);
+        rest = &rest[close + 7..];
+        let Some(hex) = hex else {
+            continue;
+        };
+        let slot = match name.as_str() {
+            "Background Color" => &mut colors.background,
+            "Foreground Color" => &mut colors.foreground,
+            "Cursor Color" => &mut colors.cursor,
+            "Cursor Text Color" => &mut colors.cursor_text,
+            "Selection Color" => &mut colors.selection_bg,
+            "Selected Text Color" => &mut colors.selection_fg,
+            _ => match name
+                .strip_prefix("Ansi ")
+                .and_then(|n| n.strip_suffix(" Color"))
+                .and_then(|n| n.parse::<usize>().ok())
+            {
+                Some(i) if i < 16 => &mut colors.palette[i],
+                _ => continue,
+            },
+        };
+        *slot = Some(hex);
+    }
+    if colors.background.is_none() {
+        return Err("no Background Color entry".to_string());
+    }
+    Ok(colors)
+}
+
+/// `#rrggbb` from the component entries of one iTerm2 color dict.
+fn iterm_dict_hex(dict: &str) -> Option<String> {
+    let component = |name: &str| -> Option<u8> {
+        let key = format!("<key>{} Component</key>", name);
+        let after = &dict[dict.find(&key)? + key.len()..];
+        let value_start = after.find('>')? + 1;
+        let value_end = after.find("</")?;
+        let value: f64 = after.get(value_start..value_end)?.trim().parse().ok()?;
+        Some((value.clamp(0.0, 1.0) * 255.0).round() as u8)
+    };
+    let (r, g, b) = (component("Red")?, component("Green")?, component("Blue")?);
+    Some(format!("#{:02x}{:02x}{:02x}", r, g, b))
+}
+
+/// A Windows Terminal scheme object, or a settings.json whose first entry
+/// in `schemes` is used.
+fn parse_windows_terminal(content: &str) -> Result<Colors, String> {
+    let value: serde_json::Value = serde_json::from_str(content).map_err(|e| e.to_string())?;
+    let scheme = match value.get("schemes") {
+        Some(schemes) => schemes
+            .as_array()
+            .and_then(|s| s.first())
+            .ok_or("empty schemes list")?,
+        None => &value,
+    };
+    let get = |key: &str| scheme.get(key).and_then(|v| v.as_str());
+    let hex = |key: &str| get(key).and_then(normalize_hex);
+    let mut colors = Colors {
+        name: get("name").map(String::from),
+        background: hex("background"),
+        foreground: hex("foreground"),
+        cursor: hex("cursorColor"),
+        selection_bg: hex("selectionBackground"),
+        ..Colors::default()
+    };
+    for (i, name) in export::WT_COLOR_NAMES.iter().enumerate() {
+        colors.palette[i] = hex(name);
+    }
+    if colors.background.is_none() {
+        return Err("no background".to_string());
+    }
+    Ok(colors)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize_hex_forms() {
+        assert_eq!(normalize_hex("0x1A1B26"), Some("#1a1b26".to_string()));
+        assert_eq!(normalize_hex(" #C0CAF5 "), Some("#c0caf5".to_string()));
+        assert_eq!(normalize_hex("CellForeground"), None);
+    }
+
+    #[test]
+    fn detect_by_extension_then_content() {
+        assert_eq!(Source::detect(Path::new("a.toml"), ""), Source::Alacritty);
+        assert_eq!(
+            Source::detect(Path::new("a.itermcolors"), ""),
+            Source::Iterm2
+        );
+        assert_eq!(
+            Source::detect(Path::new("scheme"), "  { \"name\": 1 }"),
+            Source::WindowsTerminal
+        );
+        assert_eq!(
+            Source::detect(Path::new("scheme"), "[colors.primary]"),
+            Source::Alacritty
+        );
+        assert_eq!(
+            Source::detect(Path::new("scheme"), "background #000000"),
+            Source::Kitty
+        );
+        assert_eq!(
+            Source::detect(Path::new("nord.conf"), "background = #000000"),
+            Source::Ghostty
+        );
+        assert_eq!(
+            Source::detect(Path::new("-"), "palette = 0=#000000"),
+            Source::Ghostty
+        );
+    }
+
+    #[test]
+    fn parses_ghostty_config() {
+        let colors = parse_ghostty(
+            "# Theme: Nord\nfont-size = 13\nbackground = #2E3440\ncursor-color = #d8dee9\n\
+             palette = 1=#bf616a\npalette = 16=#ffffff\n",
+        )
+        .unwrap();
+        assert_eq!(colors.name.as_deref(), Some("Nord"));
+        assert_eq!(colors.background.as_deref(), Some("#2e3440"));
+        assert_eq!(colors.cursor.as_deref(), Some("#d8dee9"));
+        assert_eq!(colors.palette[1].as_deref(), Some("#bf616a"));
+        assert!(parse_ghostty("font-size = 13").is_err());
+    }
+
+    #[test]
+    fn alacritty_sections() {
+        let toml = r##"
+# Tokyo Night
+[colors.primary]
+background = '#1a1b26'
+foreground = "0xc0caf5" # trailing comment
+
+[colors.cursor]
+cursor = '#c0caf5'
+text = 'CellBackground'
+
+[colors.selection]
+background = '#283457'
+
+[colors.normal]
+black = '#15161e'
+red = '#f7768e'
+
+[colors.bright]
+white = '#c0caf5'
+"##;
+        let colors = parse_alacritty(toml).unwrap();
+        assert_eq!(colors.background.as_deref(), Some("#1a1b26"));
+        assert_eq!(colors.foreground.as_deref(), Some("#c0caf5"));
+        assert_eq!(colors.cursor.as_deref(), Some("#c0caf5"));
+        assert_eq!(colors.cursor_text, None);
+        assert_eq!(colors.selection_bg.as_deref(), Some("#283457"));
+        assert_eq!(colors.palette[0].as_deref(), Some("#15161e"));
+        assert_eq!(colors.palette[1].as_deref(), Some("#f7768e"));
+        assert_eq!(colors.palette[15].as_deref(), Some("#c0caf5"));
+    }
+
+    #[test]
+    fn alacritty_without_primary_fails() {
+        assert!(parse_alacritty("[window]\nopacity = 0.9\n").is_err());
+    }
+
+    #[test]
+    fn kitty_round_trips_export() {
+        let theme = crate::creator::CreatorState::new("Round").build_preview_config();
+        let colors = parse_kitty(&export::to_kitty(&theme)).unwrap();
+        assert_eq!(colors.background.as_ref(), Some(&theme.background));
+        assert_eq!(colors.cursor_text, theme.cursor_text);
+        assert_eq!(colors.palette[15].as_ref(), Some(&theme.palette[15]));
+    }
+
+    #[test]
+    fn iterm2_components() {
+        let plist = r#"<?xml version="1.0" encoding="UTF-8"?>
+<plist version="1.0">
+<dict>
+	<key>Ansi 1 Color</key>
+	<dict>
+		<key>Blue Component</key>
+		<real>0.0</real>
+		<key>Color Space</key>
+		<string>sRGB</string>
+		<key>Green Component</key>
+		<real>0.0</real>
+		<key>Red Component</key>
+		<real>1</real>
+	</dict>
+	<key>Background Color</key>
+	<dict>
+		<key>Blue Component</key>
+		<real>0.14901961386203766</real>
+		<key>Green Component</key>
+		<real>0.10588235408067703</real>
+		<key>Red Component</key>
+		<real>0.10196078568696976</real>
+	</dict>
+</dict>
+</plist>"#;
+        let colors = parse_iterm2(plist).unwrap();
+        assert_eq!(colors.palette[1].as_deref(), Some("#ff0000"));
+        assert_eq!(colors.background.as_deref(), Some("#1a1b26"));
+    }
+
+    #[test]
+    fn windows_terminal_round_trips_export() {
+        let theme = crate::creator::CreatorState::new("Round").build_preview_config();
+        let colors = parse_windows_terminal(&export::to_windows_terminal(&theme)).unwrap();
+        assert_eq!(colors.name.as_deref(), Some("Round"));
+        assert_eq!(colors.selection_bg, theme.selection_bg);
+        assert_eq!(colors.palette[8].as_ref(), Some(&theme.palette[8]));
+    }
+
+    #[test]
+    fn windows_terminal_settings_uses_first_scheme() {
+        let json = r##"{ "schemes": [{ "name": "One", "background": "#000000" }] }"##;
+        let colors = parse_windows_terminal(json).unwrap();
+        assert_eq!(colors.name.as_deref(), Some("One"));
+    }
+
+    #[test]
+    fn into_theme_requires_foreground_and_fills_palette() {
+        let colors = Colors {
+            background: Some("#000000".to_string()),
+            ..Colors::default()
+        };
+        assert!(colors.clone().into_theme("x").is_err());
+
+        let colors = Colors {
+            foreground: Some("#ffffff".to_string()),
+            ..colors
+        };
+        let theme = colors.into_theme("My Scheme").unwrap();
+        assert_eq!(theme.title, "My Scheme");
+        assert_eq!(theme.slug, "my-scheme");
+        assert_eq!(theme.palette.len(), 16);
+        assert!(theme.is_dark);
+    }
+}
@@ -0,0 +1,181 @@
+use std::fs;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+use crate::api::{self, FetchParams, SortOrder};
+use crate::atomic;
+use crate::collection;
+use crate::theme::{ConfigResponse, GhosttyConfig};
+
+/// Themes per page when browsing the library, like the site's listings.
+const PAGE_SIZE: usize = 20;
+
+/// Every theme the user has opened or collected, kept for offline browsing:
+/// `~/.config/ghostty-styles/library/<slug>.json`.
+pub fn library_dir() -> PathBuf {
+    collection::base_dir().join("library")
+}
+
+/// Save a theme to the library, replacing an older copy. Best effort: a
+/// failed write only means it won't be there offline.
+pub fn remember(theme: &GhosttyConfig) {
+    if !api::is_file_safe(&theme.slug) {
+        return;
+    }
+    let dir = library_dir();
+    if fs::create_dir_all(&dir).is_err() {
+        return;
+    }
+    match serde_json::to_string(theme) {
+        Ok(json) => {
+            if let Err(e) = atomic::write(&dir.join(format!("{}.json", theme.slug)), json) {
+                log::warn!("Couldn't save '{}' to the library: {}", theme.slug, e);
+            }
+        }
+        Err(e) => log::warn!("Couldn't save '{}' to the library: {}", theme.slug, e),
+    }
+}
+
+/// A saved theme by slug.
+pub fn load(slug: &str) -> Option<GhosttyConfig> {
+    if !api::is_file_safe(slug) {
+        return None;
+    }
+    let json = fs::read_to_string(library_dir().join(format!("{}.json", slug))).ok()?;
+    serde_json::from_str(&json).ok()
+}
+
+/// All saved themes, most recently saved first. Unreadable files are skipped.
+pub fn load_all() -> Vec<GhosttyConfig> {
+    let Ok(entries) = fs::read_dir(library_dir()) else {
+        return Vec::new();
+    };
+    let mut themes: Vec<(SystemTime, GhosttyConfig)> = entries
+        .flatten()
+        .filter(|e| e.path().extension().is_some_and(|ext| ext == "json"))
+        .filter_map(|e| {
+            let saved = e.metadata().and_then(|m| m.modified()).ok()?;
+            let json = fs::read_to_string(e.path()).ok()?;
+            Some((saved, serde_json::from_str(&json).ok()?))
+        })
+        .collect();
+    themes.sort_by_key(|(saved, _)| std::cmp::Reverse(*saved));
+    themes.into_iter().map(|(_, theme)| theme).collect()
+}
+
+/// One page of `themes` (most recent first) filtered and sorted the way the
+/// site would answer `params`. Newest means most recently saved, and
+/// trending goes by view count.
+pub fn search(themes: Vec<GhosttyConfig>, params: &FetchParams) -> ConfigResponse {
+    let query = params
+        .query
+        .as_deref()
+        .map(str::to_lowercase)
+        .filter(|q| !q.is_empty());
+    let mut matches: Vec<GhosttyConfig> = themes
+        .into_iter()
+        .filter(|t| params.dark.is_none_or(|dark| t.is_dark == dark))
+        .filter(|t| params.tag.as_ref().is_none_or(|tag| t.tags.contains(tag)))
+        .filter(|t| query.as_ref().is_none_or(|q| matches_query(t, q)))
+        .collect();
+    match params.sort {
+        SortOrder::Popular => matches.sort_by_key(|t| std::cmp::Reverse(t.vote_count)),
+        SortOrder::Trending => matches.sort_by_key(|t| std::cmp::Reverse(t.view_count)),
+        SortOrder::Newest => {}
+    }
+
+    let total = matches.len();
+    let total_pages = total.div_ceil(PAGE_SIZE).max(1);
+    let page = (params.page.max(1) as usize).min(total_pages);
+    let configs = matches
+        .into_iter()
+        .skip((page - 1) * PAGE_SIZE)
+        .take(PAGE_SIZE)
+        .collect();
+    ConfigResponse {
+        configs,
+        total: total as i32,
+        page: page as i32,
+        per_page: PAGE_SIZE as i32,
+        total_pages: total_pages as i32,
+        cached: None,
+    }
+}
+
+fn matches_query(theme: &GhosttyConfig, query: &str) -> bool {
+    std::iter::once(&theme.title)
+        .chain(&theme.description)
+        .chain(&theme.author_name)
+        .chain(&theme.tags)
+        .any(|field| field.to_lowercase().contains(query))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn theme(slug: &str, is_dark: bool, votes: i32, tags: &[&str]) -> GhosttyConfig {
+        serde_json::from_value(serde_json::json!({
+            "id": slug,
+            "slug": slug,
+            "title": slug.replace('-', " "),
+            "rawConfig": "",
+            "background": "#000000",
+            "foreground": "#ffffff",
+            "palette": [],
+            "isDark": is_dark,
+            "tags": tags,
+            "isFeatured": false,
+            "voteCount": votes,
+            "viewCount": 0,
+            "downloadCount": 0,
+        }))
+        .unwrap()
+    }
+
+    fn slugs(resp: &ConfigResponse) -> Vec<&str> {
+        resp.configs.iter().map(|t| t.slug.as_str()).collect()
+    }
+
+    #[test]
+    fn search_filters_by_query_tag_and_mode() {
+        let themes = vec![
+            theme("nord", true, 5, &["minimal"]),
+            theme("nord-light", false, 3, &["minimal"]),
+            theme("gruvbox", true, 9, &["retro"]),
+        ];
+        let params = FetchParams {
+            query: Some("NORD".to_string()),
+            dark: Some(true),
+            ..FetchParams::default()
+        };
+        assert_eq!(slugs(&search(themes.clone(), &params)), ["nord"]);
+
+        let params = FetchParams {
+            tag: Some("minimal".to_string()),
+            ..FetchParams::default()
+        };
+        assert_eq!(slugs(&search(themes, &params)), ["nord", "nord-light"]);
+    }
+
+    #[test]
+    fn search_sorts_and_pages() {
+        let themes: Vec<GhosttyConfig> = (0..25)
+            .map(|i| theme(&format!("t{}", i), true, i, &[]))
+            .collect();
+        let params = FetchParams::default();
+        let first = search(themes.clone(), &params);
+        assert_eq!(first.total, 25);
+        assert_eq!(first.total_pages, 2);
+        assert_eq!(first.configs[0].slug, "t24");
+
+        let params = FetchParams {
+            sort: SortOrder::Newest,
+            page: 2,
+            ..FetchParams::default()
+        };
+        let second = search(themes, &params);
+        assert_eq!(second.page, 2);
+        assert_eq!(slugs(&second), ["t20", "t21", "t22", "t23", "t24"]);
+    }
+}
@@ -0,0 +1,265 @@
+use std::fs::{self, File, TryLockError};
+use std::io::{Read, Seek, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Condvar, Mutex};
+use std::thread::{self, ThreadId};
+use std::time::{Duration, Instant};
+
+use crate::collection;
+use crate::error::{Error, Result};
+
+/// How long to keep retrying a lock another process holds before giving up.
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+const RETRY_EVERY: Duration = Duration::from_millis(100);
+
+/// Files that the TUI, the daemon, and CLI commands all write.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resource {
+    /// Collections and `config.json`, including the cycling pointer.
+    State,
+    /// The Ghostty config and its backups.
+    GhosttyConfig,
+}
+
+impl Resource {
+    fn file_name(self) -> &'static str {
+        match self {
+            Resource::State => "state.lock",
+            Resource::GhosttyConfig => "ghostty-config.lock",
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Resource::State => "Collections",
+            Resource::GhosttyConfig => "The Ghostty config",
+        }
+    }
+}
+
+/// A lock some thread of this process holds or is waiting on the file for.
+struct Held {
+    path: PathBuf,
+    /// `None` while the owner still waits on other processes.
+    file: Option<File>,
+    owner: ThreadId,
+    /// Guards the owner has taken.
+    count: usize,
+}
+
+/// Locks this process holds. File locks belong to an open file, so taking
+/// one again through a new file would wait on ourselves; a thread taking
+/// one it already holds just adds a guard, and other threads wait on
+/// `RELEASED` for the owner to let go.
+static HELD: Mutex<Vec<Held>> = Mutex::new(Vec::new());
+static RELEASED: Condvar = Condvar::new();
+
+static QUIET: AtomicBool = AtomicBool::new(false);
+static NOTICE: Mutex<Option<String>> = Mutex::new(None);
+
+/// An advisory lock on a `Resource`, released when the last guard its
+/// thread took for it is dropped.
+pub struct Lock {
+    path: PathBuf,
+}
+
+impl Drop for Lock {
+    fn drop(&mut self) {
+        let mut held = HELD.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(pos) = held.iter().position(|h| h.path == self.path) {
+            held[pos].count -= 1;
+            if held[pos].count == 0 {
+                // Closing the file releases the lock
+                held.remove(pos);
+                RELEASED.notify_all();
+            }
+        }
+    }
+}
+
+/// Take the lock on `resource`, retrying for a while if another process
+/// holds it. Waits are printed to stderr, or kept for `take_notice` after
+/// `quiet`.
+pub fn acquire(resource: Resource) -> Result<Lock> {
+    collection::ensure_dirs()?;
+    let path = collection::base_dir().join(resource.file_name());
+    acquire_at(&path, resource.label(), TIMEOUT)
+}
+
+/// Keep lock waits off stderr, which the TUI draws over.
+pub fn quiet() {
+    QUIET.store(true, Ordering::Relaxed);
+}
+
+/// The last lock wait since asked, when `quiet`.
+pub fn take_notice() -> Option<String> {
+    NOTICE.lock().unwrap_or_else(|e| e.into_inner()).take()
+}
+
+fn acquire_at(path: &Path, label: &str, timeout: Duration) -> Result<Lock> {
+    let me = thread::current().id();
+    let mut held = HELD.lock().unwrap_or_else(|e| e.into_inner());
+    loop {
+        match held.iter_mut().find(|h| h.path == path) {
+            Some(entry) if entry.owner == me => {
+                entry.count += 1;
+                return Ok(Lock {
+                    path: path.to_path_buf(),
+                });
+            }
+            Some(_) => held = RELEASED.wait(held).unwrap_or_else(|e| e.into_inner()),
+            None => break,
+        }
+    }
+    // Claimed before letting go of `HELD`, so other threads wait here
+    // instead of on the file, while we wait on other processes
+    held.push(Held {
+        path: path.to_path_buf(),
+        file: None,
+        owner: me,
+        count: 1,
+    });
+    drop(held);
+
+    match lock_file(path, label, timeout) {
+        Ok(file) => {
+            let mut held = HELD.lock().unwrap_or_else(|e| e.into_inner());
+            if let Some(entry) = held.iter_mut().find(|h| h.path == path) {
+                entry.file = Some(file);
+            }
+            Ok(Lock {
+                path: path.to_path_buf(),
+            })
+        }
+        Err(e) => {
+            let mut held = HELD.lock().unwrap_or_else(|e| e.into_inner());
+            held.retain(|h| h.path != path);
+            RELEASED.notify_all();
+            Err(e)
+        }
+    }
+}
+
+/// Lock the file at `path` against other processes, retrying for up to
+/// `timeout`.
+fn lock_file(path: &Path, label: &str, timeout: Duration) -> Result<File> {
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .read(true)
+        .write(true)
+        .open(path)
+        .map_err(|e| Error::io("open", path, e))?;
+    let started = Instant::now();
+    let mut warned = false;
+    // `flock` on Unix, `LockFileEx` on Windows
+    while let Err(e) = file.try_lock() {
+        if let TryLockError::Error(err) = e {
+            return Err(Error::io("lock", path, err));
+        }
+        let holder = holder(&mut file);
+        if started.elapsed() >= timeout {
+            return Err(Error::Locked(format!(
+                "{} is locked by another ghostty-styles process{}; try again",
+                label, holder
+            )));
+        }
+        if !warned {
+            warned = true;
+            let message = format!(
+                "{} is locked by another ghostty-styles process{}, retrying",
+                label, holder
+            );
+            log::info!("{}", message);
+            if !QUIET.load(Ordering::Relaxed) {
+                eprintln!("{}...", message);
+            }
+        }
+        std::thread::sleep(RETRY_EVERY);
+    }
+    if warned && QUIET.load(Ordering::Relaxed) {
+        *NOTICE.lock().unwrap_or_else(|e| e.into_inner()) = Some(format!(
+            "waited {:.1}s for another ghostty-styles process",
+            started.elapsed().as_secs_f32()
+        ));
+    }
+
+    // Name ourselves so anyone waiting can say who has it
+    let _ = file.set_len(0);
+    let _ = file.rewind();
+    let _ = write!(file, "{}", std::process::id());
+    Ok(file)
+}
+
+/// ` (pid N)` for the process that last took the lock, if it said.
+fn holder(file: &mut File) -> String {
+    let mut pid = String::new();
+    let _ = file.rewind();
+    let _ = file.read_to_string(&mut pid);
+    match pid.trim().parse::<u32>() {
+        Ok(pid) => format!(" (pid {})", pid),
+        Err(_) => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scratch_path(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "ghostty-styles-lock-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir.join("test.lock")
+    }
+
+    #[test]
+    fn acquire_is_reentrant_within_a_process() {
+        let path = scratch_path("reentrant");
+        let outer = acquire_at(&path, "Test", Duration::ZERO).unwrap();
+        let inner = acquire_at(&path, "Test", Duration::ZERO).unwrap();
+        drop(inner);
+        assert!(HELD.lock().unwrap().iter().any(|h| h.path == path));
+        drop(outer);
+        assert!(!HELD.lock().unwrap().iter().any(|h| h.path == path));
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn other_threads_wait_for_the_holder() {
+        let path = scratch_path("threads");
+        let outer = acquire_at(&path, "Test", Duration::ZERO).unwrap();
+        let (tx, rx) = std::sync::mpsc::channel();
+        let waiter = {
+            let path = path.clone();
+            thread::spawn(move || {
+                let lock = acquire_at(&path, "Test", Duration::ZERO);
+                tx.send(lock.is_ok()).unwrap();
+            })
+        };
+        assert!(rx.recv_timeout(Duration::from_millis(200)).is_err());
+        drop(outer);
+        assert!(rx.recv_timeout(Duration::from_secs(5)).unwrap());
+        waiter.join().unwrap();
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn acquire_gives_up_on_a_lock_held_elsewhere() {
+        let path = scratch_path("held");
+        // Another open file stands in for another process
+        let other = File::create(&path).unwrap();
+        other.try_lock().unwrap();
+        let err = acquire_at(&path, "Test", Duration::ZERO).err().unwrap();
+        assert!(matches!(err, Error::Locked(_)));
+        drop(other);
+        assert!(acquire_at(&path, "Test", Duration::ZERO).is_ok());
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
@@ -0,0 +1,329 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Mutex;
+
+use log::{Level, LevelFilter, Log, Metadata, Record};
+
+use crate::{collection, darkmode};
+
+/// Overrides `log_level` in config.json, e.g. `GHOSTTY_STYLES_LOG=debug`.
+pub const ENV_VAR: &str = "GHOSTTY_STYLES_LOG";
+
+/// Past this size the log is moved to `.log.1` and started over.
+const MAX_LOG_SIZE: u64 = 1024 * 1024;
+
+pub fn log_path() -> PathBuf {
+    collection::state_dir().join("ghostty-styles.log")
+}
+
+/// Start logging to the log file if a filter is configured. Logging is
+/// opt-in, and a bad filter or unwritable file only costs a warning.
+pub fn init() {
+    let spec = std::env::var(ENV_VAR)
+        .ok()
+        .filter(|s| !s.trim().is_empty())
+        .or_else(|| collection::load_config().log_level);
+    let Some(spec) = spec else {
+        return;
+    };
+    let filter = match Filter::parse(&spec) {
+        Ok(filter) => filter,
+        Err(e) => {
+            eprintln!("Logging disabled: {}", e);
+            return;
+        }
+    };
+    let file = match open_log() {
+        Ok(file) => file,
+        Err(e) => {
+            eprintln!("Logging disabled: {}", e);
+            return;
+        }
+    };
+    let max_level = filter.max_level();
+    let logger = FileLogger {
+        filter,
+        file: Mutex::new(file),
+    };
+    if log::set_boxed_logger(Box::new(logger)).is_ok() {
+        log::set_max_level(max_level);
+    }
+}
+
+fn open_log() -> Result<File, String> {
+    let path = log_path();
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    rotate(&path);
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .map_err(|e| format!("Failed to open {}: {}", path.display(), e))
+}
+
+/// Past `MAX_LOG_SIZE`, move the log at `path` to `.log.1` so it starts
+/// over. Returns whether it was moved.
+pub fn rotate(path: &Path) -> bool {
+    fs::metadata(path).is_ok_and(|m| m.len() > MAX_LOG_SIZE)
+        && fs::rename(path, path.with_extension("log.1")).is_ok()
+}
+
+/// A `daemon.log` line: `2024-05-01 09:00:00 INFO  Applied 'Nord' ...`.
+pub fn daemon_line(level: Level, msg: &str) -> String {
+    format!("{} {:<5} {}", timestamp(darkmode::unix_now()), level, msg)
+}
+
+/// The level of a `daemon_line`, if `line` is one.
+fn line_level(line: &str) -> Option<Level> {
+    line.split_whitespace().nth(2)?.parse().ok()
+}
+
+/// The last `count` lines of `text` at `min_level` or above. Lines
+/// without a level (say, a panic message) are always kept.
+fn tail(text: &str, count: usize, min_level: Level) -> Vec<&str> {
+    let kept: Vec<&str> = text
+        .lines()
+        .filter(|line| line_level(line).is_none_or(|level| level <= min_level))
+        .collect();
+    kept[kept.len().saturating_sub(count)..].to_vec()
+}
+
+/// `cycle logs`: print the end of `daemon.log`, then with `follow` keep
+/// printing lines as the daemon writes them.
+pub fn print_daemon_log(count: usize, follow: bool, min_level: Level) -> crate::error::Result<()> {
+    use crate::error::Error;
+
+    let path = collection::daemon_log_path();
+    let text = match fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound && follow => String::new(),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            println!("No daemon log yet. It's written by `ghostty-styles cycle start --detach`.");
+            return Ok(());
+        }
+        Err(e) => return Err(Error::io("read", &path, e)),
+    };
+    for line in tail(&text, count, min_level) {
+        println!("{}", line);
+    }
+    if !follow {
+        return Ok(());
+    }
+
+    let mut offset = text.len() as u64;
+    let mut partial = String::new();
+    loop {
+        std::thread::sleep(std::time::Duration::from_millis(500));
+        let Ok(meta) = fs::metadata(&path) else {
+            continue;
+        };
+        // Rotated or cleared: start from the top of the new file
+        if meta.len() < offset {
+            offset = 0;
+            partial.clear();
+        }
+        if meta.len() == offset {
+            continue;
+        }
+        let mut file = File::open(&path).map_err(|e| Error::io("read", &path, e))?;
+        file.seek(SeekFrom::Start(offset))
+            .map_err(|e| Error::io("read", &path, e))?;
+        let mut chunk = String::new();
+        let read = file
+            .read_to_string(&mut chunk)
+            .map_err(|e| Error::io("read", &path, e))?;
+        offset += read as u64;
+        partial.push_str(&chunk);
+        // Hold back a line the daemon is still writing
+        let complete = partial.rfind('\n').map_or(0, |i| i + 1);
+        for line in tail(&partial[..complete], usize::MAX, min_level) {
+            println!("{}", line);
+        }
+        partial.drain(..complete);
+    }
+}
+
+/// A default level plus per-module overrides: `warn,api=debug,daemon=info`.
+#[derive(Debug, PartialEq)]
+struct Filter {
+    default: LevelFilter,
+    modules: Vec<(String, LevelFilter)>,
+}
+
+impl Filter {
+    fn parse(spec: &str) -> Result<Self, String> {
+        let mut filter = Filter {
+            default: LevelFilter::Off,
+            modules: Vec::new(),
+        };
+        for part in spec.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            let level = |s: &str| {
+                LevelFilter::from_str(s.trim()).map_err(|_| {
+                    format!(
+                        "'{}' is not a log level (use error, warn, info, debug, or trace)",
+                        s.trim()
+                    )
+                })
+            };
+            match part.split_once('=') {
+                Some((module, lvl)) => filter
+                    .modules
+                    .push((module.trim().to_string(), level(lvl)?)),
+                None => filter.default = level(part)?,
+            }
+        }
+        Ok(filter)
+    }
+
+    /// The level for a log target, from the most specific matching module.
+    /// Other crates only log when named, so `debug` doesn't mean hyper's
+    /// connection chatter.
+    fn level_for(&self, target: &str) -> LevelFilter {
+        let module = module_name(target);
+        let default = if module.len() < target.len() {
+            self.default
+        } else {
+            LevelFilter::Off
+        };
+        self.modules
+            .iter()
+            .filter(|(name, _)| {
+                module == name
+                    || module
+                        .strip_prefix(name.as_str())
+                        .is_some_and(|rest| rest.starts_with("::"))
+            })
+            .max_by_key(|(name, _)| name.len())
+            .map_or(default, |(_, level)| *level)
+    }
+
+    fn max_level(&self) -> LevelFilter {
+        self.modules
+            .iter()
+            .map(|(_, level)| *level)
+            .fold(self.default, Ord::max)
+    }
+}
+
+/// `ghostty_styles::api` -> `api`; other crates' targets are kept whole.
+fn module_name(target: &str) -> &str {
+    target.strip_prefix("ghostty_styles::").unwrap_or(target)
+}
+
+struct FileLogger {
+    filter: Filter,
+    file: Mutex<File>,
+}
+
+impl Log for FileLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.filter.level_for(metadata.target())
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let line = format!(
+            "{} {:<5} [{}] {}: {}\n",
+            timestamp(darkmode::unix_now()),
+            record.level(),
+            std::process::id(),
+            module_name(record.target()),
+            record.args()
+        );
+        if let Ok(mut file) = self.file.lock() {
+            let _ = file.write_all(line.as_bytes());
+        }
+    }
+
+    fn flush(&self) {
+        if let Ok(mut file) = self.file.lock() {
+            let _ = file.flush();
+        }
+    }
+}
+
+/// Local "YYYY-MM-DD HH:MM:SS" for a Unix timestamp.
+fn timestamp(secs: u64) -> String {
+    let tm = darkmode::local_tm_at(secs);
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+        tm.tm_year + 1900,
+        tm.tm_mon + 1,
+        tm.tm_mday,
+        tm.tm_hour,
+        tm.tm_min,
+        tm.tm_sec
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tail_keeps_the_last_lines_at_or_above_a_level() {
+        let log = format!(
+            "{}\n{}\nthread 'main' panicked\n{}\n",
+            daemon_line(Level::Info, "Applied 'Nord'"),
+            daemon_line(Level::Warn, "Error: offline"),
+            daemon_line(Level::Info, "Applied 'Dracula'"),
+        );
+        let warn = tail(&log, 10, Level::Warn);
+        assert_eq!(warn.len(), 2);
+        assert!(warn[0].ends_with("WARN  Error: offline"));
+        assert_eq!(warn[1], "thread 'main' panicked");
+        let last = tail(&log, 1, Level::Info);
+        assert!(last[0].ends_with("Applied 'Dracula'"));
+    }
+
+    #[test]
+    fn parse_default_and_module_levels() {
+        let filter = Filter::parse("warn, api=debug,daemon=info").unwrap();
+        assert_eq!(filter.default, LevelFilter::Warn);
+        assert_eq!(
+            filter.modules,
+            vec![
+                ("api".to_string(), LevelFilter::Debug),
+                ("daemon".to_string(), LevelFilter::Info),
+            ]
+        );
+        assert_eq!(filter.max_level(), LevelFilter::Debug);
+    }
+
+    #[test]
+    fn parse_rejects_unknown_levels() {
+        assert!(Filter::parse("loud").is_err());
+        assert!(Filter::parse("api=loud").is_err());
+    }
+
+    #[test]
+    fn module_only_filter_silences_the_rest() {
+        let filter = Filter::parse("api=debug").unwrap();
+        assert_eq!(filter.level_for("ghostty_styles::api"), LevelFilter::Debug);
+        assert_eq!(filter.level_for("ghostty_styles::config"), LevelFilter::Off);
+    }
+
+    #[test]
+    fn level_for_picks_most_specific_module() {
+        let filter = Filter::parse("info,ui=warn,ui::browser=trace").unwrap();
+        assert_eq!(
+            filter.level_for("ghostty_styles::ui::browser"),
+            LevelFilter::Trace
+        );
+        assert_eq!(
+            filter.level_for("ghostty_styles::ui::details"),
+            LevelFilter::Warn
+        );
+        assert_eq!(filter.level_for("ghostty_styles::uix"), LevelFilter::Info);
+        assert_eq!(filter.level_for("reqwest::connect"), LevelFilter::Off);
+        let filter = Filter::parse("info,reqwest=debug").unwrap();
+        assert_eq!(filter.level_for("reqwest::connect"), LevelFilter::Debug);
+    }
+}
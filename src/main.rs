@@ -1,21 +1,42 @@
 mod api;
 mod app;
+mod archive;
+mod atomic;
 mod cli;
 mod collection;
 mod config;
+mod control;
 mod creator;
 mod cycling;
 mod daemon;
 mod darkmode;
+mod diff;
+mod error;
 mod export;
+mod favorites;
 mod ghostty;
+mod gitsync;
+mod history;
+mod import;
+mod library;
+mod lock;
+mod logging;
+mod notification;
 mod preview;
+mod process;
+mod render;
+mod schedule;
+mod share;
 mod shell_hook;
+mod smart;
+mod sync;
+mod tasks;
 mod theme;
 mod ui;
+mod votes;
 
-use std::io;
-use std::time::Duration;
+use std::io::{self, IsTerminal};
+use std::time::{Duration, Instant};
 
 use clap::Parser;
 use crossterm::event::{
@@ -29,60 +50,868 @@ use ratatui::backend::CrosstermBackend;
 use ratatui::Terminal;
 
 use app::{App, CollectionsMode, InputMode, Screen};
-use cli::{Cli, CollectionAction, Commands, ModeAction};
+use cli::{
+    BackupsAction, Cli, CollectionAction, Commands, DoAction, FavoritesAction, ModeAction,
+    ProfileAction, SyncAction,
+};
+use error::Error;
 
 fn main() {
     let cli = Cli::parse();
+    error::set_json(cli.json_errors);
+    logging::init();
+    if let Some(name) = cli.profile {
+        collection::set_profile(name);
+    }
+    // Creating or deleting a profile is how an unknown one gets fixed
+    if !matches!(cli.command, Some(Commands::Profile { .. })) {
+        if let Err(e) = collection::check_requested_profile() {
+            error::exit(e);
+        }
+    }
 
     match cli.command {
-        None => run_tui(),
-        Some(cmd) => dispatch_command(cmd),
+        None => run_tui(cli.offline),
+        Some(cmd) => dispatch_command(cmd, cli.json),
     }
 }
 
-fn dispatch_command(cmd: Commands) {
+fn dispatch_command(cmd: Commands, json: bool) {
     match cmd {
-        Commands::Collection { action } => handle_collection(action),
-        Commands::Next => match cycling::apply_next() {
-            Ok(msg) => println!("{}", msg),
-            Err(e) => {
-                eprintln!("Error: {}", e);
-                std::process::exit(1);
+        Commands::Collection { action } => handle_collection(action, json),
+        Commands::Next {
+            dry_run: true,
+            count,
+            tags,
+            ..
+        } => {
+            let config = collection::load_config();
+            let want_dark = cycling::resolve_want_dark(&config);
+            match cycling::preview(&config, want_dark, &tags, count) {
+                Ok(upcoming) => {
+                    for (i, (coll_name, theme)) in upcoming.iter().enumerate() {
+                        println!("  {}. {} from '{}'", i + 1, theme.title, coll_name);
+                    }
+                    if let Some((_, next)) = upcoming.first() {
+                        println!();
+                        show_planned(Ok(cycling::entry_config(next)), config::ApplyScope::Colors);
+                    }
+                }
+                Err(e) => error::exit(e),
             }
-        },
+        }
+        Commands::Next {
+            if_pending, tags, ..
+        } => {
+            // A pending switch from the daemon takes the place of a plain next
+            let result = match cycling::take_pending() {
+                Ok(Some(msg)) => Ok(msg),
+                Ok(None) if if_pending => return,
+                Ok(None) => cycling::apply_next(&tags),
+                Err(e) => Err(e),
+            };
+            match result {
+                Ok(msg) => println!("{}", msg),
+                Err(e) => error::exit(e),
+            }
+        }
+        Commands::Prev => {
+            let config = collection::load_config();
+            let want_dark = cycling::resolve_want_dark(&config);
+            match cycling::apply(
+                &config,
+                &cycling::Step::Prev,
+                want_dark,
+                history::Trigger::Manual,
+            ) {
+                Ok(msg) => println!("{}", msg),
+                Err(e) => error::exit(e),
+            }
+        }
+        Commands::Goto { target } => {
+            let config = collection::load_config();
+            let step = cycling::Step::Goto(cycling::Target::parse(&target));
+            match cycling::apply(&config, &step, None, history::Trigger::Manual) {
+                Ok(msg) => println!("{}", msg),
+                Err(e) => error::exit(e),
+            }
+        }
         Commands::Cycle { action } => {
             use cli::CycleAction;
             let result = match action {
-                CycleAction::Start => daemon::start(),
+                CycleAction::Start {
+                    detach,
+                    notify,
+                    restore,
+                } => daemon::start(detach, notify, restore),
                 CycleAction::Stop => daemon::stop(),
-                CycleAction::Status => daemon::status(),
+                CycleAction::Logs {
+                    follow,
+                    lines,
+                    level,
+                } => logging::print_daemon_log(lines, follow, level),
+                CycleAction::Pause => daemon::control(control::Control::Pause),
+                CycleAction::Resume => daemon::control(control::Control::Resume),
+                CycleAction::Skip => daemon::control(control::Control::Skip),
+                CycleAction::Status => daemon::status(json),
+                CycleAction::History { limit } => history::print(limit, json).map_err(Error::from),
             };
             if let Err(e) = result {
-                eprintln!("Error: {}", e);
-                std::process::exit(1);
+                error::exit(e)
+            }
+        }
+        Commands::Create { from, import } => {
+            run_tui_create(from, import);
+        }
+        Commands::Export {
+            slug,
+            out,
+            format,
+            copy,
+        } => {
+            let result = tasks::block_on(api::fetch_config_by_id(&slug)).and_then(|theme| {
+                match &out {
+                    _ if copy => {
+                        let content = match format {
+                            Some(format) => format.render(&theme),
+                            None => export::ghostty_config(&theme),
+                        };
+                        share::copy_to_clipboard(&content)?;
+                        eprintln!("Copied '{}' to the clipboard", theme.title);
+                        Ok(None)
+                    }
+                    // Stdout carries the theme itself, so no message
+                    Some(out) if out.as_os_str() == "-" => {
+                        export::export_to(&theme, format, out).map(|()| None)
+                    }
+                    Some(out) => export::export_to(&theme, format, out)
+                        .map(|()| Some(out.display().to_string())),
+                    None => {
+                        export::export_as(&theme, format.unwrap_or(export::Format::Kitty)).map(Some)
+                    }
+                }
+            });
+            match result {
+                Ok(Some(path)) => println!("Exported to {}", path),
+                Ok(None) => {}
+                Err(e) => error::exit(e),
             }
         }
-        Commands::Create { from } => {
-            run_tui_create(from);
+        Commands::Render { slug, out } => {
+            let result = render::check_output(&out)
+                .map_err(Error::Invalid)
+                .and_then(|()| tasks::block_on(api::fetch_config_by_id(&slug)))
+                .and_then(|theme| Ok(render::render_to_file(&theme, &out)?));
+            match result {
+                Ok(()) => println!("Rendered '{}' to {}", slug, out.display()),
+                Err(e) => error::exit(e),
+            }
+        }
+        Commands::Apply {
+            theme,
+            share,
+            no_reload,
+            dry_run,
+            scope,
+        } if theme == "-" => {
+            if share {
+                error::exit(Error::Invalid(
+                    "--share needs a theme slug or link".to_string(),
+                ));
+            }
+            let result = io::read_to_string(io::stdin())
+                .map_err(|e| Error::io("read", std::path::Path::new("stdin"), e))
+                .map(|content| config::theme_from_config(&content, "stdin"))
+                .and_then(|theme| {
+                    if theme.raw_config.is_empty() {
+                        return Err(Error::Invalid(
+                            "No color settings found on stdin".to_string(),
+                        ));
+                    }
+                    Ok(theme)
+                });
+            if dry_run {
+                show_planned(result, scope);
+            } else {
+                let result = result.and_then(|theme| apply_with_warnings(theme, scope));
+                report_applied(result, !no_reload);
+            }
+        }
+        Commands::Apply {
+            theme,
+            share,
+            no_reload,
+            dry_run,
+            scope,
+        } => {
+            let Some(slug) = share::slug_from_link(&theme) else {
+                error::exit(Error::Invalid(format!(
+                    "'{}' is not a theme slug or ghostty-styles link",
+                    theme
+                )));
+            };
+            if share {
+                let url = share::web_url(&slug);
+                println!("{}\n{}", url, share::deep_link(&slug));
+                if let Err(e) = share::copy_to_clipboard(&url) {
+                    eprintln!("{}", e);
+                }
+                return;
+            }
+            let result = tasks::block_on(api::fetch_config_by_id(&slug));
+            if dry_run {
+                show_planned(result, scope);
+                return;
+            }
+            let result = result.and_then(|theme| apply_with_warnings(theme, scope));
+            report_applied(result, !no_reload);
+        }
+        Commands::Current => match collection::load_applied() {
+            applied if json => print_json(&applied, "current theme"),
+            Some(applied) => {
+                let ago = darkmode::unix_now().saturating_sub(applied.applied_at);
+                let slug = if applied.slug.is_empty() {
+                    String::new()
+                } else {
+                    format!(" ({})", applied.slug)
+                };
+                println!(
+                    "{}{}, applied {} ago",
+                    applied.title,
+                    slug,
+                    daemon::format_countdown(ago)
+                );
+            }
+            None => println!("No theme applied by ghostty-styles yet."),
+        },
+        Commands::History { limit } => {
+            if let Err(e) = history::print(limit, json) {
+                error::exit(Error::from(e));
+            }
+        }
+        Commands::Backups { action } => handle_backups(action),
+        Commands::Rollback { backup: true, .. } => report_applied(config::restore_backup(1), true),
+        Commands::Rollback { steps, .. } => report_applied(rollback(steps), true),
+        Commands::Preview { theme, duration } => {
+            if let Err(e) = preview_theme(&theme, duration.as_deref()) {
+                error::exit(e);
+            }
+        }
+        Commands::Sync {
+            action: Some(action),
+        } => {
+            let result = match action {
+                SyncAction::Init { remote } => gitsync::init(remote.as_deref()),
+                SyncAction::Push => gitsync::push(),
+                SyncAction::Pull => gitsync::pull(),
+            };
+            match result {
+                Ok(summary) => println!("{}", summary),
+                Err(e) => error::exit(e),
+            }
+        }
+        Commands::Sync { action: None } => {
+            let targets = collection::load_config().sync_targets;
+            if targets.is_empty() {
+                println!(
+                    "No sync targets configured. Add them to `sync_targets` in {}",
+                    collection::config_path().display()
+                );
+                return;
+            }
+            let theme = match sync::current_theme() {
+                Ok(theme) => theme,
+                Err(e) => error::exit(e),
+            };
+            let mut failed = false;
+            for (target, result) in targets.iter().zip(sync::run(&theme, &targets)) {
+                match result {
+                    Ok(path) => println!("Wrote {}", path.display()),
+                    Err(e) => {
+                        eprintln!("{}: {}", sync::describe(target), e);
+                        failed = true;
+                    }
+                }
+            }
+            if failed {
+                std::process::exit(1);
+            }
         }
         Commands::Mode { action } => {
             handle_mode(action);
         }
+        Commands::Profile { action } => {
+            if let Err(e) = handle_profile(action) {
+                error::exit(e);
+            }
+        }
+        Commands::Login { token } => {
+            if let Err(e) = login(token) {
+                error::exit(e);
+            }
+        }
+        Commands::Logout => match api::auth::logout() {
+            Ok(true) => println!("Logged out"),
+            Ok(false) => println!("Not logged in"),
+            Err(e) => error::exit(e),
+        },
+        Commands::Favorites { action } => {
+            handle_favorites(action);
+        }
+        Commands::Search {
+            query,
+            tag,
+            sort,
+            dark,
+            light,
+            any,
+            page,
+        } => {
+            let filter = cli::BrowseFilter {
+                query,
+                tag,
+                dark,
+                light,
+                any,
+                mine: false,
+            };
+            let params = api::FetchParams {
+                page: page.max(1),
+                ..browse_params(&filter, sort)
+            };
+            match tasks::block_on(api::fetch_configs(&params)) {
+                Ok(resp) if json => print_json(&resp, "search results"),
+                Ok(resp) => print_search_results(&resp),
+                Err(e) => error::exit(e),
+            }
+        }
+        Commands::Random {
+            collection: Some(name),
+            dark,
+            light,
+            ..
+        } => {
+            let config = collection::load_config();
+            let want_dark = match (dark, light) {
+                (true, _) => Some(true),
+                (_, true) => Some(false),
+                _ => cycling::resolve_want_dark(&config),
+            };
+            match cycling::apply_random(&config, &name, want_dark) {
+                Ok(msg) => println!("{}", msg),
+                Err(e) => error::exit(e),
+            }
+        }
+        Commands::Random {
+            tag, dark, light, ..
+        } => {
+            let filter = cli::BrowseFilter {
+                query: None,
+                tag,
+                dark,
+                light,
+                any: false,
+                mine: false,
+            };
+            let params = browse_params(&filter, api::SortOrder::Popular);
+            let result = tasks::block_on(api::fetch_random(&params))
+                .inspect(library::remember)
+                .and_then(|theme| config::apply_theme(&theme).map(|path| (theme, path)));
+            report_applied(result, true);
+        }
+        Commands::Do { action } => {
+            handle_do(action);
+        }
+    }
+}
+
+fn handle_backups(action: BackupsAction) {
+    match action {
+        BackupsAction::List => {
+            let backups = config::list_backups();
+            if backups.is_empty() {
+                println!("No backups yet. One is made each time a theme is applied.");
+                return;
+            }
+            for (i, backup) in backups.iter().enumerate() {
+                let taken = if backup.taken.is_empty() {
+                    "(undated)"
+                } else {
+                    &backup.taken
+                };
+                let theme = config::backup_theme(backup)
+                    .map(|title| format!("  {}", title))
+                    .unwrap_or_default();
+                println!("  {}. {}{}", i + 1, taken, theme);
+            }
+        }
+        BackupsAction::Restore { n } => report_applied(config::restore_backup(n), true),
+    }
+}
+
+fn handle_profile(action: ProfileAction) -> error::Result<()> {
+    match action {
+        ProfileAction::List => {
+            let config = collection::load_config();
+            if config.profiles.is_empty() {
+                println!("No profiles. Add one with `ghostty-styles profile create <name>`.");
+                return Ok(());
+            }
+            for (name, profile) in &config.profiles {
+                let marker = if config.profile.as_deref() == Some(name) {
+                    "*"
+                } else {
+                    " "
+                };
+                let hosts = if profile.hosts.is_empty() {
+                    String::new()
+                } else {
+                    format!(" (hosts: {})", profile.hosts.join(", "))
+                };
+                let active = profile.active_collection.as_deref().unwrap_or("none");
+                println!("{} {}{}  active: {}", marker, name, hosts, active);
+            }
+            if config.profile.is_none() {
+                let host = collection::hostname().unwrap_or_else(|| "unknown".to_string());
+                println!("No profile matches this machine ({}).", host);
+            }
+        }
+        ProfileAction::Create { name, hosts } => {
+            let _lock = lock::acquire(lock::Resource::State)?;
+            let mut config = collection::load_config();
+            config.create_profile(&name, hosts)?;
+            collection::save_config(&config)?;
+            println!("Created profile '{}'", name);
+        }
+        ProfileAction::Delete { name } => {
+            let _lock = lock::acquire(lock::Resource::State)?;
+            let mut config = collection::load_config();
+            config.delete_profile(&name)?;
+            collection::save_config(&config)?;
+            println!("Deleted profile '{}'", name);
+        }
+    }
+    Ok(())
+}
+
+/// Re-apply the theme applied `steps` switches before the current one.
+fn rollback(steps: usize) -> error::Result<(theme::GhosttyConfig, String)> {
+    let events = history::load();
+    let event = history::nth_back(&events, steps).ok_or_else(|| {
+        Error::Invalid(format!(
+            "The history only goes back {} (see `ghostty-styles history`)",
+            events.len().saturating_sub(1)
+        ))
+    })?;
+    let theme = if event.raw_config.is_empty() {
+        // Recorded before history kept the colors
+        match library::load(&event.slug) {
+            Some(theme) => theme,
+            None => tasks::block_on(api::fetch_config_by_id(&event.slug))?,
+        }
+    } else {
+        theme::GhosttyConfig {
+            slug: event.slug.clone(),
+            ..config::theme_from_config(&event.raw_config, &event.title)
+        }
+    };
+    let origin = history::Origin {
+        collection: event.collection.clone(),
+        trigger: history::Trigger::Rollback,
+        mode: None,
+    };
+    config::apply_theme_from(&theme, config::ApplyScope::Colors, origin).map(|path| (theme, path))
+}
+
+/// Show a theme through OSC sequences until a key is pressed or `duration`
+/// passes, then restore the terminal's own colors.
+fn preview_theme(target: &str, duration: Option<&str>) -> error::Result<()> {
+    let slug = share::slug_from_link(target).ok_or_else(|| {
+        Error::Invalid(format!(
+            "'{}' is not a theme slug or ghostty-styles link",
+            target
+        ))
+    })?;
+    let deadline = duration
+        .map(|d| {
+            schedule::parse_interval(d)
+                .map(|d| Instant::now() + d)
+                .map_err(|e| Error::Invalid(format!("Invalid --duration: {}", e)))
+        })
+        .transpose()?;
+    let theme = tasks::block_on(api::fetch_config_by_id(&slug))?;
+
+    // Without a terminal to read keys from, only a duration can end it
+    let raw = enable_raw_mode().is_ok();
+    if !raw && deadline.is_none() {
+        return Err(Error::Invalid(
+            "Previewing needs a terminal to press a key in, or --duration".to_string(),
+        ));
+    }
+    let saved = preview::save_current_colors();
+    preview::apply_osc_preview(&theme);
+    let prompt = match duration {
+        Some(d) => format!("Previewing '{}' for {}", theme.title, d),
+        None => format!("Previewing '{}'", theme.title),
+    };
+    if raw {
+        print!("{}, press any key to restore\r\n", prompt);
+    } else {
+        println!("{}", prompt);
+    }
+    let _ = io::Write::flush(&mut io::stdout());
+
+    loop {
+        let wait = match deadline {
+            Some(deadline) => match deadline.checked_duration_since(Instant::now()) {
+                Some(left) => left,
+                None => break,
+            },
+            None => Duration::from_secs(60),
+        };
+        if !raw {
+            std::thread::sleep(wait);
+            break;
+        }
+        match event::poll(wait) {
+            Ok(true) => {
+                if let Ok(Event::Key(key)) = event::read() {
+                    if key.kind == KeyEventKind::Press {
+                        break;
+                    }
+                }
+            }
+            Ok(false) => {}
+            Err(_) => break,
+        }
+    }
+
+    preview::restore_colors(&saved);
+    if raw {
+        let _ = disable_raw_mode();
+    }
+    Ok(())
+}
+
+/// Print where a theme was applied, first asking Ghostty to reload when
+/// `reload` is set.
+fn report_applied(result: error::Result<(theme::GhosttyConfig, String)>, reload: bool) {
+    match result {
+        Ok((theme, path)) if !reload => println!("Applied '{}' to {}", theme.title, path),
+        Ok((theme, path)) => match ghostty::try_reload_config() {
+            Ok(_) => println!("Applied '{}' to {} (reloaded)", theme.title, path),
+            Err(_) => println!(
+                "Applied '{}' to {} (reload with {})",
+                theme.title,
+                path,
+                ghostty::reload_shortcut_label()
+            ),
+        },
+        Err(e) => error::exit(e),
+    }
+}
+
+/// Print the change applying a theme would make to the Ghostty config.
+fn show_planned(theme: error::Result<theme::GhosttyConfig>, scope: config::ApplyScope) {
+    match theme.and_then(|theme| config::plan_apply(&theme, scope).map(|plan| (theme, plan))) {
+        Ok((theme, plan)) => {
+            print_warnings(&theme.title, &plan.warnings);
+            let diff = plan.diff();
+            if diff.is_empty() {
+                println!(
+                    "Applying '{}' would leave {} unchanged",
+                    theme.title,
+                    plan.path.display()
+                );
+            } else {
+                println!(
+                    "Applying '{}' would change {}:",
+                    theme.title,
+                    plan.path.display()
+                );
+                diff::print(&diff);
+            }
+        }
+        Err(e) => error::exit(e),
+    }
+}
+
+/// Apply `scope` of a theme for the `apply` command, first printing what
+/// validating its config turned up.
+fn apply_with_warnings(
+    theme: theme::GhosttyConfig,
+    scope: config::ApplyScope,
+) -> error::Result<(theme::GhosttyConfig, String)> {
+    let check = theme::validate_raw_config(&theme.raw_config);
+    print_warnings(&theme.title, &check.warnings);
+    config::apply_theme_from(&theme, scope, history::Origin::default()).map(|path| (theme, path))
+}
+
+fn print_warnings(title: &str, warnings: &[String]) {
+    for warning in warnings {
+        eprintln!("Warning: '{}': {}", title, warning);
+    }
+}
+
+fn login(token: Option<String>) -> error::Result<()> {
+    let token = match token {
+        Some(token) => token,
+        None => {
+            use std::io::{BufRead, Write};
+            print!("Paste an API token from your ghostty-style.vercel.app account: ");
+            let _ = io::stdout().flush();
+            let mut line = String::new();
+            io::stdin()
+                .lock()
+                .read_line(&mut line)
+                .map_err(|e| Error::io("read", std::path::Path::new("stdin"), e))?;
+            line
+        }
+    };
+    let token = token.trim();
+    if token.is_empty() {
+        return Err(Error::Invalid("No token given".to_string()));
+    }
+    let account = tasks::block_on(api::auth::verify(token))?;
+    api::auth::save(token, &account)?;
+    println!("Logged in as {}", account.username);
+    Ok(())
+}
+
+fn handle_favorites(action: FavoritesAction) {
+    let favorites = favorites::load();
+    match action {
+        FavoritesAction::List => {
+            if favorites.is_empty() {
+                println!("No favorites yet. Star themes with '*' in the TUI.");
+                return;
+            }
+            for (i, favorite) in favorites.iter().enumerate() {
+                println!("  {}. {} ({})", i + 1, favorite.title, favorite.slug);
+            }
+        }
+        FavoritesAction::Apply { target } => {
+            let favorite = match target {
+                Some(target) => favorites::find(&favorites, &target)
+                    .ok_or_else(|| Error::not_found("Favorite", &target)),
+                None => {
+                    use rand::seq::SliceRandom;
+                    favorites.choose(&mut rand::thread_rng()).ok_or_else(|| {
+                        Error::Invalid(
+                            "No favorites yet. Star themes with '*' in the TUI.".to_string(),
+                        )
+                    })
+                }
+            };
+            // The library copy works offline; fetch in case it's gone
+            let result = favorite
+                .and_then(|f| match library::load(&f.slug) {
+                    Some(theme) => Ok(theme),
+                    None => tasks::block_on(api::fetch_config_by_id(&f.slug)),
+                })
+                .and_then(|theme| config::apply_theme(&theme).map(|path| (theme, path)));
+            report_applied(result, true);
+        }
     }
 }
 
+fn handle_do(action: DoAction) {
+    let result = match action {
+        DoAction::List {
+            filter,
+            sort,
+            limit,
+        } => browse(&filter, sort, limit).map(|themes| {
+            for theme in &themes {
+                println!("{}\t{}", theme.slug, theme.title);
+            }
+        }),
+        DoAction::AddTo {
+            collection,
+            filter,
+            sort,
+            limit,
+        } => add_browsed(&collection, &filter, sort, limit),
+        DoAction::AddTrendingTo {
+            collection,
+            filter,
+            limit,
+        } => add_browsed(&collection, &filter, api::SortOrder::Trending, limit),
+        DoAction::ApplyTop { filter, sort } => {
+            let result = browse(&filter, sort, 1)
+                .and_then(|themes| {
+                    themes
+                        .into_iter()
+                        .next()
+                        .ok_or_else(|| Error::Other("No themes match those filters".to_string()))
+                })
+                .and_then(|theme| config::apply_theme(&theme).map(|path| (theme, path)));
+            report_applied(result, true);
+            Ok(())
+        }
+    };
+    if let Err(e) = result {
+        error::exit(e);
+    }
+}
+
+/// Print `value` as pretty JSON for `--json`; `what` names it in the error.
+fn print_json(value: &impl serde::Serialize, what: &str) {
+    match serde_json::to_string_pretty(value) {
+        Ok(out) => println!("{}", out),
+        Err(e) => error::exit(Error::parse(what, e)),
+    }
+}
+
+/// Print a listing as a table of slug, title, votes, and tags.
+fn print_search_results(resp: &theme::ConfigResponse) {
+    if resp.configs.is_empty() {
+        println!("No themes found.");
+        return;
+    }
+    let title = |t: &theme::GhosttyConfig| -> String {
+        if t.title.chars().count() > 40 {
+            format!("{}...", t.title.chars().take(37).collect::<String>())
+        } else {
+            t.title.clone()
+        }
+    };
+    let slug_width = resp
+        .configs
+        .iter()
+        .map(|t| t.slug.chars().count())
+        .fold("SLUG".len(), usize::max);
+    let title_width = resp
+        .configs
+        .iter()
+        .map(|t| title(t).chars().count())
+        .fold("TITLE".len(), usize::max);
+    println!(
+        "{:<sw$}  {:<tw$}  {:>5}  TAGS",
+        "SLUG",
+        "TITLE",
+        "VOTES",
+        sw = slug_width,
+        tw = title_width
+    );
+    for theme in &resp.configs {
+        println!(
+            "{:<sw$}  {:<tw$}  {:>5}  {}",
+            theme.slug,
+            title(theme),
+            theme.vote_count,
+            theme.tags.join(", "),
+            sw = slug_width,
+            tw = title_width
+        );
+    }
+    let cached = resp
+        .cached
+        .map(|age| format!(", cached {} ago", daemon::format_countdown(age.as_secs())))
+        .unwrap_or_default();
+    println!(
+        "Page {} of {} ({} {}{})",
+        resp.page,
+        resp.total_pages,
+        resp.total,
+        if resp.total == 1 { "theme" } else { "themes" },
+        cached
+    );
+}
+
+/// The first `limit` themes the browse screen would list for these filters.
+fn browse(
+    filter: &cli::BrowseFilter,
+    sort: api::SortOrder,
+    limit: usize,
+) -> error::Result<Vec<theme::GhosttyConfig>> {
+    tasks::block_on(api::fetch_top(&browse_params(filter, sort), limit))
+}
+
+/// The first page of the listing the browse screen would show.
+fn browse_params(filter: &cli::BrowseFilter, sort: api::SortOrder) -> api::FetchParams {
+    let dark = if filter.dark {
+        Some(true)
+    } else if filter.light {
+        Some(false)
+    } else if filter.any {
+        None
+    } else {
+        collection::load_config().dark_filter()
+    };
+    api::FetchParams {
+        query: filter.query.clone(),
+        tag: filter.tag.clone(),
+        sort,
+        page: 1,
+        dark,
+        mine: filter.mine,
+    }
+}
+
+/// Run a smart collection's query now and report what it found.
+fn resolve_smart(name: &str, query: &smart::SmartQuery) {
+    eprintln!("Fetching the {}...", query.describe());
+    match tasks::block_on(smart::resolve(query))
+        .and_then(|fetched| smart::save_resolved(name, &fetched))
+    {
+        Ok((coll, added)) => println!(
+            "'{}': {} themes, {} new",
+            coll.name,
+            coll.themes.len(),
+            added
+        ),
+        Err(e) => error::exit(e),
+    }
+}
+
+fn add_browsed(
+    name: &str,
+    filter: &cli::BrowseFilter,
+    sort: api::SortOrder,
+    limit: usize,
+) -> error::Result<()> {
+    // Load first so a typo fails before any fetching
+    let mut coll = collection::load_collection(name)?;
+    coll.require_fixed()?;
+    let themes = browse(filter, sort, limit)?;
+    let added: Vec<&str> = themes
+        .iter()
+        .inspect(|theme| library::remember(theme))
+        .filter(|theme| coll.add((*theme).into()))
+        .map(|theme| theme.title.as_str())
+        .collect();
+    if !added.is_empty() {
+        collection::save_collection(&coll)?;
+    }
+    for title in &added {
+        println!("Added '{}' to collection '{}'", title, coll.name);
+    }
+    let skipped = themes.len() - added.len();
+    if skipped > 0 {
+        println!("{} already in '{}'", skipped, coll.name);
+    }
+    Ok(())
+}
+
 fn handle_mode(action: ModeAction) {
     use collection::ModePreference;
 
     let mut config = collection::load_config();
+    // Choosing a mode outright ends any temporary override
+    if !matches!(action, ModeAction::Status) {
+        config.mode_override = None;
+    }
 
     match action {
-        ModeAction::Dark => {
+        ModeAction::Dark { duration: Some(d) } => override_mode(config, ModePreference::Dark, &d),
+        ModeAction::Light { duration: Some(d) } => override_mode(config, ModePreference::Light, &d),
+        ModeAction::Dark { duration: None } => {
             config.mode_preference = Some(ModePreference::Dark);
             save_mode_config(&config);
             println!("Mode: dark (only dark themes will be used)");
         }
-        ModeAction::Light => {
+        ModeAction::Light { duration: None } => {
             config.mode_preference = Some(ModePreference::Light);
             save_mode_config(&config);
             println!("Mode: light (only light themes will be used)");
@@ -102,28 +931,22 @@ fn handle_mode(action: ModeAction) {
             light_after,
         } => {
             if darkmode::parse_hhmm(&dark_after).is_none() {
-                eprintln!(
+                error::exit(Error::Invalid(format!(
                     "Invalid time format for --dark-after: '{}' (use HH:MM)",
                     dark_after
-                );
-                std::process::exit(1);
+                )));
             }
             if darkmode::parse_hhmm(&light_after).is_none() {
-                eprintln!(
+                error::exit(Error::Invalid(format!(
                     "Invalid time format for --light-after: '{}' (use HH:MM)",
                     light_after
-                );
-                std::process::exit(1);
+                )));
             }
             config.mode_preference = Some(ModePreference::AutoTime);
             config.dark_after = dark_after.clone();
             config.light_after = light_after.clone();
             save_mode_config(&config);
-            let state = match darkmode::resolve_mode(
-                &ModePreference::AutoTime,
-                &dark_after,
-                &light_after,
-            ) {
+            let state = match darkmode::resolve_mode(&ModePreference::AutoTime, &config) {
                 Some(true) => "dark",
                 Some(false) => "light",
                 None => "unknown",
@@ -133,6 +956,19 @@ fn handle_mode(action: ModeAction) {
                 dark_after, light_after, state
             );
         }
+        ModeAction::AutoSun { lat, lon } => {
+            if !(-90.0..=90.0).contains(&lat) || !(-180.0..=180.0).contains(&lon) {
+                error::exit(Error::Invalid(
+                    "Invalid location: latitude must be within ±90 and longitude within ±180"
+                        .to_string(),
+                ));
+            }
+            config.mode_preference = Some(ModePreference::AutoSun);
+            config.latitude = Some(lat);
+            config.longitude = Some(lon);
+            save_mode_config(&config);
+            print_mode_status(&config);
+        }
         ModeAction::Off => {
             config.mode_preference = None;
             save_mode_config(&config);
@@ -144,53 +980,137 @@ fn handle_mode(action: ModeAction) {
     }
 }
 
+/// Force `mode` for `duration`, leaving the saved preference to take over after.
+fn override_mode(
+    mut config: collection::AppConfig,
+    mode: collection::ModePreference,
+    duration: &str,
+) {
+    let duration = match schedule::parse_interval(duration) {
+        Ok(d) => d,
+        Err(e) => error::exit(Error::Invalid(format!("Invalid --for duration: {}", e))),
+    };
+    let until = darkmode::unix_now() + duration.as_secs();
+    let label = mode.label();
+    config.mode_override = Some(collection::ModeOverride { mode, until });
+    save_mode_config(&config);
+    let then = config.mode_preference.as_ref().map_or("off", |p| p.label());
+    println!(
+        "Mode: {} until {} (in {}), then back to {}",
+        label,
+        darkmode::format_clock(until),
+        daemon::format_countdown(duration.as_secs()),
+        then
+    );
+}
+
 fn save_mode_config(config: &collection::AppConfig) {
     if let Err(e) = collection::save_config(config) {
-        eprintln!("Error saving config: {}", e);
-        std::process::exit(1);
+        error::exit(e)
     }
 }
 
 fn print_mode_status(config: &collection::AppConfig) {
-    match &config.mode_preference {
-        None => println!("Mode: off (no filtering)"),
-        Some(pref) => {
-            let state = match darkmode::resolve_mode(pref, &config.dark_after, &config.light_after)
-            {
-                Some(true) => "dark",
-                Some(false) => "light",
-                None => "undetectable",
-            };
-            match pref {
-                collection::ModePreference::Dark => println!("Mode: dark"),
-                collection::ModePreference::Light => println!("Mode: light"),
-                collection::ModePreference::AutoOs => {
-                    println!("Mode: auto-os (currently {})", state);
-                }
-                collection::ModePreference::AutoTime => {
-                    println!(
-                        "Mode: auto-time (dark after {}, light after {}, currently {})",
-                        config.dark_after, config.light_after, state
-                    );
-                }
+    use collection::ModePreference;
+
+    if let Some(profile) = &config.profile {
+        println!("Profile:    {}", profile);
+    }
+
+    if let Some(coll) = schedule::collection_with_own_mode(config) {
+        println!(
+            "Collection: '{}' uses its own mode ({})",
+            coll.name,
+            coll.mode_label().unwrap_or_default()
+        );
+    }
+    let config = &schedule::mode_config(config);
+    let now = darkmode::unix_now();
+    if let Some(o) = config.active_override(now) {
+        println!(
+            "Override:   {} until {} (in {}), then {}",
+            o.mode.label(),
+            darkmode::format_clock(o.until),
+            daemon::format_countdown(o.until - now),
+            config.mode_preference.as_ref().map_or("off", |p| p.label())
+        );
+    }
+    let Some(pref) = config.effective_mode() else {
+        println!("Mode:       off (no filtering)");
+        return;
+    };
+    let pref = &pref;
+    let state = match darkmode::resolve_mode(pref, config) {
+        Some(true) => "dark",
+        Some(false) => "light",
+        None => "undetectable",
+    };
+    println!("Mode:       {}", pref.label());
+    let reason = if config.active_override(now).is_some() {
+        "temporary override".to_string()
+    } else {
+        darkmode::mode_reason(pref, config)
+    };
+    println!("Currently:  {} ({})", state, reason);
+    match pref {
+        ModePreference::AutoOs => println!("Next flip:  when the OS appearance changes"),
+        ModePreference::AutoTime | ModePreference::AutoSun => {
+            if let Some((to_dark, at, secs)) = darkmode::next_flip(pref, config) {
+                println!(
+                    "Next flip:  {} at {} (in {})",
+                    if to_dark { "dark" } else { "light" },
+                    at,
+                    daemon::format_countdown(secs)
+                );
             }
         }
+        ModePreference::Dark | ModePreference::Light => {}
     }
 }
 
-fn handle_collection(action: CollectionAction) {
+fn handle_collection(action: CollectionAction, json: bool) {
     match action {
         CollectionAction::Create { name } => match collection::create_collection(&name) {
             Ok(created) => {
                 println!("Created collection '{}'", created.name);
                 prompt_daemon_and_hook(&created.name);
             }
-            Err(e) => {
-                eprintln!("Error creating collection: {}", e);
-                std::process::exit(1);
-            }
+            Err(e) => error::exit(e),
         },
-        CollectionAction::List => {
+        CollectionAction::List { archived } if json => {
+            let config = collection::load_config();
+            let role = |name: &str| {
+                if config.active_collection.as_deref() == Some(name) {
+                    Some("active")
+                } else if config.dark_collection.as_deref() == Some(name) {
+                    Some("dark")
+                } else if config.light_collection.as_deref() == Some(name) {
+                    Some("light")
+                } else {
+                    None
+                }
+            };
+            let list: Vec<_> = collection::list_collections()
+                .iter()
+                .filter_map(|name| match collection::load_collection(name) {
+                    Ok(col) if col.archived && !archived => None,
+                    Ok(col) => Some(serde_json::json!({
+                        "name": name,
+                        "role": role(name),
+                        "themes": col.themes.len(),
+                        "smart": col.smart.is_some(),
+                        "archived": col.archived,
+                    })),
+                    Err(e) => Some(serde_json::json!({
+                        "name": name,
+                        "role": role(name),
+                        "error": e.to_string(),
+                    })),
+                })
+                .collect();
+            print_json(&list, "collection list");
+        }
+        CollectionAction::List { archived } => {
             let names = collection::list_collections();
             if names.is_empty() {
                 println!("No collections yet. Create one with:");
@@ -199,110 +1119,567 @@ fn handle_collection(action: CollectionAction) {
             }
             let config = collection::load_config();
             let active = config.active_collection.as_deref();
+            let mut hidden = 0;
             for name in &names {
                 let marker = if active == Some(name.as_str()) {
                     " (active)"
+                } else if config.dark_collection.as_deref() == Some(name.as_str()) {
+                    " (dark)"
+                } else if config.light_collection.as_deref() == Some(name.as_str()) {
+                    " (light)"
                 } else {
                     ""
                 };
                 match collection::load_collection(name) {
+                    Ok(col) if col.archived && !archived => hidden += 1,
                     Ok(col) => {
                         let count = col.themes.len();
                         let theme_word = if count == 1 { "theme" } else { "themes" };
-                        println!("  {}{} - {} {}", name, marker, count, theme_word);
+                        let smart = if col.smart.is_some() { " (smart)" } else { "" };
+                        let archived = if col.archived { " (archived)" } else { "" };
+                        println!(
+                            "  {}{} - {} {}{}{}",
+                            name, marker, count, theme_word, smart, archived
+                        );
+                    }
+                    Err(_) => {
+                        println!("  {}{} - (error loading)", name, marker);
+                    }
+                }
+            }
+            if hidden > 0 {
+                println!(
+                    "{} archived collection{} hidden; show with --archived",
+                    hidden,
+                    if hidden == 1 { "" } else { "s" }
+                );
+            }
+        }
+        CollectionAction::Show { name } if json => match collection::load_collection(&name) {
+            Ok(col) => {
+                let themes: Vec<_> = col
+                    .themes
+                    .iter()
+                    .enumerate()
+                    .map(|(i, theme)| {
+                        serde_json::json!({
+                            "slug": theme.slug,
+                            "title": theme.title,
+                            "is_dark": theme.is_dark,
+                            "twin": theme.twin,
+                            "weight": theme.weight,
+                            "enabled": theme.enabled,
+                            "tags": theme.tags,
+                            "current": i == col.current_index,
+                        })
+                    })
+                    .collect();
+                let show = serde_json::json!({
+                    "name": col.name,
+                    "order": col.order,
+                    "interval": col.interval,
+                    "schedule": col.schedule,
+                    "mode": col.mode_label(),
+                    "smart": col.smart.as_ref().map(|query| query.describe()),
+                    "tag_filter": col.tag_filter,
+                    "archived": col.archived,
+                    "current_index": col.current_index,
+                    "themes": themes,
+                });
+                print_json(&show, "collection");
+            }
+            Err(e) => error::exit(e),
+        },
+        CollectionAction::Show { name } => match collection::load_collection(&name) {
+            Ok(col) => {
+                let order_str = col.order.label();
+                let interval_str = col.interval.as_deref().unwrap_or("not set");
+                println!("Collection: {}", col.name);
+                println!("Themes:     {}", col.themes.len());
+                println!("Order:      {}", order_str);
+                println!("Interval:   {}", interval_str);
+                if let Some(ref expr) = col.schedule {
+                    println!("Schedule:   {}", expr);
+                }
+                if let Some(mode) = col.mode_label() {
+                    println!("Mode:       {}", mode);
+                }
+                if let Some(query) = &col.smart {
+                    println!("Smart:      {}", query.describe());
+                }
+                if !col.tag_filter.is_empty() {
+                    println!("Tags:       only {}", col.tag_filter.join(" or "));
+                }
+                if col.themes.is_empty() && col.smart.is_some() {
+                    println!();
+                    println!("No themes yet. Fetch them with:");
+                    println!("  ghostty-styles collection update {}", name);
+                } else if col.themes.is_empty() {
+                    println!();
+                    println!("No themes yet. Add one with:");
+                    println!("  ghostty-styles collection add {} <slug>", name);
+                } else {
+                    println!();
+                    for (i, theme) in col.themes.iter().enumerate() {
+                        let marker = if i == col.current_index { " <-" } else { "" };
+                        let twin = theme
+                            .twin
+                            .as_ref()
+                            .map(|t| format!(" (twin: {})", t))
+                            .unwrap_or_default();
+                        let weight = match theme.weight {
+                            1 => String::new(),
+                            w => format!(" x{}", w),
+                        };
+                        let off = if theme.enabled { "" } else { " (disabled)" };
+                        println!(
+                            "  {}. {}{}{}{}{}",
+                            i + 1,
+                            theme.title,
+                            twin,
+                            weight,
+                            off,
+                            marker
+                        );
+                    }
+                }
+            }
+            Err(e) => error::exit(e),
+        },
+        CollectionAction::Add {
+            collection: coll_name,
+            slugs,
+            stdin,
+        } => {
+            // Fail before fetching anything if the collection doesn't exist
+            if let Err(e) = collection::load_collection(&coll_name).and_then(|c| c.require_fixed())
+            {
+                error::exit(e)
+            }
+            let mut list = slugs.join(" ");
+            if stdin {
+                match io::read_to_string(io::stdin()) {
+                    Ok(text) => list = format!("{}\n{}", list, text),
+                    Err(e) => error::exit(Error::io("read", std::path::Path::new("stdin"), e)),
+                }
+            }
+            let slugs = collection::parse_slug_list(&list);
+            if slugs.is_empty() {
+                error::exit(Error::Invalid("No theme slugs given".to_string()))
+            }
+
+            let live = io::stderr().is_terminal() && slugs.len() > 1;
+            if !live {
+                eprintln!("Fetching {} themes...", slugs.len());
+            }
+            let results = tasks::block_on(tasks::fetch_all(&slugs, false, |done, total| {
+                if live {
+                    eprint!("\rFetching themes... {}/{}", done, total);
+                }
+            }));
+            if live {
+                eprintln!();
+            }
+            for config in results.iter().flatten() {
+                library::remember(config);
+            }
+
+            let report = {
+                // Held until saved, so a switch by the daemon in between isn't undone
+                let _lock = match lock::acquire(lock::Resource::State) {
+                    Ok(lock) => lock,
+                    Err(e) => error::exit(e),
+                };
+                let mut col = match collection::load_collection(&coll_name) {
+                    Ok(col) => col,
+                    Err(e) => error::exit(e),
+                };
+                let report =
+                    collection::add_fetched(&mut col, slugs.into_iter().zip(results).collect());
+                if !report.added.is_empty() {
+                    if let Err(e) = collection::save_collection(&col) {
+                        error::exit(e)
+                    }
+                }
+                report
+            };
+            for title in &report.added {
+                println!("Added '{}'", title);
+            }
+            for title in &report.duplicates {
+                println!("'{}' is already in '{}'", title, coll_name);
+            }
+            for (slug, e) in &report.failed {
+                eprintln!("Error fetching theme '{}': {}", slug, e);
+            }
+            println!("'{}': {}", coll_name, report.summary());
+            if !report.failed.is_empty() {
+                std::process::exit(1);
+            }
+        }
+        CollectionAction::Use { name, dark, light } => {
+            // Verify collection exists and isn't archived
+            if let Err(e) = collection::load_collection(&name).and_then(|c| c.require_unarchived())
+            {
+                error::exit(e)
+            }
+            let mut config = collection::load_config();
+            let message = if dark {
+                config.dark_collection = Some(name.clone());
+                format!("Dark mode collection set to '{}'", name)
+            } else if light {
+                config.light_collection = Some(name.clone());
+                format!("Light mode collection set to '{}'", name)
+            } else {
+                config.active_collection = Some(name.clone());
+                format!("Active collection set to '{}'", name)
+            };
+            match collection::save_config(&config) {
+                Ok(()) => {
+                    println!("{}", message);
+                }
+                Err(e) => error::exit(e),
+            }
+        }
+        CollectionAction::Remove {
+            collection: coll_name,
+            theme,
+        } => {
+            // Held until saved, so a switch by the daemon in between isn't undone
+            let _lock = match lock::acquire(lock::Resource::State) {
+                Ok(lock) => lock,
+                Err(e) => error::exit(e),
+            };
+            let mut coll = match collection::load_collection(&coll_name) {
+                Ok(c) => c,
+                Err(e) => error::exit(e),
+            };
+            if let Err(e) = coll.require_fixed() {
+                error::exit(e)
+            }
+            let index = match cycling::find_fuzzy(&coll.themes, &theme) {
+                Ok(index) => index,
+                Err(e) => error::exit(e),
+            };
+            let removed = coll.remove(index);
+            if let Err(e) = collection::save_collection(&coll) {
+                error::exit(e)
+            }
+            println!("Removed '{}' from '{}'", removed.title, coll.name);
+        }
+        CollectionAction::Update { name } => {
+            let coll = match collection::load_collection(&name) {
+                Ok(coll) => coll,
+                Err(e) => error::exit(e),
+            };
+            if let Some(query) = &coll.smart {
+                resolve_smart(&coll.name, query);
+                return;
+            }
+            // Themes from the creator aren't on the site
+            let slugs: Vec<String> = coll
+                .themes
+                .into_iter()
+                .filter(|t| !t.is_local())
+                .map(|t| t.slug)
+                .collect();
+            if slugs.is_empty() {
+                println!("'{}' has no themes to update", name);
+                return;
+            }
+            eprintln!("Fetching {} themes...", slugs.len());
+            let results = tasks::block_on(tasks::fetch_all(&slugs, true, |_, _| {}));
+            let fetched = slugs.into_iter().zip(results).collect();
+            let report = match collection::save_refreshed(&name, &fetched) {
+                Ok(report) => report,
+                Err(e) => error::exit(e),
+            };
+            for title in &report.changed {
+                println!("Updated '{}'", title);
+            }
+            for slug in &report.gone {
+                println!("'{}' no longer exists on the site; kept as is", slug);
+            }
+            for (slug, e) in &report.failed {
+                eprintln!("Error fetching theme '{}': {}", slug, e);
+            }
+            println!("'{}': {}", name, report.summary());
+            if !report.failed.is_empty() {
+                std::process::exit(1);
+            }
+        }
+        CollectionAction::Smart {
+            name,
+            query,
+            tag,
+            sort,
+            limit,
+            dark,
+            light,
+            mine,
+        } => {
+            let query = smart::SmartQuery {
+                query,
+                tag,
+                sort,
+                dark: if dark {
+                    Some(true)
+                } else if light {
+                    Some(false)
+                } else {
+                    None
+                },
+                mine,
+                limit,
+                resolved_at: 0,
+            };
+            let (coll, created) = match smart::save_query(&name, query.clone()) {
+                Ok(saved) => saved,
+                Err(e) => error::exit(e),
+            };
+            let verb = if created { "Created" } else { "Updated" };
+            println!(
+                "{} smart collection '{}': the {}",
+                verb,
+                coll.name,
+                query.describe()
+            );
+            resolve_smart(&coll.name, &query);
+            if created {
+                prompt_daemon_and_hook(&coll.name);
+            }
+        }
+        CollectionAction::Twin {
+            collection: coll_name,
+            theme,
+            twin,
+        } => {
+            let mut coll = match collection::load_collection(&coll_name) {
+                Ok(c) => c,
+                Err(e) => error::exit(e),
+            };
+            // A twin named by slug that isn't in the collection yet is
+            // fetched and added alongside its partner
+            let mut fetched = None;
+            if let Some(slug) = twin.as_deref().filter(|t| {
+                let target = cycling::Target::parse(t);
+                matches!(target, cycling::Target::Slug(_))
+                    && cycling::find_target(&coll.themes, &target).is_none()
+            }) {
+                match tasks::block_on(api::fetch_config_by_id(slug)) {
+                    Ok(config) => {
+                        library::remember(&config);
+                        coll.add((&config).into());
+                        fetched = Some(config.title);
                     }
-                    Err(_) => {
-                        println!("  {}{} - (error loading)", name, marker);
+                    Err(e) => error::exit(e),
+                }
+            }
+            let find = |target: &str| {
+                cycling::find_target(&coll.themes, &cycling::Target::parse(target))
+                    .ok_or_else(|| Error::not_found("Theme", target))
+            };
+            let pair = find(&theme).and_then(|a| Ok((a, twin.as_deref().map(find).transpose()?)));
+            let result = pair.and_then(|(a, b)| coll.link_twins(a, b).map(|_| (a, b)));
+            match result {
+                Ok((a, b)) => {
+                    if let Some(title) = fetched {
+                        println!("Added '{}' to collection '{}'", title, coll.name);
+                    }
+                    if let Err(e) = collection::save_collection(&coll) {
+                        error::exit(e)
+                    }
+                    match b {
+                        Some(b) => println!(
+                            "Paired '{}' with '{}'",
+                            coll.themes[a].title, coll.themes[b].title
+                        ),
+                        None => println!("Unpaired '{}'", coll.themes[a].title),
                     }
                 }
+                Err(e) => error::exit(e),
             }
         }
-        CollectionAction::Show { name } => match collection::load_collection(&name) {
-            Ok(col) => {
-                let order_str = match col.order {
-                    collection::CycleOrder::Sequential => "sequential",
-                    collection::CycleOrder::Shuffle => "shuffle",
-                };
-                let interval_str = col.interval.as_deref().unwrap_or("not set");
-                println!("Collection: {}", col.name);
-                println!("Themes:     {}", col.themes.len());
-                println!("Order:      {}", order_str);
-                println!("Interval:   {}", interval_str);
-                if col.themes.is_empty() {
-                    println!();
-                    println!("No themes yet. Add one with:");
-                    println!("  ghostty-styles collection add {} <slug>", name);
-                } else {
-                    println!();
-                    for (i, theme) in col.themes.iter().enumerate() {
-                        let marker = if i == col.current_index { " <-" } else { "" };
-                        println!("  {}. {}{}", i + 1, theme.title, marker);
-                    }
+        CollectionAction::Interval { name, interval } => {
+            if let Some(ref interval) = interval {
+                if let Err(e) = schedule::parse_interval(interval) {
+                    error::exit(Error::Invalid(e))
                 }
             }
-            Err(e) => {
-                eprintln!("Error: {}", e);
-                std::process::exit(1);
+            let mut coll = match collection::load_collection(&name) {
+                Ok(c) => c,
+                Err(e) => error::exit(e),
+            };
+            coll.interval = interval.clone();
+            if let Err(e) = collection::save_collection(&coll) {
+                error::exit(e)
             }
-        },
-        CollectionAction::Add {
-            collection: coll_name,
-            slug,
+            match interval {
+                Some(interval) => println!("Interval for '{}' set to '{}'", name, interval),
+                None => println!("Cleared interval for '{}'", name),
+            }
+        }
+        CollectionAction::Tags { name, tags } => {
+            // Held until saved, so a switch by the daemon in between isn't undone
+            let _lock = match lock::acquire(lock::Resource::State) {
+                Ok(lock) => lock,
+                Err(e) => error::exit(e),
+            };
+            let mut coll = match collection::load_collection(&name) {
+                Ok(c) => c,
+                Err(e) => error::exit(e),
+            };
+            coll.tag_filter = tags;
+            if let Err(e) = collection::save_collection(&coll) {
+                error::exit(e)
+            }
+            if coll.tag_filter.is_empty() {
+                println!("'{}' cycles every theme again", coll.name);
+                return;
+            }
+            let tagged = coll
+                .themes
+                .iter()
+                .filter(|t| t.has_any_tag(&coll.tag_filter))
+                .count();
+            println!(
+                "'{}' only cycles themes tagged {} ({} of {})",
+                coll.name,
+                coll.tag_filter.join(" or "),
+                tagged,
+                coll.themes.len()
+            );
+            let untagged = coll
+                .themes
+                .iter()
+                .filter(|t| t.tags.is_empty() && !t.is_local())
+                .count();
+            if untagged > 0 {
+                println!(
+                    "{} theme(s) have no tags saved; `ghostty-styles collection update {}` fetches them",
+                    untagged, coll.name
+                );
+            }
+        }
+        CollectionAction::Mode {
+            name,
+            mode,
+            dark_after,
+            light_after,
+            global,
         } => {
-            // Fetch theme from API
-            match api::fetch_config_by_id(&slug) {
-                Ok(config) => {
-                    let theme = collection::CollectionTheme {
-                        slug: config.slug,
-                        title: config.title.clone(),
-                        is_dark: config.is_dark,
-                        raw_config: config.raw_config,
-                    };
-                    match collection::load_collection(&coll_name) {
-                        Ok(mut col) => {
-                            col.themes.push(theme);
-                            match collection::save_collection(&col) {
-                                Ok(()) => {
-                                    println!(
-                                        "Added '{}' to collection '{}'",
-                                        config.title, coll_name
-                                    );
-                                }
-                                Err(e) => {
-                                    eprintln!("Error saving collection: {}", e);
-                                    std::process::exit(1);
-                                }
-                            }
-                        }
-                        Err(e) => {
-                            eprintln!("Error: {}", e);
-                            std::process::exit(1);
-                        }
+            for (flag, time) in [
+                ("--dark-after", &dark_after),
+                ("--light-after", &light_after),
+            ] {
+                if let Some(time) = time.as_deref() {
+                    if darkmode::parse_hhmm(time).is_none() {
+                        error::exit(Error::Invalid(format!(
+                            "Invalid time format for {}: '{}' (use HH:MM)",
+                            flag, time
+                        )));
                     }
                 }
-                Err(e) => {
-                    eprintln!("Error fetching theme '{}': {}", slug, e);
-                    std::process::exit(1);
-                }
             }
-        }
-        CollectionAction::Use { name } => {
-            // Verify collection exists
-            if let Err(e) = collection::load_collection(&name) {
-                eprintln!("Error: {}", e);
-                std::process::exit(1);
+            let mut coll = match collection::load_collection(&name) {
+                Ok(c) => c,
+                Err(e) => error::exit(e),
+            };
+            let changed = global || mode.is_some() || dark_after.is_some() || light_after.is_some();
+            if global {
+                coll.mode_preference = None;
+                coll.dark_after = None;
+                coll.light_after = None;
             }
-            let mut config = collection::load_config();
-            config.active_collection = Some(name.clone());
-            match collection::save_config(&config) {
-                Ok(()) => {
-                    println!("Active collection set to '{}'", name);
+            if mode.is_some() {
+                coll.mode_preference = mode;
+            }
+            if dark_after.is_some() {
+                coll.dark_after = dark_after;
+            }
+            if light_after.is_some() {
+                coll.light_after = light_after;
+            }
+            if changed {
+                if let Err(e) = collection::save_collection(&coll) {
+                    error::exit(e)
                 }
-                Err(e) => {
-                    eprintln!("Error saving config: {}", e);
-                    std::process::exit(1);
+            }
+            match coll.mode_label() {
+                Some(label) => println!("'{}' uses its own mode: {}", coll.name, label),
+                None => println!("'{}' follows the global mode", coll.name),
+            }
+            let config = collection::load_config();
+            if coll.has_own_mode()
+                && (config.dark_collection.is_some() || config.light_collection.is_some())
+            {
+                println!(
+                    "Dark/light collections are set, so this only applies on days a weekday rule picks '{}'",
+                    coll.name
+                );
+            }
+        }
+        CollectionAction::Schedule { name, expr } => {
+            if let Some(ref expr) = expr {
+                if let Err(e) = schedule::CronSchedule::parse(expr) {
+                    error::exit(Error::Invalid(e))
                 }
             }
+            let mut coll = match collection::load_collection(&name) {
+                Ok(c) => c,
+                Err(e) => error::exit(e),
+            };
+            coll.schedule = expr.clone();
+            if let Err(e) = collection::save_collection(&coll) {
+                error::exit(e)
+            }
+            match expr {
+                Some(expr) => println!("Schedule for '{}' set to '{}'", name, expr),
+                None => println!("Cleared schedule for '{}'", name),
+            }
+        }
+        CollectionAction::Export { name, file } => {
+            let result = archive::export_json(&name)
+                .map_err(Error::from)
+                .and_then(|json| match &file {
+                    Some(path) => atomic::write(path, json + "\n")
+                        .map_err(|e| Error::io("write", path, e))
+                        .map(|()| eprintln!("Exported '{}' to {}", name, path.display())),
+                    None => {
+                        println!("{}", json);
+                        Ok(())
+                    }
+                });
+            if let Err(e) = result {
+                error::exit(e);
+            }
+        }
+        CollectionAction::Import {
+            source,
+            name,
+            refresh,
+        } => import_collection(&source, name.as_deref(), refresh),
+        CollectionAction::ExportArchive { name, file } => {
+            match archive::export_archive(&name, &file) {
+                Ok(count) => println!(
+                    "Exported '{}' ({} themes) to {}",
+                    name,
+                    count,
+                    file.display()
+                ),
+                Err(e) => error::exit(e),
+            }
+        }
+        CollectionAction::ImportArchive { file, name } => {
+            match archive::import_archive(&file, name.as_deref()) {
+                Ok((coll, author)) => println!(
+                    "Imported collection '{}' ({} themes){}",
+                    coll.name,
+                    coll.themes.len(),
+                    author.map(|a| format!(" by {}", a)).unwrap_or_default()
+                ),
+                Err(e) => error::exit(e),
+            }
         }
         CollectionAction::Delete { name } => {
             match collection::delete_collection(&name) {
@@ -320,13 +1697,119 @@ fn handle_collection(action: CollectionAction) {
                     }
                     println!("Deleted collection '{}'", name);
                 }
+                Err(e) => error::exit(e),
+            }
+        }
+        CollectionAction::Archive { name } => match collection::set_archived(&name, true) {
+            Ok(coll) => println!(
+                "Archived '{}'; bring it back with `ghostty-styles collection unarchive {}`",
+                coll.name, coll.name
+            ),
+            Err(e) => error::exit(e),
+        },
+        CollectionAction::Unarchive { name } => match collection::set_archived(&name, false) {
+            Ok(coll) => println!("Unarchived '{}'", coll.name),
+            Err(e) => error::exit(e),
+        },
+        CollectionAction::Rename { old, new } => match collection::rename_collection(&old, &new) {
+            Ok(renamed) => println!("Renamed collection '{}' to '{}'", old, renamed.name),
+            Err(e) => error::exit(e),
+        },
+        CollectionAction::Duplicate { name, copy } => {
+            match collection::duplicate_collection(&name, &copy) {
+                Ok(created) => println!(
+                    "Copied '{}' to '{}' ({} themes)",
+                    name,
+                    created.name,
+                    created.themes.len()
+                ),
+                Err(e) => error::exit(e),
+            }
+        }
+    }
+}
+
+/// `collection import`: shared JSON from a file or URL, or an archive or
+/// theme pack by path.
+fn import_collection(source: &str, rename: Option<&str>, refresh: bool) {
+    let is_url = source.starts_with("https://") || source.starts_with("http://");
+    let path = std::path::Path::new(source);
+    let json = if is_url {
+        match tasks::block_on(api::download(source)) {
+            Ok(json) => json,
+            Err(e) => error::exit(e),
+        }
+    } else {
+        // Archives are gzip, which never starts with `{`
+        match std::fs::read(path) {
+            Ok(bytes) if bytes.trim_ascii_start().starts_with(b"{") => {
+                String::from_utf8_lossy(&bytes).into_owned()
+            }
+            Ok(_) if refresh => error::exit(Error::Invalid(
+                "--refresh only applies to JSON collections".to_string(),
+            )),
+            Ok(_) => {
+                return handle_collection(
+                    CollectionAction::ImportArchive {
+                        file: path.to_path_buf(),
+                        name: rename.map(str::to_string),
+                    },
+                    false,
+                )
+            }
+            Err(e) => error::exit(Error::io("read", path, e)),
+        }
+    };
+
+    let mut imported = match archive::parse_json(&json) {
+        Ok(imported) => imported,
+        Err(e) => error::exit(e),
+    };
+    for skipped in &imported.skipped {
+        eprintln!("Skipped {}", skipped);
+    }
+    let coll = &mut imported.collection;
+    let mut stale = 0;
+    if refresh {
+        let slugs: Vec<String> = coll.themes.iter().map(|t| t.slug.clone()).collect();
+        let results = tasks::block_on(tasks::fetch_all(&slugs, true, |_, _| {}));
+        for (theme, result) in coll.themes.iter_mut().zip(results) {
+            match result {
+                Ok(fresh) => {
+                    theme.title = fresh.title;
+                    theme.is_dark = fresh.is_dark;
+                    theme.raw_config = fresh.raw_config;
+                }
                 Err(e) => {
-                    eprintln!("Error: {}", e);
-                    std::process::exit(1);
+                    eprintln!("Kept the exported copy of '{}': {}", theme.slug, e);
+                    stale += 1;
                 }
             }
         }
     }
+    if let Err(e) = archive::save_imported(coll, rename) {
+        error::exit(e);
+    }
+
+    let mut notes = Vec::new();
+    if imported.duplicates > 0 {
+        notes.push(format!("{} duplicates dropped", imported.duplicates));
+    }
+    if !imported.skipped.is_empty() {
+        notes.push(format!("{} skipped", imported.skipped.len()));
+    }
+    if refresh {
+        notes.push(format!(
+            "{} refreshed from the site",
+            coll.themes.len() - stale
+        ));
+    }
+    println!(
+        "Imported collection '{}' ({} themes{})",
+        coll.name,
+        coll.themes.len(),
+        notes.iter().map(|n| format!(", {}", n)).collect::<String>()
+    );
 }
 
 fn prompt_daemon_and_hook(name: &str) {
@@ -339,7 +1822,9 @@ fn prompt_daemon_and_hook(name: &str) {
     if io::stdin().lock().read_line(&mut input).is_ok() {
         let trimmed = input.trim();
         if !trimmed.is_empty() {
-            if let Ok(mut coll) = collection::load_collection(name) {
+            if let Err(e) = schedule::parse_interval(trimmed) {
+                eprintln!("{}. Set one later from the TUI (press C, then i).", e);
+            } else if let Ok(mut coll) = collection::load_collection(name) {
                 coll.interval = Some(trimmed.to_string());
                 let _ = collection::save_collection(&coll);
                 println!("Interval set to '{}'", trimmed);
@@ -351,7 +1836,7 @@ fn prompt_daemon_and_hook(name: &str) {
     shell_hook::prompt_install();
 }
 
-fn run_tui() {
+fn run_tui(offline: bool) {
     // Ghostty detection
     let term_program = std::env::var("TERM_PROGRAM").unwrap_or_default();
     if term_program.to_lowercase() != "ghostty" {
@@ -373,6 +1858,8 @@ fn run_tui() {
 
     // Setup terminal
     enable_raw_mode().expect("Failed to enable raw mode");
+    // Lock waits would print over the screen; the footer shows them instead
+    lock::quiet();
     let mut stdout = io::stdout();
     execute!(stdout, EnterAlternateScreen, EnableMouseCapture)
         .expect("Failed to enter alternate screen");
@@ -380,7 +1867,8 @@ fn run_tui() {
     let mut terminal = Terminal::new(backend).expect("Failed to create terminal");
 
     let mut app = App::new();
-    app.trigger_fetch();
+    app.offline = offline;
+    app.fetch_now();
 
     let result = run_app(&mut terminal, &mut app);
 
@@ -400,7 +1888,7 @@ fn run_tui() {
     }
 }
 
-fn run_tui_create(from_slug: Option<String>) {
+fn run_tui_create(from_slug: Option<String>, import_path: Option<std::path::PathBuf>) {
     let term_program = std::env::var("TERM_PROGRAM").unwrap_or_default();
     if term_program.to_lowercase() != "ghostty" {
         eprintln!("ghostty-styles requires the Ghostty terminal.");
@@ -408,18 +1896,22 @@ fn run_tui_create(from_slug: Option<String>) {
     }
 
     let source_theme = if let Some(ref slug) = from_slug {
-        match api::fetch_config_by_id(slug) {
+        match tasks::block_on(api::fetch_config_by_id(slug)) {
             Ok(theme) => Some(theme),
-            Err(e) => {
-                eprintln!("Error fetching theme '{}': {}", slug, e);
-                std::process::exit(1);
-            }
+            Err(e) => error::exit(e),
+        }
+    } else if let Some(ref path) = import_path {
+        match import::import_file(path) {
+            Ok(theme) => Some(theme),
+            Err(e) => error::exit(format!("Error importing '{}': {}", path.display(), e)),
         }
     } else {
         None
     };
 
     enable_raw_mode().expect("Failed to enable raw mode");
+    // Lock waits would print over the screen; the footer shows them instead
+    lock::quiet();
     let mut stdout = io::stdout();
     execute!(stdout, EnterAlternateScreen, EnableMouseCapture)
         .expect("Failed to enter alternate screen");
@@ -461,23 +1953,31 @@ fn run_app(
 ) -> Result<(), io::Error> {
     loop {
         app.poll_background();
-
-        terminal.draw(|f| {
-            match app.screen {
-                Screen::Browse => ui::render_browser(f, app),
-                Screen::Detail | Screen::Confirm => ui::render_detail(f, app),
-                Screen::Collections => ui::render_collections(f, app),
-                Screen::Create => ui::render_creator(f, app),
-                Screen::CreateMeta => ui::render_create_meta(f, app),
-            }
-            if app.show_help {
-                ui::render_help(f, app);
-            }
-        })?;
+        app.poll_os_mode();
+        app.poll_next_switch();
+        app.sync_collections_preview();
+
+        // Only draw when input, a background message, or the keepalive asks
+        if app.redraw.due(Instant::now()) {
+            terminal.draw(|f| {
+                match app.screen {
+                    Screen::Browse => ui::render_browser(f, app),
+                    Screen::Detail | Screen::Confirm => ui::render_detail(f, app),
+                    Screen::Collections => ui::render_collections(f, app),
+                    Screen::Create => ui::render_creator(f, app),
+                    Screen::CreateMeta => ui::render_create_meta(f, app),
+                }
+                if app.show_help {
+                    ui::render_help(f, app);
+                }
+            })?;
+            app.redraw.drawn(Instant::now());
+        }
 
         // Poll for events with a timeout so we can check background messages
         if event::poll(Duration::from_millis(50))? {
             let ev = event::read()?;
+            app.redraw.mark();
             match ev {
                 Event::Key(key) => {
                     if key.kind != KeyEventKind::Press {
@@ -515,10 +2015,8 @@ fn run_app(
                         Screen::CreateMeta => handle_create_meta_input(app, key.code),
                     }
                 }
-                Event::Mouse(mouse) => {
-                    if app.screen == Screen::Create {
-                        handle_create_mouse(app, mouse);
-                    }
+                Event::Mouse(mouse) if app.screen == Screen::Create => {
+                    handle_create_mouse(app, mouse);
                 }
                 _ => {}
             }
@@ -567,11 +2065,7 @@ fn handle_browse_input(app: &mut App, key: KeyCode) {
             }
             KeyCode::Char('j') | KeyCode::Down => app.select_next(),
             KeyCode::Char('k') | KeyCode::Up => app.select_prev(),
-            KeyCode::Enter | KeyCode::Char('l') | KeyCode::Right => {
-                if !app.themes.is_empty() {
-                    app.screen = Screen::Detail;
-                }
-            }
+            KeyCode::Enter | KeyCode::Char('l') | KeyCode::Right => app.open_detail(),
             KeyCode::Char('/') => {
                 app.input_mode = InputMode::Search;
                 app.search_input = app.active_query.clone().unwrap_or_default();
@@ -586,30 +2080,37 @@ fn handle_browse_input(app: &mut App, key: KeyCode) {
             KeyCode::Char(']') => app.next_page(),
             KeyCode::Char('[') => app.prev_page(),
             KeyCode::Char('p') => app.toggle_osc_preview(),
-            KeyCode::Char('a') => {
-                if !app.themes.is_empty() {
-                    app.screen = Screen::Confirm;
-                }
-            }
-            KeyCode::Char('c') => {
-                if !app.themes.is_empty() {
-                    app.open_collection_popup();
-                }
+            KeyCode::Char('a') if !app.themes.is_empty() => app.confirm_apply(),
+            KeyCode::Char('c') if !app.themes.is_empty() => {
+                app.open_collection_popup();
             }
             KeyCode::Char('C') => {
                 app.enter_collections();
             }
             KeyCode::Char('r') => {
-                app.trigger_fetch();
+                app.fetch_now();
             }
+            KeyCode::Char('o') => app.toggle_offline(),
+            KeyCode::Char('*') => app.toggle_favorite(),
+            KeyCode::Char('F') => app.toggle_favorites_filter(),
+            KeyCode::Char('M') => app.toggle_mine(),
+            KeyCode::Char('R') => app.select_random(),
             _ => {}
         },
+        InputMode::CollectionSelect | InputMode::CollectionCreate => {
+            handle_collection_popup_input(app, key)
+        }
+    }
+}
+
+/// Keys for the "Add to Collection" popup, shared by the browser and the
+/// creator's save screen.
+fn handle_collection_popup_input(app: &mut App, key: KeyCode) {
+    match app.input_mode {
         InputMode::CollectionSelect => match key {
-            KeyCode::Char('j') | KeyCode::Down => {
-                if !app.collection_names.is_empty() {
-                    app.collection_popup_cursor =
-                        (app.collection_popup_cursor + 1).min(app.collection_names.len() - 1);
-                }
+            KeyCode::Char('j') | KeyCode::Down if !app.collection_names.is_empty() => {
+                app.collection_popup_cursor =
+                    (app.collection_popup_cursor + 1).min(app.collection_names.len() - 1);
             }
             KeyCode::Char('k') | KeyCode::Up => {
                 app.collection_popup_cursor = app.collection_popup_cursor.saturating_sub(1);
@@ -647,6 +2148,7 @@ fn handle_browse_input(app: &mut App, key: KeyCode) {
             }
             _ => {}
         },
+        _ => {}
     }
 }
 
@@ -656,15 +2158,17 @@ fn handle_detail_input(app: &mut App, key: KeyCode) {
             app.screen = Screen::Browse;
         }
         KeyCode::Char('p') => app.toggle_osc_preview(),
-        KeyCode::Char('a') => {
-            app.screen = Screen::Confirm;
-        }
+        KeyCode::Char('a') => app.confirm_apply(),
         KeyCode::Char('c') => {
             app.open_collection_popup();
         }
         KeyCode::Char('f') => {
             app.enter_creator_from_theme();
         }
+        KeyCode::Char('s') => app.share_theme(),
+        KeyCode::Char('y') => app.copy_theme_config(),
+        KeyCode::Char('*') => app.toggle_favorite(),
+        KeyCode::Char('v') => app.vote(),
         _ => {}
     }
 }
@@ -677,6 +2181,7 @@ fn handle_confirm_input(app: &mut App, key: KeyCode) {
         KeyCode::Char('n') | KeyCode::Esc => {
             app.screen = Screen::Detail;
         }
+        KeyCode::Char('s') => app.cycle_apply_scope(),
         _ => {}
     }
 }
@@ -720,14 +2225,58 @@ fn handle_collections_input(app: &mut App, key: KeyCode) {
             }
             _ => {}
         },
+        CollectionsMode::Rename | CollectionsMode::Duplicate => match key {
+            KeyCode::Enter => {
+                let target = app.collections_input.trim().to_string();
+                if let Some(name) = app
+                    .selected_collection_name()
+                    .filter(|_| !target.is_empty())
+                {
+                    let result = if app.collections_mode == CollectionsMode::Rename {
+                        collection::rename_collection(&name, &target)
+                            .map(|c| (format!("Renamed '{}' to '{}'", name, c.name), c.name))
+                    } else {
+                        collection::duplicate_collection(&name, &target)
+                            .map(|c| (format!("Copied '{}' to '{}'", name, c.name), c.name))
+                    };
+                    match result {
+                        Ok((message, created)) => {
+                            app.status_message = Some(message);
+                            app.refresh_collections();
+                            app.select_collection(&created);
+                        }
+                        Err(e) => {
+                            app.status_message = Some(format!("Error: {}", e));
+                        }
+                    }
+                }
+                app.collections_mode = CollectionsMode::Normal;
+                app.collections_input.clear();
+            }
+            KeyCode::Esc => {
+                app.collections_mode = CollectionsMode::Normal;
+                app.collections_input.clear();
+            }
+            KeyCode::Backspace => {
+                app.collections_input.pop();
+            }
+            KeyCode::Char(c) => {
+                app.collections_input.push(c);
+            }
+            _ => {}
+        },
         CollectionsMode::SetInterval => match key {
             KeyCode::Enter => {
-                if let Some(name) = app.collections_list.get(app.collections_cursor).cloned() {
+                if let Some(name) = app.selected_collection_name() {
                     if let Ok(mut coll) = collection::load_collection(&name) {
                         let trimmed = app.collections_input.trim().to_string();
                         if trimmed.is_empty() {
                             coll.interval = None;
                             app.status_message = Some(format!("Cleared interval for '{}'", name));
+                        } else if let Err(e) = schedule::parse_interval(&trimmed) {
+                            // Keep the popup open so the value can be fixed
+                            app.status_message = Some(e);
+                            return;
                         } else {
                             coll.interval = Some(trimmed.clone());
                             app.status_message =
@@ -753,9 +2302,53 @@ fn handle_collections_input(app: &mut App, key: KeyCode) {
             }
             _ => {}
         },
+        CollectionsMode::SetModeTimes => match key {
+            KeyCode::Enter => {
+                if let Some(name) = app.selected_collection_name() {
+                    if let Ok(mut coll) = collection::load_collection(&name) {
+                        let trimmed = app.collections_input.trim();
+                        if trimmed.is_empty() {
+                            coll.dark_after = None;
+                            coll.light_after = None;
+                            app.status_message =
+                                Some(format!("'{}' uses the global switch times", name));
+                        } else if let Some((dark, light)) = darkmode::parse_switch_times(trimmed) {
+                            app.status_message = Some(format!(
+                                "'{}' switches to dark at {} and light at {}",
+                                name, dark, light
+                            ));
+                            coll.dark_after = Some(dark);
+                            coll.light_after = Some(light);
+                        } else {
+                            // Keep the popup open so the value can be fixed
+                            app.status_message =
+                                Some("Use HH:MM-HH:MM, e.g. 20:00-06:30".to_string());
+                            return;
+                        }
+                        if let Err(e) = collection::save_collection(&coll) {
+                            app.status_message = Some(format!("Error: {}", e));
+                        }
+                        app.refresh_collections();
+                    }
+                }
+                app.collections_mode = CollectionsMode::Normal;
+                app.collections_input.clear();
+            }
+            KeyCode::Esc => {
+                app.collections_mode = CollectionsMode::Normal;
+                app.collections_input.clear();
+            }
+            KeyCode::Backspace => {
+                app.collections_input.pop();
+            }
+            KeyCode::Char(c) => {
+                app.collections_input.push(c);
+            }
+            _ => {}
+        },
         CollectionsMode::ConfirmDelete => match key {
             KeyCode::Char('y') => {
-                if let Some(name) = app.collections_list.get(app.collections_cursor).cloned() {
+                if let Some(name) = app.selected_collection_name() {
                     match collection::delete_collection(&name) {
                         Ok(()) => {
                             // Clear active if it was the deleted one
@@ -784,11 +2377,9 @@ fn handle_collections_input(app: &mut App, key: KeyCode) {
 
 fn handle_collections_list_input(app: &mut App, key: KeyCode) {
     match key {
-        KeyCode::Char('j') | KeyCode::Down => {
-            if !app.collections_list.is_empty() {
-                app.collections_cursor =
-                    (app.collections_cursor + 1).min(app.collections_list.len() - 1);
-            }
+        KeyCode::Char('j') | KeyCode::Down if !app.collections_list.is_empty() => {
+            app.collections_cursor =
+                (app.collections_cursor + 1).min(app.collections_list.len() - 1);
         }
         KeyCode::Char('k') | KeyCode::Up => {
             app.collections_cursor = app.collections_cursor.saturating_sub(1);
@@ -800,18 +2391,23 @@ fn handle_collections_list_input(app: &mut App, key: KeyCode) {
             app.collections_mode = CollectionsMode::NewCollection;
             app.collections_input.clear();
         }
-        KeyCode::Char('d') => {
-            if !app.collections_list.is_empty() {
-                app.collections_mode = CollectionsMode::ConfirmDelete;
-            }
+        KeyCode::Char('d') if !app.collections_list.is_empty() => {
+            app.collections_mode = CollectionsMode::ConfirmDelete;
         }
         KeyCode::Char('u') => {
-            if let Some(name) = app.collections_list.get(app.collections_cursor).cloned() {
+            if let Some(name) = app.selected_collection_name() {
+                if let Err(e) =
+                    collection::load_collection(&name).and_then(|c| c.require_unarchived())
+                {
+                    app.status_message = Some(e.to_string());
+                    return;
+                }
                 let mut config = collection::load_config();
                 config.active_collection = Some(name.clone());
                 match collection::save_config(&config) {
                     Ok(()) => {
                         app.status_message = Some(format!("Activated collection '{}'", name));
+                        app.refresh_collections();
                     }
                     Err(e) => {
                         app.status_message = Some(format!("Error: {}", e));
@@ -820,16 +2416,11 @@ fn handle_collections_list_input(app: &mut App, key: KeyCode) {
             }
         }
         KeyCode::Char('s') => {
-            if let Some(name) = app.collections_list.get(app.collections_cursor).cloned() {
+            if let Some(name) = app.selected_collection_name() {
                 if let Ok(mut coll) = collection::load_collection(&name) {
-                    coll.order = match coll.order {
-                        collection::CycleOrder::Sequential => collection::CycleOrder::Shuffle,
-                        collection::CycleOrder::Shuffle => collection::CycleOrder::Sequential,
-                    };
-                    let order_label = match coll.order {
-                        collection::CycleOrder::Sequential => "sequential",
-                        collection::CycleOrder::Shuffle => "shuffle",
-                    };
+                    coll.order = coll.order.next();
+                    coll.shuffle_bag.clear();
+                    let order_label = coll.order.label();
                     match collection::save_collection(&coll) {
                         Ok(()) => {
                             app.status_message =
@@ -842,12 +2433,63 @@ fn handle_collections_list_input(app: &mut App, key: KeyCode) {
                 }
             }
         }
-        KeyCode::Char('i') => {
-            if !app.collections_list.is_empty() {
-                app.collections_mode = CollectionsMode::SetInterval;
-                app.collections_input.clear();
+        KeyCode::Char('i') if !app.collections_list.is_empty() => {
+            app.collections_mode = CollectionsMode::SetInterval;
+            app.collections_input.clear();
+        }
+        KeyCode::Char('m') => {
+            if let Some(name) = app.selected_collection_name() {
+                if let Ok(mut coll) = collection::load_collection(&name) {
+                    // Off (global) -> dark -> light -> auto-os -> auto-time -> auto-sun -> off
+                    coll.mode_preference = match &coll.mode_preference {
+                        None => Some(collection::ModePreference::Dark),
+                        Some(pref) => pref.next(),
+                    };
+                    let label = coll
+                        .mode_preference
+                        .as_ref()
+                        .map_or("the global mode", |p| p.label());
+                    app.status_message = Some(match collection::save_collection(&coll) {
+                        Ok(()) => format!("'{}' uses {}", name, label),
+                        Err(e) => format!("Error: {}", e),
+                    });
+                    app.refresh_collections();
+                }
             }
         }
+        KeyCode::Char('t') => {
+            if let Some(name) = app.selected_collection_name() {
+                let coll = collection::load_collection(&name).ok();
+                app.collections_mode = CollectionsMode::SetModeTimes;
+                app.collections_input = coll
+                    .and_then(|c| Some(format!("{}-{}", c.dark_after?, c.light_after?)))
+                    .unwrap_or_default();
+            }
+        }
+        KeyCode::Char('r') => {
+            if let Some(name) = app.selected_collection_name() {
+                app.collections_mode = CollectionsMode::Rename;
+                app.collections_input = name;
+            }
+        }
+        KeyCode::Char('y') => {
+            if let Some(name) = app.selected_collection_name() {
+                app.collections_mode = CollectionsMode::Duplicate;
+                app.collections_input = format!("{}-copy", name);
+            }
+        }
+        KeyCode::Char('A') => {
+            app.collections_show_archived = !app.collections_show_archived;
+            app.refresh_collections();
+            app.status_message = Some(
+                if app.collections_show_archived {
+                    "Showing archived collections"
+                } else {
+                    "Hiding archived collections"
+                }
+                .to_string(),
+            );
+        }
         KeyCode::Char('h') | KeyCode::Left | KeyCode::Esc => {
             app.screen = Screen::Browse;
         }
@@ -857,6 +2499,21 @@ fn handle_collections_list_input(app: &mut App, key: KeyCode) {
 
 fn handle_collections_theme_input(app: &mut App, key: KeyCode) {
     match key {
+        KeyCode::Char('r') => app.refresh_collection_themes(),
+        KeyCode::Enter => {
+            if let Some(name) = app.selected_collection_name() {
+                let app_config = collection::load_config();
+                let index = app.collections_theme_cursor;
+                app.status_message = Some(match cycling::apply_at(&app_config, &name, index) {
+                    Ok(msg) => {
+                        app.applied_slug = collection::load_applied().map(|a| a.slug);
+                        msg
+                    }
+                    Err(e) => format!("Error: {}", e),
+                });
+                app.reload_collection_detail();
+            }
+        }
         KeyCode::Char('j') | KeyCode::Down => {
             if let Some(ref coll) = app.collections_detail {
                 if !coll.themes.is_empty() {
@@ -869,16 +2526,15 @@ fn handle_collections_theme_input(app: &mut App, key: KeyCode) {
             app.collections_theme_cursor = app.collections_theme_cursor.saturating_sub(1);
         }
         KeyCode::Char('x') => {
-            if let Some(name) = app.collections_list.get(app.collections_cursor).cloned() {
+            if let Some(name) = app.selected_collection_name() {
+                // Held until saved, so a switch by the daemon in between
+                // isn't undone; save_collection reports a lock timeout
+                let _lock = lock::acquire(lock::Resource::State);
                 if let Ok(mut coll) = collection::load_collection(&name) {
-                    if app.collections_theme_cursor < coll.themes.len() {
-                        let removed = coll.themes.remove(app.collections_theme_cursor);
-                        // Adjust current_index if needed
-                        if coll.themes.is_empty() {
-                            coll.current_index = 0;
-                        } else if coll.current_index >= coll.themes.len() {
-                            coll.current_index = coll.themes.len() - 1;
-                        }
+                    if let Err(e) = coll.require_fixed() {
+                        app.status_message = Some(e.to_string());
+                    } else if app.collections_theme_cursor < coll.themes.len() {
+                        let removed = coll.remove(app.collections_theme_cursor);
                         match collection::save_collection(&coll) {
                             Ok(()) => {
                                 app.status_message =
@@ -890,8 +2546,9 @@ fn handle_collections_theme_input(app: &mut App, key: KeyCode) {
                                 } else if app.collections_theme_cursor >= theme_count {
                                     app.collections_theme_cursor = theme_count - 1;
                                 }
-                                // Refresh the detail view
+                                // Refresh the detail view and the list's count
                                 app.collections_detail = Some(coll);
+                                app.refresh_collections();
                             }
                             Err(e) => {
                                 app.status_message = Some(format!("Error: {}", e));
@@ -901,6 +2558,22 @@ fn handle_collections_theme_input(app: &mut App, key: KeyCode) {
                 }
             }
         }
+        KeyCode::Char('e') => edit_collection_theme(app, |theme| {
+            theme.enabled = !theme.enabled;
+            format!(
+                "{} '{}'",
+                if theme.enabled { "Enabled" } else { "Disabled" },
+                theme.title
+            )
+        }),
+        KeyCode::Char('+') | KeyCode::Char('=') => edit_collection_theme(app, |theme| {
+            theme.weight = (theme.weight + 1).min(collection::MAX_WEIGHT);
+            format!("'{}' shuffle weight {}", theme.title, theme.weight)
+        }),
+        KeyCode::Char('-') => edit_collection_theme(app, |theme| {
+            theme.weight = theme.weight.saturating_sub(1).max(1);
+            format!("'{}' shuffle weight {}", theme.title, theme.weight)
+        }),
         KeyCode::Char('h') | KeyCode::Left | KeyCode::Esc => {
             app.collections_viewing_themes = false;
             app.collections_detail = None;
@@ -909,6 +2582,31 @@ fn handle_collections_theme_input(app: &mut App, key: KeyCode) {
     }
 }
 
+/// Change the theme under the cursor in the open collection and save it,
+/// showing what `edit` reports.
+fn edit_collection_theme(
+    app: &mut App,
+    edit: impl FnOnce(&mut collection::CollectionTheme) -> String,
+) {
+    let Some(name) = app.selected_collection_name() else {
+        return;
+    };
+    // Held until saved, so a switch by the daemon in between isn't undone
+    let _lock = lock::acquire(lock::Resource::State);
+    let Ok(mut coll) = collection::load_collection(&name) else {
+        return;
+    };
+    let Some(theme) = coll.themes.get_mut(app.collections_theme_cursor) else {
+        return;
+    };
+    let message = edit(theme);
+    app.status_message = Some(match collection::save_collection(&coll) {
+        Ok(()) => message,
+        Err(e) => format!("Error: {}", e),
+    });
+    app.collections_detail = Some(coll);
+}
+
 fn handle_create_input(app: &mut App, key: KeyCode, modifiers: KeyModifiers) {
     use crate::creator::{ColorField, PickerMode, SliderFocus};
 
@@ -1012,20 +2710,16 @@ fn handle_create_input(app: &mut App, key: KeyCode, modifiers: KeyModifiers) {
         let visible_rows: usize = 18;
 
         match key {
-            KeyCode::Char('j') | KeyCode::Down => {
-                if state.field_index < field_count - 1 {
-                    state.field_index += 1;
-                    if state.field_index >= state.field_scroll + visible_rows {
-                        state.field_scroll = state.field_index + 1 - visible_rows;
-                    }
+            KeyCode::Char('j') | KeyCode::Down if state.field_index < field_count - 1 => {
+                state.field_index += 1;
+                if state.field_index >= state.field_scroll + visible_rows {
+                    state.field_scroll = state.field_index + 1 - visible_rows;
                 }
             }
-            KeyCode::Char('k') | KeyCode::Up => {
-                if state.field_index > 0 {
-                    state.field_index -= 1;
-                    if state.field_index < state.field_scroll {
-                        state.field_scroll = state.field_index;
-                    }
+            KeyCode::Char('k') | KeyCode::Up if state.field_index > 0 => {
+                state.field_index -= 1;
+                if state.field_index < state.field_scroll {
+                    state.field_scroll = state.field_index;
                 }
             }
             KeyCode::Enter | KeyCode::Char('l') => {
@@ -1080,6 +2774,13 @@ fn handle_create_input(app: &mut App, key: KeyCode, modifiers: KeyModifiers) {
 }
 
 fn handle_create_meta_input(app: &mut App, key: KeyCode) {
+    if matches!(
+        app.input_mode,
+        InputMode::CollectionSelect | InputMode::CollectionCreate
+    ) {
+        handle_collection_popup_input(app, key);
+        return;
+    }
     // First check if we're in editing mode
     let is_editing = app.create_meta_state.as_ref().is_some_and(|m| m.editing);
     let field_index = app.create_meta_state.as_ref().map_or(0, |m| m.field_index);
@@ -1178,6 +2879,25 @@ fn handle_create_meta_input(app: &mut App, key: KeyCode) {
                     _ => {}
                 }
             }
+            4 => {
+                // Editing pack name, kept on creator_state across saves
+                match key {
+                    KeyCode::Enter | KeyCode::Esc => {
+                        meta.editing = false;
+                    }
+                    KeyCode::Char(c) => {
+                        if let Some(ref mut creator) = app.creator_state {
+                            creator.pack_name.push(c);
+                        }
+                    }
+                    KeyCode::Backspace => {
+                        if let Some(ref mut creator) = app.creator_state {
+                            creator.pack_name.pop();
+                        }
+                    }
+                    _ => {}
+                }
+            }
             _ => {}
         }
     } else {
@@ -1185,7 +2905,7 @@ fn handle_create_meta_input(app: &mut App, key: KeyCode) {
         match key {
             KeyCode::Char('j') | KeyCode::Down => {
                 if let Some(ref mut meta) = app.create_meta_state {
-                    meta.field_index = (meta.field_index + 1).min(4);
+                    meta.field_index = (meta.field_index + 1).min(5);
                 }
             }
             KeyCode::Char('k') | KeyCode::Up => {
@@ -1195,7 +2915,7 @@ fn handle_create_meta_input(app: &mut App, key: KeyCode) {
             }
             KeyCode::Enter => {
                 if let Some(ref mut meta) = app.create_meta_state {
-                    if meta.field_index <= 3 {
+                    if meta.field_index <= 4 {
                         meta.editing = true;
                     }
                 }
@@ -1253,21 +2973,45 @@ fn handle_create_meta_input(app: &mut App, key: KeyCode) {
                     }
                 }
             }
-            KeyCode::Char('u') => {
-                // Upload
-                if let Some(ref state) = app.creator_state {
-                    if state.title.trim().is_empty() {
-                        app.status_message = Some("Title cannot be empty".into());
+            KeyCode::Char('p') => {
+                // Save into the theme pack
+                if let (Some(state), Some(meta)) = (&app.creator_state, &app.create_meta_state) {
+                    let pack = state.pack_name.trim();
+                    app.status_message = Some(if state.title.trim().is_empty() {
+                        "Title cannot be empty".into()
+                    } else if pack.is_empty() {
+                        "Enter a pack name first".into()
                     } else {
-                        match export::upload_theme(state) {
-                            Ok(msg) => {
-                                app.status_message = Some(msg);
-                            }
-                            Err(e) => {
-                                app.status_message = Some(format!("Error: {}", e));
-                            }
+                        let path = archive::pack_path(pack);
+                        let variant = state.pack_variant(&meta.tags);
+                        match archive::add_to_pack(
+                            &path,
+                            pack,
+                            Some(&meta.author_name),
+                            &state.build_preview_config(),
+                            variant,
+                        ) {
+                            Ok(count) => format!(
+                                "Saved as the {} variant in {} ({} themes)",
+                                variant,
+                                path.display(),
+                                count
+                            ),
+                            Err(e) => format!("Error: {}", e),
                         }
-                    }
+                    });
+                }
+            }
+            KeyCode::Char('u') => app.upload_created_theme(),
+            KeyCode::Char('c') => {
+                let untitled = app
+                    .creator_state
+                    .as_ref()
+                    .is_some_and(|s| s.title.trim().is_empty());
+                if untitled {
+                    app.status_message = Some("Title cannot be empty".into());
+                } else {
+                    app.open_collection_popup();
                 }
             }
             KeyCode::Esc => {
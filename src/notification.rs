@@ -0,0 +1,60 @@
+use std::process::Command;
+
+/// Show a desktop notification: `notify-send` on Linux, Notification
+/// Center through `osascript` on macOS.
+pub fn send(title: &str, body: &str) -> Result<(), String> {
+    #[cfg(target_os = "macos")]
+    let mut cmd = {
+        let mut cmd = Command::new("/usr/bin/osascript");
+        cmd.args([
+            "-e",
+            &format!(
+                "display notification {} with title {}",
+                applescript_string(body),
+                applescript_string(title)
+            ),
+        ]);
+        cmd
+    };
+    #[cfg(not(target_os = "macos"))]
+    let mut cmd = {
+        let mut cmd = Command::new("notify-send");
+        cmd.args(["--app-name=ghostty-styles", title, body]);
+        cmd
+    };
+
+    let output = cmd.output().map_err(|e| match e.kind() {
+        std::io::ErrorKind::NotFound => {
+            format!("{} isn't installed", cmd.get_program().to_string_lossy())
+        }
+        _ => format!("couldn't show a notification: {}", e),
+    })?;
+    if output.status.success() {
+        return Ok(());
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+    Err(if stderr.is_empty() {
+        "notification command failed".to_string()
+    } else {
+        stderr
+    })
+}
+
+/// `s` as a quoted AppleScript string literal.
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn applescript_string(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn applescript_strings_escape_quotes() {
+        assert_eq!(
+            applescript_string(r#"Applied "Nord" \ 'c1'"#),
+            r#""Applied \"Nord\" \\ 'c1'""#
+        );
+    }
+}
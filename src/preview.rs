@@ -25,6 +25,39 @@ pub fn apply_osc_preview(theme: &GhosttyConfig) {
     let _ = stdout.flush();
 }
 
+/// Build the OSC sequences that set the colors in a theme's raw Ghostty
+/// config. Keys without an OSC equivalent are skipped.
+pub fn osc_from_raw_config(raw_config: &str) -> String {
+    let mut out = String::new();
+    for line in raw_config.lines() {
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let value = value.trim();
+        match key.trim() {
+            "foreground" => push_osc(&mut out, "10", value),
+            "background" => push_osc(&mut out, "11", value),
+            "cursor-color" => push_osc(&mut out, "12", value),
+            "palette" => {
+                if let Some((index, color)) = value.split_once('=') {
+                    if let Ok(index) = index.trim().parse::<u8>() {
+                        push_osc(&mut out, &format!("4;{}", index), color.trim());
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    out
+}
+
+/// Append `OSC <code>;<color> BEL` if `color` is a valid hex color.
+fn push_osc(out: &mut String, code: &str, color: &str) {
+    if let Some((r, g, b)) = GhosttyConfig::parse_hex(color) {
+        out.push_str(&format!("\x1b]{};#{:02x}{:02x}{:02x}\x07", code, r, g, b));
+    }
+}
+
 /// Query current terminal colors and save them for later restoration.
 /// Returns a snapshot of saved colors as OSC restore sequences.
 pub fn save_current_colors() -> SavedColors {
@@ -50,3 +83,23 @@ pub fn restore_colors(_saved: &SavedColors) {
 }
 
 pub struct SavedColors;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn osc_from_raw_config_sets_colors() {
+        let raw = "background = #1a1b26\nforeground = c0caf5\ncursor-color = #FF0000\npalette = 1=#f7768e";
+        assert_eq!(
+            osc_from_raw_config(raw),
+            "\x1b]11;#1a1b26\x07\x1b]10;#c0caf5\x07\x1b]12;#ff0000\x07\x1b]4;1;#f7768e\x07"
+        );
+    }
+
+    #[test]
+    fn osc_from_raw_config_skips_other_keys_and_bad_values() {
+        let raw = "font-size = 14\nbackground = nope\npalette = x=#000000\n# comment";
+        assert_eq!(osc_from_raw_config(raw), "");
+    }
+}
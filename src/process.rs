@@ -0,0 +1,243 @@
+use std::fs::File;
+
+use crate::error::{Error, Result};
+
+#[cfg(unix)]
+use nix::sys::signal::{self, SigSet, Signal};
+#[cfg(unix)]
+use nix::unistd::{self, ForkResult, Pid};
+
+#[cfg(windows)]
+use windows_sys::Win32::Foundation::{CloseHandle, BOOL, HANDLE};
+#[cfg(windows)]
+use windows_sys::Win32::System::Threading::{OpenProcess, PROCESS_ACCESS_RIGHTS};
+
+/// Set in the environment of the background copy `cycle start --detach`
+/// launches on Windows, which has no `fork`.
+#[cfg(windows)]
+const DETACHED_ENV: &str = "GHOSTTY_STYLES_DETACHED";
+
+/// Exit code Windows reports for a process that hasn't exited.
+#[cfg(windows)]
+const STILL_ACTIVE: u32 = 259;
+
+/// Check whether a process with the given PID is alive.
+#[cfg(unix)]
+pub fn is_alive(pid: i32) -> bool {
+    signal::kill(Pid::from_raw(pid), None).is_ok()
+}
+
+#[cfg(windows)]
+pub fn is_alive(pid: i32) -> bool {
+    use windows_sys::Win32::System::Threading::{
+        GetExitCodeProcess, PROCESS_QUERY_LIMITED_INFORMATION,
+    };
+
+    let Some(handle) = open(pid, PROCESS_QUERY_LIMITED_INFORMATION) else {
+        return false;
+    };
+    let mut code = 0;
+    // SAFETY: the handle is open until `close` below
+    let alive = unsafe { GetExitCodeProcess(handle, &mut code) } != 0 && code == STILL_ACTIVE;
+    close(handle);
+    alive
+}
+
+/// Ask the daemon to shut down: SIGTERM, which it waits for in its loop.
+#[cfg(unix)]
+pub fn terminate(pid: i32) -> Result<()> {
+    signal::kill(Pid::from_raw(pid), Signal::SIGTERM)
+        .map_err(|e| Error::Other(format!("Failed to send SIGTERM to PID {}: {}", pid, e)))
+}
+
+/// Ask the daemon to shut down over the control channel, so it can still
+/// restore the theme, and kill it outright if that fails.
+#[cfg(windows)]
+pub fn terminate(pid: i32) -> Result<()> {
+    use crate::control::{self, Control};
+    use windows_sys::Win32::System::Threading::{TerminateProcess, PROCESS_TERMINATE};
+
+    if control::send(Control::Shutdown).is_ok() {
+        return Ok(());
+    }
+    let Some(handle) = open(pid, PROCESS_TERMINATE) else {
+        return Err(Error::Other(format!(
+            "Failed to stop PID {}: {}",
+            pid,
+            std::io::Error::last_os_error()
+        )));
+    };
+    // SAFETY: the handle is open until `close` below
+    let killed = unsafe { TerminateProcess(handle, 1) } != 0;
+    let err = std::io::Error::last_os_error();
+    close(handle);
+    if killed {
+        Ok(())
+    } else {
+        Err(Error::Other(format!("Failed to stop PID {}: {}", pid, err)))
+    }
+}
+
+/// Fork into a new session with stdout and stderr going to `log`. Returns
+/// the child's PID in the parent and `None` in the child, which carries on
+/// as the daemon.
+#[cfg(unix)]
+pub fn detach(log: File) -> Result<Option<i32>> {
+    use std::os::fd::AsRawFd;
+
+    let null = File::open("/dev/null")
+        .map_err(|e| Error::io("open", std::path::Path::new("/dev/null"), e))?;
+
+    // SAFETY: nothing else runs yet (the watchers start after this), so
+    // the child's copy of the process is consistent
+    match unsafe { unistd::fork() } {
+        Ok(ForkResult::Parent { child }) => return Ok(Some(child.as_raw())),
+        Ok(ForkResult::Child) => {}
+        Err(e) => return Err(Error::Other(format!("Couldn't fork the daemon: {}", e))),
+    }
+    // Leave the terminal's session so closing it doesn't stop the daemon
+    let _ = unistd::setsid();
+    let _ = unistd::dup2(null.as_raw_fd(), 0);
+    redirect_output(log)?;
+    Ok(None)
+}
+
+/// Send stdout and stderr to `log` from now on, e.g. after it's rotated.
+#[cfg(unix)]
+pub fn redirect_output(log: File) -> Result<()> {
+    use std::os::fd::AsRawFd;
+
+    for fd in [1, 2] {
+        unistd::dup2(log.as_raw_fd(), fd)
+            .map_err(|e| Error::Other(format!("Couldn't redirect output: {}", e)))?;
+    }
+    Ok(())
+}
+
+#[cfg(windows)]
+pub fn redirect_output(log: File) -> Result<()> {
+    use std::os::windows::io::IntoRawHandle;
+    use windows_sys::Win32::System::Console::{SetStdHandle, STD_ERROR_HANDLE, STD_OUTPUT_HANDLE};
+
+    let stderr = log
+        .try_clone()
+        .map_err(|e| Error::Other(format!("Couldn't redirect output: {}", e)))?;
+    for (which, file) in [(STD_OUTPUT_HANDLE, log), (STD_ERROR_HANDLE, stderr)] {
+        // SAFETY: the handle is handed over for good, so it stays open for
+        // as long as it's the standard handle
+        if unsafe { SetStdHandle(which, file.into_raw_handle()) } == 0 {
+            return Err(Error::Other(format!(
+                "Couldn't redirect output: {}",
+                std::io::Error::last_os_error()
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Launch this same command again, detached from the console, with stdout
+/// and stderr going to `log`. Returns its PID here and `None` in the copy,
+/// which carries on as the daemon.
+#[cfg(windows)]
+pub fn detach(log: File) -> Result<Option<i32>> {
+    use std::os::windows::process::CommandExt;
+    use std::process::{Command, Stdio};
+    use windows_sys::Win32::System::Threading::{CREATE_NEW_PROCESS_GROUP, DETACHED_PROCESS};
+
+    if std::env::var_os(DETACHED_ENV).is_some() {
+        return Ok(None);
+    }
+    let failed = |e: std::io::Error| Error::Other(format!("Couldn't start the daemon: {}", e));
+    let exe = std::env::current_exe().map_err(failed)?;
+    let stdout = log.try_clone().map_err(failed)?;
+    let child = Command::new(exe)
+        .args(std::env::args_os().skip(1))
+        .env(DETACHED_ENV, "1")
+        .stdin(Stdio::null())
+        .stdout(stdout)
+        .stderr(log)
+        .creation_flags(DETACHED_PROCESS | CREATE_NEW_PROCESS_GROUP)
+        .spawn()
+        .map_err(failed)?;
+    Ok(Some(child.id() as i32))
+}
+
+/// Call `on_shutdown` with the signal's name on the first SIGTERM (as sent
+/// by `cycle stop`) or SIGINT. The signals are blocked and waited for on a
+/// thread of their own, so call this before starting any other thread.
+#[cfg(unix)]
+pub fn watch_shutdown(on_shutdown: impl FnOnce(&'static str) + Send + 'static) -> Result<()> {
+    let mut signals = SigSet::empty();
+    signals.add(Signal::SIGTERM);
+    signals.add(Signal::SIGINT);
+    signals
+        .thread_block()
+        .map_err(|e| Error::Other(format!("Couldn't catch SIGTERM/SIGINT: {}", e)))?;
+    std::thread::spawn(move || {
+        if let Ok(sig) = signals.wait() {
+            on_shutdown(sig.as_str());
+        }
+    });
+    Ok(())
+}
+
+/// Call `on_shutdown` with the event's name on the first Ctrl+C,
+/// Ctrl+Break, or console close. A detached daemon has no console, and
+/// `cycle stop` reaches it over the control channel instead.
+#[cfg(windows)]
+pub fn watch_shutdown(on_shutdown: impl FnOnce(&'static str) + Send + 'static) -> Result<()> {
+    use std::sync::Mutex;
+    use windows_sys::Win32::System::Console::{
+        SetConsoleCtrlHandler, CTRL_BREAK_EVENT, CTRL_CLOSE_EVENT, CTRL_C_EVENT,
+    };
+
+    type Callback = Box<dyn FnOnce(&'static str) + Send>;
+    static CALLBACK: Mutex<Option<Callback>> = Mutex::new(None);
+
+    unsafe extern "system" fn handler(event: u32) -> BOOL {
+        let name = match event {
+            CTRL_C_EVENT => "Ctrl+C",
+            CTRL_BREAK_EVENT => "Ctrl+Break",
+            CTRL_CLOSE_EVENT => "console close",
+            _ => return 0,
+        };
+        if let Some(callback) = CALLBACK.lock().ok().and_then(|mut c| c.take()) {
+            callback(name);
+        }
+        1
+    }
+
+    *CALLBACK.lock().unwrap_or_else(|e| e.into_inner()) = Some(Box::new(on_shutdown));
+    // SAFETY: `handler` is a plain function that lives for the whole program
+    if unsafe { SetConsoleCtrlHandler(Some(handler), 1) } == 0 {
+        return Err(Error::Other(format!(
+            "Couldn't catch Ctrl+C: {}",
+            std::io::Error::last_os_error()
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(windows)]
+fn open(pid: i32, access: PROCESS_ACCESS_RIGHTS) -> Option<HANDLE> {
+    let pid = u32::try_from(pid).ok()?;
+    // SAFETY: OpenProcess takes no pointers and returns null on failure
+    let handle = unsafe { OpenProcess(access, 0, pid) };
+    (!handle.is_null()).then_some(handle)
+}
+
+#[cfg(windows)]
+fn close(handle: HANDLE) {
+    // SAFETY: only called once, on a handle from `open`
+    unsafe { CloseHandle(handle) };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn this_process_is_alive() {
+        assert!(is_alive(std::process::id() as i32));
+    }
+}
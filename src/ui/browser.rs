@@ -5,6 +5,7 @@ use ratatui::widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragra
 use ratatui::Frame;
 
 use crate::app::{App, InputMode, AVAILABLE_TAGS};
+use crate::daemon;
 use crate::ui::preview::ThemePreview;
 
 const ACCENT: Color = Color::Rgb(187, 154, 247); // Purple accent
@@ -86,6 +87,15 @@ fn render_top_bar(f: &mut Frame, app: &App, area: Rect) {
 
     // Filter info
     let mut filter_spans = Vec::new();
+    if app.offline {
+        filter_spans.push(Span::styled(" offline", Style::default().fg(Color::Yellow)));
+    }
+    if app.favorites_only {
+        filter_spans.push(Span::styled(" ★", Style::default().fg(Color::Yellow)));
+    }
+    if app.mine_only {
+        filter_spans.push(Span::styled(" mine", Style::default().fg(Color::Yellow)));
+    }
     filter_spans.push(Span::styled(
         format!(" {} ", app.sort.label()),
         Style::default().fg(ACCENT),
@@ -102,9 +112,19 @@ fn render_top_bar(f: &mut Frame, app: &App, area: Rect) {
             Style::default().fg(Color::Rgb(200, 170, 100)),
         ));
     }
+    let os = if app.filter_follows_os { "os " } else { "" };
     match app.dark_filter {
-        Some(true) => filter_spans.push(Span::styled("dark ", Style::default().fg(DIM))),
-        Some(false) => filter_spans.push(Span::styled("light ", Style::default().fg(DIM))),
+        Some(true) => filter_spans.push(Span::styled(
+            format!("{}dark ", os),
+            Style::default().fg(DIM),
+        )),
+        Some(false) => filter_spans.push(Span::styled(
+            format!("{}light ", os),
+            Style::default().fg(DIM),
+        )),
+        None if app.filter_follows_os => {
+            filter_spans.push(Span::styled("os ? ", Style::default().fg(DIM)))
+        }
         None => {}
     }
     filter_spans.push(Span::styled(
@@ -138,7 +158,7 @@ fn render_main(f: &mut Frame, app: &App, area: Rect) {
             )),
             Line::from(""),
             Line::from(Span::styled(
-                "  Press 'r' to retry",
+                "  Press 'r' to retry, or 'o' to browse themes you've seen offline",
                 Style::default().fg(DIM),
             )),
         ]);
@@ -174,11 +194,17 @@ fn render_theme_list(f: &mut Frame, app: &App, area: Rect) {
             let is_selected = i == app.selected;
             let indicator = if is_selected { ">" } else { " " };
 
+            let star = if app.favorites.contains(&theme.slug) {
+                "★"
+            } else {
+                " "
+            };
             let mut spans = vec![
                 Span::styled(
                     format!("{} ", indicator),
                     Style::default().fg(if is_selected { ACCENT } else { DIM }),
                 ),
+                Span::styled(format!("{} ", star), Style::default().fg(Color::Yellow)),
                 Span::styled(
                     truncate(&theme.title, 28),
                     Style::default()
@@ -195,9 +221,19 @@ fn render_theme_list(f: &mut Frame, app: &App, area: Rect) {
                 ),
             ];
 
+            if app.applied_slug.as_deref() == Some(theme.slug.as_str()) {
+                spans.push(Span::styled(
+                    " \u{25cf} applied",
+                    Style::default().fg(Color::Green),
+                ));
+            }
+
             // Vote count
             spans.push(Span::styled(
-                format!(" {} ", vote_icon(theme.vote_count)),
+                format!(
+                    " {} ",
+                    vote_icon(theme.vote_count, app.voted.contains(&theme.slug))
+                ),
                 Style::default().fg(DIM),
             ));
 
@@ -214,14 +250,22 @@ fn render_theme_list(f: &mut Frame, app: &App, area: Rect) {
         })
         .collect();
 
+    let mut title_spans = vec![Span::styled(
+        format!(" Themes ({}) ", app.total_results),
+        Style::default().fg(ACCENT),
+    )];
+    if let Some(age) = app.listing_cached {
+        title_spans.push(Span::styled(
+            format!("cached {} ago ", daemon::format_countdown(age.as_secs())),
+            Style::default().fg(Color::Yellow),
+        ));
+    }
+
     let list = List::new(items).highlight_style(Style::default()).block(
         Block::default()
             .borders(Borders::RIGHT)
             .border_style(Style::default().fg(Color::Rgb(60, 60, 80)))
-            .title(Span::styled(
-                format!(" Themes ({}) ", app.total_results),
-                Style::default().fg(ACCENT),
-            )),
+            .title(Line::from(title_spans)),
     );
 
     let mut state = ListState::default().with_selected(Some(app.selected));
@@ -248,7 +292,7 @@ fn render_preview_panel(f: &mut Frame, app: &App, area: Rect) {
 fn render_bottom_bar(f: &mut Frame, app: &App, area: Rect) {
     let mut spans = vec![];
 
-    if let Some(ref msg) = app.status_message {
+    if let Some(msg) = app.status_line() {
         spans.push(Span::styled(
             format!(" {} ", msg),
             Style::default().fg(Color::Rgb(130, 200, 130)),
@@ -272,7 +316,7 @@ fn render_bottom_bar(f: &mut Frame, app: &App, area: Rect) {
             ("/", "search"),
             ("t", "tags"),
             ("s", "sort"),
-            ("d", "dark/light"),
+            ("d", "dark/light/os"),
             ("m", "mode"),
             ("p", "preview"),
             ("a", "apply"),
@@ -332,7 +376,7 @@ fn render_tag_popup(f: &mut Frame, app: &App, area: Rect) {
     f.render_widget(list, popup_area);
 }
 
-fn render_collection_popup(f: &mut Frame, app: &App, area: Rect) {
+pub(super) fn render_collection_popup(f: &mut Frame, app: &App, area: Rect) {
     let popup_width = 40u16;
 
     if app.input_mode == InputMode::CollectionCreate {
@@ -430,9 +474,12 @@ fn truncate(s: &str, max: usize) -> String {
     }
 }
 
-fn vote_icon(count: i32) -> String {
-    if count > 0 {
-        format!("{}{}", '\u{2665}', count) // heart + count
+/// A filled heart for themes voted for here, else an outline.
+fn vote_icon(count: i32, voted: bool) -> String {
+    if voted {
+        format!("{}{}", '\u{2665}', count)
+    } else if count > 0 {
+        format!("{}{}", '\u{2661}', count)
     } else {
         String::new()
     }
@@ -5,7 +5,8 @@ use ratatui::widgets::{Block, Borders, Clear, List, ListItem, Paragraph};
 use ratatui::Frame;
 
 use crate::app::{App, CollectionsMode};
-use crate::collection;
+use crate::ui::preview::ThemePreview;
+use crate::{daemon, darkmode};
 
 const ACCENT: Color = Color::Rgb(187, 154, 247);
 const DIM: Color = Color::Rgb(100, 100, 120);
@@ -28,8 +29,19 @@ pub fn render_collections(f: &mut Frame, app: &App) {
 
     // Overlay popups for modal modes
     match app.collections_mode {
-        CollectionsMode::NewCollection => render_new_collection_popup(f, app, area),
-        CollectionsMode::SetInterval => render_set_interval_popup(f, app, area),
+        CollectionsMode::NewCollection => render_name_popup(f, app, area, " New Collection "),
+        CollectionsMode::Rename => render_name_popup(f, app, area, " Rename Collection "),
+        CollectionsMode::Duplicate => render_name_popup(f, app, area, " Duplicate As "),
+        CollectionsMode::SetInterval => {
+            render_input_popup(f, app, area, " Set Interval ", " e.g. 30m, 1h, 1h30m")
+        }
+        CollectionsMode::SetModeTimes => render_input_popup(
+            f,
+            app,
+            area,
+            " Mode Switch Times ",
+            " dark-light, e.g. 20:00-06:30",
+        ),
         CollectionsMode::ConfirmDelete => render_confirm_delete_popup(f, app, area),
         CollectionsMode::Normal => {}
     }
@@ -71,32 +83,45 @@ fn render_main(f: &mut Frame, app: &App, area: Rect) {
         return;
     }
 
+    // Looking inside a collection, the theme under the cursor is previewed
+    // on the right
+    let selected = app.collections_preview.as_ref();
+    let constraints = if selected.is_some() {
+        vec![
+            Constraint::Percentage(30),
+            Constraint::Percentage(35),
+            Constraint::Percentage(35),
+        ]
+    } else {
+        vec![Constraint::Percentage(45), Constraint::Percentage(55)]
+    };
     let chunks = Layout::default()
         .direction(Direction::Horizontal)
-        .constraints([Constraint::Percentage(45), Constraint::Percentage(55)])
+        .constraints(constraints)
         .split(area);
 
     render_collection_list(f, app, chunks[0]);
     render_theme_panel(f, app, chunks[1]);
+    if let Some(theme) = selected {
+        let block = Block::default()
+            .title(Span::styled(" Preview ", Style::default().fg(ACCENT)))
+            .borders(Borders::LEFT)
+            .border_style(Style::default().fg(Color::Rgb(60, 60, 80)));
+        let inner = block.inner(chunks[2]);
+        f.render_widget(block, chunks[2]);
+        f.render_widget(ThemePreview { theme }, inner);
+    }
 }
 
 fn render_collection_list(f: &mut Frame, app: &App, area: Rect) {
-    let config = collection::load_config();
-    let active_name = config.active_collection.as_deref();
-
     let items: Vec<ListItem> = app
         .collections_list
         .iter()
         .enumerate()
-        .map(|(i, name)| {
+        .map(|(i, summary)| {
             let is_selected = i == app.collections_cursor;
-            let is_active = active_name == Some(name.as_str());
             let indicator = if is_selected { ">" } else { " " };
-            let active_marker = if is_active { " *" } else { "" };
-
-            let theme_count = collection::load_collection(name)
-                .map(|c| c.themes.len())
-                .unwrap_or(0);
+            let active_marker = if summary.active { " *" } else { "" };
 
             let spans = vec![
                 Span::styled(
@@ -104,7 +129,7 @@ fn render_collection_list(f: &mut Frame, app: &App, area: Rect) {
                     Style::default().fg(if is_selected { ACCENT } else { DIM }),
                 ),
                 Span::styled(
-                    name.clone(),
+                    summary.name.clone(),
                     Style::default()
                         .fg(if is_selected {
                             Color::White
@@ -124,7 +149,20 @@ fn render_collection_list(f: &mut Frame, app: &App, area: Rect) {
                         .add_modifier(Modifier::BOLD),
                 ),
                 Span::styled(
-                    format!("  ({} themes)", theme_count),
+                    format!(
+                        "  ({} themes{}{})",
+                        summary.theme_count,
+                        if summary.smart { ", smart" } else { "" },
+                        if summary.archived { ", archived" } else { "" }
+                    ),
+                    Style::default().fg(DIM),
+                ),
+                Span::styled(
+                    summary
+                        .mode
+                        .as_ref()
+                        .map(|m| format!(" [{}]", m))
+                        .unwrap_or_default(),
                     Style::default().fg(DIM),
                 ),
             ];
@@ -138,7 +176,17 @@ fn render_collection_list(f: &mut Frame, app: &App, area: Rect) {
             .borders(Borders::RIGHT)
             .border_style(Style::default().fg(Color::Rgb(60, 60, 80)))
             .title(Span::styled(
-                format!(" Collections ({}) ", app.collections_list.len()),
+                format!(
+                    " Collections ({}){} ",
+                    app.collections_list.len(),
+                    app.collections_next_switch
+                        .map(|at| format!(
+                            " · next change {} (in {})",
+                            darkmode::format_clock(at),
+                            daemon::format_countdown(at.saturating_sub(darkmode::unix_now()))
+                        ))
+                        .unwrap_or_default()
+                ),
                 Style::default().fg(ACCENT),
             )),
     );
@@ -181,7 +229,11 @@ fn render_theme_panel(f: &mut Frame, app: &App, area: Rect) {
             )),
             Line::from(""),
             Line::from(Span::styled(
-                "  Add themes from the Browse screen with 'c'.",
+                if coll.smart.is_some() {
+                    "  Its query hasn't found any yet; press 'r' to run it again."
+                } else {
+                    "  Add themes from the Browse screen with 'c'."
+                },
                 Style::default().fg(DIM),
             )),
         ])
@@ -207,17 +259,28 @@ fn render_theme_panel(f: &mut Frame, app: &App, area: Rect) {
         .split(area);
 
     // Info line: order, interval
-    let order_str = match coll.order {
-        collection::CycleOrder::Sequential => "sequential",
-        collection::CycleOrder::Shuffle => "shuffle",
+    let order_str = coll.order.label();
+    let (timing_label, timing_str) = match coll.schedule {
+        Some(ref expr) => ("  Schedule: ", expr.as_str()),
+        None => (
+            "  Interval: ",
+            coll.interval.as_deref().unwrap_or("not set"),
+        ),
     };
-    let interval_str = coll.interval.as_deref().unwrap_or("not set");
-    let info = Paragraph::new(Line::from(vec![
+    let mut info_spans = vec![
         Span::styled("  Order: ", Style::default().fg(DIM)),
         Span::styled(order_str, Style::default().fg(Color::White)),
-        Span::styled("  Interval: ", Style::default().fg(DIM)),
-        Span::styled(interval_str, Style::default().fg(Color::White)),
-    ]));
+        Span::styled(timing_label, Style::default().fg(DIM)),
+        Span::styled(timing_str, Style::default().fg(Color::White)),
+    ];
+    if let Some(query) = &coll.smart {
+        info_spans.push(Span::styled("  Smart: ", Style::default().fg(DIM)));
+        info_spans.push(Span::styled(
+            query.describe(),
+            Style::default().fg(Color::White),
+        ));
+    }
+    let info = Paragraph::new(Line::from(info_spans));
     f.render_widget(info, inner_layout[0]);
 
     // Theme list
@@ -252,6 +315,14 @@ fn render_theme_panel(f: &mut Frame, app: &App, area: Rect) {
                         }),
                 ),
                 Span::styled(mode_indicator, Style::default().fg(DIM)),
+                Span::styled(
+                    match (theme.enabled, theme.weight) {
+                        (false, _) => " off".to_string(),
+                        (true, 1) => String::new(),
+                        (true, w) => format!(" x{}", w),
+                    },
+                    Style::default().fg(Color::Rgb(255, 200, 50)),
+                ),
                 Span::styled(
                     current_marker.to_string(),
                     Style::default().fg(Color::Rgb(130, 200, 130)),
@@ -276,7 +347,7 @@ fn render_theme_panel(f: &mut Frame, app: &App, area: Rect) {
 fn render_bottom_bar(f: &mut Frame, app: &App, area: Rect) {
     let mut spans = vec![];
 
-    if let Some(ref msg) = app.status_message {
+    if let Some(msg) = app.status_line() {
         spans.push(Span::styled(
             format!(" {} ", msg),
             Style::default().fg(Color::Rgb(130, 200, 130)),
@@ -284,7 +355,15 @@ fn render_bottom_bar(f: &mut Frame, app: &App, area: Rect) {
     } else {
         let hints: Vec<(&str, &str)> = match app.collections_mode {
             CollectionsMode::Normal if app.collections_viewing_themes => {
-                vec![("j/k", "nav"), ("x", "remove"), ("Esc", "back")]
+                vec![
+                    ("j/k", "nav"),
+                    ("Enter", "apply"),
+                    ("x", "remove"),
+                    ("e", "enable/disable"),
+                    ("+/-", "weight"),
+                    ("r", "refresh"),
+                    ("Esc", "back"),
+                ]
             }
             CollectionsMode::Normal => {
                 vec![
@@ -295,12 +374,22 @@ fn render_bottom_bar(f: &mut Frame, app: &App, area: Rect) {
                     ("u", "activate"),
                     ("s", "order"),
                     ("i", "interval"),
+                    ("r", "rename"),
+                    ("y", "duplicate"),
+                    ("A", "archived"),
+                    ("m", "mode"),
+                    ("t", "mode times"),
                     ("Esc", "back"),
                 ]
             }
-            CollectionsMode::NewCollection => {
+            CollectionsMode::NewCollection
+            | CollectionsMode::Rename
+            | CollectionsMode::Duplicate => {
                 vec![("type", "name"), ("Enter", "confirm"), ("Esc", "cancel")]
             }
+            CollectionsMode::SetModeTimes => {
+                vec![("type", "times"), ("Enter", "confirm"), ("Esc", "cancel")]
+            }
             CollectionsMode::SetInterval => {
                 vec![
                     ("type", "interval"),
@@ -326,7 +415,7 @@ fn render_bottom_bar(f: &mut Frame, app: &App, area: Rect) {
     f.render_widget(bar, area);
 }
 
-fn render_new_collection_popup(f: &mut Frame, app: &App, area: Rect) {
+fn render_name_popup(f: &mut Frame, app: &App, area: Rect, title: &str) {
     let popup_width = 40u16;
     let popup_height = 5u16;
     let x = area.width.saturating_sub(popup_width) / 2;
@@ -348,17 +437,15 @@ fn render_new_collection_popup(f: &mut Frame, app: &App, area: Rect) {
     ];
     let paragraph = Paragraph::new(lines).block(
         Block::default()
-            .title(Span::styled(
-                " New Collection ",
-                Style::default().fg(ACCENT),
-            ))
+            .title(Span::styled(title, Style::default().fg(ACCENT)))
             .borders(Borders::ALL)
             .border_style(Style::default().fg(ACCENT)),
     );
     f.render_widget(paragraph, popup_area);
 }
 
-fn render_set_interval_popup(f: &mut Frame, app: &App, area: Rect) {
+/// A one-line input popup with a hint above the field.
+fn render_input_popup(f: &mut Frame, app: &App, area: Rect, title: &str, hint: &str) {
     let popup_width = 40u16;
     let popup_height = 6u16;
     let x = area.width.saturating_sub(popup_width) / 2;
@@ -368,10 +455,7 @@ fn render_set_interval_popup(f: &mut Frame, app: &App, area: Rect) {
     f.render_widget(Clear, popup_area);
 
     let lines = vec![
-        Line::from(Span::styled(
-            " e.g. 30m, 1h, 2h30m",
-            Style::default().fg(DIM),
-        )),
+        Line::from(Span::styled(hint, Style::default().fg(DIM))),
         Line::from(Span::styled(
             format!(" > {}_ ", app.collections_input),
             Style::default().fg(Color::White),
@@ -384,7 +468,7 @@ fn render_set_interval_popup(f: &mut Frame, app: &App, area: Rect) {
     ];
     let paragraph = Paragraph::new(lines).block(
         Block::default()
-            .title(Span::styled(" Set Interval ", Style::default().fg(ACCENT)))
+            .title(Span::styled(title, Style::default().fg(ACCENT)))
             .borders(Borders::ALL)
             .border_style(Style::default().fg(ACCENT)),
     );
@@ -392,11 +476,7 @@ fn render_set_interval_popup(f: &mut Frame, app: &App, area: Rect) {
 }
 
 fn render_confirm_delete_popup(f: &mut Frame, app: &App, area: Rect) {
-    let name = app
-        .collections_list
-        .get(app.collections_cursor)
-        .cloned()
-        .unwrap_or_default();
+    let name = app.selected_collection_name().unwrap_or_default();
 
     let popup_width = 40u16;
     let popup_height = 5u16;
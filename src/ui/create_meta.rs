@@ -4,7 +4,7 @@ use ratatui::text::{Line, Span};
 use ratatui::widgets::{Block, Borders, Paragraph, Wrap};
 use ratatui::Frame;
 
-use crate::app::App;
+use crate::app::{App, InputMode};
 
 const ACCENT: Color = Color::Rgb(187, 154, 247);
 const DIM: Color = Color::Rgb(100, 100, 120);
@@ -181,10 +181,37 @@ pub fn render_create_meta(f: &mut Frame, app: &App) {
         },
     ]));
     lines.push(Line::from(""));
+
+    // Field 4: Pack name
+    let sel = meta.field_index == 4;
+    let marker = if sel { "> " } else { "  " };
+    let pack_display = if creator.pack_name.is_empty() {
+        "(optional, for p)"
+    } else {
+        &creator.pack_name
+    };
+    lines.push(Line::from(vec![
+        Span::styled(marker, field_style(sel)),
+        Span::styled("Pack: ", Style::default().fg(DIM)),
+        Span::styled(
+            pack_display,
+            Style::default().fg(if creator.pack_name.is_empty() {
+                DIM
+            } else {
+                Color::White
+            }),
+        ),
+        if sel && meta.editing {
+            Span::styled("_", Style::default().fg(ACCENT))
+        } else {
+            Span::styled("", Style::default())
+        },
+    ]));
+    lines.push(Line::from(""));
     lines.push(Line::from(""));
 
     // Action buttons
-    let sel = meta.field_index == 4;
+    let sel = meta.field_index == 5;
     lines.push(Line::from(vec![
         Span::styled(if sel { "> " } else { "  " }, field_style(sel)),
         Span::styled(" a ", Style::default().fg(Color::Black).bg(ACCENT)),
@@ -192,7 +219,9 @@ pub fn render_create_meta(f: &mut Frame, app: &App) {
         Span::styled(" e ", Style::default().fg(Color::Black).bg(Color::Green)),
         Span::styled(" Export  ", Style::default().fg(DIM)),
         Span::styled(" u ", Style::default().fg(Color::Black).bg(Color::Cyan)),
-        Span::styled(" Upload ", Style::default().fg(DIM)),
+        Span::styled(" Upload  ", Style::default().fg(DIM)),
+        Span::styled(" p ", Style::default().fg(Color::Black).bg(Color::Yellow)),
+        Span::styled(" Pack ", Style::default().fg(DIM)),
     ]));
 
     f.render_widget(Paragraph::new(lines).wrap(Wrap { trim: false }), form_inner);
@@ -224,6 +253,8 @@ pub fn render_create_meta(f: &mut Frame, app: &App) {
             ("a", "apply"),
             ("e", "export"),
             ("u", "upload"),
+            ("p", "pack"),
+            ("c", "collection"),
             ("Esc", "back"),
         ]
     };
@@ -240,6 +271,13 @@ pub fn render_create_meta(f: &mut Frame, app: &App) {
         ));
     }
     f.render_widget(Paragraph::new(Line::from(spans)), outer[2]);
+
+    if matches!(
+        app.input_mode,
+        InputMode::CollectionSelect | InputMode::CollectionCreate
+    ) {
+        super::browser::render_collection_popup(f, app, area);
+    }
 }
 
 fn field_style(selected: bool) -> Style {
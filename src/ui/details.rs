@@ -36,6 +36,14 @@ pub fn render_detail(f: &mut Frame, app: &App) {
                 .fg(Color::White)
                 .add_modifier(Modifier::BOLD),
         ),
+        Span::styled(
+            if app.favorites.contains(&theme.slug) {
+                " ★"
+            } else {
+                ""
+            },
+            Style::default().fg(Color::Yellow),
+        ),
         Span::styled(
             theme
                 .author_name
@@ -70,7 +78,7 @@ pub fn render_detail(f: &mut Frame, app: &App) {
     let footer_spans = if app.screen == Screen::Confirm {
         vec![
             Span::styled(
-                " Apply this theme? ",
+                format!(" Apply this theme ({})? ", app.apply_scope.label()),
                 Style::default()
                     .fg(Color::Rgb(255, 200, 50))
                     .add_modifier(Modifier::BOLD),
@@ -78,7 +86,14 @@ pub fn render_detail(f: &mut Frame, app: &App) {
             Span::styled("y", Style::default().fg(ACCENT)),
             Span::styled("/", Style::default().fg(DIM)),
             Span::styled("n", Style::default().fg(ACCENT)),
+            Span::styled("  s", Style::default().fg(ACCENT)),
+            Span::styled(" colors/full/palette", Style::default().fg(DIM)),
         ]
+    } else if let Some(msg) = app.status_line() {
+        vec![Span::styled(
+            format!(" {} ", msg),
+            Style::default().fg(Color::Rgb(130, 200, 130)),
+        )]
     } else {
         vec![
             Span::styled(" Esc", Style::default().fg(ACCENT)),
@@ -91,6 +106,14 @@ pub fn render_detail(f: &mut Frame, app: &App) {
             Span::styled(" collect  ", Style::default().fg(DIM)),
             Span::styled("f", Style::default().fg(ACCENT)),
             Span::styled(" fork  ", Style::default().fg(DIM)),
+            Span::styled("s", Style::default().fg(ACCENT)),
+            Span::styled(" share  ", Style::default().fg(DIM)),
+            Span::styled("y", Style::default().fg(ACCENT)),
+            Span::styled(" copy  ", Style::default().fg(DIM)),
+            Span::styled("*", Style::default().fg(ACCENT)),
+            Span::styled(" star  ", Style::default().fg(DIM)),
+            Span::styled("v", Style::default().fg(ACCENT)),
+            Span::styled(" vote  ", Style::default().fg(DIM)),
         ]
     };
     let footer = Paragraph::new(Line::from(footer_spans));
@@ -137,6 +160,14 @@ fn render_info_panel(f: &mut Frame, app: &App, area: Rect) {
             format!("{}", theme.vote_count),
             Style::default().fg(Color::White),
         ),
+        Span::styled(
+            if app.voted.contains(&theme.slug) {
+                " \u{2665} voted"
+            } else {
+                ""
+            },
+            Style::default().fg(Color::Rgb(240, 120, 140)),
+        ),
         Span::styled("  Views: ", Style::default().fg(DIM)),
         Span::styled(
             format!("{}", theme.view_count),
@@ -168,6 +199,26 @@ fn render_info_panel(f: &mut Frame, app: &App, area: Rect) {
     }
     lines.push(Line::from(""));
 
+    if app.screen == Screen::Confirm {
+        if let Some(ref diff) = app.apply_diff {
+            for warning in &app.apply_warnings {
+                lines.push(Line::from(Span::styled(
+                    format!(" \u{26a0} {}", warning),
+                    Style::default().fg(Color::Rgb(255, 200, 50)),
+                )));
+            }
+            if !app.apply_warnings.is_empty() {
+                lines.push(Line::from(""));
+            }
+            push_diff(&mut lines, diff);
+            let paragraph = Paragraph::new(lines)
+                .wrap(Wrap { trim: false })
+                .block(Block::default().borders(Borders::NONE));
+            f.render_widget(paragraph, area);
+            return;
+        }
+    }
+
     // Raw config header
     lines.push(Line::from(Span::styled(
         " Raw Config:",
@@ -199,3 +250,44 @@ fn render_info_panel(f: &mut Frame, app: &App, area: Rect) {
         .block(Block::default().borders(Borders::NONE));
     f.render_widget(paragraph, area);
 }
+
+/// The change applying would make to the Ghostty config, colored like
+/// `git diff`.
+fn push_diff(lines: &mut Vec<Line>, diff: &str) {
+    if diff.is_empty() {
+        lines.push(Line::from(Span::styled(
+            " The Ghostty config already matches this theme",
+            Style::default().fg(DIM),
+        )));
+        return;
+    }
+    let mut diff_lines = diff.lines();
+    let path = diff_lines
+        .next()
+        .and_then(|l| l.strip_prefix("--- "))
+        .unwrap_or_default();
+    lines.push(Line::from(Span::styled(
+        format!(" Changes to {}:", path),
+        Style::default().fg(ACCENT).add_modifier(Modifier::BOLD),
+    )));
+    lines.push(Line::from(Span::styled(
+        " ─────────────────────────────",
+        Style::default().fg(Color::Rgb(60, 60, 80)),
+    )));
+    // Skip the `+++` header; the path is already shown
+    for line in diff_lines.skip(1) {
+        let color = if line.starts_with("@@") {
+            Color::Rgb(110, 170, 200)
+        } else if line.starts_with('+') {
+            Color::Rgb(120, 200, 120)
+        } else if line.starts_with('-') {
+            Color::Rgb(220, 110, 110)
+        } else {
+            Color::Gray
+        };
+        lines.push(Line::from(Span::styled(
+            format!(" {}", line),
+            Style::default().fg(color),
+        )));
+    }
+}
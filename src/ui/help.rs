@@ -36,17 +36,18 @@ pub fn render_help(f: &mut Frame, app: &App) {
         Line::from(""),
         Line::from(Span::styled("Browse", Style::default().fg(ACCENT))),
         Line::from("  j/k or arrows: navigate  |  Enter/l: details"),
-        Line::from("  /: search  |  t: tags  |  s: sort  |  d: dark/light"),
+        Line::from("  /: search  |  t: tags  |  s: sort  |  d: dark/light/os"),
         Line::from("  m: mode  |  p: live preview  |  a: apply"),
-        Line::from("  c: add to collection  |  C: collections"),
-        Line::from("  n: new theme  |  [ ]: page  |  r: refresh  |  q/Esc: quit"),
+        Line::from("  c: add to collection  |  C: collections  |  *: star  |  F: starred only  |  M: mine"),
+        Line::from("  n: new theme  |  [ ]: page  |  r: refresh  |  R: random  |  o: offline library  |  q/Esc: quit"),
         Line::from(""),
         Line::from(Span::styled("Detail", Style::default().fg(ACCENT))),
-        Line::from("  h/Left/Esc: back  |  p: preview  |  a: apply  |  c: collect  |  f: fork"),
+        Line::from("  h/Left/Esc: back  |  p: preview  |  a: apply  |  c: collect  |  f: fork  |  s: share  |  y: copy  |  *: star  |  v: vote"),
+        Line::from("  confirm: y/Enter apply, n/Esc cancel, s colors/full theme/palette only"),
         Line::from(""),
         Line::from(Span::styled("Collections", Style::default().fg(ACCENT))),
-        Line::from("  list: j/k nav, Enter view, n new, d delete, u activate, s order, i interval"),
-        Line::from("  themes: j/k nav, x remove, Esc back"),
+        Line::from("  list: j/k nav, Enter view, n new, d delete, u activate, s order, i interval, r rename, y duplicate, m mode, t mode times"),
+        Line::from("  themes: j/k nav, x remove, e enable/disable, +/- shuffle weight, r refresh from site, Esc back"),
         Line::from(""),
         Line::from(Span::styled("Creator", Style::default().fg(ACCENT))),
         Line::from("  j/k nav fields, Enter edit, g generate, p preview, s save, Esc back"),
@@ -0,0 +1,31 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::atomic;
+use crate::collection;
+use crate::error::{Error, Result};
+
+/// `~/.config/ghostty-styles/votes.json`: slugs of themes voted for from
+/// here, so the heart shows filled even though listings don't say.
+pub fn votes_path() -> PathBuf {
+    collection::base_dir().join("votes.json")
+}
+
+pub fn load() -> HashSet<String> {
+    fs::read_to_string(votes_path())
+        .ok()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+pub fn save(votes: &HashSet<String>) -> Result<()> {
+    let path = votes_path();
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| Error::io("create", parent, e))?;
+    }
+    let mut slugs: Vec<&String> = votes.iter().collect();
+    slugs.sort();
+    let json = serde_json::to_string_pretty(&slugs).map_err(|e| Error::parse("votes", e))?;
+    atomic::write(&path, json).map_err(|e| Error::io("write", &path, e))
+}